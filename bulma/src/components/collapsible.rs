@@ -0,0 +1,58 @@
+use web_sys::HtmlElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct CollapsibleProps {
+    /// The title displayed within the header, which toggles the section when clicked.
+    pub title: String,
+    /// Whether the section is currently expanded.
+    pub open: bool,
+    /// Emits the requested state when the header is clicked, leaving the parent to update `open`.
+    pub ontoggle: Callback<bool>,
+    #[prop_or_default]
+    pub classes: Classes,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+#[function_component(Collapsible)]
+pub fn collapsible(props: &CollapsibleProps) -> Html {
+    let body = use_node_ref();
+
+    {
+        // Animate by transitioning the max height between zero and the measured content height, re-measuring
+        // after every render so the section grows with its content
+        let body = body.clone();
+        let open = props.open;
+        use_effect(move || {
+            if let Some(element) = body.cast::<HtmlElement>() {
+                let height = if open { element.scroll_height() } else { 0 };
+                let _ = element
+                    .style()
+                    .set_property("max-height", &format!("{height}px"));
+            }
+            || ()
+        });
+    }
+
+    let toggle = {
+        let open = props.open;
+        props.ontoggle.reform(move |_: MouseEvent| !open)
+    };
+
+    html! {
+        <div class={ classes!("card", "collapsible", props.open.then(|| "is-active"), props.classes.clone()) }>
+            <header class="card-header" onclick={ toggle }>
+                <p class="card-header-title">{ &props.title }</p>
+                <button class="card-header-icon" aria-label="toggle" aria-expanded={ props.open.to_string() }>
+                    <span class="icon">
+                        <i class={ if props.open { "fas fa-angle-up" } else { "fas fa-angle-down" } }></i>
+                    </span>
+                </button>
+            </header>
+            <div class="collapsible-body" ref={ body }>
+                <div class="card-content">{ for props.children.iter() }</div>
+            </div>
+        </div>
+    }
+}
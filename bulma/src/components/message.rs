@@ -0,0 +1,61 @@
+use crate::Color;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct MessageProps {
+    #[prop_or_default]
+    pub color: Option<Color>,
+    /// The classes of an optional icon displayed alongside the message (e.g. `fas fa-circle-info`).
+    #[prop_or_default]
+    pub icon: Option<String>,
+    /// Whether a delete button is shown, which hides the message when clicked.
+    #[prop_or_default]
+    pub dismissible: bool,
+    /// Emitted once the message has been dismissed.
+    #[prop_or_default]
+    pub ondismiss: Option<Callback<()>>,
+    #[prop_or_default]
+    pub classes: Classes,
+    #[prop_or_default]
+    pub children: Children,
+}
+
+#[function_component(Message)]
+pub fn message(props: &MessageProps) -> Html {
+    let dismissed = use_state(|| false);
+    if *dismissed {
+        return Html::default();
+    }
+
+    let dismiss = {
+        let dismissed = dismissed.clone();
+        let ondismiss = props.ondismiss.clone();
+        Callback::from(move |_: MouseEvent| {
+            dismissed.set(true);
+            if let Some(ondismiss) = &ondismiss {
+                ondismiss.emit(());
+            }
+        })
+    };
+
+    html! {
+        <article class={ classes!("message", props.color.as_ref().map(|c| c.as_str().to_string()),
+            props.classes.clone()) }>
+            <div class="message-body">
+                if props.dismissible {
+                    <button class="delete is-pulled-right" aria-label="delete" onclick={ dismiss }></button>
+                }
+                <div class="media">
+                    if let Some(icon) = &props.icon {
+                        <div class="media-left">
+                            <span class="icon">
+                                <i class={ icon.clone() }></i>
+                            </span>
+                        </div>
+                    }
+                    <div class="media-content">{ for props.children.iter() }</div>
+                </div>
+            </div>
+        </article>
+    }
+}
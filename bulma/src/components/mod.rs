@@ -0,0 +1,7 @@
+mod collapsible;
+mod message;
+mod sentinel;
+
+pub use collapsible::{Collapsible, CollapsibleProps};
+pub use message::{Message, MessageProps};
+pub use sentinel::{Sentinel, SentinelProps};
@@ -0,0 +1,68 @@
+use gloo_console::error;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, IntersectionObserver, IntersectionObserverEntry, IntersectionObserverInit};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct SentinelProps {
+    /// Emitted whenever the sentinel scrolls into (or within the margin of) the viewport.
+    pub onvisible: Callback<()>,
+    /// The distance from the viewport within which the sentinel is considered visible, as a CSS margin.
+    #[prop_or("800px")]
+    pub margin: &'static str,
+}
+
+/// An empty element which reports when it nears the viewport, such as for loading further items whilst scrolling.
+#[function_component(Sentinel)]
+pub fn sentinel(props: &SentinelProps) -> Html {
+    let node = use_node_ref();
+
+    {
+        let node = node.clone();
+        let (onvisible, margin) = (props.onvisible.clone(), props.margin);
+        use_effect_with_deps(
+            move |_| {
+                let callback = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+                    let visible = entries.iter().any(|entry| {
+                        entry
+                            .dyn_into::<IntersectionObserverEntry>()
+                            .map_or(false, |entry| entry.is_intersecting())
+                    });
+                    if visible {
+                        onvisible.emit(())
+                    }
+                }) as Box<dyn Fn(js_sys::Array)>);
+
+                let mut options = IntersectionObserverInit::new();
+                options.root_margin(margin);
+                let observer = match IntersectionObserver::new_with_options(
+                    callback.as_ref().unchecked_ref(),
+                    &options,
+                ) {
+                    Ok(observer) => Some(observer),
+                    Err(e) => {
+                        error!("unable to observe the sentinel: {:?}", e);
+                        None
+                    }
+                };
+                if let (Some(observer), Some(element)) = (observer.as_ref(), node.cast::<Element>())
+                {
+                    observer.observe(&element);
+                }
+
+                move || {
+                    if let Some(observer) = observer {
+                        observer.disconnect();
+                    }
+                    drop(callback);
+                }
+            },
+            (),
+        );
+    }
+
+    html! {
+        <div class="sentinel" ref={ node }></div>
+    }
+}
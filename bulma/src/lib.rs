@@ -5,6 +5,7 @@ use web_sys::{Document, Element, KeyboardEvent, NodeList};
 
 pub mod carousel;
 pub mod collapsible;
+pub mod components;
 pub mod toast;
 
 pub fn add_modals(document: &Document) {
@@ -178,6 +179,31 @@ pub fn add_navigation_listeners(document: &Document) {
     }
 }
 
+#[derive(Clone, PartialEq)]
+pub enum Color {
+    Primary,
+    Link,
+    Info,
+    Success,
+    Warning,
+    Danger,
+    Custom(String),
+}
+
+impl Color {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Color::Primary => "is-primary",
+            Color::Link => "is-link",
+            Color::Info => "is-info",
+            Color::Success => "is-success",
+            Color::Warning => "is-warning",
+            Color::Danger => "is-danger",
+            Color::Custom(class) => class.as_str(),
+        }
+    }
+}
+
 pub trait ElementList {
     fn to_list<T: AsRef<Element> + JsCast>(self) -> Vec<T>;
 }
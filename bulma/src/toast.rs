@@ -1,5 +1,8 @@
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+pub use crate::Color;
 
 pub fn toast(
     message: String,
@@ -12,6 +15,7 @@ pub fn toast(
     opacity: Option<f32>,
     animate: Option<Animate>,
     extra_classes: Option<String>,
+    action: Option<Action>,
 ) {
     let options = Options {
         message,
@@ -25,7 +29,48 @@ pub fn toast(
         animate,
         extra_classes,
     };
-    default::toast(JsValue::from_serde(&options).expect("could not serialise options"));
+    let js_options = JsValue::from_serde(&options).expect("could not serialise options");
+    if let Some(action) = action {
+        // Replace the message with an element containing both the message and the action
+        match action_message(&options.message, action) {
+            Ok(message) => {
+                let _ = js_sys::Reflect::set(&js_options, &JsValue::from_str("message"), &message);
+            }
+            Err(e) => gloo_console::error!(e),
+        }
+    }
+    default::toast(js_options);
+}
+
+/// An action offered alongside the message of a toast (e.g. undo), with the callback invoked when clicked.
+pub struct Action {
+    pub label: String,
+    pub callback: Box<dyn FnOnce()>,
+}
+
+fn action_message(message: &str, action: Action) -> Result<web_sys::Element, JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("could not get document"))?;
+
+    let container = document.create_element("div")?;
+    container.set_class_name("toast-action");
+    let text = document.create_element("span")?;
+    text.set_text_content(Some(message));
+    container.append_child(&text)?;
+
+    let button = document
+        .create_element("button")?
+        .dyn_into::<web_sys::HtmlElement>()?;
+    button.set_class_name("button is-small is-light");
+    button.set_text_content(Some(&action.label));
+    let callback = action.callback;
+    button.set_onclick(Some(
+        Closure::once_into_js(move || callback()).unchecked_ref(),
+    ));
+    container.append_child(&button)?;
+
+    Ok(container)
 }
 
 #[derive(Serialize)]
@@ -36,30 +81,6 @@ pub struct Animate {
     pub out: String,
 }
 
-pub enum Color {
-    Primary,
-    Link,
-    Info,
-    Success,
-    Warning,
-    Danger,
-    Custom(String),
-}
-
-impl Color {
-    fn as_str(&self) -> &str {
-        match self {
-            Color::Primary => "is-primary",
-            Color::Link => "is-link",
-            Color::Info => "is-info",
-            Color::Success => "is-success",
-            Color::Warning => "is-warning",
-            Color::Danger => "is-danger",
-            Color::Custom(class) => class.as_str(),
-        }
-    }
-}
-
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Options<'a> {
@@ -0,0 +1,117 @@
+//! Generates a page with Open Graph tags for each notable collection, so that links shared to chat apps show a
+//! preview. Run by trunk once built where `OPEN_GRAPH` is set (see `Trunk.toml`), with each page served in place of the
+//! collection route and redirecting to the app via its hash-based route.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io};
+
+#[path = "../../src/config/notable.rs"]
+mod notable;
+
+/// The url at which the site is hosted, overridden via the `SITE_URL` environment variable.
+const SITE_URL: &str = "https://niftygallery.evilrobot.industries";
+const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+fn main() -> io::Result<()> {
+    // Written to the staging directory when run by trunk, otherwise the default output directory
+    let dir = PathBuf::from(env::var("TRUNK_STAGING_DIR").unwrap_or_else(|_| "dist".to_string()));
+    let site = env::var("SITE_URL").unwrap_or_else(|_| SITE_URL.to_string());
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(10))
+        .build();
+    // Pages are still generated whilst offline, albeit without their preview images
+    let online = match agent.head(IPFS_GATEWAY).call() {
+        Err(ureq::Error::Transport(e)) => {
+            eprintln!("unable to reach the network ({e}), so no preview images are included");
+            false
+        }
+        _ => true,
+    };
+
+    for (name, address, base_uri, total_supply) in notable::COLLECTIONS {
+        let image = online.then(|| image(&agent, base_uri)).flatten();
+        if online && image.is_none() {
+            eprintln!("no preview image could be found for {name}");
+        }
+        let page = page(&site, name, address, *total_supply, image.as_deref());
+        write(&dir.join("c").join(address), &page)?;
+    }
+    println!(
+        "generated pages for {} collections",
+        notable::COLLECTIONS.len()
+    );
+    Ok(())
+}
+
+/// The image of the first token of the collection, used as its preview.
+fn image(agent: &ureq::Agent, base_uri: &str) -> Option<String> {
+    [0, 1].iter().find_map(|token| {
+        let response = agent
+            .get(&format!("{base_uri}{token}"))
+            .call()
+            .ok()?
+            .into_string()
+            .ok()?;
+        let metadata: serde_json::Value = serde_json::from_str(&response).ok()?;
+        let image = metadata.get("image")?.as_str()?;
+        Some(match image.strip_prefix("ipfs://") {
+            Some(path) => format!("{IPFS_GATEWAY}{}", path.trim_start_matches("ipfs/")),
+            None => image.to_string(),
+        })
+    })
+}
+
+fn page(
+    site: &str,
+    name: &str,
+    address: &str,
+    total_supply: Option<u32>,
+    image: Option<&str>,
+) -> String {
+    let name = escape(name);
+    let description = match total_supply {
+        Some(total_supply) => {
+            format!("Explore all {total_supply} tokens of {name} on Nifty Gallery")
+        }
+        None => format!("Explore the tokens of {name} on Nifty Gallery"),
+    };
+    let image = image.map_or(String::new(), |image| {
+        format!(
+            "\n    <meta property=\"og:image\" content=\"{}\" />",
+            escape(image)
+        )
+    });
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <title>{name} | Nifty Gallery</title>
+    <meta property="og:type" content="website" />
+    <meta property="og:site_name" content="Nifty Gallery" />
+    <meta property="og:title" content="{name}" />
+    <meta property="og:description" content="{description}" />
+    <meta property="og:url" content="{site}/c/{address}" />{image}
+    <meta name="twitter:card" content="summary_large_image" />
+    <script type="text/javascript">window.location.replace("/#/c/{address}" + window.location.search);</script>
+    <noscript><meta http-equiv="Refresh" content="0; url='/#/c/{address}'" /></noscript>
+</head>
+<body>
+</body>
+</html>
+"#
+    )
+}
+
+fn write(dir: &Path, page: &str) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("index.html"), page)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
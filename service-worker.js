@@ -0,0 +1,35 @@
+// Caches the app shell, so that the app (along with any collections already indexed, which are held within the
+// browser) can be browsed offline. Written in JavaScript rather than as a worker binary, as the handlers of a service
+// worker must be registered whilst the script is first evaluated, ahead of any WebAssembly being instantiated.
+const CACHE = "nifty-gallery-shell";
+// The page served for any route whilst offline, as every route is rendered by the app
+const SHELL = "/index.html";
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.add(SHELL)).then(() => self.skipWaiting()));
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(self.clients.claim());
+});
+
+// Requests of the app itself (the page, bundles, worker scripts and assets) are made via the network where available,
+// refreshing the cached copies so that updates are picked up, and otherwise served from the cache
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    if (request.method !== "GET" || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+    const key = request.mode === "navigate" ? SHELL : request;
+    event.respondWith(
+        fetch(request)
+            .then((response) => {
+                if (response.ok) {
+                    const copy = response.clone();
+                    event.waitUntil(caches.open(CACHE).then((cache) => cache.put(key, copy)));
+                }
+                return response;
+            })
+            .catch(() => caches.match(key).then((cached) => cached || Response.error()))
+    );
+});
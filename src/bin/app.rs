@@ -1,7 +1,8 @@
 fn main() {
-    console_error_panic_hook::set_once();
+    // Panics are recorded as errors (shown on the debug page) before being written to the console
+    std::panic::set_hook(Box::new(nifty_gallery::logging::panicked));
 
-    wasm_logger::init(wasm_logger::Config::new(log::Level::Trace));
+    nifty_gallery::logging::init();
     yew::start_app::<nifty_gallery::App>();
     log::trace!("app started");
 }
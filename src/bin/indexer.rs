@@ -0,0 +1,10 @@
+use workers::PublicWorker;
+
+fn main() {
+    console_error_panic_hook::set_once();
+
+    wasm_logger::init(wasm_logger::Config::new(log::Level::Trace));
+    log::trace!("starting indexer worker...");
+    workers::indexer::Worker::register();
+    log::trace!("indexer worker started");
+}
@@ -0,0 +1,7 @@
+//! The current time per the clock of the app thread, which can be replaced via [workers::clock] so that timestamps are
+//! deterministic.
+use chrono::{DateTime, TimeZone, Utc};
+
+pub fn now() -> DateTime<Utc> {
+    Utc.timestamp_millis(workers::clock::now() as i64)
+}
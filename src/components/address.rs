@@ -1,9 +1,12 @@
+use crate::components::holdings::Holdings;
 use crate::storage::Get;
-use crate::{storage, Route};
+use crate::{models, storage, Route};
+use bulma::components::Message;
+use bulma::Color;
 use std::rc::Rc;
 use std::str::FromStr;
-use workers::etherscan::{Contract, Request, Response, TypeExtensions};
-use workers::{Bridge, Bridged};
+use workers::etherscan::{Contract, Holding, Request, Response, TypeExtensions};
+use workers::{ens, Bridge, Bridged};
 use yew::prelude::*;
 use yew_router::prelude::*;
 
@@ -12,15 +15,22 @@ const THROTTLE_SECONDS: u64 = 5;
 pub struct Address {
     worker: Box<dyn Bridge<workers::etherscan::Worker>>,
     status: Option<String>,
+    holdings: Option<Rc<Vec<Holding>>>,
+    /// The address, once validated or resolved from an ENS name.
+    address: Option<workers::etherscan::Address>,
 }
 
 pub enum AddressMsg {
     CheckAddressType(workers::etherscan::Address),
     Contract(Contract),
     NoContract(workers::etherscan::Address),
+    Holdings(workers::etherscan::Address, Vec<Holding>),
+    HoldingsFailed,
     InvalidAddress(String),
-    // ResolveUri(models::Collection),
-    // UriResolved(UriType, String, models::Collection),
+    // ENS
+    Name(String, workers::etherscan::Address),
+    NoName(String),
+    NameFailed(String),
 }
 
 #[derive(PartialEq, Properties)]
@@ -34,34 +44,50 @@ impl Component for Address {
     type Properties = AddressProps;
 
     fn create(ctx: &Context<Self>) -> Self {
-        // Validate address
+        // Validate address, resolving any ENS name
         let mut address = None;
+        let mut status = None;
         match crate::Address::from_str(&ctx.props().address) {
             Ok(a) => {
                 address = Some(a);
                 ctx.link().send_message(AddressMsg::CheckAddressType(a));
             }
+            Err(_) if ens::is_name(&ctx.props().address) => {
+                status = Some(format!("Resolving {} via ENS...", ctx.props().address));
+            }
             Err(_) => {
                 ctx.link()
                     .send_message(AddressMsg::InvalidAddress(ctx.props().address.clone()));
             }
         }
 
-        Self {
-            worker: workers::etherscan::Worker::bridge(Rc::new({
-                let link = ctx.link().clone();
-                move |e: workers::etherscan::Response| match e {
-                    Response::Contract(contract) => {
-                        log::trace!("contract found");
-                        link.send_message(Self::Message::Contract(contract))
-                    }
-                    Response::NoContract(address) => {
-                        link.send_message(Self::Message::NoContract(address))
-                    }
-                    _ => {}
+        let mut worker = workers::etherscan::Worker::bridge(Rc::new({
+            let link = ctx.link().clone();
+            move |e: workers::etherscan::Response| match e {
+                Response::Contract(contract) => {
+                    log::trace!("contract found");
+                    link.send_message(AddressMsg::Contract(contract))
                 }
-            })),
-            status: None,
+                Response::NoContract(address) => link.send_message(AddressMsg::NoContract(address)),
+                Response::Holdings(address, holdings) => {
+                    link.send_message(AddressMsg::Holdings(address, holdings))
+                }
+                Response::HoldingsFailed(_) => link.send_message(AddressMsg::HoldingsFailed),
+                Response::Name(name, address) => link.send_message(AddressMsg::Name(name, address)),
+                Response::NoName(name) => link.send_message(AddressMsg::NoName(name)),
+                Response::NameFailed(name) => link.send_message(AddressMsg::NameFailed(name)),
+                _ => {}
+            }
+        }));
+        if status.is_some() {
+            worker.send(Request::Name(ctx.props().address.trim().to_lowercase()));
+        }
+
+        Self {
+            worker,
+            status,
+            holdings: None,
+            address,
         }
     }
 
@@ -73,8 +99,11 @@ impl Component for Address {
                 let id = TypeExtensions::format(&address);
                 if let Some(_) = storage::Collection::get(id.as_str()) {
                     log::trace!("switching to collection...");
-                    // Switch to collection view
-                    ctx.link().history().unwrap().push(Route::Collection { id });
+                    // Switch to collection view, replacing the address so that navigating back does not redirect again
+                    ctx.link()
+                        .history()
+                        .unwrap()
+                        .replace(Route::Collection { id });
                     return false;
                 }
 
@@ -87,88 +116,76 @@ impl Component for Address {
                 false
             }
             AddressMsg::Contract(contract) => {
-                let address = TypeExtensions::format(&contract.address);
-                log::trace!("address {address} is a contract, switching to collection...");
+                // Store the collection, so that the collection view resolves its uri without re-requesting the contract
+                let id = models::Collection::contract_id(contract.chain, &contract.address);
+                if storage::Collection::get(id.as_str()).is_none() {
+                    storage::Collection::store(&models::Collection::Contract {
+                        address: contract.address,
+                        name: contract.name,
+                        base_uri: None,
+                        start_token: 0,
+                        total_supply: None,
+                        last_viewed: None,
+                        overrides: Default::default(),
+                        position: Default::default(),
+                        chain: contract.chain,
+                    });
+                }
+
+                log::trace!("address {id} is a contract, switching to collection...");
                 ctx.link()
                     .history()
                     .unwrap()
-                    .push(Route::Collection { id: address });
-
-                // self.status = Some(format!(
-                //     "Contract for {} found, resolving collection uri...",
-                //     &contract.contract_name
-                // ));
-                // self.name = Some(contract.contract_name.clone());
-                // self.abi = Some(contract.abi);
-                // ctx.link()
-                //     .send_message(AddressMsg::ResolveUri(models::Collection {
-                //         name: contract.contract_name,
-                //         address: Some(TypeExtensions::format(&self.address)),
-                //         start_token: 0,
-                //     }));
-                true
+                    .replace(Route::Collection { id });
+                false
             }
             AddressMsg::NoContract(address) => {
+                // Not a contract, so assume a wallet and request its holdings
+                self.worker.send(Request::Holdings(address));
                 self.status = Some(format!(
-                    "No contract found for {address}. Stay tuned for wallet address support",
+                    "Retrieving the tokens held by {address} via etherscan.io..."
                 ));
                 true
             }
-            // AddressMsg::ResolveUri(collection) => {
-            //     let api_key = ctx
-            //         .props()
-            //         .api_key
-            //         .as_ref()
-            //         .map_or("".to_string(), |k| k.clone());
-            //     let address = self.address;
-            //     let throttle = ctx.props().api_key.as_ref().map_or(THROTTLE_SECONDS, |_| 0);
-            //     if let Some(function) = self.prepare_call() {
-            //         ctx.link().send_future(async move {
-            //             {
-            //                 sleep(Duration::from_secs(throttle)).await;
-            //                 match Address::call(api_key, address, function).await {
-            //                     None => AddressMsg::NoContract,
-            //                     Some((uri_type, uri)) => {
-            //                         AddressMsg::UriResolved(uri_type, uri, collection)
-            //                     }
-            //                 }
-            //             }
-            //         });
-            //     }
-            //
-            //     false
-            // }
-            // AddressMsg::UriResolved(uri_type, uri, collection) => {
-            //     // Encode uri before passing to route
-            //     let token_uri = crate::uri::TokenUri::parse(&uri, true).unwrap();
-            //     let route = match uri_type {
-            //         UriType::BaseUri => Route::CollectionToken {
-            //             uri: token_uri.uri.clone(),
-            //             token: 0,
-            //         },
-            //         UriType::TokenUri => match token_uri.token {
-            //             None => Route::Token {
-            //                 uri: token_uri.uri.clone(),
-            //             },
-            //             Some(token) => Route::CollectionToken {
-            //                 uri: token_uri.uri.clone(),
-            //                 token,
-            //             },
-            //         },
-            //     };
-            //
-            //     // Cache collection
-            //     if cache::Collection::get(&token_uri.uri).is_none() {
-            //         cache::Collection::insert(token_uri.uri, collection);
-            //     }
-            //
-            //     ctx.link().history().unwrap().push(route);
-            //     false
-            // }
+            AddressMsg::Holdings(address, holdings) => {
+                if self.address != Some(address) {
+                    return false;
+                }
+                self.status = holdings
+                    .is_empty()
+                    .then(|| format!("No tokens are held by {address}."));
+                self.holdings = Some(Rc::new(holdings));
+                true
+            }
+            AddressMsg::HoldingsFailed => {
+                self.status = Some(
+                    "Unable to retrieve the tokens held by the address via etherscan.io. Please try again..."
+                        .to_string(),
+                );
+                true
+            }
             AddressMsg::InvalidAddress(address) => {
                 self.status = Some(format!("The value of {address} is not a valid address.",));
                 true
             }
+            // ENS
+            AddressMsg::Name(name, address) => {
+                log::trace!("{name} resolved to {address}");
+                self.address = Some(address);
+                ctx.link()
+                    .send_message(AddressMsg::CheckAddressType(address));
+                false
+            }
+            AddressMsg::NoName(name) => {
+                self.status = Some(format!("No address is set for {name}."));
+                true
+            }
+            AddressMsg::NameFailed(name) => {
+                self.status = Some(format!(
+                    "Unable to resolve {name} via etherscan.io. Please try again..."
+                ));
+                true
+            }
         }
     }
 
@@ -176,21 +193,21 @@ impl Component for Address {
         html! {
             <section class="section is-fullheight">
             if let Some(status) = &self.status {
-                <article class="message is-success">
-                    <div class="message-body">
-                        { status }
-                    </div>
-                </article>
+                <Message color={ Color::Success } icon="fas fa-circle-info">
+                    { status }
+                </Message>
 
                 if let None = ctx.props().api_key {
-                    <article class="message is-danger">
-                        <div class="message-body">
-                            { format!("Note: No API key has been configured for the etherscan.io API. Requests are \
-                            therefore throttled to a single request every {THROTTLE_SECONDS} seconds.") }
-                        </div>
-                    </article>
+                    <Message color={ Color::Danger } icon="fas fa-triangle-exclamation" dismissible=true>
+                        { format!("Note: No API key has been configured for the etherscan.io API. Requests are \
+                        therefore throttled to a single request every {THROTTLE_SECONDS} seconds.") }
+                    </Message>
                 }
             }
+            if let Some(holdings) = &self.holdings {
+                <h1 class="title nifty-name">{ &ctx.props().address }</h1>
+                <Holdings holdings={ holdings.clone() } />
+            }
             </section>
         }
     }
@@ -0,0 +1,613 @@
+use crate::{config, models, notifications, storage, uri};
+use std::collections::HashSet;
+use thousands::Separable;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use workers::metadata::Metadata;
+use yew::prelude::*;
+
+/// The number of images downloaded at once whilst archiving.
+const CONCURRENCY: usize = 4;
+/// The number of images downloaded to estimate the size of the images of the collection.
+const SAMPLE: usize = 3;
+/// The number of images archived between writes of the state of the archive to storage.
+const SAVE_INTERVAL: usize = 25;
+
+/// Archives a collection for offline viewing, downloading the image of each token as it is indexed. The size of the
+/// archive is estimated ahead of starting, with the content of each token hashed so that the archive can be verified.
+pub struct Archive {
+    archive: Option<models::Archive>,
+    /// The estimated size of the archive, whilst confirming that the collection is to be archived.
+    estimate: Option<Option<Estimate>>,
+    downloading: HashSet<u32>,
+    /// The number of images archived since the archive was last stored.
+    unsaved: usize,
+    /// Whether storage is full, at which point archiving stops.
+    full: bool,
+    /// Whether the archive is being verified, along with the outcome once verified.
+    verifying: bool,
+    verification: Option<Verification>,
+}
+
+pub enum Message {
+    /// Estimates the size of the archive, ahead of confirming.
+    Estimate,
+    Estimated(Estimate),
+    Cancel,
+    Start,
+    /// Downloads the images of any indexed tokens not yet archived.
+    Next,
+    /// The image of the token has been archived (with its size in bytes and the hashes of its content), or could not
+    /// be downloaded.
+    Archived(u32, Option<(u64, models::Hashes)>),
+    Full,
+    Remove,
+    Removed,
+    /// Verifies the archived copies against the hashes taken when archived, or the images served by the source where
+    /// specified.
+    Verify(bool),
+    Verified(Verification),
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    /// The collection identifier.
+    pub collection: String,
+    /// The number of tokens indexed, along with the total supply where known.
+    pub indexed: usize,
+    pub total_supply: Option<u32>,
+    /// Whether indexing of the collection has completed.
+    pub completed: bool,
+}
+
+/// The outcome of verifying an archive.
+pub struct Verification {
+    /// Whether the images served by the source were verified, rather than the archived copies.
+    source: bool,
+    verified: usize,
+    /// The tokens whose metadata or image no longer matches that archived.
+    changed: Vec<u32>,
+    /// The tokens whose archived copies (or images from the source) could not be read.
+    unavailable: Vec<u32>,
+}
+
+/// The estimated size of the archive of a collection, in bytes.
+pub struct Estimate {
+    tokens: usize,
+    metadata: u64,
+    images: Option<u64>,
+    /// The storage remaining available to the site, where reported by the browser.
+    available: Option<u64>,
+}
+
+impl Component for Archive {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let archive = storage::Archive::get(&ctx.props().collection);
+        // Resume any archiving in progress
+        if archive.as_ref().map_or(false, |a| a.completed.is_none()) {
+            ctx.link().send_message(Message::Next);
+        }
+        Self {
+            archive,
+            estimate: None,
+            downloading: HashSet::new(),
+            unsaved: 0,
+            full: false,
+            verifying: false,
+            verification: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Estimate => {
+                self.estimate = Some(None);
+                let collection = ctx.props().collection.clone();
+                let tokens = ctx
+                    .props()
+                    .total_supply
+                    .map_or(ctx.props().indexed, |total_supply| total_supply as usize);
+                ctx.link().send_future(async move {
+                    Message::Estimated(estimate(&collection, tokens).await)
+                });
+                true
+            }
+            Message::Estimated(estimate) => {
+                // Ignore any estimate completing once cancelled
+                if self.estimate.is_none() {
+                    return false;
+                }
+                self.estimate = Some(Some(estimate));
+                true
+            }
+            Message::Cancel => {
+                self.estimate = None;
+                true
+            }
+            Message::Start => {
+                log::info!(collection = ctx.props().collection.as_str(); "archiving collection...");
+                self.estimate = None;
+                self.full = false;
+                let archive = models::Archive::start();
+                storage::Archive::store(&ctx.props().collection, &archive);
+                self.archive = Some(archive);
+                ctx.link().send_message(Message::Next);
+                true
+            }
+            Message::Next => {
+                let archive = match self.archive.as_mut() {
+                    Some(archive) if archive.completed.is_none() && !self.full => archive,
+                    _ => return false,
+                };
+                let collection = ctx.props().collection.as_str();
+                let pending: Vec<u32> = storage::Token::ids(collection)
+                    .into_iter()
+                    .filter(|token| !archive.contains(*token) && !self.downloading.contains(token))
+                    .take(CONCURRENCY.saturating_sub(self.downloading.len()))
+                    .collect();
+
+                // Completed once every token has been indexed and its image archived
+                if pending.is_empty() && self.downloading.is_empty() {
+                    if ctx.props().completed {
+                        archive.completed = Some(crate::clock::now());
+                        storage::Archive::store(collection, archive);
+                        self.unsaved = 0;
+                        log::info!(collection = collection; "collection archived");
+                        notifications::notify(
+                            "Collection archived, and can now be viewed offline".to_string(),
+                            Some(notifications::Color::Success),
+                        );
+                        return true;
+                    }
+                    return false;
+                }
+
+                for token in pending {
+                    let metadata = match storage::Token::get(collection, token)
+                        .and_then(|token| token.metadata)
+                        .filter(|metadata| !metadata.image.is_empty())
+                    {
+                        Some(metadata) => metadata,
+                        None => {
+                            archive.failed.insert(token);
+                            continue;
+                        }
+                    };
+                    self.downloading.insert(token);
+                    let collection = collection.to_string();
+                    ctx.link()
+                        .send_future(async move { download(collection, token, metadata).await });
+                }
+                // Continue past any tokens without an image
+                if self.downloading.is_empty() {
+                    ctx.link().send_message(Message::Next);
+                }
+                true
+            }
+            Message::Archived(token, size) => {
+                if !self.downloading.remove(&token) {
+                    return false;
+                }
+                if let Some(archive) = self.archive.as_mut() {
+                    match size {
+                        Some((size, hashes)) => {
+                            archive.archived.insert(token);
+                            archive.bytes += size;
+                            archive.hashes.insert(token, hashes);
+                        }
+                        None => {
+                            log::warn!(collection = ctx.props().collection.as_str(), token = token;
+                                "unable to archive the image");
+                            archive.failed.insert(token);
+                        }
+                    }
+                    self.unsaved += 1;
+                    if self.unsaved >= SAVE_INTERVAL {
+                        storage::Archive::store(&ctx.props().collection, archive);
+                        self.unsaved = 0;
+                    }
+                }
+                ctx.link().send_message(Message::Next);
+                true
+            }
+            Message::Full => {
+                if !self.full {
+                    self.full = true;
+                    notifications::notify(
+                        "Storage is full: archiving has stopped. Free up some space for this site within the browser to continue.".to_string(),
+                        Some(notifications::Color::Danger),
+                    );
+                }
+                self.downloading.clear();
+                self.save(ctx);
+                true
+            }
+            Message::Remove => {
+                self.archive = None;
+                self.downloading.clear();
+                self.unsaved = 0;
+                let collection = ctx.props().collection.clone();
+                ctx.link().send_future(async move {
+                    storage::Archive::remove(&collection).await;
+                    Message::Removed
+                });
+                true
+            }
+            Message::Removed => {
+                notifications::notify("Archive removed".to_string(), None);
+                false
+            }
+            Message::Verify(source) => {
+                let archive = match self.archive.as_ref() {
+                    Some(archive) if !self.verifying => archive.clone(),
+                    _ => return false,
+                };
+                self.verifying = true;
+                self.verification = None;
+                let collection = ctx.props().collection.clone();
+                ctx.link().send_future(async move {
+                    Message::Verified(verify(&collection, &archive, source).await)
+                });
+                true
+            }
+            Message::Verified(verification) => {
+                self.verifying = false;
+                let (message, color) =
+                    match verification.changed.len() + verification.unavailable.len() {
+                        0 => (
+                            format!(
+                                "{} tokens verified",
+                                verification.verified.separate_with_commas()
+                            ),
+                            notifications::Color::Success,
+                        ),
+                        _ => (
+                            format!(
+                                "{} tokens verified, {} changed and {} unavailable",
+                                verification.verified.separate_with_commas(),
+                                verification.changed.len().separate_with_commas(),
+                                verification.unavailable.len().separate_with_commas()
+                            ),
+                            notifications::Color::Warning,
+                        ),
+                    };
+                log::info!(collection = ctx.props().collection.as_str(), changed = verification.changed.len();
+                    "archive verified");
+                notifications::notify(message, Some(color));
+                self.verification = Some(verification);
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        // Archive any further tokens indexed
+        ctx.link().send_message(Message::Next);
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let tokens = ctx
+            .props()
+            .total_supply
+            .map_or(ctx.props().indexed, |total_supply| total_supply as usize);
+        html! {
+            <>
+            if let Some(archive) = self.archive.as_ref() {
+                if archive.completed.is_some() {
+                    <div class="field has-addons">
+                        <div class="control">
+                            <span class="button is-static">
+                                <span class="icon is-small has-tooltip-bottom"
+                                    data-tooltip={ format!("Archived ({})", size(archive.bytes)) }>
+                                    <i class="fa-solid fa-box-archive"></i>
+                                </span>
+                                <span>{ "Offline" }</span>
+                            </span>
+                        </div>
+                        <div class="control">
+                            <button onclick={ ctx.link().callback(|_| Message::Verify(false)) } class="button"
+                                disabled={ self.verifying }>
+                                <span class="icon is-small has-tooltip-bottom" data-tooltip="Verify Archive">
+                                    <i class="fa-solid fa-shield-halved"></i>
+                                </span>
+                            </button>
+                        </div>
+                        <div class="control">
+                            <button onclick={ ctx.link().callback(|_| Message::Verify(true)) } class="button"
+                                disabled={ self.verifying }>
+                                <span class="icon is-small has-tooltip-bottom" data-tooltip="Re-check Against Source">
+                                    <i class="fa-solid fa-cloud-arrow-down"></i>
+                                </span>
+                            </button>
+                        </div>
+                        <div class="control">
+                            <button onclick={ ctx.link().callback(|_| Message::Remove) } class="button">
+                                <span class="icon is-small has-tooltip-bottom" data-tooltip="Remove Archive">
+                                    <i class="fa-solid fa-trash-can"></i>
+                                </span>
+                            </button>
+                        </div>
+                    </div>
+                    if self.verifying {
+                        <i class="is-loading ml-2"></i>
+                    } else if let Some(verification) = self.verification.as_ref() {
+                        <span class={ classes!("tag", "ml-2", if verification.changed.is_empty() { "is-success" } else { "is-warning" }) }
+                            title={ format!("Changed: {:?}", verification.changed) }>
+                            { format!("{} {} / {} changed", if verification.source { "Source" } else { "Archive" },
+                                verification.changed.len().separate_with_commas(),
+                                (verification.verified + verification.changed.len()).separate_with_commas()) }
+                        </span>
+                    }
+                } else {
+                    <span class="tag is-info is-light is-medium">
+                        <span class="icon"><i class="fa-solid fa-box-archive"></i></span>
+                        <span>
+                            { format!("Archiving {} / {} ({})", archive.archived.len().separate_with_commas(),
+                                tokens.separate_with_commas(), size(archive.bytes)) }
+                        </span>
+                        <button onclick={ ctx.link().callback(|_| Message::Remove) } class="delete is-small"></button>
+                    </span>
+                }
+            } else {
+                <button onclick={ ctx.link().callback(|_| Message::Estimate) } class="button">
+                    <span class="icon is-small has-tooltip-bottom" data-tooltip="Archive for Offline Viewing">
+                        <i class="fa-solid fa-box-archive"></i>
+                    </span>
+                </button>
+            }
+            if let Some(estimate) = self.estimate.as_ref() {
+                <div class="modal is-active">
+                    <div class="modal-background" onclick={ ctx.link().callback(|_| Message::Cancel) }></div>
+                    <div class="modal-card">
+                        <header class="modal-card-head">
+                            <p class="modal-card-title">{ "Archive Collection" }</p>
+                            <button onclick={ ctx.link().callback(|_| Message::Cancel) } class="delete" aria-label="close"></button>
+                        </header>
+                        <section class="modal-card-body">
+                            <p class="block">{ "Archiving indexes the metadata of every token of the collection and \
+                            downloads its image, so that the collection can be viewed offline." }</p>
+                            if let Some(estimate) = estimate {
+                                <table class="table is-fullwidth">
+                                    <tbody>
+                                        <tr><td>{ "Tokens" }</td><td>{ estimate.tokens.separate_with_commas() }</td></tr>
+                                        <tr><td>{ "Metadata" }</td><td>{ format!("about {}", size(estimate.metadata)) }</td></tr>
+                                        <tr>
+                                            <td>{ "Images" }</td>
+                                            <td>{ estimate.images.map_or("unknown".to_string(), |images| format!("about {}", size(images))) }</td>
+                                        </tr>
+                                        if let Some(available) = estimate.available {
+                                            <tr><td>{ "Storage available" }</td><td>{ size(available) }</td></tr>
+                                        }
+                                    </tbody>
+                                </table>
+                                if estimate.exceeds_available() {
+                                    <p class="help is-danger">{ "The archive is likely to exceed the storage available \
+                                    to this site." }</p>
+                                }
+                            } else {
+                                <p class="has-text-grey">{ "Estimating the size of the archive…" }</p>
+                            }
+                        </section>
+                        <footer class="modal-card-foot">
+                            <button onclick={ ctx.link().callback(|_| Message::Start) } class="button is-primary"
+                                disabled={ estimate.is_none() }>{ "Archive" }</button>
+                            <button onclick={ ctx.link().callback(|_| Message::Cancel) } class="button">{ "Cancel" }</button>
+                        </footer>
+                    </div>
+                </div>
+            }
+            </>
+        }
+    }
+
+    fn destroy(&mut self, ctx: &Context<Self>) {
+        // Archiving resumes once the collection is next viewed
+        self.save(ctx);
+    }
+}
+
+impl Archive {
+    /// Stores the state of the archive, where changed since last stored.
+    fn save(&mut self, ctx: &Context<Self>) {
+        if let Some(archive) = self.archive.as_ref().filter(|_| self.unsaved > 0) {
+            storage::Archive::store(&ctx.props().collection, archive);
+            self.unsaved = 0;
+        }
+    }
+}
+
+impl Estimate {
+    fn exceeds_available(&self) -> bool {
+        self.available.map_or(false, |available| {
+            self.metadata + self.images.unwrap_or_default() > available
+        })
+    }
+}
+
+/// Estimates the size of the archive from the average size of the metadata of the tokens indexed, and of a sample of
+/// their images.
+async fn estimate(collection: &str, tokens: usize) -> Estimate {
+    let indexed = storage::Token::all(collection);
+    let metadata: Vec<usize> = indexed
+        .iter()
+        .filter_map(|token| token.metadata.as_ref())
+        .filter_map(|metadata| serde_json::to_vec(metadata).ok())
+        .map(|json| json.len())
+        .collect();
+    let metadata = match metadata.len() {
+        0 => 0,
+        count => metadata.iter().sum::<usize>() as u64 / count as u64 * tokens as u64,
+    };
+
+    let mut images = Vec::new();
+    for image in indexed
+        .iter()
+        .filter_map(|token| token.metadata.as_ref())
+        .map(|metadata| uri::resolve(&metadata.image))
+        .take(SAMPLE)
+    {
+        if let Some((bytes, _)) = fetch(&image).await {
+            images.push(bytes.len() as u64);
+        }
+    }
+    let images = (!images.is_empty())
+        .then(|| images.iter().sum::<u64>() / images.len() as u64 * tokens as u64);
+
+    Estimate {
+        tokens,
+        metadata,
+        images,
+        available: available().await,
+    }
+}
+
+/// The storage remaining available to the site, as estimated by the browser.
+async fn available() -> Option<u64> {
+    let promise = web_sys::window()?.navigator().storage().estimate().ok()?;
+    let estimate = JsFuture::from(promise).await.ok()?;
+    let value = |key: &str| {
+        js_sys::Reflect::get(&estimate, &JsValue::from_str(key))
+            .ok()
+            .and_then(|value| value.as_f64())
+    };
+    Some((value("quota")? - value("usage").unwrap_or_default()).max(0.0) as u64)
+}
+
+/// Downloads the image of the token into the archive, hashing it along with the metadata of the token.
+async fn download(collection: String, token: u32, metadata: Metadata) -> Message {
+    let (bytes, content_type) = match fetch(&uri::resolve(&metadata.image)).await {
+        Some(image) => image,
+        None => return Message::Archived(token, None),
+    };
+    let hashes = match hashes(&metadata, &bytes).await {
+        Some(hashes) => hashes,
+        None => return Message::Archived(token, None),
+    };
+    let mut options = web_sys::BlobPropertyBag::new();
+    if let Some(content_type) = content_type.as_deref() {
+        options.type_(content_type);
+    }
+    let blob = match web_sys::Blob::new_with_u8_array_sequence_and_options(
+        &js_sys::Array::of1(&js_sys::Uint8Array::from(bytes.as_slice())),
+        &options,
+    ) {
+        Ok(blob) => blob,
+        Err(e) => {
+            log::error!("unable to create the image blob: {e:?}");
+            return Message::Archived(token, None);
+        }
+    };
+    match storage::Archive::store_image(&collection, token, &blob).await {
+        Ok(_) => Message::Archived(token, Some((bytes.len() as u64, hashes))),
+        Err(true) => Message::Full,
+        Err(false) => Message::Archived(token, None),
+    }
+}
+
+/// Verifies the metadata and image of each archived token against the hashes taken when archived. The images are
+/// either read from the archive, to detect any corruption of the local copies, or requested again from the source, to
+/// detect any change since archived.
+async fn verify(collection: &str, archive: &models::Archive, source: bool) -> Verification {
+    let mut verification = Verification {
+        source,
+        verified: 0,
+        changed: Vec::new(),
+        unavailable: Vec::new(),
+    };
+    for (token, archived) in archive.hashes.iter() {
+        let metadata =
+            match storage::Token::get(collection, *token).and_then(|token| token.metadata) {
+                Some(metadata) => metadata,
+                None => {
+                    verification.unavailable.push(*token);
+                    continue;
+                }
+            };
+        let image = if source {
+            fetch(&uri::resolve(&metadata.image))
+                .await
+                .map(|(bytes, _)| bytes)
+        } else {
+            match storage::Archive::image(collection, *token).await {
+                Some(blob) => JsFuture::from(blob.array_buffer())
+                    .await
+                    .ok()
+                    .map(|buffer| js_sys::Uint8Array::new(&buffer).to_vec()),
+                None => None,
+            }
+        };
+        match image {
+            Some(image) => match hashes(&metadata, &image).await {
+                Some(hashes) if hashes == *archived => verification.verified += 1,
+                Some(_) => verification.changed.push(*token),
+                None => verification.unavailable.push(*token),
+            },
+            None => verification.unavailable.push(*token),
+        }
+    }
+    verification
+}
+
+/// Hashes the metadata and image of a token.
+async fn hashes(metadata: &Metadata, image: &[u8]) -> Option<models::Hashes> {
+    Some(models::Hashes {
+        metadata: sha256(&serde_json::to_vec(metadata).ok()?).await?,
+        image: sha256(image).await?,
+    })
+}
+
+/// The SHA-256 digest of the content (as hex), using the Web Crypto api.
+async fn sha256(content: &[u8]) -> Option<String> {
+    let promise = web_sys::window()?
+        .crypto()
+        .ok()?
+        .subtle()
+        .digest_with_str_and_buffer_source("SHA-256", &js_sys::Uint8Array::from(content))
+        .map_err(|e| log::error!("unable to hash the content: {e:?}"))
+        .ok()?;
+    let digest = JsFuture::from(promise).await.ok()?;
+    Some(hex::encode(js_sys::Uint8Array::new(&digest).to_vec()))
+}
+
+/// Fetches the image along with its content type, retrying via the CORS proxy should the request fail.
+async fn fetch(image: &str) -> Option<(Vec<u8>, Option<String>)> {
+    match request(image).await {
+        Some(image) => Some(image),
+        None if !uri::is_opaque(image) => {
+            request(&format!("{}{image}", config::cors_proxy())).await
+        }
+        None => None,
+    }
+}
+
+async fn request(url: &str) -> Option<(Vec<u8>, Option<String>)> {
+    let response = gloo_net::http::Request::get(url)
+        .send()
+        .await
+        .ok()
+        .filter(|response| response.ok())?;
+    let content_type = response.headers().get("content-type");
+    Some((response.binary().await.ok()?, content_type))
+}
+
+/// Formats the size in bytes for display (e.g. `1.2 MB`).
+fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    if size < 1_000.0 {
+        return format!("{bytes} B");
+    }
+    let mut unit = "B";
+    for u in UNITS {
+        if size < 1_000.0 {
+            break;
+        }
+        size /= 1_000.0;
+        unit = u;
+    }
+    format!("{size:.1} {unit}")
+}
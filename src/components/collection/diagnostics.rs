@@ -0,0 +1,634 @@
+use crate::components::logs::Logs;
+use crate::snapshot::{Diff, Snapshot};
+use crate::storage::Get;
+use crate::{config, models, notifications, storage, Route};
+use chrono::{DateTime, Utc};
+use std::rc::Rc;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use workers::diagnostics::{Check, Status};
+use workers::metadata::Overrides;
+use workers::{diagnostics, etherscan, Bridge, Bridged};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// Runs a series of checks against a collection, reporting the results along with hints for remediation.
+pub struct Diagnostics {
+    etherscan: Box<dyn Bridge<etherscan::Worker>>,
+    worker: Box<dyn Bridge<diagnostics::Worker>>,
+    collection: Option<models::Collection>,
+    checks: Vec<Check>,
+    /// The number of checks still in progress.
+    pending: usize,
+    /// The overrides being edited, with headers as `Name: value` lines.
+    gateway: String,
+    proxy: String,
+    headers: String,
+    method: String,
+    body: String,
+    pointer: String,
+    lenient: bool,
+    /// The minimum interval between requests whilst indexing, in milliseconds.
+    interval: String,
+    /// Whether the indexed tokens of the collection have been loaded from storage, from which snapshots are taken.
+    loaded: bool,
+    /// When the snapshot of the collection was taken and the number of tokens within it, along with the differences
+    /// found when last compared.
+    snapshot: Option<(DateTime<Utc>, usize)>,
+    diff: Option<Diff>,
+    tab: Tab,
+}
+
+/// The tabs of the diagnostics: the checks of the collection, or the events logged whilst viewing it.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tab {
+    Checks,
+    Logs,
+}
+
+pub enum Message {
+    Run,
+    Tab(Tab),
+    // Contract
+    Contract(etherscan::Contract),
+    NoContract,
+    ContractFailed(u8),
+    // Total Supply
+    TotalSupply(u32),
+    NoTotalSupply,
+    TotalSupplyFailed,
+    // Metadata
+    Diagnosed(String, Vec<Check>),
+    // Overrides
+    Gateway(String),
+    Proxy(String),
+    Headers(String),
+    Method(String),
+    Body(String),
+    Pointer(String),
+    Lenient(bool),
+    Interval(String),
+    SaveOverrides,
+    // Snapshot
+    Loaded,
+    TakeSnapshot,
+    CompareSnapshot,
+    // Ignore
+    None,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    /// The collection identifier.
+    pub id: String,
+}
+
+impl Component for Diagnostics {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Message::Run);
+        ctx.link().send_future({
+            let id = ctx.props().id.clone();
+            async move {
+                storage::Token::load(&id).await;
+                Message::Loaded
+            }
+        });
+
+        Self {
+            etherscan: etherscan::Worker::bridge(Rc::new({
+                let link = ctx.link().clone();
+                move |e: etherscan::Response| {
+                    link.send_message(match e {
+                        etherscan::Response::Contract(contract) => Message::Contract(contract),
+                        etherscan::Response::NoContract(_) => Message::NoContract,
+                        etherscan::Response::ContractFailed(_, attempts) => {
+                            Message::ContractFailed(attempts)
+                        }
+                        etherscan::Response::TotalSupply(total_supply) => {
+                            Message::TotalSupply(total_supply)
+                        }
+                        etherscan::Response::NoTotalSupply(_) => Message::NoTotalSupply,
+                        etherscan::Response::TotalSupplyFailed(_) => Message::TotalSupplyFailed,
+                        _ => Message::None,
+                    })
+                }
+            })),
+            worker: diagnostics::Worker::bridge(Rc::new({
+                let link = ctx.link().clone();
+                move |e: diagnostics::Response| {
+                    link.send_message(Message::Diagnosed(e.url, e.checks))
+                }
+            })),
+            gateway: String::new(),
+            proxy: String::new(),
+            headers: String::new(),
+            method: String::new(),
+            body: String::new(),
+            pointer: String::new(),
+            lenient: false,
+            interval: String::new(),
+            collection: None,
+            checks: Vec::new(),
+            pending: 0,
+            loaded: false,
+            snapshot: storage::Snapshot::get(ctx.props().id.as_str())
+                .map(|snapshot| (snapshot.taken, snapshot.hashes.len())),
+            diff: None,
+            tab: Tab::Checks,
+        }
+        .with_collection(storage::Collection::get(ctx.props().id.as_str()))
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Run => {
+                self.checks.clear();
+                self.pending = 0;
+
+                let collection = match self.collection.as_ref() {
+                    Some(collection) => collection,
+                    None => {
+                        self.checks.push(Check::new(
+                            "Collection",
+                            Status::Fail,
+                            "The collection has not been loaded".to_string(),
+                            Some("Open the collection first, so that its details can be resolved."),
+                        ));
+                        return true;
+                    }
+                };
+
+                // Contract, followed by total supply once resolved
+                if let models::Collection::Contract { address, chain, .. } = collection {
+                    if *chain != etherscan::Chain::Ethereum {
+                        self.etherscan.send(etherscan::Request::Chain(
+                            *chain,
+                            config::api(*chain).to_string(),
+                        ));
+                    }
+                    self.etherscan.send(etherscan::Request::Contract(*address));
+                    self.pending += 1;
+                }
+
+                // Base uri and sample token
+                match collection.url(*collection.start_token()) {
+                    Some(url) => {
+                        self.checks.push(Check::new(
+                            "Base URI",
+                            Status::Pass,
+                            format!(
+                                "The metadata is served from {}",
+                                collection
+                                    .base_uri()
+                                    .as_ref()
+                                    .map_or("", |base_uri| base_uri.as_str())
+                            ),
+                            None,
+                        ));
+                        self.worker.send(diagnostics::Request {
+                            url,
+                            token: Some(*collection.start_token()),
+                            cors_proxy: Some(config::cors_proxy()),
+                            overrides: config::overrides(collection.overrides()),
+                        });
+                        self.pending += 1;
+                    }
+                    None => self.checks.push(Check::new(
+                        "Base URI",
+                        Status::Fail,
+                        "The base URI of the collection has not been resolved".to_string(),
+                        Some("The contract may not expose a known URI function (e.g. baseURI or tokenURI), or the etherscan.io API may be throttling requests."),
+                    )),
+                }
+                true
+            }
+            Message::Tab(tab) => {
+                self.tab = tab;
+                true
+            }
+            // Contract
+            Message::Contract(contract) => {
+                self.checks.push(Check::new(
+                    "Contract",
+                    Status::Pass,
+                    format!(
+                        "The verified contract source of {} was found",
+                        contract.name
+                    ),
+                    None,
+                ));
+                // Contract now cached by worker, so request total supply
+                self.etherscan
+                    .send(etherscan::Request::TotalSupply(contract.address));
+                true
+            }
+            Message::NoContract => {
+                self.checks.push(Check::new(
+                    "Contract",
+                    Status::Fail,
+                    "No verified contract source was found".to_string(),
+                    Some("The contract source must be verified on etherscan.io in order to call its functions."),
+                ));
+                self.pending -= 1;
+                true
+            }
+            Message::ContractFailed(attempts) => {
+                self.checks.push(Check::new(
+                    "Contract",
+                    Status::Fail,
+                    format!("The contract could not be retrieved after {attempts} attempts"),
+                    Some("Requests without an etherscan.io API key are throttled. Configure an API key and try again."),
+                ));
+                self.pending -= 1;
+                true
+            }
+            // Total Supply
+            Message::TotalSupply(total_supply) => {
+                self.checks.push(Check::new(
+                    "Total Supply",
+                    Status::Pass,
+                    format!("The total supply resolved to {total_supply}"),
+                    None,
+                ));
+                self.pending -= 1;
+                true
+            }
+            Message::NoTotalSupply => {
+                self.checks.push(Check::new(
+                    "Total Supply",
+                    Status::Warning,
+                    "The contract does not expose a totalSupply function".to_string(),
+                    Some("Indexing continues until tokens are no longer found, so the collection size is approximate."),
+                ));
+                self.pending -= 1;
+                true
+            }
+            Message::TotalSupplyFailed => {
+                self.checks.push(Check::new(
+                    "Total Supply",
+                    Status::Fail,
+                    "The call to totalSupply failed".to_string(),
+                    Some("The etherscan.io API may be throttling requests. Wait a while and try again."),
+                ));
+                self.pending -= 1;
+                true
+            }
+            // Metadata
+            Message::Diagnosed(url, mut checks) => {
+                // Ignore any results which dont pertain to the current collection
+                if self
+                    .collection
+                    .as_ref()
+                    .and_then(|c| c.url(*c.start_token()))
+                    .map_or(true, |sample| sample != url)
+                {
+                    return false;
+                }
+
+                self.checks.append(&mut checks);
+                self.pending -= 1;
+                true
+            }
+            // Overrides
+            Message::Gateway(gateway) => {
+                self.gateway = gateway;
+                false
+            }
+            Message::Proxy(proxy) => {
+                self.proxy = proxy;
+                false
+            }
+            Message::Headers(headers) => {
+                self.headers = headers;
+                false
+            }
+            Message::Method(method) => {
+                self.method = method;
+                true
+            }
+            Message::Body(body) => {
+                self.body = body;
+                false
+            }
+            Message::Pointer(pointer) => {
+                self.pointer = pointer;
+                false
+            }
+            Message::Lenient(lenient) => {
+                self.lenient = lenient;
+                true
+            }
+            Message::Interval(interval) => {
+                self.interval = interval;
+                false
+            }
+            Message::SaveOverrides => {
+                let overrides = Overrides {
+                    gateway: Some(self.gateway.trim().to_string()).filter(|g| !g.is_empty()),
+                    proxy: Some(self.proxy.trim().to_string()).filter(|p| !p.is_empty()),
+                    headers: self
+                        .headers
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                        .filter(|(name, _)| !name.is_empty())
+                        .collect(),
+                    method: Some(self.method.clone()).filter(|m| !m.is_empty() && m != "GET"),
+                    body: Some(self.body.trim().to_string()).filter(|b| !b.is_empty()),
+                    pointer: Some(self.pointer.trim().to_string()).filter(|p| !p.is_empty()),
+                    lenient: self.lenient,
+                    interval: self.interval.trim().parse().ok(),
+                };
+                if let Some(collection) = self.collection.as_mut() {
+                    collection.set_overrides(overrides);
+                    storage::Collection::store(collection);
+                    notifications::notify("Overrides saved".to_string(), None);
+                    ctx.link().send_message(Message::Run);
+                }
+                false
+            }
+            // Snapshot
+            Message::Loaded => {
+                self.loaded = true;
+                true
+            }
+            Message::TakeSnapshot => {
+                let id = ctx.props().id.as_str();
+                let snapshot = Snapshot::take(storage::Token::all(id).iter());
+                storage::Snapshot::store(id, &snapshot);
+                notifications::notify(
+                    format!("Snapshot taken of {} tokens", snapshot.hashes.len()),
+                    None,
+                );
+                self.snapshot = Some((snapshot.taken, snapshot.hashes.len()));
+                self.diff = None;
+                true
+            }
+            Message::CompareSnapshot => {
+                let id = ctx.props().id.as_str();
+                if let Some(snapshot) = storage::Snapshot::get(id) {
+                    let current = Snapshot::take(storage::Token::all(id).iter());
+                    self.diff = Some(snapshot.compare(&current));
+                }
+                true
+            }
+            // Ignore
+            Message::None => false,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let id = ctx.props().id.clone();
+        let run = ctx.link().callback(|_| Message::Run);
+        let name = self
+            .collection
+            .as_ref()
+            .and_then(|c| c.name().map(|name| name.to_string()))
+            .unwrap_or_else(|| id.clone());
+
+        html! {
+            <section id="diagnostics" class="section is-fullheight">
+                <h1 class="title nifty-name">
+                    <Link<Route> to={ Route::Collection { id: id.clone() } }>{ name }</Link<Route>>
+                </h1>
+                <div class="level is-mobile">
+                    <div class="level-left">
+                        <span class="level-item">{ "Diagnostics" }</span>
+                        <div class="level-item">
+                            <button onclick={ run } class="button" disabled={ self.pending > 0 }>
+                                <span class="icon is-small has-tooltip-bottom" data-tooltip="Run Again">
+                                    <i class="fa-solid fa-rotate"></i>
+                                </span>
+                            </button>
+                        </div>
+                        if self.pending > 0 {
+                            <i class="is-loading level-item"></i>
+                        }
+                    </div>
+                </div>
+
+                <div class="tabs">
+                    <ul>
+                        { for [(Tab::Checks, "Checks"), (Tab::Logs, "Logs")].into_iter().map(|(tab, label)| html! {
+                            <li class={ classes!((self.tab == tab).then(|| "is-active")) }>
+                                <a onclick={ ctx.link().callback(move |_| Message::Tab(tab)) }>{ label }</a>
+                            </li>
+                        }) }
+                    </ul>
+                </div>
+
+                if self.tab == Tab::Logs {
+                    <Logs collection={ Some(id.clone()) } />
+                } else {
+                    <table class="table is-fullwidth">
+                        <tbody>
+                        { self.checks.iter().map(|check| {
+                            let (icon, color) = match check.status {
+                                Status::Pass => ("fa-solid fa-circle-check", "has-text-success"),
+                                Status::Warning => ("fa-solid fa-triangle-exclamation", "has-text-warning"),
+                                Status::Fail => ("fa-solid fa-circle-xmark", "has-text-danger"),
+                            };
+                            html! {
+                                <tr>
+                                    <td class={ color }>
+                                        <span class="icon"><i class={ icon }></i></span>
+                                    </td>
+                                    <th>{ &check.name }</th>
+                                    <td>
+                                        <p>{ &check.detail }</p>
+                                        if let Some(hint) = &check.hint {
+                                            <p class="is-size-7 has-text-grey">{ hint }</p>
+                                        }
+                                    </td>
+                                </tr>
+                            }
+                        }).collect::<Html>() }
+                        </tbody>
+                    </table>
+
+                    if self.collection.is_some() {
+                        <div class="box">
+                            <h2 class="subtitle">{ "Overrides" }</h2>
+                            <p class="help">{ "Some collections only work via a specific gateway or proxy, or serve their \
+                            metadata from POST/GraphQL endpoints. Any overrides take precedence when requesting the metadata \
+                            of this collection." }</p>
+                            <div class="field">
+                                <label class="label">{ "IPFS Gateway" }</label>
+                                <div class="control">
+                                    <input class="input" type="text" placeholder="e.g. cloudflare-ipfs.com"
+                                        value={ self.gateway.clone() } oninput={ ctx.link().callback(|e: InputEvent|
+                                            Message::Gateway(e.target_unchecked_into::<HtmlInputElement>().value())) } />
+                                </div>
+                            </div>
+                            <div class="field">
+                                <label class="label">{ "Proxy" }</label>
+                                <div class="control">
+                                    <input class="input" type="text" placeholder="e.g. https://proxy.example.com/"
+                                        value={ self.proxy.clone() } oninput={ ctx.link().callback(|e: InputEvent|
+                                            Message::Proxy(e.target_unchecked_into::<HtmlInputElement>().value())) } />
+                                </div>
+                            </div>
+                            <div class="field">
+                                <label class="label">{ "Request Headers" }</label>
+                                <div class="control">
+                                    <textarea class="textarea" rows="2" placeholder="e.g. Authorization: Bearer ..."
+                                        value={ self.headers.clone() } oninput={ ctx.link().callback(|e: InputEvent|
+                                            Message::Headers(e.target_unchecked_into::<HtmlTextAreaElement>().value())) }>
+                                    </textarea>
+                                </div>
+                                <p class="help">{ "One Name: value header per line, such as an API key for private or pre-release \
+                                collections. Headers are only stored locally, within this browser." }</p>
+                            </div>
+                            <div class="field">
+                                <label class="label">{ "Request Method" }</label>
+                                <div class="control">
+                                    <div class="select">
+                                        <select onchange={ ctx.link().callback(|e: Event|
+                                            Message::Method(e.target_unchecked_into::<HtmlSelectElement>().value())) }>
+                                            { ["GET", "POST"].into_iter().map(|method| html! {
+                                                <option value={ method } selected={ self.method() == method }>{ method }</option>
+                                            }).collect::<Html>() }
+                                        </select>
+                                    </div>
+                                </div>
+                            </div>
+                            if self.method() == "POST" {
+                                <div class="field">
+                                    <label class="label">{ "Request Body" }</label>
+                                    <div class="control">
+                                        <textarea class="textarea is-family-monospace" rows="4"
+                                            placeholder={ r#"e.g. {"query": "{ token(id: \"{id}\") { metadata } }"}"# }
+                                            value={ self.body.clone() } oninput={ ctx.link().callback(|e: InputEvent|
+                                                Message::Body(e.target_unchecked_into::<HtmlTextAreaElement>().value())) }>
+                                        </textarea>
+                                    </div>
+                                    <p class="help">{ "Any {id} is replaced by the token identifier. GraphQL endpoints typically \
+                                    also require a Content-Type: application/json header." }</p>
+                                </div>
+                            }
+                            <div class="field">
+                                <label class="label">{ "Metadata Location" }</label>
+                                <div class="control">
+                                    <input class="input" type="text" placeholder="e.g. /data/token/metadata"
+                                        value={ self.pointer.clone() } oninput={ ctx.link().callback(|e: InputEvent|
+                                            Message::Pointer(e.target_unchecked_into::<HtmlInputElement>().value())) } />
+                                </div>
+                                <p class="help">{ "A JSON pointer to the metadata within the response, where wrapped (e.g. by GraphQL)." }</p>
+                            </div>
+                            <div class="field">
+                                <label class="label">{ "Request Interval" }</label>
+                                <div class="control">
+                                    <input class="input" type="number" min="0" placeholder="250"
+                                        value={ self.interval.clone() } oninput={ ctx.link().callback(|e: InputEvent|
+                                            Message::Interval(e.target_unchecked_into::<HtmlInputElement>().value())) } />
+                                </div>
+                                <p class="help">{ "The minimum delay (in milliseconds) between requests whilst indexing. \
+                                Increase for gateways which rate limit aggressively (e.g. mypinata.cloud), or decrease for \
+                                those tolerating bursts." }</p>
+                            </div>
+                            <div class="field">
+                                <label class="checkbox">
+                                    <input type="checkbox" checked={ self.lenient } onchange={ ctx.link().callback(|e: Event|
+                                        Message::Lenient(e.target_unchecked_into::<HtmlInputElement>().checked())) } />
+                                    { " Parse leniently" }
+                                </label>
+                                <p class="help">{ "Recovers what it can from malformed metadata, such as numbers as strings, \
+                                attributes as a bare object or trailing commas, with the fields coerced reported by the checks." }</p>
+                            </div>
+                            <button onclick={ ctx.link().callback(|_| Message::SaveOverrides) } class="button is-primary"
+                                disabled={ self.pending > 0 }>
+                                { "Save" }
+                            </button>
+                        </div>
+                    }
+
+                    <div class="box">
+                        <h2 class="subtitle">{ "Snapshot" }</h2>
+                        <p class="help">{ "Freezes the metadata of the indexed tokens, so that any tokens changed, added or \
+                        removed since can later be listed. Useful for auditing collections whose metadata is mutable." }</p>
+                        if let Some((taken, tokens)) = self.snapshot {
+                            <p class="mt-2">
+                                { format!("Snapshot of {tokens} tokens taken {}",
+                                    taken.with_timezone(&chrono::Local).format("%d %b %Y %H:%M")) }
+                            </p>
+                        }
+                        <div class="buttons mt-2">
+                            <button onclick={ ctx.link().callback(|_| Message::TakeSnapshot) } class="button"
+                                disabled={ !self.loaded }>
+                                { "Take Snapshot" }
+                            </button>
+                            <button onclick={ ctx.link().callback(|_| Message::CompareSnapshot) } class="button is-primary"
+                                disabled={ !self.loaded || self.snapshot.is_none() }>
+                                { "Compare" }
+                            </button>
+                        </div>
+                        if let Some(diff) = self.diff.as_ref() {
+                            if diff.is_empty() {
+                                <p>{ "No tokens have changed since the snapshot was taken." }</p>
+                            } else {
+                                { self.differences(&id, "Changed", &diff.changed) }
+                                { self.differences(&id, "Added", &diff.added) }
+                                { self.differences(&id, "Removed", &diff.removed) }
+                            }
+                        }
+                    </div>
+                }
+            </section>
+        }
+    }
+}
+
+impl Diagnostics {
+    /// Lists the tokens differing from the snapshot, linking to each.
+    fn differences(&self, collection: &str, label: &str, tokens: &[u32]) -> Html {
+        if tokens.is_empty() {
+            return Html::default();
+        }
+        html! {
+            <div class="field">
+                <label class="label">{ format!("{label} ({})", tokens.len()) }</label>
+                <div class="tags">
+                    { tokens.iter().map(|token| html! {
+                        <Link<Route> classes="tag" to={ Route::CollectionToken { id: collection.to_string(), token: *token } }>
+                            { token.to_string() }
+                        </Link<Route>>
+                    }).collect::<Html>() }
+                </div>
+            </div>
+        }
+    }
+
+    /// The request method being edited, defaulting to GET.
+    fn method(&self) -> &str {
+        match self.method.as_str() {
+            "" => "GET",
+            method => method,
+        }
+    }
+
+    /// Initialises the collection, along with the values of its overrides for editing.
+    fn with_collection(mut self, collection: Option<models::Collection>) -> Self {
+        if let Some(overrides) = collection.as_ref().map(|c| c.overrides()) {
+            self.gateway = overrides.gateway.clone().unwrap_or_default();
+            self.proxy = overrides.proxy.clone().unwrap_or_default();
+            self.headers = overrides
+                .headers
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}"))
+                .collect::<Vec<String>>()
+                .join("\n");
+            self.method = overrides.method.clone().unwrap_or_default();
+            self.body = overrides.body.clone().unwrap_or_default();
+            self.pointer = overrides.pointer.clone().unwrap_or_default();
+            self.lenient = overrides.lenient;
+            self.interval = overrides
+                .interval
+                .map(|interval| interval.to_string())
+                .unwrap_or_default();
+        }
+        self.collection = collection;
+        self
+    }
+}
@@ -0,0 +1,36 @@
+use bulma::components::Sentinel;
+use yew::prelude::*;
+
+/// The distance from the viewport within which the image of a card is rendered, so that it has loaded by the time it is
+/// scrolled to.
+const MARGIN: &str = "400px";
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    /// The classes of the figure, such as its ratio.
+    #[prop_or_default]
+    pub classes: Classes,
+    pub children: Children,
+}
+
+/// A figure whose contents (typically an image) are only rendered once it nears the viewport, showing a skeleton
+/// placeholder until then. Avoids requesting every image of a large page at once, which would saturate the bandwidth
+/// available to the images actually in view.
+#[function_component(Lazy)]
+pub fn lazy(props: &Properties) -> Html {
+    let visible = use_state_eq(|| false);
+    let onvisible = {
+        let visible = visible.clone();
+        Callback::from(move |_| visible.set(true))
+    };
+    html! {
+        <figure class={ props.classes.clone() }>
+            if *visible {
+                { for props.children.iter() }
+            } else {
+                <div class="skeleton has-ratio"></div>
+                <Sentinel { onvisible } margin={ MARGIN } />
+            }
+        </figure>
+    }
+}
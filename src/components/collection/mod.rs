@@ -1,51 +1,264 @@
+use crate::components::image::Image;
+use crate::components::market::Prices;
+use crate::components::DataSaver;
 use crate::storage::Get;
-use crate::{models, notifications, storage, uri, Address, Route, Scroll};
+use crate::{
+    config, models, notifications, redirect, search, storage, transition, uri, Address, Route,
+    Scroll,
+};
+use bulma::components::Sentinel;
 use bulma::toast::Color;
+use gloo_timers::callback::Timeout;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
 use std::rc::Rc;
 use std::str::FromStr;
 use thousands::Separable;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use workers::etherscan::TypeExtensions;
+use workers::market::TraitFloors;
 use workers::metadata::Metadata;
-use workers::{etherscan, metadata, Bridge, Bridged, Url};
+use workers::{etherscan, indexer, market, metadata, Bridge, Bridged, Url};
 use yew::prelude::*;
+use yew_router::history::{AnyHistory, History, HistoryListener, Location};
 use yew_router::prelude::*;
 
+mod archive;
+pub mod diagnostics;
+mod lazy;
+pub mod rarity;
+mod sidebar;
 pub mod token;
 
+/// The number of matching tokens listed when searching.
+const SEARCH_RESULTS: usize = 10;
+/// The number of attributes of each token listed within the list layout.
+const KEY_ATTRIBUTES: usize = 3;
+/// The number of pages of cards kept whilst infinitely scrolling, with those scrolled past replaced by a spacer.
+const MAX_PAGES: usize = 4;
+/// The number of tokens indexed since the rarity of the collection was last calculated, after which it is recalculated.
+const RARITY_INTERVAL: usize = 250;
+/// The number of times a failed step of resolving the collection is retried, before giving up.
+const RETRIES: u8 = 5;
+/// The delay before the first retry of a failed step (in milliseconds), doubled with each subsequent retry.
+const RETRY_DELAY: u32 = 2_000;
+/// The tokens probed in turn when scanning for the first token of a collection whose tokens start at an arbitrary
+/// identifier, covering the offsets commonly used by contracts.
+const START_CANDIDATES: [u32; 10] = [
+    0, 1, 100, 101, 1_000, 1_001, 10_000, 10_001, 100_000, 1_000_000,
+];
+
 pub struct Collection {
     etherscan: Box<dyn Bridge<etherscan::Worker>>,
     metadata: Box<dyn Bridge<metadata::Worker>>,
+    /// The background indexing of the collection, which continues whilst navigating elsewhere.
+    indexer: Box<dyn Bridge<indexer::Worker>>,
+    progress: Option<indexer::Progress>,
+    /// The number of tokens whose metadata could not be indexed, which can be retried.
+    failed: usize,
+    /// Whether indexing awaits being resumed, as data is being saved.
+    indexing_paused: bool,
+    /// The base uri to which requests for the metadata of the collection are consistently redirected, offered in place
+    /// of its own until dismissed.
+    redirect: Option<String>,
+    redirect_dismissed: bool,
+    _market: Option<Box<dyn Bridge<market::Worker>>>,
     collection: Option<models::Collection>,
-    tokens: Vec<models::Token>,
+    /// The details of the collection from the market data api, such as a description and banner.
+    details: Option<market::Details>,
+    /// The floor price and last sale of the collection from the market data api, where an api key is configured.
+    prices: Option<market::Prices>,
+    /// The floor price of each trait value from the market data api, listed alongside the traits.
+    trait_floors: Option<Rc<TraitFloors>>,
+    tokens: Vec<Rc<models::Token>>,
     notified_indexing: bool,
     indexed: usize,
+    /// Tokens fetched on demand since the last write to storage, which are written in batches to limit serialisation.
+    unflushed: Vec<Rc<models::Token>>,
+    /// The attribute filter applied to the collection, along with the number of matching tokens.
+    filter: Option<models::Filter>,
+    matches: Option<usize>,
+    /// The order in which the tokens are listed, other than by token identifier only once indexed.
+    sort: models::Sort,
+    /// Whether further pages are loaded whilst scrolling, rather than paged through.
+    infinite: bool,
+    layout: storage::Layout,
+    scroll: Scrolled,
+    grid: NodeRef,
+    /// The values of each trait of the indexed tokens, by which the collection can be filtered.
+    traits: Rc<sidebar::Traits>,
+    /// The rank of each token by rarity, recalculated in the background as further tokens are indexed. The rarity
+    /// worker is only bridged once first required.
+    ranks: Rc<HashMap<u32, u32>>,
+    rarity: Option<Box<dyn Bridge<workers::rarity::Worker>>>,
+    ranking: bool,
+    _history_listener: Option<HistoryListener>,
+    watched: bool,
+    favorite: bool,
     page: usize,
     page_size: usize,
     working: bool,
+    /// The step reached whilst resolving the collection, until its first tokens are available.
+    step: Step,
+    /// The pending retry of any failed steps of resolving the collection, along with the number of retries attempted
+    /// since a step last succeeded.
+    retry: Option<Retry>,
+    retries: u8,
+    /// Tokens requested on demand, outside of the sequential indexing of the collection.
+    fetching: HashSet<u32>,
+    /// The token next requested by the background indexing.
+    cursor: Option<u32>,
+    /// The candidates yet to be probed whilst scanning for the first token, starting with that being probed.
+    scanning: Option<VecDeque<u32>>,
+    /// The search query, along with the number of matching tokens and the names of the first few. The search index
+    /// is loaded once first searched, until further tokens are indexed.
+    query: String,
+    results: (usize, Vec<(u32, String)>),
+    search_index: Option<Rc<search::Index>>,
+    /// Whether the indexed tokens of the collection have been loaded from storage, with messages deferred until then.
+    loaded: bool,
+    deferred: Vec<Message>,
+}
+
+/// The steps of resolving a collection: the contract, followed by the metadata uri and then the token metadata.
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Contract,
+    Uri,
+    Metadata,
+    Ready,
+    Failed(String),
+}
+
+impl Step {
+    /// Describes the step, along with its position within the steps required for the collection.
+    fn describe(&self, collection: &models::Collection) -> Option<(usize, usize, &'static str)> {
+        // Collections from a url already have a (base) uri, so only require metadata
+        let offset = match collection {
+            models::Collection::Url { .. } => 2,
+            _ => 0,
+        };
+        let (step, description) = match self {
+            Step::Contract => (1, "fetching contract…"),
+            Step::Uri => (2, "resolving metadata uri…"),
+            Step::Metadata => (3, "fetching metadata…"),
+            Step::Ready | Step::Failed(_) => return None,
+        };
+        Some((step - offset, 3 - offset, description))
+    }
+
+    /// Advances to the next step, ignoring any earlier steps re-requested once resolved.
+    fn advance(&mut self, next: Step) {
+        let position = |step: &Step| match step {
+            Step::Contract => 0,
+            Step::Uri => 1,
+            Step::Metadata => 2,
+            Step::Ready => 3,
+            Step::Failed(_) => 4,
+        };
+        if position(&next) > position(self) || matches!(self, Step::Failed(_)) {
+            *self = next;
+        }
+    }
+}
+
+/// A scheduled retry of the steps of resolving the collection which are still missing.
+struct Retry {
+    /// The failed step, for display.
+    step: &'static str,
+    attempt: u8,
+    /// The delay before retrying, in milliseconds.
+    delay: u32,
+    _timeout: Timeout,
+}
+
+impl Retry {
+    fn describe(&self) -> String {
+        format!("{} failed, retrying in {}s…", self.step, self.delay / 1_000)
+    }
 }
 
 pub enum Message {
+    /// The indexed tokens of the collection have been loaded from storage.
+    Loaded,
     // Contract
+    /// Selects the chain of the collection, for any subsequent requests for its contract.
+    Chain(etherscan::Chain),
+    /// Restores the contract cached by a previous visit, along with the results of its calls.
+    CachedContract(Address, etherscan::Cached),
     MissingApiKey,
     RequestContract(Address),
     Contract(etherscan::Contract),
     NoContract(Address),
     ContractFailed(Address, u8),
+    /// Re-requests any steps of resolving the collection which are still missing, following a failure.
+    Retry,
     CopyAddress,
+    /// Copies a link to the collection to the clipboard.
+    Share,
     // URI
     RequestUri(Address),
     Uri(String, Option<u32>),
+    NoUri,
     UriFailed,
     // Total Supply
     RequestTotalSupply(Address),
     TotalSupply(u32),
+    TotalSupplyFailed,
+    // Indexing
+    /// Indexes the collection in the background, once its metadata uri is known, optionally retrying any tokens whose
+    /// metadata previously failed.
+    Index(bool),
+    /// Resumes indexing, having been paused to save data.
+    ResumeIndexing,
+    Progress(indexer::Progress),
+    /// Rewrites the base uri of the collection to that to which its requests are redirected.
+    RewriteBaseUri,
+    DismissRedirect,
+    // Rarity
+    Rank,
+    Ranked(String, workers::rarity::Rarity),
     // Metadata
-    RequestMetadata(u32),
     Metadata(String, u32, Metadata),
     NotFound(u32),
     MetadataFailed(u32),
+    /// Requests the metadata of a token on demand, ahead of the sequential indexing.
+    Fetch(u32),
+    /// Overrides the token from which the collection starts, indexing it again from that token.
+    StartToken(u32),
+    /// Probes a series of candidate start tokens, for collections whose tokens start at an arbitrary identifier.
+    ScanStartToken,
+    /// Requests the metadata of any tokens within the range which have not yet been indexed, ahead of the sequential
+    /// indexing.
+    FetchRange(Range<u32>),
     // Paging
     Page(usize),
+    // Filtering
+    Filter(Option<models::Filter>),
+    ClearFilter,
+    // Sorting
+    Sort(models::Sort),
+    // Layout
+    Layout(storage::Layout),
+    // Infinite scrolling
+    /// Loads the following page, once scrolled near to the end.
+    More,
+    /// Loads the preceding page, once scrolled back near to the start.
+    Less,
+    /// Loads the last page again, should it not have been complete.
+    Refill,
+    // Watchlist
+    ToggleWatch,
+    Watched(bool),
+    // Favorites
+    ToggleFavorite,
+    // Search
+    Search(String),
+    // Market
+    Details(String, market::Details),
+    Prices(String, market::Prices),
+    TraitFloors(String, TraitFloors),
     // Ignore
     None,
 }
@@ -62,12 +275,60 @@ impl Component for Collection {
     type Properties = Properties;
 
     fn create(ctx: &Context<Self>) -> Self {
+        // Load the indexed tokens of the collection, ahead of handling any messages
+        ctx.link().send_future({
+            let id = ctx.props().id.clone();
+            async move {
+                storage::Token::load(&id).await;
+                Message::Loaded
+            }
+        });
+
+        // Select the chain of the collection, ahead of any requests for its contract
+        if let Some((chain, address)) = models::Collection::parse_contract(&ctx.props().id) {
+            if chain != etherscan::Chain::Ethereum {
+                ctx.link().send_message(Message::Chain(chain));
+            }
+            // Restore any contract cached by a previous visit, rather than requesting it again
+            if let Some(contract) = storage::Contract::get(chain, &address) {
+                ctx.link()
+                    .send_message(Message::CachedContract(address, contract));
+            }
+        }
+
+        let settings = storage::Settings::get();
+        // Restore the view preferences of the collection, unless overridden by the query string
+        let mut preferences = storage::Preferences::get(ctx.props().id.as_str());
+        match ctx
+            .link()
+            .location()
+            .and_then(|location| location.query::<models::Filter>().ok())
+        {
+            Some(filter) => {
+                preferences.filter = Some(filter);
+                storage::Preferences::store(ctx.props().id.as_str(), &preferences);
+            }
+            None => {
+                if let Some(filter) = preferences.filter.as_ref() {
+                    if let Err(e) = ctx.link().history().unwrap().replace_with_query(
+                        Route::Collection {
+                            id: ctx.props().id.clone(),
+                        },
+                        filter,
+                    ) {
+                        log::error!("unable to restore the collection filter: {e:?}")
+                    }
+                }
+            }
+        }
+
         // Check if collection already exists locally
         let mut collection = storage::Collection::get(ctx.props().id.as_str());
         match collection.as_mut() {
             None => {
-                // Check if identifier is an address
-                if let Ok(address) = Address::from_str(&ctx.props().id) {
+                // Check if identifier is a (chain-qualified) address
+                if let Some((chain, address)) = models::Collection::parse_contract(&ctx.props().id)
+                {
                     collection = Some(models::Collection::Contract {
                         address,
                         name: TypeExtensions::format(&address),
@@ -75,6 +336,9 @@ impl Component for Collection {
                         start_token: 0,
                         total_supply: None,
                         last_viewed: None,
+                        overrides: Default::default(),
+                        position: Default::default(),
+                        chain,
                     });
 
                     if let None = ctx.props().api_key {
@@ -93,10 +357,12 @@ impl Component for Collection {
                                     start_token: 0,
                                     total_supply: None,
                                     last_viewed: None,
+                                    overrides: Default::default(),
+                                    position: Default::default(),
                                 };
-                                storage::Collection::store(c.clone());
+                                storage::Collection::store(&c);
                                 collection = Some(c);
-                                ctx.link().send_message(Message::RequestMetadata(0))
+                                ctx.link().send_message(Message::Index(false))
                             }
                             Err(e) => {
                                 log::error!("unable to parse the collection url '{url}': {e:?}")
@@ -117,7 +383,6 @@ impl Component for Collection {
                         address,
                         base_uri,
                         total_supply,
-                        start_token,
                         ..
                     } => {
                         // Check if base uri missing
@@ -125,9 +390,7 @@ impl Component for Collection {
                             None => ctx
                                 .link()
                                 .send_message(Message::RequestUri(address.clone())),
-                            Some(_) => ctx
-                                .link()
-                                .send_message(Message::RequestMetadata(start_token.clone())),
+                            Some(_) => ctx.link().send_message(Message::Index(false)),
                         }
 
                         // Check if total supply missing
@@ -136,17 +399,80 @@ impl Component for Collection {
                                 .send_message(Message::RequestTotalSupply(address.clone()))
                         }
                     }
-                    models::Collection::Url { start_token, .. } => ctx
-                        .link()
-                        .send_message(Message::RequestMetadata(start_token.clone())),
+                    models::Collection::Url { .. } => {
+                        ctx.link().send_message(Message::Index(false))
+                    }
                 }
 
-                // Initialise first page
-                ctx.link().send_message(Message::Page(1));
+                // Initialise first page, or the page last viewed where configured
+                let page = match settings.start_page {
+                    storage::StartPage::Last if !settings.infinite_scroll => {
+                        collection.position().page.unwrap_or(1)
+                    }
+                    _ => 1,
+                };
+                ctx.link().send_message(Message::Page(page));
 
                 // Update last viewed on collection and store
                 collection.set_last_viewed();
-                storage::Collection::store(collection.clone())
+                storage::Collection::store(collection)
+            }
+        }
+
+        let step = match collection.as_ref() {
+            Some(collection) if collection.base_uri().is_some() => Step::Metadata,
+            _ => Step::Contract,
+        };
+        let watched = collection.as_ref().map_or(false, |collection| {
+            storage::Watchlist::contains(&collection.id())
+        });
+
+        // Use previously fetched market data where possible, otherwise request it (for contracts on ethereum only)
+        let id = ctx.props().id.as_str();
+        let details = storage::Market::details(id);
+        let prices = storage::Market::prices(id, None);
+        let trait_floors = storage::Market::trait_floors(id);
+        let mut market = None;
+        if let Some(api) = config::MARKET_DATA_API.filter(|_| Address::from_str(id).is_ok()) {
+            // Prices are only requested with a key, and are otherwise omitted
+            let api_key = config::market_api_key().filter(|_| prices.is_none());
+            if details.is_none() || trait_floors.is_none() || api_key.is_some() {
+                let mut worker = market::Worker::bridge(Rc::new({
+                    let link = ctx.link().clone();
+                    move |e: market::Response| match e {
+                        market::Response::Details(collection, details) => {
+                            link.send_message(Message::Details(collection, details))
+                        }
+                        market::Response::Prices(collection, None, prices) => {
+                            link.send_message(Message::Prices(collection, prices))
+                        }
+                        market::Response::TraitFloors(collection, floors) => {
+                            link.send_message(Message::TraitFloors(collection, floors))
+                        }
+                        _ => {}
+                    }
+                }));
+                if details.is_none() {
+                    worker.send(market::Request::Details {
+                        api: api.to_string(),
+                        collection: id.to_string(),
+                    });
+                }
+                if trait_floors.is_none() {
+                    worker.send(market::Request::TraitFloors {
+                        api: api.to_string(),
+                        collection: id.to_string(),
+                    });
+                }
+                if let Some(api_key) = api_key {
+                    worker.send(market::Request::Prices {
+                        api: api.to_string(),
+                        api_key,
+                        collection: id.to_string(),
+                        token: None,
+                    });
+                }
+                market = Some(worker);
             }
         }
 
@@ -161,13 +487,24 @@ impl Component for Collection {
                             Message::ContractFailed(address, attempts)
                         }
                         etherscan::Response::Uri(uri, token) => Message::Uri(uri, token),
-                        etherscan::Response::NoUri(_address) => Message::UriFailed,
+                        etherscan::Response::NoUri(_address) => Message::NoUri,
                         etherscan::Response::UriFailed(_address) => Message::UriFailed,
                         etherscan::Response::TotalSupply(total_supply) => {
                             Message::TotalSupply(total_supply)
                         }
                         etherscan::Response::NoTotalSupply(_) => Message::None,
-                        etherscan::Response::TotalSupplyFailed(_) => Message::None,
+                        etherscan::Response::TotalSupplyFailed(_) => Message::TotalSupplyFailed,
+                        etherscan::Response::Owner(..)
+                        | etherscan::Response::NoOwner(..)
+                        | etherscan::Response::OwnerFailed(..)
+                        | etherscan::Response::Holdings(..)
+                        | etherscan::Response::HoldingsFailed(_)
+                        | etherscan::Response::GasOracle(_)
+                        | etherscan::Response::GasOracleFailed
+                        | etherscan::Response::Name(..)
+                        | etherscan::Response::NoName(_)
+                        | etherscan::Response::NameFailed(_)
+                        | etherscan::Response::Cached(..) => Message::None,
                     })
                 }
             })),
@@ -183,24 +520,107 @@ impl Component for Collection {
                     metadata::Response::Failed(_url, token) => link.send_message(
                         Message::MetadataFailed(token.expect("expected valid token")),
                     ),
+                    metadata::Response::Hosts(_) => {}
                 }
             })),
+            indexer: indexer::Worker::bridge(Rc::new({
+                let link = ctx.link().clone();
+                move |e: indexer::Response| {
+                    if let indexer::Response::Progress(progress) = e {
+                        link.send_message(Message::Progress(progress))
+                    }
+                }
+            })),
+            progress: None,
+            failed: 0,
+            indexing_paused: settings.saving_data(),
+            redirect: None,
+            redirect_dismissed: false,
+            _market: market,
             collection,
+            details,
+            prices,
+            trait_floors: trait_floors.map(Rc::new),
             tokens: Vec::new(),
             notified_indexing: false,
             indexed: 0,
+            unflushed: Vec::new(),
+            filter: preferences.filter,
+            sort: preferences.sort,
+            infinite: settings.infinite_scroll,
+            layout: settings.layout,
+            scroll: Scrolled::new(1),
+            grid: NodeRef::default(),
+            matches: None,
+            traits: Rc::new(Vec::new()),
+            ranks: Rc::new(HashMap::new()),
+            rarity: None,
+            ranking: false,
+            // Update the filter when navigating between filtered views of the same collection
+            _history_listener: ctx.link().add_history_listener(ctx.link().callback({
+                let id = ctx.props().id.clone();
+                move |history: AnyHistory| {
+                    let location = history.location();
+                    match location.route::<Route>() {
+                        Some(Route::Collection { id: route }) if route == id => {
+                            Message::Filter(location.query::<models::Filter>().ok())
+                        }
+                        _ => Message::None,
+                    }
+                }
+            })),
+            watched,
+            favorite: storage::Favorites::contains_collection(&ctx.props().id),
             page: 1,
-            page_size: 25,
+            page_size: settings.page_size(),
             working: false,
+            step,
+            retry: None,
+            retries: 0,
+            fetching: HashSet::new(),
+            cursor: None,
+            scanning: None,
+            query: String::new(),
+            results: (0, Vec::new()),
+            search_index: None,
+            loaded: false,
+            deferred: Vec::new(),
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        if !self.loaded {
+            if let Message::Loaded = msg {
+                self.loaded = true;
+                self.ranks = Rc::new(storage::Rarity::ranks(ctx.props().id.as_str()));
+                ctx.link()
+                    .send_message_batch(std::mem::take(&mut self.deferred));
+                return true;
+            }
+            self.deferred.push(msg);
+            return false;
+        }
+
         match msg {
+            Message::Loaded => false,
             // Contract
+            Message::Chain(chain) => {
+                self.etherscan.send(etherscan::Request::Chain(
+                    chain,
+                    config::api(chain).to_string(),
+                ));
+                false
+            }
+            Message::CachedContract(address, contract) => {
+                self.etherscan
+                    .send(etherscan::Request::Cached(address, contract));
+                false
+            }
             Message::MissingApiKey => {
                 notifications::notify(
-                    "Warning: No API key has been configured for the etherscan.io API. Requests are therefore throttled.".to_string(),
+                    "Warning: No API key has been configured for the etherscan.io API. Requests are therefore throttled. \
+                    An API key can be configured within settings."
+                        .to_string(),
                     Some(Color::Warning),
                 );
                 false
@@ -208,32 +628,52 @@ impl Component for Collection {
             Message::RequestContract(address) => {
                 // Request contract info via etherscan worker
                 self.etherscan.send(etherscan::Request::Contract(address));
+                let chain = self
+                    .collection
+                    .as_ref()
+                    .map_or(etherscan::Chain::Ethereum, |collection| collection.chain());
                 notifications::notify(
                     format!(
-                        "Checking if address {} is a contract via etherscan.io...",
-                        address
+                        "Checking if address {} is a contract via {}...",
+                        address,
+                        chain.explorer()
                     ),
                     None,
                 );
+                self.step.advance(Step::Contract);
                 self.working = true;
                 true
             }
             Message::Contract(contract) => {
                 // Initialise collection from contract
-                let collection = match storage::Collection::get(&contract.address) {
+                let id = models::Collection::contract_id(contract.chain, &contract.address);
+                let collection = match storage::Collection::get(id.as_str()) {
                     None => models::Collection::Contract {
                         address: contract.address,
                         name: contract.name.clone(),
                         base_uri: None,
                         start_token: 0,
                         total_supply: None,
-                        last_viewed: Some(chrono::offset::Utc::now()),
+                        last_viewed: Some(crate::clock::now()),
+                        overrides: Default::default(),
+                        position: Default::default(),
+                        chain: contract.chain,
                     },
-                    Some(collection) => collection,
+                    Some(mut collection) => {
+                        // Replace the placeholder name of an imported collection
+                        if let models::Collection::Contract { address, name, .. } = &mut collection
+                        {
+                            if *name == TypeExtensions::format(address) {
+                                *name = contract.name.clone();
+                            }
+                        }
+                        collection
+                    }
                 };
 
                 // Check if collection missing any data which can be resolved from contract
                 self.working = false;
+                self.retries = 0;
                 if let models::Collection::Contract {
                     address,
                     base_uri,
@@ -242,13 +682,14 @@ impl Component for Collection {
                 } = &collection
                 {
                     if let None = base_uri {
-                        log::trace!("attempting to resolve uri from contract ...");
+                        log::trace!(collection = ctx.props().id.as_str(); "attempting to resolve uri from contract...");
                         ctx.link()
                             .send_message(Message::RequestUri(address.clone()));
                         self.working = true;
                     }
                     if let None = total_supply {
-                        log::trace!("attempting to resolve total supply from contract ...");
+                        log::trace!(collection = ctx.props().id.as_str();
+                            "attempting to resolve total supply from contract...");
                         ctx.link()
                             .send_message(Message::RequestTotalSupply(address.clone()));
                         self.working = true;
@@ -256,7 +697,7 @@ impl Component for Collection {
                 }
 
                 // Store collection locally
-                storage::Collection::store(collection.clone());
+                storage::Collection::store(&collection);
                 self.collection = Some(collection);
                 true
             }
@@ -265,19 +706,55 @@ impl Component for Collection {
                     format!("No contract found for {address}"),
                     Some(Color::Danger),
                 );
+                self.step
+                    .advance(Step::Failed(format!("No contract found for {address}")));
                 self.working = false;
                 true
             }
             Message::ContractFailed(address, attempts) => {
+                if self.retry(ctx, "Contract lookup") {
+                    return true;
+                }
                 notifications::notify(
                     format!(
                         "Contract could not be found for {address}, despite {attempts} attempts"
                     ),
                     Some(Color::Danger),
                 );
+                self.step.advance(Step::Failed(format!(
+                    "The contract could not be fetched, despite {attempts} attempts"
+                )));
                 self.working = false;
                 true
             }
+            Message::Retry => {
+                self.retry = None;
+                let collection = match self.collection.as_ref() {
+                    Some(collection) => collection,
+                    None => return true,
+                };
+                if let models::Collection::Contract {
+                    address,
+                    base_uri,
+                    total_supply,
+                    ..
+                } = collection
+                {
+                    // Resume from the contract where not yet stored, which in turn resolves the uri and total supply
+                    if storage::Collection::get(collection.id().as_str()).is_none() {
+                        ctx.link().send_message(Message::RequestContract(*address));
+                    } else {
+                        if base_uri.is_none() {
+                            ctx.link().send_message(Message::RequestUri(*address));
+                        }
+                        if total_supply.is_none() {
+                            ctx.link()
+                                .send_message(Message::RequestTotalSupply(*address));
+                        }
+                    }
+                }
+                true
+            }
             Message::CopyAddress => {
                 if let Some(models::Collection::Contract { address, .. }) = self.collection {
                     let window = web_sys::window().expect("global window does not exists");
@@ -287,6 +764,19 @@ impl Component for Collection {
                 }
                 false
             }
+            Message::Share => {
+                let route = Route::Collection {
+                    id: ctx.props().id.clone(),
+                };
+                if let (Some(url), Some(clipboard)) = (
+                    route.share_url(),
+                    web_sys::window().and_then(|window| window.navigator().clipboard()),
+                ) {
+                    let _ = clipboard.write_text(&url);
+                    notifications::notify("Link copied to clipboard".to_string(), None);
+                }
+                false
+            }
             // URI
             Message::RequestUri(address) => {
                 // Request contract info via etherscan worker
@@ -294,6 +784,7 @@ impl Component for Collection {
                     address,
                     1, // Default to one rather than zero to minimize failed contract calls
                 ));
+                self.step.advance(Step::Uri);
                 self.working = true;
                 true
             }
@@ -301,8 +792,11 @@ impl Component for Collection {
                 if let Some(collection) = self.collection.as_mut() {
                     match uri::parse(&uri) {
                         Ok(url) => {
-                            // Check if url contains token
+                            // Check if url contains token, with ERC-1155 templates used as is
                             match token {
+                                Some(_) if metadata::is_template(url.as_str()) => {
+                                    collection.set_base_uri(url);
+                                }
                                 Some(_) => {
                                     // Parse url to remove the final path segment (token) to use as base uri
                                     if let Some(base_uri) = url
@@ -319,12 +813,10 @@ impl Component for Collection {
                                     collection.set_base_uri(url);
                                 }
                             }
-                            storage::Collection::store(collection.clone());
+                            storage::Collection::store(collection);
+                            self.retries = 0;
 
-                            // Request first item in collection
-                            ctx.link().send_message(Message::RequestMetadata(
-                                collection.start_token().clone(),
-                            ));
+                            ctx.link().send_message(Message::Index(false));
                             return true;
                         }
                         Err(e) => {
@@ -333,18 +825,26 @@ impl Component for Collection {
                                 "Could not determine the collection url".to_string(),
                                 Some(Color::Danger),
                             );
+                            self.step.advance(Step::Failed(format!(
+                                "The metadata uri {uri} could not be parsed"
+                            )));
                         }
                     }
                 }
                 self.working = false;
                 true
             }
-            Message::UriFailed => {
+            // Only transient failures are retried, as otherwise the contract has no uri function
+            Message::UriFailed if self.retry(ctx, "Metadata uri resolution") => true,
+            Message::UriFailed | Message::NoUri => {
                 notifications::notify(
                     "Unable to determine the collection url via etherscan.io. Please try again..."
                         .to_string(),
                     Some(Color::Danger),
                 );
+                self.step.advance(Step::Failed(
+                    "The metadata uri could not be resolved from the contract".to_string(),
+                ));
                 self.working = false;
                 true
             }
@@ -359,110 +859,488 @@ impl Component for Collection {
             Message::TotalSupply(total_supply) => {
                 if let Some(collection) = self.collection.as_mut() {
                     collection.set_total_supply(total_supply);
-                    storage::Collection::store(collection.clone());
+                    storage::Collection::store(collection);
                 }
                 self.working = false;
                 false
             }
-            // Metadata
-            Message::RequestMetadata(token) => {
-                // Check if token already exists in current view
-                if self.tokens.iter().any(|t| t.id == token) {
-                    // Request next token
-                    ctx.link().send_message(Message::RequestMetadata(token + 1));
-                } else {
-                    if let Some(collection) = self.collection.as_ref() {
-                        // Check if token already exists within storage
-                        if let Some(_token) = storage::Token::get(collection.id().as_str(), token) {
-                            // Request next token
-                            ctx.link().send_message(Message::RequestMetadata(token + 1));
-                        }
-                        // Otherwise request metadata
-                        else if let Some(url) = collection.url(token) {
-                            self.metadata.send(metadata::Request {
-                                url,
-                                token: Some(token),
-                                cors_proxy: Some(crate::config::CORS_PROXY.to_string()),
-                            });
-                            self.working = true;
-                            return true;
+            Message::TotalSupplyFailed => {
+                // The total supply is not required to view the collection, so is only retried
+                self.working = false;
+                self.retry(ctx, "Total supply retrieval")
+            }
+            // Indexing
+            Message::Index(retry) => {
+                let collection = match self.collection.as_ref() {
+                    Some(collection) => collection,
+                    None => return false,
+                };
+                if let Some(base_uri) = collection.base_uri() {
+                    // Whilst saving data, only the tokens of the current page are requested until indexing is resumed
+                    if self.indexing_paused && !retry {
+                        if let Some(range) = self.range() {
+                            ctx.link().send_message(Message::FetchRange(range));
                         }
+                        self.step.advance(Step::Ready);
+                        return true;
+                    }
+                    let id = collection.id();
+                    let cursor = storage::Cursor::get(&id);
+                    self.failed = cursor.as_ref().map_or(0, |cursor| cursor.failed.len());
+                    self.indexer.send(indexer::Request::Index {
+                        collection: id.clone(),
+                        base_uri: base_uri.to_string(),
+                        start_token: *collection.start_token(),
+                        total_supply: *collection.total_supply(),
+                        indexed: storage::Token::ids(id.as_str()),
+                        cursor,
+                        retry,
+                        cors_proxy: Some(crate::config::cors_proxy()),
+                        overrides: crate::config::overrides(collection.overrides()),
+                    });
+                    self.step.advance(Step::Metadata);
+                    self.working = true;
+                    return true;
+                }
+                false
+            }
+            Message::ResumeIndexing => {
+                self.indexing_paused = false;
+                ctx.link().send_message(Message::Index(false));
+                true
+            }
+            Message::Progress(progress) => {
+                // Ignore the progress of any other collection
+                let collection = match self.collection.as_mut() {
+                    Some(collection) if collection.id() == progress.collection => collection,
+                    _ => return false,
+                };
+
+                // Skip any leading tokens which could not be found
+                if let Some(start_token) = progress.start_token {
+                    if start_token > *collection.start_token() {
+                        collection.increment_start_token(start_token - *collection.start_token());
+                        storage::Collection::store(collection);
                     }
                 }
+                if !progress.completed && !self.notified_indexing {
+                    let message = match collection.base_uri() {
+                        Some(url) if url.as_str().contains("ipfs") => {
+                            "Indexing collection from IPFS, this may take some time..."
+                        }
+                        _ => "Indexing collection...",
+                    };
+                    notifications::notify(message.to_string(), None);
+                    self.notified_indexing = true;
+                }
+                if progress.completed && progress.indexed == 0 {
+                    self.step.advance(Step::Failed(
+                        "No token metadata could be found for the collection".to_string(),
+                    ));
+                }
 
+                // Offer to skip any redirect, as the redirects of tokens are persisted ahead of the progress
+                if !self.redirect_dismissed {
+                    self.redirect = collection.base_uri().and_then(|base_uri| {
+                        redirect::target(
+                            base_uri.as_str(),
+                            &storage::Redirects::get(&collection.id()),
+                        )
+                    });
+                }
+
+                // Recalculate the rarity periodically whilst indexing, and once completed
+                let ranked = self.ranks.len();
+                if !self.ranking
+                    && progress.indexed > 0
+                    && ((progress.completed && progress.indexed != ranked)
+                        || progress.indexed >= ranked + RARITY_INTERVAL)
+                {
+                    ctx.link().send_message(Message::Rank);
+                }
+
+                self.working = !progress.completed;
+                self.cursor = (!progress.completed).then(|| progress.cursor);
+                self.failed = progress.failed;
+                self.progress = Some(progress);
+                // Refresh the current page with the tokens indexed since
+                ctx.link().send_message(match self.infinite {
+                    true => Message::Refill,
+                    false => Message::Page(self.page),
+                });
+                true
+            }
+            Message::RewriteBaseUri => {
+                if let (Some(collection), Some(target)) =
+                    (self.collection.as_mut(), self.redirect.take())
+                {
+                    match Url::from_str(&target) {
+                        Ok(url) => {
+                            collection.set_base_uri(url);
+                            storage::Collection::store(collection);
+                            storage::Redirects::clear(&collection.id());
+                            notifications::notify(
+                                format!(
+                                    "The base uri of the collection has been rewritten to {target}"
+                                ),
+                                None,
+                            );
+                        }
+                        Err(e) => {
+                            log::error!("unable to parse the redirect target '{target}': {e:?}")
+                        }
+                    }
+                }
+                true
+            }
+            Message::DismissRedirect => {
+                self.redirect = None;
+                self.redirect_dismissed = true;
+                true
+            }
+            // Rarity
+            Message::Rank => {
+                let tokens = rarity::attributes(ctx.props().id.as_str());
+                if tokens.is_empty() {
+                    return false;
+                }
+                log::trace!(collection = ctx.props().id.as_str(); "recalculating rarity of {} tokens...", tokens.len());
+                let worker = self.rarity.get_or_insert_with(|| {
+                    let link = ctx.link().clone();
+                    workers::rarity::Worker::bridge(Rc::new(move |e: workers::rarity::Response| {
+                        link.send_message(Message::Ranked(e.collection, e.rarity))
+                    }))
+                });
+                worker.send(workers::rarity::Request {
+                    collection: ctx.props().id.clone(),
+                    tokens,
+                });
+                self.ranking = true;
                 false
             }
+            Message::Ranked(collection, rarity) => {
+                // Ignore any rarity returned from worker which doesnt pertain to current collection
+                if collection != ctx.props().id {
+                    return false;
+                }
+                storage::Rarity::store(collection.as_str(), &rarity);
+                self.ranks = Rc::new(
+                    rarity
+                        .ranks
+                        .iter()
+                        .map(|rank| (rank.token, rank.rank))
+                        .collect(),
+                );
+                self.ranking = false;
+                true
+            }
+            // Metadata
             Message::Metadata(url, token, metadata) => {
                 // Ignore any metadata returned from worker which doesnt pertain to current collection
-                if !url.starts_with(
-                    self.collection
-                        .as_ref()
-                        .and_then(|c| c.base_uri().as_ref())
-                        .map_or_else(|| "", |base_uri| base_uri.as_str()),
-                ) {
-                    log::trace!(
-                        "received token {token} at {url} does not match currently viewed collection {}",
-                        ctx.props().id
+                if Some(&url) != self.collection.as_ref().and_then(|c| c.url(token)).as_ref() {
+                    log::trace!(collection = ctx.props().id.as_str(), token = token, url = url.as_str();
+                        "received token does not match currently viewed collection"
                     );
                     return false;
                 }
+                // The first token found whilst scanning becomes the start token of the collection
+                if self.scanning.as_ref().and_then(|s| s.front()) == Some(&token) {
+                    self.scanning = None;
+                    notifications::notify(format!("The first token was found at #{token}"), None);
+                    ctx.link().send_message(Message::StartToken(token));
+                    return true;
+                }
+                if !self.fetching.remove(&token) {
+                    return false;
+                }
 
-                self.working = false;
-                // Add token to collection and request next item
+                self.step.advance(Step::Ready);
                 self.add(token, metadata);
-                if token < 1000 {
-                    // limit to 1k for now
-                    if !self.notified_indexing {
-                        let message = if url.contains("ipfs") {
-                            "Indexing collection from IPFS, this may take some time..."
-                        } else {
-                            "Indexing collection..."
-                        };
-                        notifications::notify(message.to_string(), None);
-                        self.notified_indexing = true;
-                    }
-
-                    ctx.link().send_message(Message::RequestMetadata(token + 1));
-                    self.working = true;
-                }
+                self.flush();
                 true
             }
             Message::NotFound(token) | Message::MetadataFailed(token) => {
-                self.working = false;
-                if let Some(collection) = self.collection.as_mut() {
-                    if token == *collection.start_token() {
-                        collection.increment_start_token(1);
-                        ctx.link().send_message(Message::RequestMetadata(token + 1));
-                        return false;
-                    }
-                    match collection.total_supply() {
-                        Some(total_supply) => {
-                            // Continue indexing until total supply reached
-                            if token < *total_supply {
-                                ctx.link().send_message(Message::RequestMetadata(token + 1))
-                            }
-                        }
-                        None => {
-                            // Continue indexing for a maximum of 100 tokens
-                            if token < 100 {
-                                ctx.link().send_message(Message::RequestMetadata(token + 1))
-                            }
-                        }
+                if let Some(scanning) = self.scanning.as_mut().filter(|s| s.front() == Some(&token))
+                {
+                    scanning.pop_front();
+                    self.probe();
+                    return true;
+                }
+                if !self.fetching.remove(&token) {
+                    return false;
+                }
+                notifications::notify(
+                    format!("The metadata for token {token} could not be fetched"),
+                    Some(Color::Warning),
+                );
+                true
+            }
+            Message::Fetch(token) => {
+                if let Some(collection) = self.collection.as_ref() {
+                    if let Some(url) = collection.url(token) {
+                        self.metadata.send(metadata::Request::Metadata {
+                            url,
+                            token: Some(token),
+                            cors_proxy: Some(crate::config::cors_proxy()),
+                            overrides: crate::config::overrides(collection.overrides()),
+                            priority: metadata::Priority::Normal,
+                        });
+                        self.fetching.insert(token);
+                        return true;
                     }
                 }
+                false
+            }
+            Message::StartToken(start_token) => {
+                let collection = match self.collection.as_mut() {
+                    Some(collection) => collection,
+                    None => return false,
+                };
+                collection.set_start_token(start_token);
+                storage::Collection::store(collection);
+                // Indexing restarts from the new start token, rather than resuming from where it stopped
+                storage::Cursor::remove(&collection.id());
+                self.step = Step::Metadata;
+                self.page = 1;
+                ctx.link().send_message(Message::Index(false));
                 true
             }
+            Message::ScanStartToken => {
+                self.scanning = Some(START_CANDIDATES.into_iter().collect());
+                self.probe();
+                true
+            }
+            Message::FetchRange(range) => {
+                for token in range {
+                    if !self.fetching.contains(&token) && !self.tokens.iter().any(|t| t.id == token)
+                    {
+                        ctx.link().send_message(Message::Fetch(token));
+                    }
+                }
+                false
+            }
             // Paging
             Message::Page(page) => {
                 self.page = page;
+                self.flush();
 
-                if let Some(collection) = self.collection.as_ref() {
-                    let (page, total) =
-                        storage::Token::page(collection.id().as_str(), page - 1, self.page_size);
-                    self.tokens = page;
-                    self.indexed = total;
+                // Remember the page, so that viewing can be continued
+                if self.filter.is_none() && self.sort == models::Sort::default() && !self.infinite {
+                    if let Some(collection) = self.collection.as_mut() {
+                        if collection.position().page != Some(page) {
+                            collection.set_last_page(page);
+                            storage::Collection::store(collection);
+                        }
+                    }
+                }
+
+                self.tokens = self.load(ctx, page);
+                self.scroll = Scrolled::new(page);
+                true
+            }
+            // Infinite scrolling
+            Message::More => {
+                let next = self.scroll.pages.end;
+                if (next - 1) * self.page_size >= self.items() {
+                    return false;
+                }
+                let mut tokens = self.load(ctx, next);
+                self.tokens.append(&mut tokens);
+                self.scroll.pages.end += 1;
+
+                // Replace the first page with a spacer of the same height, keeping the number of cards bounded
+                if self.scroll.pages.len() > MAX_PAGES {
+                    let height = self.page_height();
+                    self.tokens.drain(..self.page_size.min(self.tokens.len()));
+                    self.scroll.spacer += height;
+                    self.scroll.heights.push(height);
+                    self.scroll.pages.start += 1;
+                }
+                true
+            }
+            Message::Less => {
+                if self.scroll.pages.start <= 1 {
+                    return false;
+                }
+                let previous = self.scroll.pages.start - 1;
+                let mut tokens = self.load(ctx, previous);
+                tokens.append(&mut self.tokens);
+                self.tokens = tokens;
+                self.scroll.spacer -= self.scroll.heights.pop().unwrap_or(self.scroll.spacer);
+                self.scroll.pages.start -= 1;
+
+                // Remove the last page, which is loaded again once scrolled to
+                if self.scroll.pages.len() > MAX_PAGES {
+                    self.tokens.truncate(MAX_PAGES * self.page_size);
+                    self.scroll.pages.end -= 1;
+                }
+                true
+            }
+            Message::Refill => {
+                // Only the last page can be incomplete, which is loaded again with any tokens indexed since
+                let last = self.scroll.pages.end - 1;
+                let loaded = self
+                    .tokens
+                    .len()
+                    .saturating_sub((self.scroll.pages.len() - 1) * self.page_size);
+                if loaded >= self.page_size {
+                    return false;
+                }
+                let mut tokens = self.load(ctx, last);
+                self.tokens.truncate(self.tokens.len() - loaded);
+                self.tokens.append(&mut tokens);
+                true
+            }
+            // Filtering
+            Message::Filter(filter) => {
+                if self.filter == filter {
+                    return false;
+                }
+                self.filter = filter;
+                self.matches = None;
+                let mut preferences = storage::Preferences::get(ctx.props().id.as_str());
+                preferences.filter = self.filter.clone();
+                storage::Preferences::store(ctx.props().id.as_str(), &preferences);
+                ctx.link().send_message(Message::Page(1));
+                false
+            }
+            Message::ClearFilter => {
+                ctx.link().history().unwrap().push(Route::Collection {
+                    id: ctx.props().id.clone(),
+                });
+                ctx.link().send_message(Message::Filter(None));
+                false
+            }
+            // Sorting
+            Message::Sort(sort) => {
+                if self.sort == sort {
+                    return false;
+                }
+                self.sort = sort;
+                let mut preferences = storage::Preferences::get(ctx.props().id.as_str());
+                preferences.sort = sort;
+                storage::Preferences::store(ctx.props().id.as_str(), &preferences);
+                ctx.link().send_message(Message::Page(1));
+                false
+            }
+            // Layout
+            Message::Layout(layout) => {
+                if self.layout == layout {
+                    return false;
+                }
+                self.layout = layout;
+                let mut settings = storage::Settings::get();
+                settings.layout = layout;
+                storage::Settings::store(&settings);
+                // The heights of any pages scrolled past no longer apply
+                ctx.link().send_message(Message::Page(self.page));
+                false
+            }
+            // Watchlist
+            Message::ToggleWatch => {
+                let collection = match self.collection.as_ref() {
+                    Some(collection) => collection,
+                    None => return false,
+                };
+                if self.watched {
+                    let id = collection.id();
+                    if let Some(watched) = storage::Watchlist::remove(&id) {
+                        let link = ctx.link().clone();
+                        notifications::notify_with_action(
+                            "Removed from watchlist".to_string(),
+                            "Undo",
+                            move || {
+                                storage::Watchlist::store(&id, watched);
+                                link.send_message(Message::Watched(true));
+                            },
+                        );
+                    }
+                } else {
+                    storage::Watchlist::store(
+                        &collection.id(),
+                        storage::Watched {
+                            total_supply: *collection.total_supply(),
+                            floor: None,
+                        },
+                    );
+                    notifications::notify(
+                        "Added to watchlist. You will be notified of changes to the total supply or floor price whilst the app is open.".to_string(),
+                        None,
+                    );
+                }
+                self.watched = !self.watched;
+                true
+            }
+            Message::Watched(watched) => {
+                self.watched = watched;
+                true
+            }
+            // Favorites
+            Message::ToggleFavorite => {
+                self.favorite = storage::Favorites::toggle_collection(&ctx.props().id);
+                notifications::notify(
+                    if self.favorite {
+                        "Added to favorites"
+                    } else {
+                        "Removed from favorites"
+                    }
+                    .to_string(),
+                    None,
+                );
+                true
+            }
+            // Search
+            Message::Search(query) => {
+                self.query = query;
+                self.results = (0, Vec::new());
+                let collection = match self.collection.as_ref() {
+                    Some(collection) if !self.query.trim().is_empty() => collection.id(),
+                    _ => return true,
+                };
+                let index = self
+                    .search_index
+                    .get_or_insert_with(|| Rc::new(storage::Token::search(collection.as_str())));
+                let matches = search::query(index, &self.query);
+                self.results = (
+                    matches.len(),
+                    matches
+                        .into_iter()
+                        .take(SEARCH_RESULTS)
+                        .map(|token| {
+                            let name = storage::Token::get(collection.as_str(), token)
+                                .and_then(|t| t.metadata.and_then(|m| m.name))
+                                .unwrap_or_else(|| format!("#{token}"));
+                            (token, name)
+                        })
+                        .collect(),
+                );
+                true
+            }
+            // Market
+            Message::Details(collection, details) => {
+                // Ignore any details returned from worker which dont pertain to current collection
+                if self.collection.as_ref().map(|c| c.id()) != Some(collection.clone()) {
+                    return false;
                 }
 
+                storage::Market::store_details(&collection, details.clone());
+                self.details = Some(details);
+                true
+            }
+            Message::Prices(collection, prices) => {
+                storage::Market::store_prices(&collection, None, prices.clone());
+                if self.collection.as_ref().map(|c| c.id()) != Some(collection) {
+                    return false;
+                }
+                self.prices = Some(prices);
+                true
+            }
+            Message::TraitFloors(collection, floors) => {
+                storage::Market::store_trait_floors(&collection, floors.clone());
+                if self.collection.as_ref().map(|c| c.id()) != Some(collection) {
+                    return false;
+                }
+                self.trait_floors = Some(Rc::new(floors));
                 true
             }
             // Ignore
@@ -470,9 +1348,29 @@ impl Component for Collection {
         }
     }
 
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        // Cancel any pending retry
+        self.retry = None;
+        // Ensure any buffered tokens are written when navigating away
+        self.flush();
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
+        // Render a skeleton whilst the collection is still being resolved
+        if let Some(collection) = self.collection.as_ref() {
+            if self.step != Step::Ready && self.tokens.is_empty() {
+                return html! {
+                    <div id="collection">
+                        { self.skeleton(ctx, collection) }
+                    </div>
+                };
+            }
+        }
+
         let page = self.page;
         let copy_address = ctx.link().callback(move |_| Message::CopyAddress);
+        let clear_filter = ctx.link().callback(move |_| Message::ClearFilter);
+        let toggle_watch = ctx.link().callback(move |_| Message::ToggleWatch);
         let previous_page = ctx.link().callback(move |_| {
             if let Some(window) = web_sys::window() {
                 Scroll::top(&window);
@@ -485,24 +1383,33 @@ impl Component for Collection {
             }
             Message::Page(page + 1)
         });
-        let image_onload = Callback::from(move |e: web_sys::Event| {
-            if let Some(figure) = e
-                .target_unchecked_into::<web_sys::HtmlElement>()
-                .offset_parent()
-            {
-                let _ = figure.class_list().remove_1("is-square");
-            }
-        });
-
+        let budget = self.progress.as_ref().and_then(|p| p.budget.as_ref());
         html! {
             <div id="collection">
             if let Some(collection) = &self.collection {
+                if let Some(banner) = self.details.as_ref().and_then(|d| d.banner.clone()) {
+                    <figure class="image is-banner">
+                        <img src={ banner } alt={ collection.name().unwrap_or_default().to_string() } />
+                    </figure>
+                }
                 <section class="section is-header">
+                    if self.indexing_paused {
+                        <DataSaver>
+                            <button onclick={ ctx.link().callback(|_| Message::ResumeIndexing) } class="button is-small">
+                                { "Resume indexing" }
+                            </button>
+                        </DataSaver>
+                    } else {
+                        <DataSaver />
+                    }
                     <div class="columns">
                         <div class="column">
                             if let Some(name) = collection.name() {
                                 <h1 class="title nifty-name">{ name.clone() }</h1>
                             }
+                            if let Some(description) = self.details.as_ref().and_then(|d| d.description.clone()) {
+                                <p class="description">{ description }</p>
+                            }
                             <div class="level is-mobile">
                                 <div class="level-left">
                                     if let models::Collection::Contract{ address, ..} = collection {
@@ -525,33 +1432,172 @@ impl Component for Collection {
                                             {" / "}{ total_supply.separate_with_commas() }
                                         }
                                         {" items"}
+                                        if let Some(retry) = self.retry.as_ref() {
+                                            <span class="has-text-warning">{ format!(" ({})", retry.describe()) }</span>
+                                        }
+                                        if let Some(resumes) = budget.and_then(|b| b.resumes) {
+                                            <span class="has-text-warning">
+                                                { format!(" (budget used, resuming in {})", duration(resumes)) }
+                                            </span>
+                                        } else if let Some(eta) = self.progress.as_ref().and_then(|p| p.eta) {
+                                            <span class="has-text-grey">{ format!(" (about {} remaining)", duration(eta)) }</span>
+                                        }
                                     </span>
+                                    if let Some(prices) = self.prices.clone() {
+                                        <Prices { prices } />
+                                    }
+                                    if let Some(retrying) = self.progress.as_ref().map(|p| p.retrying).filter(|r| *r > 0) {
+                                        <div class="level-item">
+                                            <button class="button" disabled={ true }>
+                                                { format!("Retrying {}...", retrying.separate_with_commas()) }
+                                            </button>
+                                        </div>
+                                    } else if self.failed > 0 {
+                                        <div class="level-item">
+                                            <button onclick={ ctx.link().callback(|_| Message::Index(true)) }
+                                                class="button is-warning is-light has-tooltip-bottom"
+                                                data-tooltip="Tokens whose metadata could not be fetched">
+                                                { format!("Retry failed ({})", self.failed.separate_with_commas()) }
+                                            </button>
+                                        </div>
+                                    }
+                                    if let Some(budget) = budget.filter(|_| self.working) {
+                                        <div class="level-item">
+                                            <Link<Route> to={ Route::Settings }>
+                                                <span class="tag is-light has-tooltip-bottom"
+                                                    data-tooltip="Metadata requests within the last hour">
+                                                    { format!("{} / {} requests", budget.requests.separate_with_commas(),
+                                                        budget.limit.separate_with_commas()) }
+                                                </span>
+                                            </Link<Route>>
+                                        </div>
+                                    }
+                                    <div class="level-item">
+                                        <Link<Route> classes="button" to={ Route::CollectionRarity { id: collection.id() } }>
+                                            <span class="icon is-small has-tooltip-bottom" data-tooltip="Rarity">
+                                                <i class="fa-solid fa-ranking-star"></i>
+                                            </span>
+                                        </Link<Route>>
+                                    </div>
+                                    <div class="level-item">
+                                        <Link<Route> classes="button" to={ Route::CollectionDiagnostics { id: collection.id() } }>
+                                            <span class="icon is-small has-tooltip-bottom" data-tooltip="Diagnostics">
+                                                <i class="fa-solid fa-stethoscope"></i>
+                                            </span>
+                                        </Link<Route>>
+                                    </div>
+                                    <div class="level-item">
+                                        <button onclick={ ctx.link().callback(|_| Message::Share) } class="button">
+                                            <span class="icon is-small has-tooltip-bottom" data-tooltip="Copy Link">
+                                                <i class="fa-solid fa-share-nodes"></i>
+                                            </span>
+                                        </button>
+                                    </div>
+                                    <div class="level-item">
+                                        <button onclick={ ctx.link().callback(|_| Message::ToggleFavorite) } class="button">
+                                            <span class="icon is-small has-tooltip-bottom"
+                                                data-tooltip={ if self.favorite { "Unfavorite" } else { "Favorite" } }>
+                                                <i class={ if self.favorite { "fa-solid fa-heart" } else { "fa-regular fa-heart" } }></i>
+                                            </span>
+                                        </button>
+                                    </div>
+                                    if let models::Collection::Contract{ .. } = collection {
+                                        <div class="level-item">
+                                            <button onclick={ toggle_watch } class="button">
+                                                <span class="icon is-small has-tooltip-bottom"
+                                                    data-tooltip={ if self.watched { "Unwatch" } else { "Watch" } }>
+                                                    <i class={ if self.watched { "fa-solid fa-eye" } else { "fa-regular fa-eye" } }></i>
+                                                </span>
+                                            </button>
+                                        </div>
+                                    }
+                                    <div class="level-item">
+                                        <archive::Archive collection={ collection.id() } indexed={ self.indexed }
+                                            total_supply={ *collection.total_supply() }
+                                            completed={ self.progress.as_ref().map_or(false, |p| p.completed) } />
+                                    </div>
+                                    if let Some(filter) = &self.filter {
+                                        <div class="level-item">
+                                            <span class="tag is-medium">
+                                                { format!("{}: {} ({})", filter.trait_type, filter.value,
+                                                    self.matches.unwrap_or_default().separate_with_commas()) }
+                                                <button onclick={ clear_filter } class="delete is-small"></button>
+                                            </span>
+                                        </div>
+                                    }
+                                    <div class="level-item">
+                                        { self.sort(ctx) }
+                                    </div>
+                                    <div class="level-item">
+                                        { self.layout(ctx) }
+                                    </div>
+                                    <div class="level-item search">
+                                        { self.search(ctx, collection) }
+                                    </div>
                                     if self.working {
                                         <i class="is-loading level-item"></i>
                                     }
                                 </div>
                             </div>
                         </div>
-                        <div class="column">
-                            <Navigate { page } page_size={ self.page_size } items={ self.indexed }
-                                previous={ previous_page.clone() } next={ next_page.clone() } />
-                        </div>
+                        if !self.infinite {
+                            <div class="column">
+                                <Navigate { page } page_size={ self.page_size } items={ self.items() }
+                                    previous={ previous_page.clone() } next={ next_page.clone() } />
+                            </div>
+                        }
                     </div>
+                    if let Some(target) = self.redirect.as_ref() {
+                        <div class="notification is-info is-light">
+                            <button class="delete" onclick={ ctx.link().callback(|_| Message::DismissRedirect) }></button>
+                            { "Requests for the metadata of this collection are consistently redirected to " }
+                            <span class="is-family-monospace">{ target }</span>
+                            { ". Rewrite the base uri of the collection to skip the redirect on every request?" }
+                            <div class="buttons mt-2">
+                                <button class="button is-small is-info"
+                                    onclick={ ctx.link().callback(|_| Message::RewriteBaseUri) }>
+                                    { "Rewrite base uri" }
+                                </button>
+                            </div>
+                        </div>
+                    }
                 </section>
 
-                // Collection page
+                // Collection page, along with the traits by which it can be filtered
                 <section class="section">
-                    <div class="columns is-multiline">{ self.tokens.iter().filter_map(|token| token.metadata.as_ref()
-                        .map(|metadata| html! {
-                            <div class="column is-one-fifth">
-                                <Link<Route> to={ Route::token(token, collection.id()) }>
-                                    <figure class="image is-square">
-                                        <img src={ metadata.image.clone() } alt={ metadata.name.clone() }
-                                             onload={ image_onload.clone() } />
-                                    </figure>
-                                </Link<Route>>
+                    <div class="columns">
+                        if !self.traits.is_empty() {
+                            <div class="column is-narrow">
+                                <sidebar::Sidebar collection={ collection.id() } traits={ self.traits.clone() }
+                                    filter={ self.filter.clone() } floors={ self.trait_floors.clone() } />
                             </div>
-                        })).collect::<Html>()  }
+                        }
+                        <div class="column">
+                            if self.infinite {
+                                <div style={ format!("height: {}px", self.scroll.spacer) }></div>
+                                if self.scroll.pages.start > 1 {
+                                    <Sentinel key={ format!("less-{}", self.scroll.pages.start) }
+                                        onvisible={ ctx.link().callback(|_| Message::Less) } />
+                                }
+                            }
+                            if self.layout == storage::Layout::List {
+                                <div class="table-container">
+                                    <table class="table is-fullwidth is-hoverable is-narrow">
+                                        <tbody ref={ self.grid.clone() }>{ self.cards(ctx, collection) }</tbody>
+                                    </table>
+                                </div>
+                            } else {
+                                <div class="columns is-multiline" ref={ self.grid.clone() }>
+                                    { self.cards(ctx, collection) }
+                                </div>
+                            }
+                            // Keyed by the pages loaded, so that the next page is requested should the end remain
+                            // visible once loaded
+                            if self.infinite && (self.scroll.pages.end - 1) * self.page_size < self.items() {
+                                <Sentinel key={ format!("more-{}", self.scroll.pages.end) }
+                                    onvisible={ ctx.link().callback(|_| Message::More) } />
+                            }
+                        </div>
                     </div>
                 </section>
             }
@@ -563,20 +1609,34 @@ impl Component for Collection {
 impl Collection {
     pub fn add(&mut self, id: u32, mut metadata: Metadata) {
         // Parse urls
-        metadata.image = uri::parse(&metadata.image).map_or(metadata.image, |url| url.to_string());
-        if let Some(animation_url) = &metadata.animation_url {
-            metadata.animation_url = uri::parse(&animation_url)
-                .map_or(metadata.animation_url, |url| Some(url.to_string()));
-        }
+        metadata.image = uri::resolve(&metadata.image);
+        metadata.animation_url = metadata.animation_url.as_deref().map(uri::resolve);
 
         if let Some(collection) = self.collection.as_ref() {
-            let token = models::Token {
+            let token = Rc::new(models::Token {
                 id,
                 metadata: Some(metadata),
                 last_viewed: None,
-            };
+            });
 
-            self.indexed = storage::Token::store(collection.id().as_str(), token.clone());
+            self.unflushed.push(token.clone());
+            self.indexed += 1;
+
+            // Only add tokens matching the current filter, whilst the current page has room
+            let sorted = self.sort != models::Sort::default() || self.infinite;
+            if let Some(filter) = self.filter.as_ref() {
+                if filter.matches(&token) {
+                    self.matches = Some(self.matches.unwrap_or_default() + 1);
+                    if self.tokens.len() < self.page_size && !sorted {
+                        self.tokens.push(token);
+                    }
+                }
+                return;
+            }
+            // The position of the token within any other order is only known once the page is reloaded
+            if sorted {
+                return;
+            }
 
             let page_start = ((self.page - 1) * self.page_size) as u32 + *collection.start_token();
             let page_end = page_start + self.page_size as u32;
@@ -585,6 +1645,586 @@ impl Collection {
             }
         }
     }
+
+    /// The order in which the tokens are listed, along with its direction.
+    fn sort(&self, ctx: &Context<Self>) -> Html {
+        let sort = self.sort;
+        let onchange = ctx.link().callback(|e: Event| {
+            let index = e
+                .target_unchecked_into::<HtmlSelectElement>()
+                .selected_index();
+            let by = models::SortBy::ALL
+                .get(index.max(0) as usize)
+                .copied()
+                .unwrap_or_default();
+            Message::Sort(by.sort())
+        });
+        let toggle = ctx.link().callback(move |_| {
+            Message::Sort(models::Sort {
+                descending: !sort.descending,
+                ..sort
+            })
+        });
+        html! {
+            <div class="field has-addons">
+                <div class="control">
+                    <div class="select">
+                        <select { onchange }>
+                            { for models::SortBy::ALL.iter().map(|by| html! {
+                                <option selected={ *by == sort.by }>{ by.label() }</option>
+                            }) }
+                        </select>
+                    </div>
+                </div>
+                <div class="control">
+                    <button onclick={ toggle } class="button">
+                        <span class="icon is-small has-tooltip-bottom"
+                            data-tooltip={ if sort.descending { "Descending" } else { "Ascending" } }>
+                            <i class={ if sort.descending { "fa-solid fa-arrow-down-wide-short" }
+                                else { "fa-solid fa-arrow-up-short-wide" } }></i>
+                        </span>
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    /// Switches between grids of small, medium or large cards, or a list.
+    fn layout(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="field has-addons">
+                { for storage::Layout::ALL.into_iter().map(|layout| {
+                    let icon = match layout {
+                        storage::Layout::Small => "fa-solid fa-table-cells",
+                        storage::Layout::Medium => "fa-solid fa-table-cells-large",
+                        storage::Layout::Large => "fa-solid fa-square",
+                        storage::Layout::List => "fa-solid fa-list",
+                    };
+                    html! {
+                        <div class="control">
+                            <button onclick={ ctx.link().callback(move |_| Message::Layout(layout)) }
+                                class={ classes!("button", (self.layout == layout).then(|| "is-primary is-selected")) }>
+                                <span class="icon is-small has-tooltip-bottom" data-tooltip={ layout.label() }>
+                                    <i class={ icon }></i>
+                                </span>
+                            </button>
+                        </div>
+                    }
+                }) }
+            </div>
+        }
+    }
+
+    /// The cards of the tokens listed, keyed so that only cards for newly indexed tokens are rendered.
+    fn cards(&self, ctx: &Context<Self>, collection: &models::Collection) -> Html {
+        let layout = self.layout;
+        match self.range() {
+            Some(range) => html! {
+                { for range.map(|id| match self.tokens.iter().find(|token| token.id == id) {
+                    Some(token) if token.metadata.is_some() => html! {
+                        <Card key={ id } collection={ collection.id() } token={ token.clone() }
+                            rank={ self.ranks.get(&id).copied() } { layout } />
+                    },
+                    _ => html! {
+                        <Placeholder key={ id } token={ id } fetching={ self.fetching.contains(&id) }
+                            fetch={ ctx.link().callback(move |_| Message::Fetch(id)) } { layout } />
+                    },
+                }) }
+            },
+            None => html! {
+                { for self.tokens.iter().filter(|token| token.metadata.is_some()).map(|token| html! {
+                    <Card key={ token.id } collection={ collection.id() } token={ token.clone() }
+                        rank={ self.ranks.get(&token.id).copied() } { layout } />
+                }) }
+            },
+        }
+    }
+
+    /// A search box, listing the first few tokens matching the query.
+    fn search(&self, ctx: &Context<Self>, collection: &models::Collection) -> Html {
+        let (matches, results) = &self.results;
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            Message::Search(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        // Jump to the first matching token on enter
+        let onkeydown = {
+            let history = ctx.link().history();
+            let id = collection.id();
+            let first = results.first().map(|(token, _)| *token);
+            Callback::from(move |e: KeyboardEvent| {
+                if e.key() == "Enter" {
+                    if let (Some(history), Some(token)) = (&history, first) {
+                        history.push(Route::CollectionToken {
+                            id: id.clone(),
+                            token,
+                        });
+                    }
+                }
+            })
+        };
+        html! {
+            <div class={ classes!("dropdown", (!self.query.trim().is_empty()).then(|| "is-active")) }>
+                <div class="dropdown-trigger">
+                    <p class="control has-icons-left">
+                        <input class="input" type="search" placeholder="Search tokens" value={ self.query.clone() }
+                            { oninput } { onkeydown } />
+                        <span class="icon is-left"><i class="fa-solid fa-magnifying-glass"></i></span>
+                    </p>
+                </div>
+                <div class="dropdown-menu">
+                    <div class="dropdown-content">
+                    { for results.iter().map(|(token, name)| html! {
+                        <Link<Route> classes="dropdown-item" to={ Route::CollectionToken { id: collection.id(), token: *token } }>
+                            { name }
+                        </Link<Route>>
+                    }) }
+                    if *matches == 0 {
+                        <p class="dropdown-item">{ "No matching tokens" }</p>
+                    } else if *matches > results.len() {
+                        <p class="dropdown-item has-text-grey">
+                            { format!("and {} more", (matches - results.len()).separate_with_commas()) }
+                        </p>
+                    }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    /// A placeholder for the collection header and tokens, along with the progress of resolving the collection.
+    fn skeleton(&self, ctx: &Context<Self>, collection: &models::Collection) -> Html {
+        html! {
+            <>
+                <section class="section is-header">
+                    <div class="skeleton is-title"></div>
+                    <div class="skeleton is-line"></div>
+                    if let Some(retry) = self.retry.as_ref() {
+                        <p class="step-indicator has-text-warning">
+                            <i class="is-loading"></i>
+                            { format!("{}, attempt {} of {RETRIES}", retry.describe(), retry.attempt) }
+                        </p>
+                    } else if let Some((step, total, description)) = self.step.describe(collection) {
+                        <p class="step-indicator">
+                            <i class="is-loading"></i>
+                            { format!("{step}/{total} {description}") }
+                        </p>
+                    }
+                    if let Step::Failed(reason) = &self.step {
+                        <article class="message is-danger">
+                            <div class="message-body">
+                                { reason }{ ". " }
+                                <Link<Route> to={ Route::CollectionDiagnostics { id: collection.id() } }>
+                                    { "Run diagnostics" }
+                                </Link<Route>>
+                                { " to investigate further." }
+                                if collection.base_uri().is_some() {
+                                    { self.start_token(ctx, collection) }
+                                }
+                            </div>
+                        </article>
+                    }
+                </section>
+                <section class="section">
+                    <div class="columns is-multiline">
+                        { for (0..self.page_size.min(10)).map(|_| html! {
+                            <div class={ classes!("column", self.layout.column()) }>
+                                <div class="skeleton is-square"></div>
+                            </div>
+                        }) }
+                    </div>
+                </section>
+            </>
+        }
+    }
+
+    /// Overrides the start token of the collection, for collections whose tokens start at an arbitrary identifier.
+    fn start_token(&self, ctx: &Context<Self>, collection: &models::Collection) -> Html {
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            u32::from_str(value.trim()).ok().map(Message::StartToken)
+        });
+        let scan = ctx.link().callback(|_| Message::ScanStartToken);
+        html! {
+            <div class="field is-grouped mt-3">
+                <div class="control">
+                    <input class="input is-small" type="number" min="0" placeholder="Start token"
+                        value={ collection.start_token().to_string() } { onchange } />
+                </div>
+                <div class="control">
+                    <button onclick={ scan } disabled={ self.scanning.is_some() }
+                        class={ classes!("button", "is-small", self.scanning.is_some().then(|| "is-loading")) }>
+                        { "Scan for first token" }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    /// Requests the metadata of the next candidate whilst scanning for the first token, until none remain.
+    fn probe(&mut self) {
+        let collection = match self.collection.as_ref() {
+            Some(collection) => collection,
+            None => return,
+        };
+        let candidate = self.scanning.as_ref().and_then(|s| s.front().copied());
+        match candidate.and_then(|candidate| Some((candidate, collection.url(candidate)?))) {
+            Some((candidate, url)) => self.metadata.send(metadata::Request::Metadata {
+                url,
+                token: Some(candidate),
+                cors_proxy: Some(crate::config::cors_proxy()),
+                overrides: crate::config::overrides(collection.overrides()),
+                priority: metadata::Priority::Normal,
+            }),
+            None => {
+                self.scanning = None;
+                notifications::notify(
+                    "No tokens were found at any of the usual start tokens".to_string(),
+                    Some(Color::Warning),
+                );
+            }
+        }
+    }
+
+    /// Schedules a retry of the steps of resolving the collection which are still missing, backing off between
+    /// attempts. Returns false once the retries are exhausted.
+    fn retry(&mut self, ctx: &Context<Self>, step: &'static str) -> bool {
+        // Any pending retry already re-requests all missing steps
+        if self.retry.is_some() {
+            return true;
+        }
+        if self.retries == RETRIES {
+            return false;
+        }
+        self.retries += 1;
+        let delay = RETRY_DELAY * 2u32.pow(self.retries as u32 - 1);
+        log::debug!(collection = ctx.props().id.as_str(), attempt = self.retries, delay = delay;
+            "retrying {step}...");
+        self.retry = Some(Retry {
+            step,
+            attempt: self.retries,
+            delay,
+            _timeout: Timeout::new(delay, {
+                let link = ctx.link().clone();
+                move || link.send_message(Message::Retry)
+            }),
+        });
+        true
+    }
+
+    /// The range of token identifiers on the current page, when paging by identifier. Only possible once the total
+    /// supply is known and when not filtering, as otherwise the tokens on a page depend on those already indexed.
+    fn range(&self) -> Option<Range<u32>> {
+        // Tokens not yet indexed are only shown in place when paging by token identifier
+        if self.filter.is_some() || self.sort != models::Sort::default() || self.infinite {
+            return None;
+        }
+        let collection = self.collection.as_ref()?;
+        let total_supply = (*collection.total_supply())?;
+        let start = *collection.start_token() + ((self.page - 1) * self.page_size) as u32;
+        let end = (start + self.page_size as u32).min(*collection.start_token() + total_supply);
+        Some(start..end.max(start))
+    }
+
+    /// Loads the page of tokens, updating the number of tokens indexed (and matching any filter).
+    fn load(&mut self, ctx: &Context<Self>, page: usize) -> Vec<Rc<models::Token>> {
+        let collection = match self.collection.as_ref() {
+            Some(collection) => collection,
+            None => return Vec::new(),
+        };
+        let id = collection.id();
+        self.traits = Rc::new(Collection::traits(id.as_str()));
+        let tokens = match (self.filter.as_ref(), self.range()) {
+            // Page by token identifier when the total supply is known, so that any tokens not yet indexed can be shown
+            // in place
+            (None, Some(range)) => {
+                self.indexed = storage::Token::count(id.as_str());
+
+                // Fetch the page on demand when well ahead of the sequential indexing, rather than waiting for the
+                // indexing to reach it
+                if let Some(cursor) = self.cursor {
+                    if range.start >= cursor + self.page_size as u32 {
+                        ctx.link().send_message(Message::FetchRange(range.clone()));
+                    }
+                }
+                storage::Token::range(id.as_str(), range)
+            }
+            (None, None) => {
+                let (page, total) =
+                    storage::Token::page(id.as_str(), self.sort, page - 1, self.page_size);
+                self.indexed = total;
+                page
+            }
+            (Some(filter), _) => {
+                let (page, matches) = storage::Token::filter(
+                    id.as_str(),
+                    filter,
+                    self.sort,
+                    page - 1,
+                    self.page_size,
+                );
+                self.matches = Some(matches);
+                self.indexed = storage::Token::count(id.as_str());
+                page
+            }
+        };
+        if self.indexed > 0 {
+            self.step.advance(Step::Ready);
+        }
+        tokens.into_iter().map(Rc::new).collect()
+    }
+
+    /// The height of the first page of cards loaded whilst infinitely scrolling, being the offset of the first card of
+    /// the following page.
+    fn page_height(&self) -> f64 {
+        let grid = match self.grid.cast::<web_sys::HtmlElement>() {
+            Some(grid) => grid,
+            None => return 0.0,
+        };
+        let cards = self
+            .tokens
+            .iter()
+            .take(self.page_size)
+            .filter(|token| token.metadata.is_some())
+            .count() as u32;
+        let offset = |index: u32| {
+            grid.children()
+                .item(index)
+                .and_then(|card| card.dyn_into::<web_sys::HtmlElement>().ok())
+                .map(|card| card.offset_top() as f64)
+        };
+        match (offset(0), offset(cards)) {
+            (Some(first), Some(next)) => next - first,
+            _ => 0.0,
+        }
+    }
+
+    /// The number of items to be paged through.
+    fn items(&self) -> usize {
+        match (self.matches, self.range()) {
+            (Some(matches), _) => matches,
+            (None, Some(_)) => self
+                .collection
+                .as_ref()
+                .and_then(|c| *c.total_supply())
+                .map_or(self.indexed, |total_supply| {
+                    self.indexed.max(total_supply as usize)
+                }),
+            (None, None) => self.indexed,
+        }
+    }
+
+    /// Summarises the trait index of the collection, as the number of tokens having each value of each trait.
+    fn traits(collection: &str) -> sidebar::Traits {
+        storage::Token::traits(collection)
+            .into_iter()
+            .map(|(trait_type, values)| {
+                let values = values
+                    .into_iter()
+                    .map(|(value, tokens)| (value, tokens.len()))
+                    .collect();
+                (trait_type, values)
+            })
+            .collect()
+    }
+
+    /// Writes any tokens indexed since the last flush to storage.
+    fn flush(&mut self) {
+        if self.unflushed.is_empty() {
+            return;
+        }
+        if let Some(collection) = self.collection.as_ref() {
+            let id = collection.id();
+            self.indexed = storage::Token::store_many(
+                id.as_str(),
+                self.unflushed.iter().map(|token| token.as_ref()),
+            );
+            self.unflushed.clear();
+            self.traits = Rc::new(Collection::traits(id.as_str()));
+            self.search_index = None;
+        }
+    }
+}
+
+/// Formats the number of seconds as a rough duration (e.g. `2h 5m`).
+fn duration(seconds: u32) -> String {
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m", seconds / 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+/// The pages loaded whilst infinitely scrolling, along with the space in place of those since scrolled past.
+struct Scrolled {
+    pages: Range<usize>,
+    /// The height (in pixels) of the pages scrolled past, and of each such page.
+    spacer: f64,
+    heights: Vec<f64>,
+}
+
+impl Scrolled {
+    fn new(page: usize) -> Self {
+        Self {
+            pages: page..page + 1,
+            spacer: 0.0,
+            heights: Vec::new(),
+        }
+    }
+}
+
+#[derive(Properties)]
+struct CardProps {
+    collection: String,
+    token: Rc<models::Token>,
+    /// The rank of the token by rarity, where ranked.
+    rank: Option<u32>,
+    layout: storage::Layout,
+}
+
+impl PartialEq for CardProps {
+    fn eq(&self, other: &Self) -> bool {
+        self.collection == other.collection
+            && Rc::ptr_eq(&self.token, &other.token)
+            && self.rank == other.rank
+            && self.layout == other.layout
+    }
+}
+
+#[function_component(Card)]
+fn card(props: &CardProps) -> Html {
+    let favorite = use_state_eq(|| storage::Favorites::contains(&props.collection, props.token.id));
+    let toggle_favorite = {
+        let favorite = favorite.clone();
+        let collection = props.collection.clone();
+        let token = props.token.id;
+        Callback::from(move |_| favorite.set(storage::Favorites::toggle(&collection, token)))
+    };
+    let route = Route::token(&props.token, props.collection.clone());
+    // The image is animated into the token page, rather than the page changing abruptly
+    let open = {
+        let history = use_history().unwrap();
+        let route = route.clone();
+        let token = props.token.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let figure = e
+                .target_dyn_into::<web_sys::Element>()
+                .and_then(|target| target.closest("figure").ok().flatten());
+            let (history, route) = (history.clone(), route.clone());
+            transition::open(figure, token, move || history.push(route));
+        })
+    };
+    let image_onload = Callback::from(move |e: web_sys::Event| {
+        if let Some(figure) = e
+            .target_unchecked_into::<web_sys::HtmlElement>()
+            .offset_parent()
+        {
+            let _ = figure.class_list().remove_1("is-square");
+        }
+    });
+
+    let favorite_button = html! {
+        <button onclick={ toggle_favorite } class={ classes!("button", "is-small", "is-favorite",
+            favorite.then(|| "is-active")) } aria-label="favorite">
+            <span class="icon is-small">
+                <i class={ if *favorite { "fa-solid fa-heart" } else { "fa-regular fa-heart" } }></i>
+            </span>
+        </button>
+    };
+    let rank = props.rank.map(|rank| html! {
+        <Link<Route> classes="tag is-dark" to={ Route::CollectionRarity { id: props.collection.clone() } }>
+            { format!("#{}", rank.separate_with_commas()) }
+        </Link<Route>>
+    });
+
+    let metadata = match props.token.metadata.as_ref() {
+        Some(metadata) => metadata,
+        None => return html! {},
+    };
+    if props.layout == storage::Layout::List {
+        return html! {
+            <tr class="is-card">
+                <td class="is-narrow">
+                    <a href={ route.to_path() } onclick={ open.clone() }>
+                        <lazy::Lazy classes={ classes!("image", "is-48x48",
+                            transition::is_shared(props.token.id).then(|| transition::SHARED)) }>
+                            <Image src={ metadata.image.clone() } alt={ metadata.name.clone() } paused={ true }
+                                lazy={ true } />
+                        </lazy::Lazy>
+                    </a>
+                </td>
+                <td>
+                    <a href={ route.to_path() } onclick={ open }>
+                        { metadata.name.clone().unwrap_or_else(|| format!("#{}", props.token.id)) }
+                    </a>
+                    if let Some(rank) = rank {
+                        <span class="ml-2">{ rank }</span>
+                    }
+                </td>
+                <td>
+                    <div class="tags">
+                    { for metadata.attributes.iter().take(KEY_ATTRIBUTES).map(|attribute| {
+                        let (trait_type, value) = attribute.map();
+                        html! { <span class="tag is-light">{ format!("{trait_type}: {value}") }</span> }
+                    }) }
+                    </div>
+                </td>
+                <td class="is-narrow">{ favorite_button }</td>
+            </tr>
+        };
+    }
+    html! {
+        <div class={ classes!("column", props.layout.column(), "is-card") }>
+            <a href={ route.to_path() } onclick={ open }>
+                <lazy::Lazy classes={ classes!("image", "is-square",
+                    transition::is_shared(props.token.id).then(|| transition::SHARED)) }>
+                    <Image src={ metadata.image.clone() } alt={ metadata.name.clone() }
+                         onload={ image_onload } paused={ true } lazy={ true } />
+                </lazy::Lazy>
+            </a>
+            { favorite_button }
+            if let Some(rank) = rank {
+                <div class="tags is-centered is-rank">{ rank }</div>
+            }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct PlaceholderProps {
+    token: u32,
+    fetching: bool,
+    fetch: Callback<MouseEvent>,
+    layout: storage::Layout,
+}
+
+/// A placeholder for a token which has not yet been indexed, allowing it to be fetched on demand.
+#[function_component(Placeholder)]
+fn placeholder(props: &PlaceholderProps) -> Html {
+    let fetch = html! {
+        <button onclick={ &props.fetch } class={ classes!("button", "is-small", props.fetching.then(|| "is-loading")) }
+            disabled={ props.fetching }>
+            { "Fetch now" }
+        </button>
+    };
+    if props.layout == storage::Layout::List {
+        return html! {
+            <tr>
+                <td class="is-narrow"><div class="image is-48x48 placeholder"></div></td>
+                <td class="has-text-grey">{ format!("#{}", props.token) }</td>
+                <td></td>
+                <td class="is-narrow">{ fetch }</td>
+            </tr>
+        };
+    }
+    html! {
+        <div class={ classes!("column", props.layout.column()) }>
+            <div class="placeholder">
+                <span class="has-text-grey">{ format!("#{}", props.token) }</span>
+                { fetch }
+            </div>
+        </div>
+    }
 }
 
 #[derive(Properties, PartialEq)]
@@ -0,0 +1,285 @@
+use super::Navigate;
+use crate::components::image::Image;
+use crate::storage::Get;
+use crate::{export, models, notifications, storage, Route, Scroll};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::rc::Rc;
+use thousands::Separable;
+use workers::rarity::Rank;
+use workers::{rarity, Bridge, Bridged};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The tokens of a collection, ordered by rarity.
+pub struct Rarity {
+    worker: Box<dyn Bridge<rarity::Worker>>,
+    collection: Option<models::Collection>,
+    ranks: Vec<(Rank, Option<models::Token>)>,
+    ranked: usize,
+    page: usize,
+    page_size: usize,
+    working: bool,
+}
+
+pub enum Message {
+    /// The indexed tokens of the collection have been loaded from storage.
+    Loaded,
+    // Rarity
+    Calculate,
+    Calculated(String, rarity::Rarity),
+    Export,
+    // Paging
+    Page(usize),
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    /// The collection identifier.
+    pub id: String,
+}
+
+impl Component for Rarity {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let id = ctx.props().id.as_str();
+
+        // Load the indexed tokens of the collection, ahead of ranking them
+        ctx.link().send_future({
+            let id = id.to_string();
+            async move {
+                storage::Token::load(&id).await;
+                Message::Loaded
+            }
+        });
+
+        Self {
+            worker: rarity::Worker::bridge(Rc::new({
+                let link = ctx.link().clone();
+                move |e: rarity::Response| {
+                    link.send_message(Message::Calculated(e.collection, e.rarity))
+                }
+            })),
+            collection: storage::Collection::get(id),
+            ranks: Vec::new(),
+            ranked: 0,
+            page: 1,
+            page_size: storage::Settings::get().page_size(),
+            working: false,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Loaded => {
+                // Recalculate when tokens have been indexed since the rarity was last calculated
+                let id = ctx.props().id.as_str();
+                let indexed = storage::Token::count(id);
+                if storage::Rarity::get(id).map_or(true, |rarity| rarity.total != indexed) {
+                    ctx.link().send_message(Message::Calculate);
+                }
+                ctx.link().send_message(Message::Page(1));
+                false
+            }
+            // Rarity
+            Message::Calculate => {
+                let tokens = attributes(ctx.props().id.as_str());
+                if tokens.is_empty() {
+                    notifications::notify(
+                        "Rarity cannot be calculated until the collection has been indexed"
+                            .to_string(),
+                        None,
+                    );
+                    return false;
+                }
+
+                log::trace!(collection = ctx.props().id.as_str(); "requesting rarity of {} tokens...", tokens.len());
+                self.worker.send(rarity::Request {
+                    collection: ctx.props().id.clone(),
+                    tokens,
+                });
+                self.working = true;
+                true
+            }
+            Message::Calculated(collection, rarity) => {
+                // Ignore any rarity returned from worker which doesnt pertain to current collection
+                if collection != ctx.props().id {
+                    return false;
+                }
+
+                storage::Rarity::store(collection.as_str(), &rarity);
+                self.working = false;
+                ctx.link().send_message(Message::Page(self.page));
+                false
+            }
+            Message::Export => {
+                let id = ctx.props().id.as_str();
+                if let Some(rarity) = storage::Rarity::get(id) {
+                    let attributes: HashMap<u32, HashMap<String, String>> = storage::Token::all(id)
+                        .into_iter()
+                        .filter_map(|token| {
+                            token.metadata.map(|metadata| {
+                                (
+                                    token.id,
+                                    metadata.attributes.iter().map(|a| a.map()).collect(),
+                                )
+                            })
+                        })
+                        .collect();
+                    let traits: Vec<&String> = rarity.frequencies.keys().sorted().collect();
+
+                    // Header, followed by a row per token with the value and score of each trait
+                    let mut rows = vec![["token", "rank", "score"]
+                        .iter()
+                        .map(|h| h.to_string())
+                        .chain(
+                            traits
+                                .iter()
+                                .flat_map(|t| [t.to_string(), format!("{t} score")]),
+                        )
+                        .collect::<Vec<String>>()];
+                    for rank in &rarity.ranks {
+                        let values = attributes.get(&rank.token);
+                        rows.push(
+                            [
+                                rank.token.to_string(),
+                                rank.rank.to_string(),
+                                rank.score.to_string(),
+                            ]
+                            .into_iter()
+                            .chain(traits.iter().flat_map(|t| {
+                                match values.and_then(|values| values.get(t.as_str())) {
+                                    Some(value) => {
+                                        [value.clone(), rarity.score(t, value).to_string()]
+                                    }
+                                    None => [String::new(), String::new()],
+                                }
+                            }))
+                            .collect(),
+                        );
+                    }
+
+                    if let Err(e) = export::download(
+                        &format!("{id}-rarity.csv"),
+                        "text/csv",
+                        &export::csv(rows),
+                    ) {
+                        log::error!("unable to export the rarity: {e:?}");
+                    }
+                }
+                false
+            }
+            // Paging
+            Message::Page(page) => {
+                self.page = page;
+                let (ranks, ranked) =
+                    storage::Rarity::page(ctx.props().id.as_str(), page - 1, self.page_size);
+                self.ranks = ranks;
+                self.ranked = ranked;
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let page = self.page;
+        let id = ctx.props().id.clone();
+        let calculate = ctx.link().callback(move |_| Message::Calculate);
+        let export = ctx.link().callback(move |_| Message::Export);
+        let previous_page = ctx.link().callback(move |_| {
+            if let Some(window) = web_sys::window() {
+                Scroll::top(&window);
+            }
+            Message::Page(page - 1)
+        });
+        let next_page = ctx.link().callback(move |_| {
+            if let Some(window) = web_sys::window() {
+                Scroll::top(&window);
+            }
+            Message::Page(page + 1)
+        });
+
+        html! {
+            <div id="collection">
+                <section class="section is-header">
+                    <div class="columns">
+                        <div class="column">
+                            <h1 class="title nifty-name">
+                                <Link<Route> to={ Route::Collection { id: id.clone() } }>
+                                    { self.collection.as_ref().and_then(|c| c.name()).unwrap_or(id.as_str()) }
+                                </Link<Route>>
+                            </h1>
+                            <div class="level is-mobile">
+                                <div class="level-left">
+                                    <span class="level-item">
+                                        { self.ranked.separate_with_commas() }{" ranked by rarity"}
+                                    </span>
+                                    <div class="level-item">
+                                        <button onclick={ calculate } class="button" disabled={ self.working }>
+                                            <span class="icon is-small has-tooltip-bottom" data-tooltip="Recalculate">
+                                                <i class="fa-solid fa-rotate"></i>
+                                            </span>
+                                        </button>
+                                    </div>
+                                    <div class="level-item">
+                                        <button onclick={ export } class="button"
+                                            disabled={ self.working || self.ranked == 0 }>
+                                            <span class="icon is-small has-tooltip-bottom" data-tooltip="Export CSV">
+                                                <i class="fa-solid fa-file-csv"></i>
+                                            </span>
+                                        </button>
+                                    </div>
+                                    if self.working {
+                                        <i class="is-loading level-item"></i>
+                                    }
+                                </div>
+                            </div>
+                        </div>
+                        <div class="column">
+                            <Navigate { page } page_size={ self.page_size } items={ self.ranked }
+                                previous={ previous_page } next={ next_page } />
+                        </div>
+                    </div>
+                </section>
+
+                <section class="section">
+                    <div class="columns is-multiline">{ self.ranks.iter().filter_map(|(rank, token)| token.as_ref()
+                        .and_then(|token| token.metadata.as_ref().map(|metadata| (token, metadata)))
+                        .map(|(token, metadata)| html! {
+                            <div class="column is-one-fifth">
+                                <Link<Route> to={ Route::token(token, id.clone()) }>
+                                    <figure class="image is-square">
+                                        <Image src={ metadata.image.clone() } alt={ metadata.name.clone() }
+                                            paused={ true } />
+                                    </figure>
+                                </Link<Route>>
+                                <div class="tags has-addons is-centered is-rank">
+                                    <span class="tag is-dark">{ format!("#{}", rank.rank.separate_with_commas()) }</span>
+                                    <span class="tag">{ format!("{:.2}", rank.score) }</span>
+                                </div>
+                            </div>
+                        })).collect::<Html>() }
+                    </div>
+                </section>
+            </div>
+        }
+    }
+}
+
+/// The attributes of each indexed token of the collection, as (trait type, value) pairs, from which its rarity is
+/// calculated.
+pub(super) fn attributes(collection: &str) -> Vec<(u32, Vec<(String, String)>)> {
+    storage::Token::all(collection)
+        .into_iter()
+        .filter_map(|token| {
+            token.metadata.map(|metadata| {
+                (
+                    token.id,
+                    metadata.attributes.iter().map(|a| a.map()).collect(),
+                )
+            })
+        })
+        .collect()
+}
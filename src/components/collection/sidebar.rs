@@ -0,0 +1,82 @@
+use crate::{models, Route};
+use bulma::components::Collapsible;
+use std::rc::Rc;
+use thousands::Separable;
+use workers::market::TraitFloors;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The values of each trait of a collection, along with the number of indexed tokens having each value.
+pub type Traits = Vec<(String, Vec<(String, usize)>)>;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    /// The collection identifier.
+    pub collection: String,
+    pub traits: Rc<Traits>,
+    /// The attribute filter currently applied to the collection.
+    pub filter: Option<models::Filter>,
+    /// The floor price of each trait value, where fetched from the market data api.
+    #[prop_or_default]
+    pub floors: Option<Rc<TraitFloors>>,
+}
+
+/// Lists the traits of the collection, filtering the collection to the tokens having the selected value.
+#[function_component(Sidebar)]
+pub fn sidebar(props: &Properties) -> Html {
+    // The trait whose values are listed, initially that of the current filter
+    let expanded = use_state(|| {
+        props
+            .filter
+            .as_ref()
+            .map(|filter| filter.trait_type.clone())
+    });
+
+    html! {
+        <aside class="menu sidebar">
+            <p class="menu-label">{ "Traits" }</p>
+            { for props.traits.iter().map(|(trait_type, values)| {
+                let ontoggle = {
+                    let expanded = expanded.clone();
+                    let trait_type = trait_type.clone();
+                    Callback::from(move |open: bool| expanded.set(open.then(|| trait_type.clone())))
+                };
+                html! {
+                    <Collapsible key={ trait_type.clone() } title={ format!("{trait_type} ({})", values.len()) }
+                        open={ (*expanded).as_ref() == Some(trait_type) } { ontoggle }>
+                        <ul class="menu-list">
+                        { for values.iter().map(|(value, count)| {
+                            let filter = models::Filter {
+                                trait_type: trait_type.clone(),
+                                value: value.clone(),
+                            };
+                            let selected = props.filter.as_ref() == Some(&filter);
+                            // Selecting the current filter again clears it
+                            let query = (!selected).then(|| filter);
+                            let floor = props
+                                .floors
+                                .as_ref()
+                                .and_then(|floors| floors.get(trait_type))
+                                .and_then(|values| values.get(value));
+                            html! {
+                                <li>
+                                    <Link<Route, models::Filter> classes={ classes!(selected.then(|| "is-active")) }
+                                        to={ Route::Collection { id: props.collection.clone() } } { query }>
+                                        { value }
+                                        <span class="is-pulled-right">
+                                            if let Some(floor) = floor {
+                                                <span class="tag is-floor">{ format!("Ξ {}", (floor * 1000.0).round() / 1000.0) }</span>
+                                            }
+                                            { count.separate_with_commas() }
+                                        </span>
+                                    </Link<Route, models::Filter>>
+                                </li>
+                            }
+                        }) }
+                        </ul>
+                    </Collapsible>
+                }
+            }) }
+        </aside>
+    }
+}
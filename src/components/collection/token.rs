@@ -1,10 +1,20 @@
+use crate::history::Visit;
 use crate::storage::RecentlyViewedItem;
 use crate::{
-    components::token, models, notifications, notifications::Color, storage, storage::Get, uri,
-    Address, Route,
+    components::{self, token},
+    config, models, notifications,
+    notifications::Color,
+    storage,
+    storage::Get,
+    transition, uri, Address, Route,
 };
+use std::collections::HashSet;
 use std::rc::Rc;
 use std::str::FromStr;
+use thousands::Separable;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use workers::etherscan::TypeExtensions;
 use workers::metadata::Metadata;
 use workers::{etherscan, metadata, Bridge, Bridged, Url};
 use yew::prelude::*;
@@ -15,13 +25,43 @@ pub struct Token {
     etherscan: Box<dyn Bridge<etherscan::Worker>>,
     metadata: Box<dyn Bridge<metadata::Worker>>,
     collection: Option<models::Collection>,
-    token: Option<models::Token>,
+    token: Option<Rc<models::Token>>,
     notified_requesting_metadata: bool,
     working: bool,
+    /// The token whose neighbours have been prefetched, along with the neighbouring tokens still being prefetched.
+    prefetched: Option<u32>,
+    prefetching: HashSet<u32>,
+    /// Whether the indexed tokens of the collection have been loaded from storage, with messages deferred until then.
+    loaded: bool,
+    deferred: Vec<Message>,
+    /// Whether the metadata of the token could not be parsed, along with the document being corrected locally and any
+    /// error parsing it.
+    failed: bool,
+    editing: Option<String>,
+    error: Option<String>,
+    /// Whether the token is rendered from a locally corrected metadata document.
+    local: bool,
+    /// The object url of any image uploaded locally for the token, along with the token last checked for one.
+    local_image: Option<String>,
+    local_image_checked: Option<u32>,
+    /// The object url of any copy of the image archived for offline viewing (or otherwise cached once displayed),
+    /// displayed in place of the original.
+    archived_image: Option<String>,
+    /// Whether the contract returns the metadata of each token on-chain as a data uri, rather than from a base uri.
+    on_chain: bool,
+    /// The current owner of the token, along with the token whose owner was last requested.
+    owner: Option<Address>,
+    owner_requested: Option<u32>,
 }
 
 pub enum Message {
+    /// The indexed tokens of the collection have been loaded from storage.
+    Loaded,
     // Contract
+    /// Selects the chain of the collection, for any subsequent requests for its contract.
+    Chain(etherscan::Chain),
+    /// Restores the contract cached by a previous visit, along with the results of its calls.
+    CachedContract(Address, etherscan::Cached),
     RequestContract(Address),
     Contract(etherscan::Contract),
     NoContract(Address),
@@ -33,11 +73,27 @@ pub enum Message {
     // Total Supply
     RequestTotalSupply(Address),
     TotalSupply(u32),
+    // Owner
+    RequestOwner(u32),
+    Owner(u32, Address),
     // Metadata
     RequestMetadata(u32),
     Metadata(String, u32, Metadata),
     NotFound(u32),
     MetadataFailed(u32),
+    /// Prefetches the metadata and images of the tokens either side of the token.
+    Prefetch(u32),
+    // Local metadata
+    /// Opens the editor, with the local metadata document of the token or an outline to be completed.
+    EditLocally,
+    LocalMetadata(String),
+    SaveLocalMetadata,
+    CancelLocalMetadata,
+    RemoveLocalMetadata,
+    // Local image
+    LocalImage(u32, Option<web_sys::Blob>),
+    ArchivedImage(u32, Option<web_sys::Blob>),
+    AttachImage(Option<web_sys::File>),
     // Viewed
     Viewed(String, u32, String, String),
     // Ignore
@@ -57,13 +113,37 @@ impl Component for Token {
     type Properties = Properties;
 
     fn create(ctx: &Context<Self>) -> Self {
+        // Load the indexed tokens of the collection, ahead of handling any messages
+        ctx.link().send_future({
+            let id = ctx.props().collection.clone();
+            async move {
+                storage::Token::load(&id).await;
+                Message::Loaded
+            }
+        });
+
+        // Select the chain of the collection, ahead of any requests for its contract
+        if let Some((chain, address)) = models::Collection::parse_contract(&ctx.props().collection)
+        {
+            if chain != etherscan::Chain::Ethereum {
+                ctx.link().send_message(Message::Chain(chain));
+            }
+            // Restore any contract cached by a previous visit, rather than requesting it again
+            if let Some(contract) = storage::Contract::get(chain, &address) {
+                ctx.link()
+                    .send_message(Message::CachedContract(address, contract));
+            }
+        }
+
         let mut collection = storage::Collection::get(ctx.props().collection.as_str());
         let token = storage::Token::get(ctx.props().collection.as_str(), ctx.props().token);
 
         match collection.as_ref() {
             None => {
-                // Check if identifier is an address
-                if let Ok(address) = Address::from_str(ctx.props().collection.as_str()) {
+                // Check if identifier is a (chain-qualified) address
+                if let Some((_, address)) =
+                    models::Collection::parse_contract(&ctx.props().collection)
+                {
                     ctx.link().send_message(Message::RequestContract(address));
                 } else {
                     // Initialise collection from url
@@ -76,8 +156,10 @@ impl Component for Token {
                                     start_token: 0,
                                     total_supply: None,
                                     last_viewed: None,
+                                    overrides: Default::default(),
+                                    position: Default::default(),
                                 };
-                                storage::Collection::store(c.clone());
+                                storage::Collection::store(&c);
                                 collection = Some(c);
                                 ctx.link()
                                     .send_message(Message::RequestMetadata(ctx.props().token))
@@ -101,25 +183,9 @@ impl Component for Token {
                         ctx.link()
                             .send_message(Message::RequestUri(address.clone()))
                     }
-                } else if let None = token {
-                    ctx.link()
-                        .send_message(Message::RequestMetadata(ctx.props().token))
                 }
             }
         }
-        if let Some(metadata) = token.as_ref().and_then(|t| t.metadata.as_ref()) {
-            // Add to recently viewed
-            ctx.link().send_message(Message::Viewed(
-                ctx.props().collection.clone(),
-                ctx.props().token,
-                metadata
-                    .name
-                    .as_ref()
-                    .unwrap_or(&ctx.props().token.to_string())
-                    .to_string(),
-                metadata.image.clone(),
-            ));
-        }
 
         Self {
             etherscan: etherscan::Worker::bridge(Rc::new({
@@ -139,6 +205,19 @@ impl Component for Token {
                         }
                         etherscan::Response::NoTotalSupply(_) => Message::None,
                         etherscan::Response::TotalSupplyFailed(_) => Message::None,
+                        etherscan::Response::Owner(_address, token, owner) => {
+                            Message::Owner(token, owner)
+                        }
+                        etherscan::Response::NoOwner(..)
+                        | etherscan::Response::OwnerFailed(..)
+                        | etherscan::Response::Holdings(..)
+                        | etherscan::Response::HoldingsFailed(_)
+                        | etherscan::Response::GasOracle(_)
+                        | etherscan::Response::GasOracleFailed
+                        | etherscan::Response::Name(..)
+                        | etherscan::Response::NoName(_)
+                        | etherscan::Response::NameFailed(_)
+                        | etherscan::Response::Cached(..) => Message::None,
                     })
                 }
             })),
@@ -154,18 +233,93 @@ impl Component for Token {
                     metadata::Response::Failed(_url, token) => link.send_message(
                         Message::MetadataFailed(token.expect("expected valid token")),
                     ),
+                    metadata::Response::Hosts(_) => {}
                 }
             })),
             collection,
-            token,
+            token: token.map(Rc::new),
             notified_requesting_metadata: false,
             working: false,
+            prefetched: None,
+            prefetching: HashSet::new(),
+            loaded: false,
+            deferred: Vec::new(),
+            failed: false,
+            editing: None,
+            error: None,
+            local: false,
+            local_image: None,
+            local_image_checked: None,
+            archived_image: None,
+            on_chain: false,
+            owner: None,
+            owner_requested: None,
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        if !self.loaded {
+            if let Message::Loaded = msg {
+                self.loaded = true;
+                ctx.link()
+                    .send_message_batch(std::mem::take(&mut self.deferred));
+            } else {
+                self.deferred.push(msg);
+                return false;
+            }
+        }
+
         match msg {
+            Message::Loaded => {
+                self.token =
+                    storage::Token::get(ctx.props().collection.as_str(), ctx.props().token)
+                        .map(Rc::new);
+                match self.token.as_ref() {
+                    None => {
+                        if self.local_metadata(ctx) {
+                            return true;
+                        }
+                        if self
+                            .collection
+                            .as_ref()
+                            .and_then(|c| c.base_uri())
+                            .is_some()
+                        {
+                            ctx.link()
+                                .send_message(Message::RequestMetadata(ctx.props().token))
+                        }
+                    }
+                    Some(token) => {
+                        if let Some(metadata) = token.metadata.as_ref() {
+                            // Add to recently viewed
+                            ctx.link().send_message(Message::Viewed(
+                                ctx.props().collection.clone(),
+                                ctx.props().token,
+                                metadata
+                                    .name
+                                    .as_ref()
+                                    .unwrap_or(&ctx.props().token.to_string())
+                                    .to_string(),
+                                metadata.image.clone(),
+                            ));
+                        }
+                    }
+                }
+                true
+            }
             // Contract
+            Message::Chain(chain) => {
+                self.etherscan.send(etherscan::Request::Chain(
+                    chain,
+                    config::api(chain).to_string(),
+                ));
+                false
+            }
+            Message::CachedContract(address, contract) => {
+                self.etherscan
+                    .send(etherscan::Request::Cached(address, contract));
+                false
+            }
             Message::RequestContract(address) => {
                 // Request contract info via etherscan worker
                 self.etherscan.send(etherscan::Request::Contract(address));
@@ -178,14 +332,18 @@ impl Component for Token {
             }
             Message::Contract(contract) => {
                 // Initialise collection from contract
-                let collection = match storage::Collection::get(&contract.address) {
+                let id = models::Collection::contract_id(contract.chain, &contract.address);
+                let collection = match storage::Collection::get(id.as_str()) {
                     None => models::Collection::Contract {
                         address: contract.address,
                         name: contract.name.clone(),
                         base_uri: None,
                         start_token: 0,
                         total_supply: None,
-                        last_viewed: Some(chrono::offset::Utc::now()),
+                        last_viewed: Some(crate::clock::now()),
+                        overrides: Default::default(),
+                        position: Default::default(),
+                        chain: contract.chain,
                     },
                     Some(collection) => collection,
                 };
@@ -200,21 +358,28 @@ impl Component for Token {
                 } = &collection
                 {
                     if let None = base_uri {
-                        log::trace!("attempting to resolve uri from contract ...");
+                        log::trace!(collection = ctx.props().collection.as_str();
+                            "attempting to resolve uri from contract...");
                         ctx.link()
                             .send_message(Message::RequestUri(address.clone()));
                         self.working = true;
                     }
                     if let None = total_supply {
-                        log::trace!("attempting to resolve total supply from contract ...");
+                        log::trace!(collection = ctx.props().collection.as_str();
+                            "attempting to resolve total supply from contract...");
                         ctx.link()
                             .send_message(Message::RequestTotalSupply(address.clone()));
                         self.working = true;
                     }
                 }
 
+                // Re-request the owner, as the worker first requires the contract
+                if self.owner.is_none() {
+                    self.owner_requested = None;
+                }
+
                 // Store collection locally
-                storage::Collection::store(collection.clone());
+                storage::Collection::store(&collection);
                 self.collection = Some(collection);
                 true
             }
@@ -247,11 +412,33 @@ impl Component for Token {
                 true
             }
             Message::Uri(uri, token) => {
+                // On-chain metadata is returned for each token as a data uri, so has no base uri to resolve
+                if metadata::is_data_uri(&uri) {
+                    self.on_chain = true;
+                    match (token, self.collection.as_ref()) {
+                        (Some(token), Some(collection)) if token == ctx.props().token => {
+                            self.metadata.send(metadata::Request::Metadata {
+                                url: uri,
+                                token: Some(token),
+                                cors_proxy: None,
+                                overrides: crate::config::overrides(collection.overrides()),
+                                priority: metadata::Priority::Normal,
+                            })
+                        }
+                        _ => ctx
+                            .link()
+                            .send_message(Message::RequestMetadata(ctx.props().token)),
+                    }
+                    return false;
+                }
                 if let Some(collection) = self.collection.as_mut() {
                     match uri::parse(&uri) {
                         Ok(url) => {
-                            // Check if url contains token
+                            // Check if url contains token, with ERC-1155 templates used as is
                             match token {
+                                Some(_) if metadata::is_template(url.as_str()) => {
+                                    collection.set_base_uri(url);
+                                }
                                 Some(_) => {
                                     // Parse url to remove the final path segment (token) to use as base uri
                                     if let Some(base_uri) = url
@@ -268,7 +455,7 @@ impl Component for Token {
                                     collection.set_base_uri(url);
                                 }
                             }
-                            storage::Collection::store(collection.clone());
+                            storage::Collection::store(collection);
 
                             // Request current item
                             ctx.link()
@@ -307,18 +494,42 @@ impl Component for Token {
             Message::TotalSupply(total_supply) => {
                 if let Some(collection) = self.collection.as_mut() {
                     collection.set_total_supply(total_supply);
-                    storage::Collection::store(collection.clone());
+                    storage::Collection::store(collection);
                 }
                 self.working = false;
                 false
             }
+            // Owner
+            Message::RequestOwner(token) => {
+                if let Some(models::Collection::Contract { address, .. }) = self.collection.as_ref()
+                {
+                    self.etherscan
+                        .send(etherscan::Request::Owner(*address, token));
+                }
+                false
+            }
+            Message::Owner(token, owner) => {
+                if token != ctx.props().token {
+                    return false;
+                }
+                self.owner = Some(owner);
+                true
+            }
             // Metadata
             Message::RequestMetadata(token) => {
                 // Check if token already exists
-                log::trace!("checking if token {token} already exists locally...");
+                log::trace!(collection = ctx.props().collection.as_str(), token = token;
+                    "checking if token already exists locally...");
                 match storage::Token::get(ctx.props().collection.as_str(), token) {
+                    // Already being prefetched, so await its result
+                    None if self.prefetching.contains(&token) => {
+                        self.working = true;
+                    }
                     None => {
-                        if let Some(url) = self.collection.as_ref().and_then(|c| c.url(token)) {
+                        let collection = self.collection.as_ref();
+                        if let Some((url, collection)) =
+                            collection.and_then(|c| c.url(token).map(|url| (url, c)))
+                        {
                             if !self.notified_requesting_metadata {
                                 let message = if url.contains("ipfs") {
                                     "Requesting metadata from IPFS, this may take some time..."
@@ -329,19 +540,30 @@ impl Component for Token {
                                 self.notified_requesting_metadata = true;
                             }
 
-                            log::trace!("requesting metadata for token {token} from {url}...");
-                            self.metadata.send(metadata::Request {
+                            log::trace!(collection = ctx.props().collection.as_str(), token = token, url = url.as_str();
+                                "requesting metadata...");
+                            self.metadata.send(metadata::Request::Metadata {
                                 url,
                                 token: Some(token),
-                                cors_proxy: Some(crate::config::CORS_PROXY.to_string()),
+                                cors_proxy: Some(crate::config::cors_proxy()),
+                                overrides: crate::config::overrides(collection.overrides()),
+                                priority: metadata::Priority::Normal,
                             });
                             self.working = true;
+                        } else if let Some(models::Collection::Contract { address, .. }) =
+                            collection.filter(|_| self.on_chain)
+                        {
+                            // Request the data uri of the token from the contract
+                            self.etherscan
+                                .send(etherscan::Request::Uri(*address, token));
+                            self.working = true;
                         }
                     }
                     Some(t) => {
                         // Add to recently viewed
                         if let Some(metadata) = &t.metadata {
-                            log::trace!("adding token to recently viewed...");
+                            log::trace!(collection = ctx.props().collection.as_str(), token = token;
+                                "adding token to recently viewed...");
                             ctx.link().send_message(Message::Viewed(
                                 ctx.props().collection.clone(),
                                 token,
@@ -354,7 +576,7 @@ impl Component for Token {
                             ));
                         }
 
-                        self.token = Some(t);
+                        self.token = Some(Rc::new(t));
                         self.working = false;
                     }
                 }
@@ -369,12 +591,22 @@ impl Component for Token {
                         .as_ref()
                         .and_then(|c| c.url(ctx.props().token))
                 {
-                    log::trace!(
-                        "received token {token} does not match currently viewed token {}",
+                    log::trace!(collection = ctx.props().collection.as_str(), token = token;
+                        "received token does not match currently viewed token {}",
                         ctx.props().token
                     );
+                    if self.prefetching.remove(&token) {
+                        log::trace!(collection = ctx.props().collection.as_str(), token = token;
+                            "storing prefetched token...");
+                        preload(&metadata.image);
+                        storage::Token::store(
+                            ctx.props().collection.as_str(),
+                            &models::Token::new(token, metadata),
+                        );
+                    }
                     return false;
                 }
+                self.prefetching.remove(&token);
 
                 // Add to recently viewed
                 ctx.link().send_message(Message::Viewed(
@@ -390,12 +622,26 @@ impl Component for Token {
 
                 // Initialise token
                 let token = models::Token::new(token, metadata);
-                storage::Token::store(ctx.props().collection.as_str(), token.clone());
-                self.token = Some(token);
+                storage::Token::store(ctx.props().collection.as_str(), &token);
+                self.token = Some(Rc::new(token));
+                self.working = false;
+                self.failed = false;
+                true
+            }
+            Message::MetadataFailed(token) if token == ctx.props().token => {
+                // Offer to correct the metadata locally, rendering the token from a prior correction where stored
+                self.prefetching.remove(&token);
                 self.working = false;
+                if !self.local_metadata(ctx) {
+                    self.failed = true;
+                }
                 true
             }
             Message::NotFound(token) | Message::MetadataFailed(token) => {
+                // Failures whilst prefetching are only of interest once the token is viewed
+                if self.prefetching.remove(&token) && token != ctx.props().token {
+                    return false;
+                }
                 self.working = false;
                 if let Some(collection) = self.collection.as_mut() {
                     if token == *collection.start_token() {
@@ -420,8 +666,165 @@ impl Component for Token {
                 }
                 true
             }
+            Message::Prefetch(token) => {
+                self.prefetched = Some(token);
+                let collection = match self.collection.as_ref() {
+                    Some(collection) => collection,
+                    None => return false,
+                };
+                if !storage::Settings::get().prefetches() {
+                    return false;
+                }
+
+                let previous = token
+                    .checked_sub(1)
+                    .filter(|previous| previous >= collection.start_token());
+                let next = Some(token + 1).filter(|_| {
+                    collection
+                        .total_supply()
+                        .map_or(true, |total_supply| token < total_supply)
+                });
+                for token in previous.into_iter().chain(next) {
+                    match storage::Token::get(ctx.props().collection.as_str(), token) {
+                        Some(t) => {
+                            if let Some(metadata) = t.metadata.as_ref() {
+                                preload(&metadata.image)
+                            }
+                        }
+                        None => {
+                            if self.prefetching.contains(&token) {
+                                continue;
+                            }
+                            if let Some(url) = collection.url(token) {
+                                log::trace!(collection = ctx.props().collection.as_str(), token = token, url = url.as_str();
+                                    "prefetching metadata...");
+                                self.metadata.send(metadata::Request::Metadata {
+                                    url,
+                                    token: Some(token),
+                                    cors_proxy: Some(crate::config::cors_proxy()),
+                                    overrides: crate::config::overrides(collection.overrides()),
+                                    priority: metadata::Priority::Low,
+                                });
+                                self.prefetching.insert(token);
+                            }
+                        }
+                    }
+                }
+                false
+            }
+            // Local metadata
+            Message::EditLocally => {
+                let token = ctx.props().token;
+                self.editing = Some(
+                    storage::LocalMetadata::get(ctx.props().collection.as_str(), token)
+                        .unwrap_or_else(|| {
+                            format!(
+                                "{{\n  \"name\": \"#{token}\",\n  \"image\": \"\",\n  \"attributes\": []\n}}"
+                            )
+                        }),
+                );
+                self.error = None;
+                true
+            }
+            Message::LocalMetadata(json) => {
+                self.editing = Some(json);
+                false
+            }
+            Message::SaveLocalMetadata => {
+                let json = match self.editing.as_ref() {
+                    Some(json) => json,
+                    None => return false,
+                };
+                match metadata::parse(json) {
+                    Ok(metadata) => {
+                        let token = ctx.props().token;
+                        storage::LocalMetadata::store(ctx.props().collection.as_str(), token, json);
+                        self.token = Some(Rc::new(models::Token::new(token, metadata)));
+                        self.local = true;
+                        self.failed = false;
+                        self.editing = None;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+                true
+            }
+            Message::CancelLocalMetadata => {
+                self.editing = None;
+                self.error = None;
+                true
+            }
+            Message::RemoveLocalMetadata => {
+                storage::LocalMetadata::remove(ctx.props().collection.as_str(), ctx.props().token);
+                self.token = None;
+                self.local = false;
+                self.editing = None;
+                ctx.link()
+                    .send_message(Message::RequestMetadata(ctx.props().token));
+                true
+            }
+            // Local image
+            Message::LocalImage(token, image) => {
+                if token != ctx.props().token {
+                    return false;
+                }
+                self.revoke_local_image();
+                self.local_image = image.and_then(|image| {
+                    web_sys::Url::create_object_url_with_blob(&image)
+                        .map_err(|e| log::error!("unable to display the local image: {e:?}"))
+                        .ok()
+                });
+                true
+            }
+            Message::ArchivedImage(token, image) => {
+                if token != ctx.props().token {
+                    return false;
+                }
+                revoke(self.archived_image.take());
+                self.archived_image = image.and_then(|image| {
+                    web_sys::Url::create_object_url_with_blob(&image)
+                        .map_err(|e| log::error!("unable to display the archived image: {e:?}"))
+                        .ok()
+                });
+                true
+            }
+            Message::AttachImage(file) => {
+                let collection = ctx.props().collection.clone();
+                let token = ctx.props().token;
+                ctx.link().send_future(async move {
+                    match file {
+                        Some(file) => {
+                            let image: web_sys::Blob = file.into();
+                            let stored =
+                                storage::LocalImage::store(&collection, token, &image).await;
+                            Message::LocalImage(token, stored.then(|| image))
+                        }
+                        None => {
+                            storage::LocalImage::remove(&collection, token).await;
+                            Message::LocalImage(token, None)
+                        }
+                    }
+                });
+                false
+            }
             // Viewed
             Message::Viewed(collection, token, name, image) => {
+                // Remember the token, so that viewing can be continued
+                if let Some(c) = self.collection.as_mut() {
+                    if c.position().token != Some(token) {
+                        c.set_last_token(token);
+                        storage::Collection::store(c);
+                    }
+                }
+
+                storage::Tours::record(&collection, token);
+                storage::RecentlyViewed::record(Visit {
+                    collection: collection.clone(),
+                    token,
+                    name: name.clone(),
+                    image: image.clone(),
+                    viewed: crate::clock::now(),
+                });
                 storage::RecentlyViewed::store(RecentlyViewedItem {
                     name,
                     image,
@@ -438,32 +841,176 @@ impl Component for Token {
     }
 
     fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        // The token is resolved once the indexed tokens have been loaded
+        if !self.loaded {
+            return false;
+        }
+        self.failed = false;
+        self.editing = None;
+        self.error = None;
+        self.local = false;
+        self.revoke_local_image();
+        revoke(self.archived_image.take());
         match storage::Token::get(ctx.props().collection.as_str(), ctx.props().token) {
+            None if self.local_metadata(ctx) => true,
             None => {
-                log::trace!("token changed, requesting metadata...");
+                log::trace!(collection = ctx.props().collection.as_str(), token = ctx.props().token;
+                    "token changed, requesting metadata...");
                 ctx.link()
                     .send_message(Message::RequestMetadata(ctx.props().token));
                 false
             }
             Some(token) => {
-                self.token = Some(token);
+                self.token = Some(Rc::new(token));
                 true
             }
         }
     }
 
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        // Prefetch the neighbouring tokens once the current token has rendered, so navigation is immediate
+        let token = ctx.props().token;
+        if self.token.as_ref().map(|t| t.id) == Some(token) && self.prefetched != Some(token) {
+            ctx.link().send_message(Message::Prefetch(token));
+        }
+
+        // Display any local copy of the image of the token
+        if self.token.is_some() && self.local_image_checked != Some(token) {
+            self.local_image_checked = Some(token);
+            let collection = ctx.props().collection.clone();
+            ctx.link().send_future(async move {
+                Message::LocalImage(token, storage::LocalImage::get(&collection, token).await)
+            });
+
+            // Display any archived copy, so that archived collections can be viewed offline, or otherwise any cached copy
+            if storage::Archive::get(&ctx.props().collection).is_some() {
+                let collection = ctx.props().collection.clone();
+                ctx.link().send_future(async move {
+                    Message::ArchivedImage(token, storage::Archive::image(&collection, token).await)
+                });
+            } else if let Some(image) = self
+                .token
+                .as_ref()
+                .and_then(|token| token.metadata.as_ref())
+                .map(|metadata| metadata.image.clone())
+            {
+                ctx.link().send_future(async move {
+                    Message::ArchivedImage(token, storage::ImageCache::get(&image).await)
+                });
+            }
+        }
+
+        // Request the current owner of the token, where the collection is a contract
+        if self.loaded
+            && self.owner_requested != Some(token)
+            && matches!(self.collection, Some(models::Collection::Contract { .. }))
+        {
+            self.owner_requested = Some(token);
+            self.owner = None;
+            ctx.link().send_message(Message::RequestOwner(token));
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        self.revoke_local_image();
+        revoke(self.archived_image.take());
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
         let start_token = self.collection.as_ref().map_or(0, |c| *c.start_token());
+        let name = self
+            .collection
+            .as_ref()
+            .and_then(|c| c.name())
+            .map_or(ctx.props().collection.clone(), |name| name.to_string());
+        let total_supply = self.collection.as_ref().and_then(|c| *c.total_supply());
+        let position = storage::Token::position(ctx.props().collection.as_str(), ctx.props().token)
+            .map(|(position, indexed)| {
+                (
+                    position,
+                    self.collection
+                        .as_ref()
+                        .and_then(|c| *c.total_supply())
+                        .map_or(indexed, |total_supply| total_supply as usize),
+                )
+            });
 
         html! {
             <section id="piece" class="section is-fullheight">
+                <components::DataSaver />
                 // Collection navigation
                 <Navigate collection={ ctx.props().collection.clone() } token={ ctx.props().token }
-                    working={ self.working } { start_token } />
+                    working={ self.working } { start_token } { total_supply } { name } { position } />
+
+                // Local metadata
+                if self.local && self.editing.is_none() {
+                    <div class="notification is-warning is-light">
+                        <span class="tag is-warning mr-2">{"Local override"}</span>
+                        {"This token is rendered from metadata corrected locally, as the original could not be parsed."}
+                        <div class="buttons mt-2">
+                            <button class="button is-small" onclick={ ctx.link().callback(|_| Message::EditLocally) }>
+                                {"Edit"}
+                            </button>
+                            <button class="button is-small is-danger is-outlined"
+                                onclick={ ctx.link().callback(|_| Message::RemoveLocalMetadata) }>
+                                {"Remove"}
+                            </button>
+                        </div>
+                    </div>
+                }
+                if self.failed && self.editing.is_none() {
+                    <article class="message is-danger">
+                        <div class="message-body">
+                            {"The metadata of this token could not be parsed. You may correct it locally, so that the \
+                            token is rendered whilst the original source remains broken."}
+                            <div class="buttons mt-2">
+                                <button class="button is-small" onclick={ ctx.link().callback(|_| Message::EditLocally) }>
+                                    {"Edit locally"}
+                                </button>
+                            </div>
+                        </div>
+                    </article>
+                }
+                if let Some(json) = self.editing.as_ref() {
+                    <div class="box">
+                        <div class="field">
+                            <label class="label">{"Metadata"}</label>
+                            <div class="control">
+                                <textarea class={classes!("textarea", "is-family-monospace", self.error.as_ref().map(|_| "is-danger"))}
+                                    rows="16" spellcheck="false" value={ json.clone() }
+                                    oninput={ ctx.link().callback(|e: InputEvent| {
+                                        let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+                                        Message::LocalMetadata(input.value())
+                                    }) } />
+                            </div>
+                            if let Some(error) = self.error.as_ref() {
+                                <p class="help is-danger">{ error }</p>
+                            }
+                        </div>
+                        <div class="buttons">
+                            <button class="button is-primary" onclick={ ctx.link().callback(|_| Message::SaveLocalMetadata) }>
+                                {"Save"}
+                            </button>
+                            <button class="button" onclick={ ctx.link().callback(|_| Message::CancelLocalMetadata) }>
+                                {"Cancel"}
+                            </button>
+                        </div>
+                    </div>
+                }
 
                 // Current Token
                 if let Some(token) = self.token.as_ref() {
-                    <token::Token token={ Rc::new(token.clone()) } />
+                    <token::Token collection={ ctx.props().collection.clone() } token={ token.clone() }
+                        local_image={ self.local_image.clone() } archived_image={ self.archived_image.clone() }
+                        onlocalimage={ ctx.link().callback(Message::AttachImage) } />
+                }
+                if let Some(owner) = self.owner.as_ref() {
+                    <p class="owner has-text-centered">
+                        {"Owned by "}
+                        <Link<Route> to={ Route::Address { address: TypeExtensions::format(owner) } }>
+                            { TypeExtensions::format(owner) }
+                        </Link<Route>>
+                    </p>
                 }
 
                 // End of collection error
@@ -484,19 +1031,220 @@ impl Component for Token {
     }
 }
 
+impl Token {
+    /// Releases the object url of the local image, where displayed.
+    fn revoke_local_image(&mut self) {
+        revoke(self.local_image.take())
+    }
+
+    /// Renders the token from its local metadata document, where stored.
+    fn local_metadata(&mut self, ctx: &Context<Self>) -> bool {
+        let token = ctx.props().token;
+        let metadata = storage::LocalMetadata::get(ctx.props().collection.as_str(), token)
+            .and_then(|json| match metadata::parse(&json) {
+                Ok(metadata) => Some(metadata),
+                Err(e) => {
+                    log::error!("unable to parse the local metadata of token {token}: {e:?}");
+                    None
+                }
+            });
+        match metadata {
+            Some(metadata) => {
+                self.token = Some(Rc::new(models::Token::new(token, metadata)));
+                self.local = true;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Releases the object url of an image, where displayed.
+fn revoke(url: Option<String>) {
+    if let Some(url) = url {
+        if let Err(e) = web_sys::Url::revoke_object_url(&url) {
+            log::trace!("unable to revoke the image url: {e:?}")
+        }
+    }
+}
+
+/// Preloads an image at low priority, so that it is already cached by the browser once displayed.
+fn preload(image: &str) {
+    match web_sys::HtmlImageElement::new() {
+        Ok(element) => {
+            if let Err(e) = element.set_attribute("fetchpriority", "low") {
+                log::trace!("unable to set the fetch priority: {e:?}")
+            }
+            element.set_src(image);
+        }
+        Err(e) => log::error!("unable to preload the image at {image}: {e:?}"),
+    }
+}
+
 #[derive(Properties, PartialEq)]
 struct NavigateProps {
     collection: String,
     token: u32,
     working: bool,
     start_token: u32,
+    /// The total supply of the collection, where known, beyond which tokens are not skipped.
+    total_supply: Option<u32>,
+    /// The name of the collection.
+    name: String,
+    /// The position of the token within the indexed tokens, along with the total number of tokens.
+    position: Option<(usize, usize)>,
+}
+
+/// The number of tokens skipped by the navigation buttons and shortcuts, in addition to moving by a single token.
+const STEPS: [u32; 2] = [10, 100];
+
+/// The token reached by moving forwards (or backwards) by the number of tokens, bounded by the start token of the
+/// collection and the last token where the total supply is known.
+fn step(token: u32, by: u32, forwards: bool, start_token: u32, total_supply: Option<u32>) -> u32 {
+    match forwards {
+        true => {
+            let last = total_supply.map_or(u32::MAX, |total_supply| {
+                (start_token + total_supply).saturating_sub(1)
+            });
+            token.saturating_add(by).min(last.max(token))
+        }
+        false => token.saturating_sub(by).max(start_token.min(token)),
+    }
 }
 
 #[function_component(Navigate)]
 fn navigate(props: &NavigateProps) -> Html {
+    // Sequential moves between tokens replace the current entry, unless configured otherwise
+    let history = use_history().unwrap();
+    let go = {
+        let history = history.clone();
+        let collection = props.collection.clone();
+        move |token: u32| {
+            let route = Route::CollectionToken {
+                id: collection.clone(),
+                token,
+            };
+            match storage::Settings::get().navigation {
+                storage::Navigation::Push => history.push(route),
+                storage::Navigation::Replace => history.replace(route),
+            }
+        }
+    };
+    let (token, start_token, total_supply) = (props.token, props.start_token, props.total_supply);
+    let target =
+        move |by: u32, forwards: bool| step(token, by, forwards, start_token, total_supply);
+    let navigate = |token: u32| {
+        let go = go.clone();
+        Callback::from(move |_| go(token))
+    };
+
+    // Arrow keys move to the previous or next token, skipping the first step whilst shift is held and the second
+    // whilst both shift and control are held
+    {
+        let go = go.clone();
+        use_effect_with_deps(
+            move |(token, working, start_token, total_supply)| {
+                let (token, working, start_token, total_supply) =
+                    (*token, *working, *start_token, *total_supply);
+                let onkeydown =
+                    Closure::<dyn Fn(KeyboardEvent)>::wrap(Box::new(move |e: KeyboardEvent| {
+                        let typing =
+                            e.target_dyn_into::<web_sys::Element>()
+                                .map_or(false, |target| {
+                                    matches!(
+                                        target.tag_name().as_str(),
+                                        "INPUT" | "SELECT" | "TEXTAREA"
+                                    )
+                                });
+                        let forwards = match e.key().as_str() {
+                            "ArrowLeft" => false,
+                            "ArrowRight" => true,
+                            _ => return,
+                        };
+                        if working || typing || e.alt_key() || e.meta_key() {
+                            return;
+                        }
+                        let by = match (e.shift_key(), e.ctrl_key()) {
+                            (true, true) => STEPS[1],
+                            (true, false) => STEPS[0],
+                            (false, false) => 1,
+                            (false, true) => return,
+                        };
+                        let target = step(token, by, forwards, start_token, total_supply);
+                        if target != token {
+                            e.prevent_default();
+                            go(target);
+                        }
+                    }));
+                let window = web_sys::window();
+                if let Some(window) = window.as_ref() {
+                    if let Err(e) = window.add_event_listener_with_callback(
+                        "keydown",
+                        onkeydown.as_ref().unchecked_ref(),
+                    ) {
+                        log::error!("unable to listen for navigation shortcuts: {e:?}")
+                    }
+                }
+                move || {
+                    if let Some(window) = window {
+                        let _ = window.remove_event_listener_with_callback(
+                            "keydown",
+                            onkeydown.as_ref().unchecked_ref(),
+                        );
+                    }
+                }
+            },
+            (
+                props.token,
+                props.working,
+                props.start_token,
+                props.total_supply,
+            ),
+        );
+    }
+
+    // The image is animated back into the card of the token within the grid
+    let close = {
+        let (collection, token) = (props.collection.clone(), props.token);
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let history = history.clone();
+            let route = Route::Collection {
+                id: collection.clone(),
+            };
+            transition::close(token, move || history.push(route));
+        })
+    };
+    let collection = Route::Collection {
+        id: props.collection.clone(),
+    }
+    .to_path();
+
     html! {
         <div class="level is-mobile">
-            <div class="level-left"></div>
+            <div class="level-left">
+                <div class="level-item">
+                    <nav class="breadcrumb" aria-label="breadcrumbs">
+                        <ul>
+                            <li>
+                                <a href={ collection.clone() } onclick={ close.clone() }>{ &props.name }</a>
+                            </li>
+                            <li class="is-active">
+                                <a href="javascript:void(0);" aria-current="page">
+                                    { props.token.to_string() }
+                                </a>
+                            </li>
+                        </ul>
+                    </nav>
+                </div>
+                if let Some((position, total)) = props.position {
+                    <div class="level-item is-hidden-mobile">
+                        <span class="tag">
+                            { format!("#{} of {}", position.separate_with_commas(), total.separate_with_commas()) }
+                        </span>
+                    </div>
+                }
+            </div>
             <div class="level-right">
                 <div class="field has-addons">
                     if props.working {
@@ -509,33 +1257,46 @@ fn navigate(props: &NavigateProps) -> Html {
                         </div>
                     }
                     <div class="control">
-                        <Link<Route> classes="button"
-                            to={Route::Collection { id: props.collection.clone() }}>
+                        <a class="button" href={ collection } onclick={ close }>
                             <span class="icon is-small has-tooltip-bottom" data-tooltip="View Collection">
                                 <i class="fa-solid fa-grip"></i>
                             </span>
-                        </Link<Route>>
+                        </a>
                     </div>
+                    { for STEPS.iter().rev().map(|by| html! {
+                        <div class="control is-hidden-mobile">
+                            <button onclick={ navigate(target(*by, false)) } class="button"
+                                disabled={ props.working || target(*by, false) == props.token }>
+                                { format!("-{by}") }
+                            </button>
+                        </div>
+                    }) }
                     <div class="control">
                         if props.token > 0 {
-                            <Link<Route> classes="button is-primary"
-                                to={Route::CollectionToken { id: props.collection.clone(), token: props.token - 1 }}
+                            <button onclick={ navigate(props.token - 1) } class="button is-primary"
                                 disabled={ props.working || props.token == props.start_token }>
                                 <span class="icon is-small">
                                     <i class="fas fa-angle-left"></i>
                                 </span>
-                            </Link<Route>>
+                            </button>
                         }
                     </div>
                     <div class="control">
-                        <Link<Route> classes="button is-primary"
-                            to={Route::CollectionToken { id: props.collection.clone(), token: props.token + 1 }}
+                        <button onclick={ navigate(props.token + 1) } class="button is-primary"
                             disabled={ props.working }>
                             <span class="icon is-small">
                                 <i class="fas fa-angle-right"></i>
                             </span>
-                        </Link<Route>>
+                        </button>
                     </div>
+                    { for STEPS.iter().map(|by| html! {
+                        <div class="control is-hidden-mobile">
+                            <button onclick={ navigate(target(*by, true)) } class="button"
+                                disabled={ props.working || target(*by, true) == props.token }>
+                                { format!("+{by}") }
+                            </button>
+                        </div>
+                    }) }
                 </div>
             </div>
         </div>
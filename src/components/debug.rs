@@ -0,0 +1,111 @@
+use crate::{logging, notifications};
+use yew::prelude::*;
+
+/// The errors (and panics) of the app, most recent first, along with details of the environment, which can be copied
+/// for inclusion within bug reports.
+#[function_component(Debug)]
+pub fn debug() -> Html {
+    let errors = use_state(logging::errors);
+
+    let navigator = web_sys::window().map(|window| window.navigator());
+    let environment = [
+        ("Version", env!("CARGO_PKG_VERSION").to_string()),
+        (
+            "Location",
+            web_sys::window()
+                .and_then(|window| window.location().href().ok())
+                .unwrap_or_default(),
+        ),
+        (
+            "User Agent",
+            navigator
+                .as_ref()
+                .and_then(|navigator| navigator.user_agent().ok())
+                .unwrap_or_default(),
+        ),
+    ];
+    let report = environment
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\n"))
+        .chain(std::iter::once("\n".to_string()))
+        .chain(errors.iter().map(|error| format!("{error}\n")))
+        .collect::<String>();
+
+    let refresh = {
+        let errors = errors.clone();
+        Callback::from(move |_| errors.set(logging::errors()))
+    };
+    let clear = {
+        let errors = errors.clone();
+        Callback::from(move |_| {
+            logging::clear_errors();
+            errors.set(Vec::new())
+        })
+    };
+    let copy = Callback::from(move |_| {
+        if let Some(clipboard) = navigator
+            .as_ref()
+            .and_then(|navigator| navigator.clipboard())
+        {
+            let _ = clipboard.write_text(&report);
+            notifications::notify("Report copied to clipboard".to_string(), None);
+        }
+    });
+
+    html! {
+        <section id="debug" class="section is-fullheight">
+            <div class="level is-mobile">
+                <div class="level-left">
+                    <h1 class="title level-item">{ "Debug" }</h1>
+                </div>
+                <div class="level-right">
+                    <div class="buttons are-small">
+                        <button onclick={ refresh } class="button">{ "Refresh" }</button>
+                        <button onclick={ copy } class="button is-primary">
+                            <span class="icon is-small"><i class="fa-solid fa-copy"></i></span>
+                            <span>{ "Copy Report" }</span>
+                        </button>
+                        <button onclick={ clear } class="button is-danger is-outlined">{ "Clear" }</button>
+                    </div>
+                </div>
+            </div>
+            <p class="help block">{ "The most recent errors of the app, including those of previous sessions. Copy \
+            the report into any bug report, as it includes the details needed to investigate." }</p>
+
+            <table class="table is-narrow is-size-7">
+                <tbody>
+                { for environment.iter().map(|(name, value)| html! {
+                    <tr>
+                        <th>{ name }</th>
+                        <td class="is-family-monospace">{ value }</td>
+                    </tr>
+                }) }
+                </tbody>
+            </table>
+
+            if errors.is_empty() {
+                <p class="has-text-grey">{ "No errors have been recorded." }</p>
+            } else {
+                <div class="table-container">
+                    <table class="table is-fullwidth is-narrow is-size-7">
+                        <tbody>
+                        { for errors.iter().rev().map(|error| html! {
+                            <tr>
+                                <td class="is-family-monospace">
+                                    { error.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string() }
+                                </td>
+                                <td>
+                                    <span class={ classes!("tag", if error.source == "panic" { "is-danger" } else { "is-light" }) }>
+                                        { &error.source }
+                                    </span>
+                                </td>
+                                <td class="is-family-monospace">{ &error.message }</td>
+                            </tr>
+                        }) }
+                        </tbody>
+                    </table>
+                </div>
+            }
+        </section>
+    }
+}
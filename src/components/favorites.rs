@@ -0,0 +1,233 @@
+use crate::components::image::Image;
+use crate::{export, import, notifications, storage, Route};
+use indexmap::IndexSet;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The collections and tokens marked as favorites. The tokens can be exported along with the watched collections as
+/// comma-separated values of `contract,tokenId` (e.g. for use within a spreadsheet), or imported from the same.
+#[function_component(Favorites)]
+pub fn favorites() -> Html {
+    let favorites = use_state(storage::Favorites::values);
+    let collections = use_state(storage::Favorites::collections);
+    let input = use_state(String::new);
+    // Whether the indexed tokens of the collections of the favorites have been loaded, so that their images are known
+    let loaded = use_state_eq(|| false);
+
+    {
+        let loaded = loaded.clone();
+        use_effect_with_deps(
+            move |favorites: &IndexSet<storage::Favorite>| {
+                let collections: IndexSet<_> = favorites
+                    .iter()
+                    .map(|favorite| favorite.collection.clone())
+                    .collect();
+                wasm_bindgen_futures::spawn_local(async move {
+                    for collection in collections {
+                        storage::Token::load(&collection).await;
+                    }
+                    loaded.set(true);
+                });
+                || {}
+            },
+            (*favorites).clone(),
+        );
+    }
+
+    let remove = |favorite: storage::Favorite| {
+        let favorites = favorites.clone();
+        Callback::from(move |_| {
+            storage::Favorites::toggle(&favorite.collection, favorite.token);
+            favorites.set(storage::Favorites::values());
+        })
+    };
+    let remove_collection = |collection: String| {
+        let collections = collections.clone();
+        Callback::from(move |_| {
+            storage::Favorites::toggle_collection(&collection);
+            collections.set(storage::Favorites::collections());
+        })
+    };
+    let export = Callback::from(|_| {
+        let rows = std::iter::once(vec!["contract".to_string(), "tokenId".to_string()])
+            .chain(
+                storage::Favorites::values()
+                    .into_iter()
+                    .map(|favorite| vec![favorite.collection, favorite.token.to_string()]),
+            )
+            .chain(
+                storage::Watchlist::values()
+                    .into_keys()
+                    .map(|collection| vec![collection, String::new()]),
+            );
+        if let Err(e) = export::download(
+            "nifty-gallery-favorites.csv",
+            "text/csv",
+            &export::csv(rows),
+        ) {
+            log::error!("unable to export the favorites: {e:?}")
+        }
+    });
+    let oninput = {
+        let input = input.clone();
+        Callback::from(move |e: InputEvent| {
+            input.set(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        })
+    };
+    let onfile = {
+        let input = input.clone();
+        Callback::from(move |e: Event| {
+            let file = e
+                .target_unchecked_into::<HtmlInputElement>()
+                .files()
+                .and_then(|files| files.get(0));
+            if let Some(file) = file {
+                let input = input.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match JsFuture::from(file.text()).await {
+                        Ok(text) => input.set(text.as_string().unwrap_or_default()),
+                        Err(e) => log::error!("unable to read the file: {e:?}"),
+                    }
+                });
+            }
+        })
+    };
+    let onimport = {
+        let input = input.clone();
+        let favorites = favorites.clone();
+        Callback::from(move |_| {
+            let entries = import::entries(&input);
+            if entries.is_empty() {
+                notifications::notify(
+                    "No entries were found".to_string(),
+                    Some(notifications::Color::Warning),
+                );
+                return;
+            }
+
+            // Entries without a token are watched collections
+            let (tokens, collections): (Vec<_>, Vec<_>) =
+                entries.into_iter().partition(|entry| entry.token.is_some());
+            let added =
+                storage::Favorites::store_many(tokens.iter().filter_map(|entry| {
+                    Some(storage::Favorite::new(&entry.collection, entry.token?))
+                }));
+            let mut watched = 0;
+            for entry in collections {
+                if !storage::Watchlist::contains(&entry.collection) {
+                    storage::Watchlist::store(&entry.collection, storage::Watched::default());
+                    watched += 1;
+                }
+            }
+            notifications::notify(
+                format!(
+                    "Imported {added} favorites ({} already present) and {watched} watched collections",
+                    tokens.len() - added
+                ),
+                None,
+            );
+            input.set(String::new());
+            favorites.set(storage::Favorites::values());
+        })
+    };
+
+    html! {
+        <section id="favorites" class="section is-fullheight">
+            <div class="level is-mobile">
+                <div class="level-left">
+                    <h1 class="title level-item">{ "Favorites" }</h1>
+                </div>
+                <div class="level-right">
+                    <button onclick={ export } class="button is-small">
+                        <span class="icon is-small"><i class="fa-solid fa-file-export"></i></span>
+                        <span>{ "Export CSV" }</span>
+                    </button>
+                </div>
+            </div>
+
+            if !collections.is_empty() {
+                <h2 class="subtitle">{ "Collections" }</h2>
+                <div class="columns is-multiline">
+                { for collections.iter().map(|collection| html! {
+                    <div class="column is-one-fifth is-card">
+                        <Link<Route> classes="box has-text-centered" to={ Route::Collection { id: collection.clone() } }>
+                            { storage::Collection::name(collection) }
+                        </Link<Route>>
+                        <button onclick={ remove_collection(collection.clone()) }
+                            class="button is-small is-favorite is-active" aria-label="remove">
+                            <span class="icon is-small"><i class="fa-solid fa-heart"></i></span>
+                        </button>
+                    </div>
+                }) }
+                </div>
+            }
+
+            if favorites.is_empty() {
+                <p class="has-text-grey block">{ "No tokens have been marked as favorites." }</p>
+            } else {
+                <h2 class="subtitle">{ "Tokens" }</h2>
+                <div class="columns is-multiline">
+                { for favorites.iter().map(|favorite| {
+                    let metadata = loaded
+                        .then(|| storage::Token::get(&favorite.collection, favorite.token))
+                        .flatten()
+                        .and_then(|token| token.metadata);
+                    html! {
+                        <div class="column is-one-fifth is-card">
+                            <Link<Route> to={ Route::CollectionToken { id: favorite.collection.clone(), token: favorite.token } }>
+                                if let Some(metadata) = metadata {
+                                    <figure class="image is-square">
+                                        <Image src={ metadata.image.clone() } alt={ metadata.name.clone() } paused={ true } />
+                                    </figure>
+                                } else {
+                                    <div class="placeholder">
+                                        <span class="has-text-grey">{ format!("#{}", favorite.token) }</span>
+                                    </div>
+                                }
+                            </Link<Route>>
+                            <p class="help has-text-centered">
+                                { format!("{} #{}", storage::Collection::name(&favorite.collection), favorite.token) }
+                            </p>
+                            <button onclick={ remove(favorite.clone()) } class="button is-small is-favorite is-active"
+                                aria-label="remove">
+                                <span class="icon is-small"><i class="fa-solid fa-heart"></i></span>
+                            </button>
+                        </div>
+                    }
+                }) }
+                </div>
+            }
+
+            <div class="box">
+                <h2 class="subtitle">{ "Import" }</h2>
+                <div class="field">
+                    <div class="control">
+                        <textarea class="textarea" value={ (*input).clone() } { oninput }
+                            placeholder="Paste comma-separated values of contract,tokenId, with the token left empty for collections to watch">
+                        </textarea>
+                    </div>
+                </div>
+                <div class="field is-grouped">
+                    <div class="control">
+                        <div class="file is-small">
+                            <label class="file-label">
+                                <input class="file-input" type="file" accept=".csv,.txt" onchange={ onfile } />
+                                <span class="file-cta">
+                                    <span class="file-icon"><i class="fa-solid fa-upload"></i></span>
+                                    <span class="file-label">{ "Choose a file..." }</span>
+                                </span>
+                            </label>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <button onclick={ onimport } class="button is-small is-primary" disabled={ input.is_empty() }>
+                            { "Import" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </section>
+    }
+}
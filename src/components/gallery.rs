@@ -0,0 +1,166 @@
+use crate::components::holdings::Holdings;
+use crate::{notifications, wallet, Address};
+use bulma::components::Message as Status;
+use bulma::Color;
+use std::rc::Rc;
+use std::str::FromStr;
+use workers::etherscan::Holding;
+use workers::{etherscan, Bridge, Bridged};
+use yew::prelude::*;
+
+/// The tokens held by the account of the connected wallet.
+pub struct Gallery {
+    etherscan: Box<dyn Bridge<etherscan::Worker>>,
+    account: Option<Address>,
+    holdings: Option<Rc<Vec<Holding>>>,
+    status: Option<String>,
+    working: bool,
+}
+
+pub enum Message {
+    // Wallet
+    Connect(bool),
+    Accounts(Vec<String>),
+    ConnectionFailed(String),
+    // Holdings
+    Holdings(Address, Vec<Holding>),
+    HoldingsFailed,
+    // Ignore
+    None,
+}
+
+impl Component for Gallery {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        // Check for an existing connection, without prompting
+        if wallet::available() {
+            ctx.link().send_message(Message::Connect(false));
+        }
+
+        Self {
+            etherscan: etherscan::Worker::bridge(Rc::new({
+                let link = ctx.link().clone();
+                move |e: etherscan::Response| {
+                    link.send_message(match e {
+                        etherscan::Response::Holdings(address, holdings) => {
+                            Message::Holdings(address, holdings)
+                        }
+                        etherscan::Response::HoldingsFailed(_) => Message::HoldingsFailed,
+                        _ => Message::None,
+                    })
+                }
+            })),
+            account: None,
+            holdings: None,
+            status: None,
+            working: false,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            // Wallet
+            Message::Connect(prompt) => {
+                ctx.link().send_future(async move {
+                    match wallet::accounts(prompt).await {
+                        Ok(accounts) => Message::Accounts(accounts),
+                        Err(e) => Message::ConnectionFailed(format!("{e:?}")),
+                    }
+                });
+                self.working = true;
+                true
+            }
+            Message::Accounts(accounts) => {
+                self.working = false;
+                if let Some(address) = accounts
+                    .first()
+                    .and_then(|account| Address::from_str(account).ok())
+                {
+                    log::trace!("requesting holdings of connected account...");
+                    self.etherscan.send(etherscan::Request::Holdings(address));
+                    self.status = Some(format!(
+                        "Retrieving the tokens held by {address} via etherscan.io..."
+                    ));
+                    self.account = Some(address);
+                    self.working = true;
+                }
+                true
+            }
+            Message::ConnectionFailed(error) => {
+                log::error!("unable to connect to wallet: {error}");
+                notifications::notify(
+                    "Unable to connect to your wallet".to_string(),
+                    Some(Color::Danger),
+                );
+                self.working = false;
+                true
+            }
+            // Holdings
+            Message::Holdings(address, holdings) => {
+                if self.account != Some(address) {
+                    return false;
+                }
+                self.holdings = Some(Rc::new(holdings));
+                self.status = None;
+                self.working = false;
+                true
+            }
+            Message::HoldingsFailed => {
+                notifications::notify(
+                    "Unable to retrieve the tokens held by your account via etherscan.io. Please try again..."
+                        .to_string(),
+                    Some(Color::Danger),
+                );
+                self.status = None;
+                self.working = false;
+                true
+            }
+            // Ignore
+            Message::None => false,
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let connect = ctx.link().callback(|_| Message::Connect(true));
+
+        html! {
+            <section id="gallery" class="section is-fullheight">
+                <div class="level is-mobile">
+                    <div class="level-left">
+                        <h1 class="title nifty-name level-item">{ "My Gallery" }</h1>
+                        if self.working {
+                            <i class="is-loading level-item"></i>
+                        }
+                    </div>
+                    <div class="level-right">
+                        if wallet::available() && self.account.is_none() {
+                            <button onclick={ connect } class="button is-primary level-item"
+                                disabled={ self.working }>
+                                { "Connect Wallet" }
+                            </button>
+                        }
+                    </div>
+                </div>
+
+                if !wallet::available() {
+                    <Status color={ Color::Info } icon="fas fa-wallet">
+                        { "No wallet was found. Install a browser wallet such as MetaMask to view the tokens held \
+                        by your account." }
+                    </Status>
+                }
+                if let Some(status) = &self.status {
+                    <Status color={ Color::Success } icon="fas fa-circle-info">{ status }</Status>
+                }
+
+                if let Some(holdings) = &self.holdings {
+                    if holdings.is_empty() {
+                        <Status icon="fas fa-circle-info">{ "No tokens are held by your account." }</Status>
+                    }
+                    <Holdings holdings={ holdings.clone() } />
+                }
+            </section>
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{notifications, storage, Route};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The tokens viewed within the configured number of days, grouped by the day on which they were viewed.
+#[function_component(History)]
+pub fn history() -> Html {
+    let visits = use_state(storage::RecentlyViewed::history);
+
+    let clear = {
+        let visits = visits.clone();
+        Callback::from(move |_| {
+            let cleared = storage::RecentlyViewed::clear_history();
+            visits.set(Vec::new());
+            let visits = visits.clone();
+            notifications::notify_with_action("History cleared".to_string(), "Undo", move || {
+                storage::RecentlyViewed::restore_history(cleared);
+                visits.set(storage::RecentlyViewed::history());
+            });
+        })
+    };
+
+    let today = crate::clock::now()
+        .with_timezone(&chrono::Local)
+        .date()
+        .naive_local();
+    let days = crate::history::days(&visits, &chrono::Local);
+
+    html! {
+        <section class="section is-fullheight">
+            <div class="level is-mobile">
+                <div class="level-left">
+                    <h1 class="title level-item">{ "History" }</h1>
+                </div>
+                if !days.is_empty() {
+                    <div class="level-right">
+                        <button onclick={ clear } class="button is-small">{ "Clear" }</button>
+                    </div>
+                }
+            </div>
+
+            if days.is_empty() {
+                <p class="has-text-grey">{ "No tokens have been viewed recently." }</p>
+            }
+            { for days.iter().map(|day| html! {
+                <div class="block">
+                    <h2 class="subtitle">{ day.describe(today) }</h2>
+                    <table class="table is-fullwidth">
+                        <tbody>
+                        { for day.visits.iter().map(|visit| html! {
+                            <tr>
+                                <td class="is-narrow">
+                                    <figure class="image is-48x48">
+                                        <img src={ visit.image.clone() } alt={ visit.name.clone() } loading="lazy" />
+                                    </figure>
+                                </td>
+                                <td>
+                                    <Link<Route> to={ Route::CollectionToken { id: visit.collection.clone(), token: visit.token } }>
+                                        { &visit.name }
+                                    </Link<Route>>
+                                    <p class="help">
+                                        <Link<Route> to={ Route::Collection { id: visit.collection.clone() } }>
+                                            { storage::Collection::name(&visit.collection) }
+                                        </Link<Route>>
+                                    </p>
+                                </td>
+                                <td class="has-text-grey has-text-right">
+                                    { visit.viewed.with_timezone(&chrono::Local).format("%H:%M").to_string() }
+                                </td>
+                            </tr>
+                        }) }
+                        </tbody>
+                    </table>
+                </div>
+            }) }
+        </section>
+    }
+}
@@ -0,0 +1,179 @@
+use crate::components::image::Image;
+use crate::{notifications, storage, Route};
+use bulma::components::Collapsible;
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashSet;
+use std::rc::Rc;
+use workers::etherscan::Holding;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+#[derive(Properties)]
+pub struct HoldingsProps {
+    pub holdings: Rc<Vec<Holding>>,
+}
+
+impl PartialEq for HoldingsProps {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.holdings, &other.holdings)
+    }
+}
+
+/// The tokens held by an address, grouped into a collapsible section per collection.
+#[function_component(Holdings)]
+pub fn holdings(props: &HoldingsProps) -> Html {
+    // Track collapsed (rather than expanded) collections, so that all are expanded initially
+    let collapsed = use_state(HashSet::<String>::new);
+    // Whether the indexed tokens of the collections held have been loaded, so that their images are known
+    let loaded = use_state_eq(|| false);
+
+    {
+        let loaded = loaded.clone();
+        use_effect_with_deps(
+            move |contracts: &IndexSet<String>| {
+                loaded.set(false);
+                let contracts = contracts.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    for contract in contracts {
+                        storage::Token::load(&contract).await;
+                    }
+                    loaded.set(true);
+                });
+                || {}
+            },
+            props
+                .holdings
+                .iter()
+                .map(|holding| holding.contract.clone())
+                .collect::<IndexSet<_>>(),
+        );
+    }
+
+    // Group by contract, preserving the order in which each collection was first encountered
+    let mut collections: IndexMap<&str, Vec<&Holding>> = IndexMap::new();
+    for holding in props.holdings.iter() {
+        collections
+            .entry(holding.contract.as_str())
+            .or_default()
+            .push(holding);
+    }
+
+    collections
+        .into_iter()
+        .map(|(contract, holdings)| {
+            let ontoggle = {
+                let collapsed = collapsed.clone();
+                let contract = contract.to_string();
+                Callback::from(move |open: bool| {
+                    let mut values = (*collapsed).clone();
+                    if open {
+                        values.remove(&contract);
+                    } else {
+                        values.insert(contract.clone());
+                    }
+                    collapsed.set(values);
+                })
+            };
+            let title = format!("{} ({})", holdings[0].name, holdings.len());
+
+            html! {
+                <Collapsible key={ contract.to_string() } { title } open={ !collapsed.contains(contract) } { ontoggle }
+                    classes="holdings">
+                    <div class="level is-mobile">
+                        <div class="level-left"></div>
+                        <div class="level-right">
+                            <Link<Route> classes="button is-small level-item"
+                                to={ Route::Collection { id: contract.to_string() } }>
+                                { "View Collection" }
+                            </Link<Route>>
+                        </div>
+                    </div>
+                    <div class="columns is-multiline">
+                    { for holdings.into_iter().map(|holding| html! {
+                        <Piece holding={ holding.clone() } loaded={ *loaded } />
+                    }) }
+                    </div>
+                </Collapsible>
+            }
+        })
+        .collect()
+}
+
+#[derive(PartialEq, Properties)]
+struct PieceProps {
+    holding: Holding,
+    /// Whether the indexed tokens of the collection have been loaded, rendering the image once known.
+    loaded: bool,
+}
+
+#[function_component(Piece)]
+fn piece(props: &PieceProps) -> Html {
+    let holding = &props.holding;
+    let favorite = use_state_eq(|| storage::Favorites::contains(&holding.contract, holding.token));
+    let toggle_favorite = {
+        let favorite = favorite.clone();
+        let contract = holding.contract.clone();
+        let token = holding.token;
+        Callback::from(move |_| favorite.set(storage::Favorites::toggle(&contract, token)))
+    };
+    let route = Route::CollectionToken {
+        id: holding.contract.clone(),
+        token: holding.token,
+    };
+    // Use the image of the token where already indexed
+    let image = props
+        .loaded
+        .then(|| storage::Token::get(&holding.contract, holding.token))
+        .flatten()
+        .and_then(|token| token.metadata)
+        .map(|metadata| metadata.image);
+    let onshare = {
+        let route = route.clone();
+        Callback::from(move |_: MouseEvent| share(&route))
+    };
+
+    html! {
+        <div class="column is-one-fifth">
+            <Link<Route> to={ route }>
+                <figure class="image is-square">
+                    if let Some(image) = image {
+                        <Image src={ image } alt={ holding.name.clone() } paused={ true } />
+                    } else {
+                        <div class="is-placeholder has-text-centered">
+                            <p>{ &holding.name }</p>
+                            <p class="has-text-weight-bold">{ format!("#{}", holding.token) }</p>
+                        </div>
+                    }
+                </figure>
+            </Link<Route>>
+            <div class="level is-mobile">
+                <div class="level-left">
+                    <span class="level-item is-size-7">{ format!("{} #{}", holding.name, holding.token) }</span>
+                </div>
+                <div class="level-right">
+                    <button onclick={ toggle_favorite } class={ classes!("button", "is-small", "level-item",
+                        "is-favorite", favorite.then(|| "is-active")) } aria-label="favorite">
+                        <span class="icon is-small has-tooltip-left" data-tooltip="Favorite">
+                            <i class={ if *favorite { "fa-solid fa-heart" } else { "fa-regular fa-heart" } }></i>
+                        </span>
+                    </button>
+                    <button onclick={ onshare } class="button is-small level-item">
+                        <span class="icon is-small has-tooltip-left" data-tooltip="Copy Link">
+                            <i class="fa-solid fa-share-nodes"></i>
+                        </span>
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Copies a link to the token to the clipboard.
+fn share(route: &Route) {
+    if let Some(window) = web_sys::window() {
+        if let (Some(url), Some(clipboard)) = (route.share_url(), window.navigator().clipboard()) {
+            let _ = clipboard.write_text(&url);
+            notifications::notify("Link copied to clipboard".to_string(), None);
+        }
+    }
+}
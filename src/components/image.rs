@@ -0,0 +1,249 @@
+use crate::{image, storage, uri};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement};
+use yew::prelude::*;
+
+/// The width of an image within a grid of five columns, or a single column on mobile.
+pub const GRID: &str = "(min-width: 769px) 20vw, 100vw";
+/// The width at which a low-resolution version of a lazily loaded image is requested via the image proxy, shown
+/// blurred until the image itself has loaded.
+const LOW_RES_WIDTH: u32 = 32;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub src: String,
+    #[prop_or_default]
+    pub alt: Option<String>,
+    /// The width at which the image is displayed, from which the browser selects the source to request.
+    #[prop_or(GRID)]
+    pub sizes: &'static str,
+    #[prop_or_default]
+    pub onload: Option<Callback<Event>>,
+    /// Whether any animation is paused, showing only the first frame until played (e.g. within grids).
+    #[prop_or_default]
+    pub paused: bool,
+    /// Whether the image is only requested once near the viewport, shown blurred (over a low-resolution version where
+    /// the image proxy is configured) until loaded.
+    #[prop_or_default]
+    pub lazy: bool,
+}
+
+/// An image, requested via the image proxy (where configured) at the width displayed and, where the proxy supports
+/// format conversion, as AVIF or WebP should the browser support either. Images displayed without the proxy are
+/// cached once loaded, with any cached copy displayed in place of requesting the image again.
+#[function_component(Image)]
+pub fn picture(props: &Properties) -> Html {
+    let cached = use_cached(&props.src);
+    let proxy = use_state(|| storage::Settings::get().image_proxy);
+    let proxy = (*proxy)
+        .as_deref()
+        .and_then(image::Proxy::parse)
+        .filter(|_| !matches!(*cached, Some(Some(_))));
+    let srcset = proxy
+        .as_ref()
+        .and_then(|proxy| proxy.srcset(&props.src, None));
+    let sizes = srcset.as_ref().map(|_| props.sizes);
+    // Inline SVG images are sanitised and re-encoded so that the browser can display them
+    let src = match &*cached {
+        Some(Some(cached)) => Some(cached.clone()),
+        // The image is only requested once the cache has been checked
+        None => None,
+        Some(None) => Some(uri::svg(&props.src).unwrap_or_else(|| props.src.clone())),
+    };
+    let img_ref = use_node_ref();
+    let canvas_ref = use_node_ref();
+    let animated = use_state_eq(|| false);
+    let playing = use_state_eq(|| false);
+    let loaded = use_state_eq(|| false);
+
+    // Any animation of a changed source is yet to be detected
+    {
+        let (animated, playing, loaded) = (animated.clone(), playing.clone(), loaded.clone());
+        use_effect_with_deps(
+            move |_| {
+                animated.set(false);
+                playing.set(false);
+                loaded.set(false);
+                || ()
+            },
+            props.src.clone(),
+        );
+    }
+    // Capture the first frame of an animated image, shown in place of the image until played
+    {
+        let (img_ref, canvas_ref) = (img_ref.clone(), canvas_ref.clone());
+        use_effect_with_deps(
+            move |(animated, playing)| {
+                if *animated && !*playing {
+                    if let Err(e) = capture(&img_ref, &canvas_ref) {
+                        log::trace!("unable to capture the first frame of the image: {e:?}")
+                    }
+                }
+                || ()
+            },
+            (*animated, *playing),
+        );
+    }
+
+    let onload = {
+        let onload = props.onload.clone();
+        let (paused, src, animated) = (props.paused, props.src.clone(), animated.clone());
+        let loaded = loaded.clone();
+        // Only the original image is cached, rather than any of the widths requested via the proxy
+        let cache = matches!(*cached, Some(None)) && srcset.is_none();
+        Callback::from(move |e: Event| {
+            loaded.set(true);
+            if cache {
+                let src = src.clone();
+                wasm_bindgen_futures::spawn_local(
+                    async move { storage::ImageCache::store(&src).await },
+                );
+            }
+            if let Some(onload) = onload.as_ref() {
+                onload.emit(e)
+            }
+            if paused && image::may_be_animated(&src) {
+                let (src, animated) = (src.clone(), animated.clone());
+                wasm_bindgen_futures::spawn_local(
+                    async move { animated.set(is_animated(&src).await) },
+                );
+            }
+        })
+    };
+    let play = {
+        let playing = playing.clone();
+        Callback::from(move |e: MouseEvent| {
+            // Play in place, rather than following any enclosing link
+            e.prevent_default();
+            e.stop_propagation();
+            playing.set(true)
+        })
+    };
+    let still = *animated && !*playing;
+    let blurred = props.lazy && !*loaded;
+    // The low-resolution version is shown as the background of the image, until the image itself is painted over it
+    let style = proxy
+        .as_ref()
+        .filter(|_| blurred && srcset.is_some())
+        .map(|proxy| {
+            format!(
+                "background-image: url('{}')",
+                proxy.url(&props.src, LOW_RES_WIDTH, None)
+            )
+        });
+
+    let img = html! {
+        <img ref={ img_ref } { src } alt={ props.alt.clone() } { srcset } { sizes } { onload } { style }
+            loading={ props.lazy.then(|| "lazy") } class={ classes!(still.then(|| "is-hidden"), blurred.then(|| "is-blurred")) } />
+    };
+
+    let picture = match proxy {
+        Some(proxy) if proxy.formats() && sizes.is_some() => html! {
+            // The browser requests the first source of a type it supports, falling back to the original format
+            <picture>
+                { for image::FORMATS.iter().map(|(format, mime)| html! {
+                    <source type={ *mime } srcset={ proxy.srcset(&props.src, Some(format)) } { sizes } />
+                }) }
+                { img }
+            </picture>
+        },
+        _ => img,
+    };
+    html! {
+        <>
+            { picture }
+            if still {
+                <canvas ref={ canvas_ref } class="is-still has-ratio"></canvas>
+                <button class="button is-play is-rounded is-small" onclick={ play }>
+                    <span class="icon is-small"><i class="fa-solid fa-play"></i></span>
+                </button>
+            }
+        </>
+    }
+}
+
+/// The object url of any cached copy of the image, once the cache has been checked. The object url is revoked once the
+/// source changes or the image is removed.
+fn use_cached(src: &str) -> UseStateHandle<Option<Option<String>>> {
+    let cached = use_state_eq(|| None);
+    {
+        let cached = cached.clone();
+        use_effect_with_deps(
+            move |src: &String| {
+                cached.set(None);
+                // The object url created, or none once revoked
+                let url = Rc::new(RefCell::new(Some(None::<String>)));
+                {
+                    let (src, url) = (src.clone(), url.clone());
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let image = storage::ImageCache::get(&src).await;
+                        let object_url = match url.borrow_mut().as_mut() {
+                            Some(url) => {
+                                *url = image.and_then(|image| {
+                                    web_sys::Url::create_object_url_with_blob(&image)
+                                        .map_err(|e| {
+                                            log::error!("unable to display the cached image: {e:?}")
+                                        })
+                                        .ok()
+                                });
+                                url.clone()
+                            }
+                            // Ignore any image loaded once the source has changed
+                            None => return,
+                        };
+                        cached.set(Some(object_url));
+                    });
+                }
+                move || {
+                    if let Some(Some(url)) = url.borrow_mut().take() {
+                        let _ = web_sys::Url::revoke_object_url(&url);
+                    }
+                }
+            },
+            src.to_string(),
+        );
+    }
+    cached
+}
+
+/// Whether the image at the source is animated, requesting the image again (typically from the browser cache) to
+/// inspect its frames. Should the image not be readable (e.g. due to CORS), GIF and APNG images are assumed animated.
+async fn is_animated(src: &str) -> bool {
+    let response = match gloo_net::http::Request::get(src).send().await {
+        Ok(response) if response.ok() => response,
+        _ => return assume_animated(src),
+    };
+    match response.binary().await {
+        Ok(bytes) => image::animated(&bytes),
+        Err(_) => assume_animated(src),
+    }
+}
+
+fn assume_animated(src: &str) -> bool {
+    let src = src.to_ascii_lowercase();
+    src.starts_with("data:image/gif")
+        || src.split(['?', '#']).next().map_or(false, |path| {
+            path.ends_with(".gif") || path.ends_with(".apng")
+        })
+}
+
+/// Draws the current (first) frame of the image onto the canvas, sized as the image.
+fn capture(img: &NodeRef, canvas: &NodeRef) -> Result<(), wasm_bindgen::JsValue> {
+    let (img, canvas) = match (
+        img.cast::<HtmlImageElement>(),
+        canvas.cast::<HtmlCanvasElement>(),
+    ) {
+        (Some(img), Some(canvas)) => (img, canvas),
+        _ => return Ok(()),
+    };
+    canvas.set_width(img.natural_width());
+    canvas.set_height(img.natural_height());
+    if let Some(context) = canvas.get_context("2d")? {
+        context
+            .dyn_into::<CanvasRenderingContext2d>()?
+            .draw_image_with_html_image_element(&img, 0.0, 0.0)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use crate::storage::Get;
+use crate::{import, models, notifications, storage, Route};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use workers::etherscan::TypeExtensions;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// Bulk registers collections from a list of contract addresses, either pasted or uploaded as a JSON/CSV file. The
+/// names and uris of the collections are resolved once each is first viewed.
+#[function_component(Import)]
+pub fn import() -> Html {
+    let open = use_state(|| false);
+    let input = use_state(String::new);
+    let imported = use_state(Vec::<String>::new);
+
+    let toggle = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(!*open))
+    };
+    let oninput = {
+        let input = input.clone();
+        Callback::from(move |e: InputEvent| {
+            input.set(e.target_unchecked_into::<HtmlTextAreaElement>().value())
+        })
+    };
+    let onfile = {
+        let input = input.clone();
+        Callback::from(move |e: Event| {
+            let file = e
+                .target_unchecked_into::<HtmlInputElement>()
+                .files()
+                .and_then(|files| files.get(0));
+            if let Some(file) = file {
+                let input = input.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match JsFuture::from(file.text()).await {
+                        Ok(text) => input.set(text.as_string().unwrap_or_default()),
+                        Err(e) => log::error!("unable to read the file: {e:?}"),
+                    }
+                });
+            }
+        })
+    };
+    let onimport = {
+        let input = input.clone();
+        let imported = imported.clone();
+        Callback::from(move |_| {
+            let addresses = import::addresses(&input);
+            if addresses.is_empty() {
+                notifications::notify(
+                    "No contract addresses were found".to_string(),
+                    Some(notifications::Color::Warning),
+                );
+                return;
+            }
+
+            let mut added = Vec::new();
+            for address in &addresses {
+                let id = TypeExtensions::format(address);
+                if storage::Collection::get(id.as_str()).is_none() {
+                    storage::Collection::store(&models::Collection::Contract {
+                        address: *address,
+                        name: id.clone(),
+                        base_uri: None,
+                        start_token: 0,
+                        total_supply: None,
+                        last_viewed: None,
+                        overrides: Default::default(),
+                        position: Default::default(),
+                        chain: Default::default(),
+                    });
+                    added.push(id);
+                }
+            }
+            notifications::notify(
+                format!(
+                    "Imported {} collections ({} already present)",
+                    added.len(),
+                    addresses.len() - added.len()
+                ),
+                None,
+            );
+            input.set(String::new());
+            imported.set(added);
+        })
+    };
+
+    html! {
+        <div class="import">
+            <button onclick={ toggle } class="button is-small is-white">
+                <span class="icon is-small"><i class="fa-solid fa-file-import"></i></span>
+                <span>{ "Import Collections" }</span>
+            </button>
+            if *open {
+                <div class="box has-text-left">
+                    <div class="field">
+                        <label class="label">{ "Contract Addresses" }</label>
+                        <div class="control">
+                            <textarea class="textarea" value={ (*input).clone() } { oninput }
+                                placeholder="Paste a JSON array or comma/line-separated list of contract addresses">
+                            </textarea>
+                        </div>
+                    </div>
+                    <div class="field is-grouped">
+                        <div class="control">
+                            <div class="file is-small">
+                                <label class="file-label">
+                                    <input class="file-input" type="file" accept=".json,.csv,.txt" onchange={ onfile } />
+                                    <span class="file-cta">
+                                        <span class="file-icon"><i class="fa-solid fa-upload"></i></span>
+                                        <span class="file-label">{ "Choose a file..." }</span>
+                                    </span>
+                                </label>
+                            </div>
+                        </div>
+                        <div class="control">
+                            <button onclick={ onimport } class="button is-small is-primary" disabled={ input.is_empty() }>
+                                { "Import" }
+                            </button>
+                        </div>
+                    </div>
+                    if !imported.is_empty() {
+                        <div class="tags">
+                        { imported.iter().map(|id| html! {
+                            <Link<Route> classes="tag is-link is-light" to={ Route::Collection { id: id.clone() } }>
+                                { id }
+                            </Link<Route>>
+                        }).collect::<Html>() }
+                        </div>
+                    }
+                </div>
+            }
+        </div>
+    }
+}
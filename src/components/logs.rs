@@ -0,0 +1,199 @@
+use crate::{export, logging, notifications};
+use itertools::Itertools;
+use log::{Level, LevelFilter};
+use std::str::FromStr;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// The levels which can be selected, from least to most verbose.
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    /// The collection to which the events shown can be limited.
+    #[prop_or_default]
+    pub collection: Option<String>,
+}
+
+/// The events logged by the app, most recent first, which can be copied or downloaded for attaching to bug reports,
+/// along with the level at which each module logs.
+#[function_component(Logs)]
+pub fn logs(props: &Properties) -> Html {
+    let events = use_state(logging::events);
+    let level = use_state(|| LevelFilter::Trace);
+    let collection_only = use_state(|| props.collection.is_some());
+    let filters = use_state(logging::filters);
+
+    let shown: Vec<&logging::Event> = events
+        .iter()
+        .rev()
+        .filter(|event| event.level <= *level)
+        .filter(|event| {
+            !*collection_only || event.field("collection") == props.collection.as_deref()
+        })
+        .collect();
+    let text = logging::export(shown.iter().rev().copied());
+    // The modules logged, along with any whose level has been set
+    let modules: Vec<String> = events
+        .iter()
+        .map(|event| event.module.clone())
+        .chain(filters.keys().cloned())
+        .sorted()
+        .dedup()
+        .collect();
+
+    let refresh = {
+        let events = events.clone();
+        Callback::from(move |_| events.set(logging::events()))
+    };
+    let clear = {
+        let events = events.clone();
+        Callback::from(move |_| {
+            logging::clear();
+            events.set(Vec::new())
+        })
+    };
+    let copy = {
+        let text = text.clone();
+        Callback::from(move |_| {
+            if let Some(clipboard) =
+                web_sys::window().and_then(|window| window.navigator().clipboard())
+            {
+                let _ = clipboard.write_text(&text);
+                notifications::notify("Log copied to clipboard".to_string(), None);
+            }
+        })
+    };
+    let download = Callback::from(move |_| {
+        if let Err(e) = export::download("nifty-gallery.log", "text/plain", &text) {
+            log::error!("unable to download the log: {e:?}")
+        }
+    });
+    let onlevel = {
+        let level = level.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+            level.set(LevelFilter::from_str(&value).unwrap_or(LevelFilter::Trace))
+        })
+    };
+    let oncollection = {
+        let collection_only = collection_only.clone();
+        Callback::from(move |e: Event| {
+            collection_only.set(e.target_unchecked_into::<HtmlInputElement>().checked())
+        })
+    };
+    let onfilter = |module: String| {
+        let filters = filters.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+            logging::set_filter(&module, LevelFilter::from_str(&value).ok());
+            filters.set(logging::filters())
+        })
+    };
+
+    html! {
+        <>
+            <div class="level is-mobile">
+                <div class="level-left">
+                    <div class="level-item">
+                        <div class="select is-small">
+                            <select onchange={ onlevel }>
+                                { for LEVELS.iter().skip(1).map(|l| html! {
+                                    <option value={ l.to_string() } selected={ *l == *level }>{ l.to_string() }</option>
+                                }) }
+                            </select>
+                        </div>
+                    </div>
+                    if props.collection.is_some() {
+                        <div class="level-item">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ *collection_only } onchange={ oncollection } />
+                                { " This collection only" }
+                            </label>
+                        </div>
+                    }
+                </div>
+                <div class="level-right">
+                    <div class="buttons are-small">
+                        <button onclick={ refresh } class="button">{ "Refresh" }</button>
+                        <button onclick={ copy } class="button">{ "Copy" }</button>
+                        <button onclick={ download } class="button">{ "Download" }</button>
+                        <button onclick={ clear } class="button is-danger is-outlined">{ "Clear" }</button>
+                    </div>
+                </div>
+            </div>
+
+            if shown.is_empty() {
+                <p class="has-text-grey">{ "No events have been logged." }</p>
+            } else {
+                <div class="table-container">
+                    <table class="table is-fullwidth is-narrow is-size-7">
+                        <tbody>
+                        { for shown.iter().map(|event| html! {
+                            <tr>
+                                <td class="is-family-monospace">
+                                    { event.timestamp.with_timezone(&chrono::Local).format("%H:%M:%S%.3f").to_string() }
+                                </td>
+                                <td><span class={ classes!("tag", color(event.level)) }>{ event.level.to_string() }</span></td>
+                                <td class="has-text-grey">{ &event.module }</td>
+                                <td>
+                                    { &event.message }
+                                    { for event.fields.iter().map(|(key, value)| html! {
+                                        <span class="tag is-light ml-1">{ format!("{key}={value}") }</span>
+                                    }) }
+                                </td>
+                            </tr>
+                        }) }
+                        </tbody>
+                    </table>
+                </div>
+            }
+
+            <div class="box">
+                <h2 class="subtitle">{ "Module Levels" }</h2>
+                <p class="help">{ "The level at which each module (and its submodules) logs, with events above the \
+                level neither written to the console nor retained." }</p>
+                <table class="table is-narrow">
+                    <tbody>
+                    { for modules.into_iter().map(|module| {
+                        let current = filters.get(&module).copied();
+                        html! {
+                            <tr>
+                                <td class="is-family-monospace">{ &module }</td>
+                                <td>
+                                    <div class="select is-small">
+                                        <select onchange={ onfilter(module.clone()) }>
+                                            <option value="" selected={ current.is_none() }>{ "Default" }</option>
+                                            { for LEVELS.iter().map(|l| html! {
+                                                <option value={ l.to_string() } selected={ current == Some(*l) }>
+                                                    { l.to_string() }
+                                                </option>
+                                            }) }
+                                        </select>
+                                    </div>
+                                </td>
+                            </tr>
+                        }
+                    }) }
+                    </tbody>
+                </table>
+            </div>
+        </>
+    }
+}
+
+fn color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "is-danger",
+        Level::Warn => "is-warning",
+        Level::Info => "is-info",
+        Level::Debug | Level::Trace => "is-light",
+    }
+}
@@ -0,0 +1,59 @@
+use workers::market;
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct PricesProps {
+    pub prices: market::Prices,
+    /// Whether the last sale is of the token, rather than of any token of the collection.
+    #[prop_or_default]
+    pub token: bool,
+}
+
+/// The floor price and last sale from the market data api, as level items, omitting any which are unknown.
+#[function_component(Prices)]
+pub fn prices(props: &PricesProps) -> Html {
+    let last_sale = if props.token {
+        "Last sale of this token"
+    } else {
+        "Last sale within the collection"
+    };
+    html! {
+        <>
+        if let Some(floor) = props.prices.floor {
+            <div class="level-item">
+                <span class="tag is-light has-tooltip-bottom" data-tooltip="Floor price">
+                    { format!("Floor Ξ {}", price(floor)) }
+                </span>
+            </div>
+        }
+        if let Some(sale) = props.prices.last_sale {
+            <div class="level-item">
+                <span class="tag is-light has-tooltip-bottom" data-tooltip={ last_sale }>
+                    { format!("Last sale Ξ {}", price(sale)) }
+                </span>
+            </div>
+        }
+        </>
+    }
+}
+
+/// Formats a price to at most four decimal places, without trailing zeros.
+fn price(value: f64) -> String {
+    let formatted = format!("{value:.4}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::price;
+
+    #[test]
+    fn formats_prices() {
+        assert_eq!("0.52", price(0.52));
+        assert_eq!("12", price(12.0));
+        assert_eq!("0.0123", price(0.012345));
+    }
+}
@@ -1,9 +1,8 @@
-use crate::models::Collection;
 use crate::storage::All;
-use crate::{models, storage, uri, Address, Route, Scroll};
+use crate::{config, models, notifications, storage, uri, Address, Route, Scroll};
 use itertools::Itertools;
-use once_cell::sync::Lazy;
 use std::str::FromStr;
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlElement, HtmlInputElement, Node};
 use workers::etherscan::TypeExtensions;
@@ -12,7 +11,117 @@ use yew_router::prelude::*;
 
 pub mod address;
 pub mod collection;
+pub mod debug;
+pub mod favorites;
+pub mod gallery;
+pub mod history;
+pub mod holdings;
+pub mod image;
+pub mod import;
+pub mod logs;
+pub mod market;
+pub mod network;
+pub mod settings;
+pub mod svg;
 pub mod token;
+pub mod tour;
+pub mod watchlist;
+
+#[derive(Properties, PartialEq)]
+pub struct DataSaverProps {
+    /// Any action offered in addition to the explanation, such as resuming indexing.
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Explains the reduced functionality whilst saving data, either as configured within settings or as requested by the
+/// browser.
+#[function_component(DataSaver)]
+pub fn data_saver(props: &DataSaverProps) -> Html {
+    if !storage::Settings::get().saving_data() {
+        return html! {};
+    }
+    html! {
+        <div class="notification is-info is-light">
+            <span class="tag is-info mr-2">{ "Data saver" }</span>
+            { format!("Data is being saved, so at most {} tokens are listed per page, neighbouring tokens are not \
+                prefetched and collections are only indexed on request. ", config::DATA_SAVER_PAGE_SIZE) }
+            <Link<Route> to={ Route::Settings }>{ "Settings" }</Link<Route>>
+            if !props.children.is_empty() {
+                <div class="buttons mt-2">{ for props.children.iter() }</div>
+            }
+        </div>
+    }
+}
+
+/// Explains that nothing will persist beyond the session where the browser restricts storage, such as whilst browsing
+/// privately.
+#[function_component(StorageRestricted)]
+pub fn storage_restricted() -> Html {
+    let dismissed = use_state_eq(|| false);
+    if storage::is_persistent() || *dismissed {
+        return html! {};
+    }
+    let dismiss = Callback::from(move |_| dismissed.set(true));
+    html! {
+        <div class="notification is-warning is-light mb-0">
+            <button class="delete" onclick={ dismiss }></button>
+            { "Your browser is restricting storage (e.g. whilst browsing privately), so settings, favorites and \
+            indexed collections will not persist once this tab is closed." }
+        </div>
+    }
+}
+
+/// Explains that only what has already been indexed can be browsed whilst the device is offline, shown until it is
+/// back online.
+#[function_component(Offline)]
+pub fn offline() -> Html {
+    let online =
+        use_state_eq(|| web_sys::window().map_or(true, |window| window.navigator().on_line()));
+    {
+        let online = online.clone();
+        use_effect_with_deps(
+            move |_| {
+                let onchange =
+                    Closure::<dyn Fn(web_sys::Event)>::wrap(Box::new(move |e: web_sys::Event| {
+                        online.set(e.type_() == "online")
+                    }));
+                let window = web_sys::window();
+                if let Some(window) = window.as_ref() {
+                    for event in ["online", "offline"] {
+                        if let Err(e) = window.add_event_listener_with_callback(
+                            event,
+                            onchange.as_ref().unchecked_ref(),
+                        ) {
+                            log::error!("unable to listen for connectivity changes: {e:?}")
+                        }
+                    }
+                }
+                move || {
+                    if let Some(window) = window {
+                        for event in ["online", "offline"] {
+                            let _ = window.remove_event_listener_with_callback(
+                                event,
+                                onchange.as_ref().unchecked_ref(),
+                            );
+                        }
+                    }
+                }
+            },
+            (),
+        );
+    }
+    if *online {
+        return html! {};
+    }
+    html! {
+        <div class="notification is-info is-light mb-0">
+            <span class="icon"><i class="fa-solid fa-plane"></i></span>
+            { "You are offline. Collections already indexed (along with any images previously viewed) can still be \
+            browsed, with anything else loaded once back online." }
+        </div>
+    }
+}
 
 #[function_component(Footer)]
 pub fn footer() -> yew::Html {
@@ -41,6 +150,8 @@ pub fn home() -> yew::Html {
                                 { "Nifty Gallery, a tool for exploring NFT collections." }
                             </p>
                             <Search />
+                            <import::Import />
+                            <Continue />
                         </div>
                     </section>
                     <section class="section" style="overflow:hidden;">
@@ -54,8 +165,42 @@ pub fn home() -> yew::Html {
     }
 }
 
+/// Offers to continue viewing the most recently viewed collections from the token last viewed.
+#[function_component(Continue)]
+fn continue_viewing() -> yew::Html {
+    let collections: Vec<(String, String, u32)> = storage::Collection::get()
+        .iter()
+        .filter(|collection| collection.last_viewed().is_some())
+        .sorted_by_key(|collection| collection.last_viewed().unwrap())
+        .rev()
+        .filter_map(|collection| {
+            let token = collection.position().token?;
+            let name = collection.name()?.to_string();
+            Some((collection.id(), name, token))
+        })
+        .take(3)
+        .collect();
+
+    html! {
+        if !collections.is_empty() {
+            <div class="continue">
+                <p class="help">{ "Continue where you left off" }</p>
+                <div class="buttons is-centered">
+                { collections.into_iter().map(|(id, name, token)| html! {
+                    <Link<Route> classes="button is-small is-light" to={ Route::CollectionToken { id, token } }>
+                        { format!("{name} #{token}") }
+                    </Link<Route>>
+                }).collect::<Html>() }
+                </div>
+            </div>
+        }
+    }
+}
+
 fn collections() -> Vec<Html> {
     let mut collections: Vec<Html> = Vec::new();
+    // Seeds the notable collections (and stores their details) on first use
+    let notable = storage::Notable::values();
 
     fn html<'a>(collections: impl Iterator<Item = &'a models::Collection>) -> Vec<Html> {
         collections
@@ -90,6 +235,24 @@ fn collections() -> Vec<Html> {
     }
     collections.append(&mut recent);
 
+    // Add imported collections, which are yet to be viewed
+    let mut imported = html(
+        storage::Collection::get()
+            .iter()
+            .filter(|collection| {
+                collection.last_viewed().is_none() && !notable.contains(&collection.id())
+            })
+            .sorted_by_key(|collection| collection.id()),
+    );
+    if imported.len() > 0 {
+        collections.push(html! {
+            <div class="dropdown-header dropdown-item">
+                { "Imported Collections" }
+            </div>
+        });
+    }
+    collections.append(&mut imported);
+
     if collections.len() > 0 {
         collections.push(html! { <hr class="dropdown-divider" /> });
     }
@@ -100,35 +263,26 @@ fn collections() -> Vec<Html> {
             { "Notable Collections" }
         </div>
     });
-    collections.append(&mut html(
-        TOP_COLLECTIONS
-            .iter()
-            .sorted_by_key(|collection| collection.name().unwrap().clone()),
-    ));
-
-    collections
-}
-
-static TOP_COLLECTIONS: Lazy<Vec<models::Collection>> = Lazy::new(|| {
-    let collections = crate::config::COLLECTIONS
+    let notable: Vec<models::Collection> = notable
         .iter()
-        .map(|(name, address, base_uri, total_supply)| {
-            Collection::new(address, name, base_uri, *total_supply)
+        .filter_map(|id| {
+            <storage::Collection as storage::Get<&str, Option<models::Collection>>>::get(id)
         })
         .collect();
-
-    // Add items to local storage
-    for collection in &collections {
-        if !storage::Collection::contains(collection) {
-            storage::Collection::store(collection.clone());
-        }
-    }
+    collections.append(&mut html(notable.iter()));
 
     collections
-});
+}
+
+#[derive(PartialEq, Properties)]
+pub struct NavigationProps {
+    /// The number of unread notifications.
+    pub unread: usize,
+    pub onread: Callback<()>,
+}
 
 #[function_component(Navigation)]
-pub fn nav() -> yew::Html {
+pub fn nav(props: &NavigationProps) -> yew::Html {
     use_effect(move || {
         let window = web_sys::window().expect("global window does not exists");
         let document = window.document().expect("expecting a document on window");
@@ -137,6 +291,9 @@ pub fn nav() -> yew::Html {
         || ()
     });
 
+    // Only indicate network status where etherscan is used, so that its worker is otherwise not loaded
+    let network = use_route::<Route>().map_or(false, |route| route.requires_etherscan());
+
     // Scroll to top of page on navigation
     if let Some(history) = use_history() {
         use_state(|| {
@@ -163,6 +320,43 @@ pub fn nav() -> yew::Html {
                 // </a>
             </div>
 
+            <div class="navbar-menu is-active">
+                <div class="navbar-end">
+                    if config::SESSION_API_KEY.get().is_some() {
+                        <div class="navbar-item">
+                            <span class="tag is-warning has-tooltip-bottom has-tooltip-multiline"
+                                data-tooltip="An etherscan.io API key was supplied via the link, which is used for this session only and is not stored">
+                                <span class="icon"><i class="fa-solid fa-key"></i></span>
+                                <span>{ "Session API Key" }</span>
+                            </span>
+                        </div>
+                    }
+                    if network {
+                        <network::Network />
+                    }
+                    <Link<Route> classes={classes!("navbar-item")} to={Route::Gallery}>
+                        <span class="icon"><i class="fa-solid fa-wallet"></i></span>
+                        <span>{ "My Gallery" }</span>
+                    </Link<Route>>
+                    <Link<Route> classes={classes!("navbar-item")} to={Route::Favorites}>
+                        <span class="icon"><i class="fa-solid fa-heart"></i></span>
+                        <span>{ "Favorites" }</span>
+                    </Link<Route>>
+                    <Link<Route> classes={classes!("navbar-item")} to={Route::History}>
+                        <span class="icon"><i class="fa-solid fa-clock-rotate-left"></i></span>
+                        <span>{ "History" }</span>
+                    </Link<Route>>
+                    <Link<Route> classes={classes!("navbar-item")} to={Route::Tours}>
+                        <span class="icon"><i class="fa-solid fa-route"></i></span>
+                        <span>{ "Tours" }</span>
+                    </Link<Route>>
+                    <NotificationCenter unread={ props.unread } onread={ props.onread.clone() } />
+                    <Link<Route> classes={classes!("navbar-item")} to={Route::Settings}>
+                        <span class="icon"><i class="fa-solid fa-gear"></i></span>
+                    </Link<Route>>
+                </div>
+            </div>
+
             // <div class="navbar-menu">
             //     <div class="navbar-start"></div>
             //
@@ -180,9 +374,94 @@ pub fn nav() -> yew::Html {
     }
 }
 
+#[function_component(NotificationCenter)]
+pub fn notification_center(props: &NavigationProps) -> yew::Html {
+    let active = use_state(|| false);
+    let notifications = storage::Notifications::values();
+
+    let toggle = {
+        let active = active.clone();
+        let unread = props.unread;
+        let onread = props.onread.clone();
+        Callback::from(move |_| {
+            // Mark notifications as read once opened
+            if !*active && unread > 0 {
+                storage::Notifications::mark_read();
+                onread.emit(());
+            }
+            active.set(!*active);
+        })
+    };
+    let clear = {
+        let active = active.clone();
+        let onread = props.onread.clone();
+        Callback::from(move |_| {
+            let cleared = storage::Notifications::clear();
+            onread.emit(());
+            active.set(false);
+            let onread = onread.clone();
+            notifications::notify_with_action(
+                "Notifications cleared".to_string(),
+                "Undo",
+                move || {
+                    storage::Notifications::restore(cleared);
+                    onread.emit(());
+                },
+            );
+        })
+    };
+
+    html! {
+        <div class={classes!("navbar-item", "has-dropdown", "notification-center", (*active).then(|| "is-active"))}>
+            <a href="javascript:void(0);" class="navbar-link is-arrowless" onclick={ toggle }>
+                <span class="icon"><i class="fa-solid fa-bell"></i></span>
+                if props.unread > 0 {
+                    <span class="tag is-danger is-rounded">{ props.unread }</span>
+                }
+            </a>
+            <div class="navbar-dropdown is-right">
+                if notifications.is_empty() {
+                    <div class="navbar-item">{ "No notifications" }</div>
+                } else {
+                    { notifications.into_iter().map(|notification| {
+                        let content = html! {
+                            <div>
+                                <p>{ notification.message }</p>
+                                <p class="is-size-7 has-text-grey">
+                                    { notification.timestamp.with_timezone(&chrono::Local).format("%d %b %H:%M").to_string() }
+                                </p>
+                            </div>
+                        };
+                        match notification.route {
+                            Some(route) => html! {
+                                <Link<Route> classes={classes!("navbar-item")} to={ route }>{ content }</Link<Route>>
+                            },
+                            None => html! { <div class="navbar-item">{ content }</div> },
+                        }
+                    }).collect::<Html>() }
+                    <hr class="navbar-divider" />
+                    <a href="javascript:void(0);" class="navbar-item" onclick={ clear }>{ "Clear" }</a>
+                }
+            </div>
+        </div>
+    }
+}
+
 #[function_component(NotFound)]
 pub fn not_found() -> yew::Html {
+    let suggestions = web_sys::window()
+        .and_then(|window| window.location().pathname().ok())
+        .map_or_else(Vec::new, |path| suggestions(&path));
+    let recent: Vec<models::Collection> = storage::Collection::get()
+        .into_iter()
+        .filter(|collection| collection.last_viewed().is_some())
+        .sorted_by_key(|collection| collection.last_viewed().unwrap())
+        .rev()
+        .take(5)
+        .collect();
+
     html! {
+        <>
         <section class="hero is-danger is-bold is-large">
             <div class="hero-body">
                 <div class="container">
@@ -195,11 +474,76 @@ pub fn not_found() -> yew::Html {
                 </div>
             </div>
         </section>
+        if !suggestions.is_empty() || !recent.is_empty() {
+            <section class="section">
+                <div class="container">
+                    if !suggestions.is_empty() {
+                        <p class="subtitle">{ "Did you mean..." }</p>
+                        <ul class="block">
+                        { suggestions.into_iter().map(|route| html! {
+                            <li><Link<Route> to={ route.clone() }>{ route.to_path() }</Link<Route>></li>
+                        }).collect::<Html>() }
+                        </ul>
+                    }
+                    if !recent.is_empty() {
+                        <p class="subtitle">{ "Recent Collections" }</p>
+                        <div class="buttons">
+                        { recent.iter().map(|collection| html! {
+                            <Link<Route> classes="button" to={ Route::Collection { id: collection.id() } }>
+                                { collection.name().unwrap_or("Unnamed") }
+                            </Link<Route>>
+                        }).collect::<Html>() }
+                        </div>
+                    }
+                </div>
+            </section>
+        }
+        </>
+    }
+}
+
+/// Suggests likely routes for an unmatched path, based on any address or url it contains.
+fn suggestions(path: &str) -> Vec<Route> {
+    let mut suggestions = Vec::new();
+
+    // Addresses, followed by an optional token
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    for (i, segment) in segments.iter().enumerate() {
+        let address = segment
+            .find("0x")
+            .and_then(|start| segment.get(start..start + 42))
+            .and_then(|address| Address::from_str(address).ok());
+        if let Some(address) = address {
+            let id = TypeExtensions::format(&address);
+            if let Some(token) = segments.get(i + 1).and_then(|t| u32::from_str(t).ok()) {
+                suggestions.push(Route::CollectionToken {
+                    id: id.clone(),
+                    token,
+                });
+            }
+            suggestions.push(Route::Collection { id: id.clone() });
+            suggestions.push(Route::Address { address: id });
+            return suggestions;
+        }
     }
+
+    // Urls, such as a token metadata url appended to the site address
+    if let Some(start) = path.find("http").or_else(|| path.find("ipfs://")) {
+        if let Ok(uri) = uri::TokenUri::parse(&path[start..], true) {
+            suggestions.push(match uri.token {
+                Some(token) => Route::CollectionToken { id: uri.uri, token },
+                None => Route::Collection { id: uri.uri },
+            });
+        }
+    }
+
+    suggestions
 }
 
 #[function_component(RecentlyViewed)]
 pub fn recently_viewed() -> yew::Html {
+    // Incremented to re-render once the items are cleared or restored
+    let version = use_state(|| 0);
     use_effect(move || {
         // Attach carousel after component is rendered
         bulma::carousel::attach(
@@ -208,6 +552,22 @@ pub fn recently_viewed() -> yew::Html {
         );
         || {}
     });
+    let clear = {
+        let version = version.clone();
+        Callback::from(move |_| {
+            let cleared = storage::RecentlyViewed::clear();
+            version.set(*version + 1);
+            let version = version.clone();
+            notifications::notify_with_action(
+                "Recently viewed cleared".to_string(),
+                "Undo",
+                move || {
+                    storage::RecentlyViewed::restore(cleared);
+                    version.set(*version + 1);
+                },
+            );
+        })
+    };
     let slides: Option<Vec<Html>> = storage::RecentlyViewed::values().map_or(None, |recent| {
         Some(
             recent
@@ -217,7 +577,7 @@ pub fn recently_viewed() -> yew::Html {
                     html! {
                         <Link<Route> to={ item.route }>
                             <figure class="image is-square">
-                                <img src={ item.image } alt={ item.name } />
+                                <image::Image src={ item.image } alt={ item.name } />
                             </figure>
                         </Link<Route>>
                     }
@@ -227,7 +587,19 @@ pub fn recently_viewed() -> yew::Html {
     });
     html! {
         if let Some(slides) = slides {
-            <p class="subtitle">{"Recently Viewed"}</p>
+            <p class="subtitle">
+                {"Recently Viewed"}
+                <Link<Route> classes="button is-small is-white" to={ Route::History }>
+                    <span class="icon is-small has-tooltip-bottom" data-tooltip="History">
+                        <i class="fa-solid fa-clock-rotate-left"></i>
+                    </span>
+                </Link<Route>>
+                <button onclick={ clear } class="button is-small is-white">
+                    <span class="icon is-small has-tooltip-bottom" data-tooltip="Clear">
+                        <i class="fa-solid fa-xmark"></i>
+                    </span>
+                </button>
+            </p>
             <div class="carousel">{ slides }</div>
         }
     }
@@ -245,6 +617,15 @@ pub fn search() -> yew::Html {
             history.clone().push(Route::Address {
                 address: TypeExtensions::format(&address),
             })
+        } else if let Some((chain, address)) = models::Collection::parse_contract(value.trim()) {
+            // Contract addresses qualified by their chain (e.g. polygon:0x...)
+            history.clone().push(Route::Collection {
+                id: models::Collection::contract_id(chain, &address),
+            })
+        } else if workers::ens::is_name(&value) {
+            history.clone().push(Route::Address {
+                address: value.trim().to_lowercase(),
+            })
         } else if let Ok(uri) = uri::TokenUri::parse(&value, true) {
             if let Some(token) = uri.token {
                 history.clone().push(Route::CollectionToken {
@@ -296,7 +677,7 @@ pub fn search() -> yew::Html {
                          aria-controls="dropdown-menu">
                         <input class="input"
                                type="text"
-                               placeholder="Enter contract address or token metadata URL"
+                               placeholder="Enter contract address (e.g. 0x... or polygon:0x...), ENS name or token metadata URL"
                                onchange={ input_change } />
                         <span class="icon is-small is-left">
                             <i class="fas fa-globe"></i>
@@ -0,0 +1,100 @@
+use crate::config;
+use gloo_timers::callback::Interval;
+use std::rc::Rc;
+use workers::etherscan::GasOracle;
+use workers::{etherscan, Bridge, Bridged};
+use yew::prelude::*;
+
+/// Polls the etherscan.io gas oracle, indicating the current gas price along with whether the API is reachable.
+pub struct Network {
+    etherscan: Box<dyn Bridge<etherscan::Worker>>,
+    gas_oracle: Option<GasOracle>,
+    /// Whether the last request succeeded, if any.
+    reachable: Option<bool>,
+    _interval: Interval,
+}
+
+pub enum Message {
+    Poll,
+    GasOracle(GasOracle),
+    GasOracleFailed,
+    // Ignore
+    None,
+}
+
+impl Component for Network {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Message::Poll);
+
+        Self {
+            etherscan: etherscan::Worker::bridge(Rc::new({
+                let link = ctx.link().clone();
+                move |e: etherscan::Response| {
+                    link.send_message(match e {
+                        etherscan::Response::GasOracle(gas_oracle) => {
+                            Message::GasOracle(gas_oracle)
+                        }
+                        etherscan::Response::GasOracleFailed => Message::GasOracleFailed,
+                        _ => Message::None,
+                    })
+                }
+            })),
+            gas_oracle: None,
+            reachable: None,
+            _interval: Interval::new(config::GAS_INTERVAL * 1_000, {
+                let link = ctx.link().clone();
+                move || link.send_message(Message::Poll)
+            }),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Poll => {
+                self.etherscan.send(etherscan::Request::GasOracle);
+                false
+            }
+            Message::GasOracle(gas_oracle) => {
+                self.gas_oracle = Some(gas_oracle);
+                self.reachable = Some(true);
+                true
+            }
+            Message::GasOracleFailed => {
+                self.reachable = Some(false);
+                true
+            }
+            // Ignore
+            Message::None => false,
+        }
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        let (status, tooltip) = match self.reachable {
+            Some(true) => ("has-text-success", "The etherscan.io API is reachable"),
+            Some(false) => (
+                "has-text-danger",
+                "The etherscan.io API is currently unreachable, so contract lookups may be slow or fail",
+            ),
+            None => ("has-text-grey-light", "Checking the etherscan.io API..."),
+        };
+
+        html! {
+            <div class="navbar-item network-status">
+                <span class={ classes!("icon", "is-small", "has-tooltip-bottom", "has-tooltip-multiline", status) }
+                    data-tooltip={ tooltip }>
+                    <i class="fa-solid fa-circle"></i>
+                </span>
+                if let Some(gas_oracle) = &self.gas_oracle {
+                    <span class="has-tooltip-bottom" data-tooltip={ format!("Safe: {} gwei, Fast: {} gwei",
+                        gas_oracle.safe, gas_oracle.fast) }>
+                        <span class="icon"><i class="fa-solid fa-gas-pump"></i></span>
+                        <span>{ format!("{} gwei", gas_oracle.propose.round()) }</span>
+                    </span>
+                }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,774 @@
+use crate::marketplace::Marketplace;
+use crate::storage::Get;
+use crate::{config, image, models, notifications, storage, uri, Address, Route};
+use std::str::FromStr;
+use thousands::Separable;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use workers::etherscan::TypeExtensions;
+use workers::indexer::{Budget, DEFAULT_BUDGET};
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The settings of the app, persisted to local storage.
+pub struct Settings {
+    /// The identifiers of the notable collections, in display order.
+    notable: Vec<String>,
+    /// The address of a collection to be added to the notable collections.
+    address: String,
+    settings: storage::AppSettings,
+    /// The data to be cleared, summarised whilst confirming that it is to be cleared.
+    clearing: Option<storage::Data>,
+}
+
+pub enum Message {
+    // Notable Collections
+    Address(String),
+    Add,
+    Remove(usize),
+    /// Moves the collection at the index up (negative) or down (positive) the order.
+    Move(usize, isize),
+    Reset,
+    // Navigation
+    Navigation(storage::Navigation),
+    StartPage(storage::StartPage),
+    HashLinks(bool),
+    // Images
+    ImageProxy(String),
+    SvgObject(bool),
+    /// Removes the images cached once displayed.
+    ClearImageCache,
+    // Indexing
+    Budget(String),
+    // Network
+    ApiKey(String),
+    MarketApiKey(String),
+    CorsProxy(String),
+    Gateway(String),
+    // Display
+    PageSize(usize),
+    InfiniteScroll(bool),
+    DataSaver(bool),
+    Prefetch(storage::Prefetch),
+    Theme(storage::Theme),
+    Video(storage::VideoSettings),
+    // Marketplaces
+    /// Shows (or hides) the links to the marketplace from the token page.
+    Marketplace(Marketplace, bool),
+    // Troubleshooting
+    /// Summarises the data held by the app, ahead of confirming that it is to be cleared.
+    SummariseData,
+    Summarised(storage::Data),
+    CancelClear,
+    ClearData,
+}
+
+/// The number of tokens which can be listed per page, being multiples of the five columns of the grid.
+const PAGE_SIZES: [usize; 4] = [25, 50, 75, 100];
+
+/// Applies the theme to the document, with the system theme following the preference of the operating system.
+pub(crate) fn apply_theme(theme: storage::Theme) {
+    let root = match web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.document_element())
+    {
+        Some(root) => root,
+        None => return,
+    };
+    let result = match theme {
+        storage::Theme::System => root.remove_attribute("data-theme"),
+        storage::Theme::Light => root.set_attribute("data-theme", "light"),
+        storage::Theme::Dark => root.set_attribute("data-theme", "dark"),
+    };
+    if let Err(e) = result {
+        log::error!("unable to apply the theme: {e:?}")
+    }
+}
+
+impl Component for Settings {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            notable: storage::Notable::values(),
+            address: String::new(),
+            settings: storage::Settings::get(),
+            clearing: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            // Notable Collections
+            Message::Address(address) => {
+                self.address = address;
+                true
+            }
+            Message::Add => {
+                let address = match Address::from_str(self.address.trim()) {
+                    Ok(address) => address,
+                    Err(_) => {
+                        notifications::notify(
+                            format!("The value of {} is not a valid address.", self.address),
+                            Some(notifications::Color::Warning),
+                        );
+                        return false;
+                    }
+                };
+                let id = TypeExtensions::format(&address);
+                if self.notable.contains(&id) {
+                    return false;
+                }
+
+                // Register the collection, with its name and uri resolved once first viewed
+                if storage::Collection::get(id.as_str()).is_none() {
+                    storage::Collection::store(&models::Collection::Contract {
+                        address,
+                        name: id.clone(),
+                        base_uri: None,
+                        start_token: 0,
+                        total_supply: None,
+                        last_viewed: None,
+                        overrides: Default::default(),
+                        position: Default::default(),
+                        chain: Default::default(),
+                    });
+                }
+                self.notable.push(id);
+                self.address.clear();
+                storage::Notable::store(&self.notable);
+                true
+            }
+            Message::Remove(index) => {
+                if index >= self.notable.len() {
+                    return false;
+                }
+                self.notable.remove(index);
+                storage::Notable::store(&self.notable);
+                true
+            }
+            Message::Move(index, offset) => {
+                let target = index as isize + offset;
+                if target < 0 || target as usize >= self.notable.len() {
+                    return false;
+                }
+                self.notable.swap(index, target as usize);
+                storage::Notable::store(&self.notable);
+                true
+            }
+            Message::Reset => {
+                self.notable = storage::Notable::reset();
+                true
+            }
+            // Navigation
+            Message::Navigation(navigation) => {
+                self.settings.navigation = navigation;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::StartPage(start_page) => {
+                self.settings.start_page = start_page;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::HashLinks(hash_links) => {
+                self.settings.hash_links = hash_links;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            // Images
+            Message::ImageProxy(template) => {
+                self.settings.image_proxy = Some(template).filter(|t| !t.trim().is_empty());
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::SvgObject(svg_object) => {
+                self.settings.svg_object = svg_object;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::ClearImageCache => {
+                wasm_bindgen_futures::spawn_local(async {
+                    storage::ImageCache::clear().await;
+                    notifications::notify("Cached images cleared".to_string(), None);
+                });
+                false
+            }
+            // Indexing
+            Message::Budget(requests) => {
+                let requests = requests.trim().replace(',', "");
+                self.settings.budget = if requests.is_empty() {
+                    Budget::Unlimited
+                } else {
+                    match u32::from_str(&requests) {
+                        Ok(requests) if requests > 0 => Budget::PerHour(requests),
+                        _ => return false,
+                    }
+                };
+                storage::Settings::store(&self.settings);
+                true
+            }
+            // Network
+            Message::ApiKey(api_key) => {
+                self.settings.api_key = Some(api_key.trim().to_string()).filter(|k| !k.is_empty());
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::MarketApiKey(api_key) => {
+                self.settings.market_api_key =
+                    Some(api_key.trim().to_string()).filter(|k| !k.is_empty());
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::CorsProxy(url) => {
+                self.settings.cors_proxy = Some(url.trim().to_string()).filter(|u| !u.is_empty());
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::Gateway(gateway) => {
+                // Only the host is used, should a url be entered
+                let gateway = gateway.trim();
+                let gateway = gateway
+                    .split_once("://")
+                    .map_or(gateway, |(_, host)| host)
+                    .trim_end_matches('/');
+                self.settings.ipfs_gateway = Some(gateway.to_string()).filter(|g| !g.is_empty());
+                uri::set_gateway(self.settings.ipfs_gateway.as_deref());
+                storage::Settings::store(&self.settings);
+                true
+            }
+            // Display
+            Message::PageSize(page_size) => {
+                self.settings.page_size = (page_size != config::PAGE_SIZE).then(|| page_size);
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::InfiniteScroll(infinite_scroll) => {
+                self.settings.infinite_scroll = infinite_scroll;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::DataSaver(data_saver) => {
+                self.settings.data_saver = data_saver;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::Prefetch(prefetch) => {
+                self.settings.prefetch = prefetch;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::Theme(theme) => {
+                self.settings.theme = theme;
+                apply_theme(theme);
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::Video(video) => {
+                self.settings.video = video;
+                storage::Settings::store(&self.settings);
+                true
+            }
+            Message::Marketplace(marketplace, shown) => {
+                if shown {
+                    self.settings.hidden_marketplaces.remove(&marketplace);
+                } else {
+                    self.settings.hidden_marketplaces.insert(marketplace);
+                }
+                storage::Settings::store(&self.settings);
+                true
+            }
+            // Troubleshooting
+            Message::SummariseData => {
+                ctx.link()
+                    .send_future(async { Message::Summarised(storage::Data::summarise().await) });
+                false
+            }
+            Message::Summarised(data) => {
+                self.clearing = Some(data);
+                true
+            }
+            Message::CancelClear => {
+                self.clearing = None;
+                true
+            }
+            Message::ClearData => {
+                self.clearing = None;
+                wasm_bindgen_futures::spawn_local(async {
+                    storage::Data::clear().await;
+                    // Reload so that nothing held in memory is stored again
+                    if let Some(Err(e)) = web_sys::window().map(|window| window.location().reload())
+                    {
+                        log::error!("unable to reload the app: {e:?}")
+                    }
+                });
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            Message::Address(e.target_unchecked_into::<HtmlInputElement>().value())
+        });
+        let onsubmit = ctx.link().callback(|e: FocusEvent| {
+            e.prevent_default();
+            Message::Add
+        });
+        let last = self.notable.len().saturating_sub(1);
+        let image_proxy = self.settings.image_proxy.clone().unwrap_or_default();
+        let invalid_proxy = !image_proxy.is_empty() && image::Proxy::parse(&image_proxy).is_none();
+        let video = self.settings.video;
+        let budget = match self.settings.budget {
+            Budget::Unlimited => String::new(),
+            Budget::PerHour(requests) => requests.to_string(),
+        };
+
+        html! {
+            <section id="settings" class="section is-fullheight">
+                <h1 class="title">{ "Settings" }</h1>
+
+                <div class="box">
+                    <div class="level is-mobile">
+                        <div class="level-left">
+                            <h2 class="subtitle level-item">{ "Notable Collections" }</h2>
+                        </div>
+                        <div class="level-right">
+                            <button onclick={ ctx.link().callback(|_| Message::Reset) }
+                                class="button is-small level-item">
+                                { "Reset to Defaults" }
+                            </button>
+                        </div>
+                    </div>
+                    <p class="help">{ "The collections featured within search, in the order listed." }</p>
+                    <table class="table is-fullwidth">
+                        <tbody>
+                        { self.notable.iter().enumerate().map(|(index, id)| {
+                            let name = storage::Collection::get(id.as_str())
+                                .and_then(|c| c.name().map(|name| name.to_string()))
+                                .unwrap_or_else(|| id.clone());
+                            html! {
+                                <tr key={ id.clone() }>
+                                    <td>
+                                        <Link<Route> to={ Route::Collection { id: id.clone() } }>{ name }</Link<Route>>
+                                    </td>
+                                    <td class="has-text-right">
+                                        <div class="buttons are-small is-right">
+                                            <button onclick={ ctx.link().callback(move |_| Message::Move(index, -1)) }
+                                                class="button" disabled={ index == 0 }>
+                                                <span class="icon is-small"><i class="fa-solid fa-arrow-up"></i></span>
+                                            </button>
+                                            <button onclick={ ctx.link().callback(move |_| Message::Move(index, 1)) }
+                                                class="button" disabled={ index == last }>
+                                                <span class="icon is-small"><i class="fa-solid fa-arrow-down"></i></span>
+                                            </button>
+                                            <button onclick={ ctx.link().callback(move |_| Message::Remove(index)) }
+                                                class="button is-danger is-light">
+                                                <span class="icon is-small"><i class="fa-solid fa-trash"></i></span>
+                                            </button>
+                                        </div>
+                                    </td>
+                                </tr>
+                            }
+                        }).collect::<Html>() }
+                        </tbody>
+                    </table>
+                    <form { onsubmit }>
+                        <div class="field has-addons">
+                            <div class="control is-expanded">
+                                <input class="input is-small" type="text" placeholder="Contract address"
+                                    value={ self.address.clone() } { oninput } />
+                            </div>
+                            <div class="control">
+                                <button type="submit" class="button is-small is-primary"
+                                    disabled={ self.address.trim().is_empty() }>
+                                    { "Add" }
+                                </button>
+                            </div>
+                        </div>
+                    </form>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Navigation" }</h2>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox"
+                                    checked={ self.settings.navigation == storage::Navigation::Replace }
+                                    onchange={ ctx.link().callback(|e: Event| {
+                                        Message::Navigation(
+                                            if e.target_unchecked_into::<HtmlInputElement>().checked() {
+                                                storage::Navigation::Replace
+                                            } else {
+                                                storage::Navigation::Push
+                                            },
+                                        )
+                                    }) } />
+                                { " Replace browser history when moving to the previous/next token" }
+                            </label>
+                        </div>
+                        <p class="help">{ "Back then returns to wherever the tokens were first opened from, such as the \
+                        collection, rather than through each token viewed." }</p>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox"
+                                    checked={ self.settings.start_page == storage::StartPage::Last }
+                                    onchange={ ctx.link().callback(|e: Event| {
+                                        Message::StartPage(
+                                            if e.target_unchecked_into::<HtmlInputElement>().checked() {
+                                                storage::StartPage::Last
+                                            } else {
+                                                storage::StartPage::First
+                                            },
+                                        )
+                                    }) } />
+                                { " Open collections at the page last viewed" }
+                            </label>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ self.settings.hash_links }
+                                    onchange={ ctx.link().callback(|e: Event| {
+                                        Message::HashLinks(e.target_unchecked_into::<HtmlInputElement>().checked())
+                                    }) } />
+                                { " Copy links in the hash-based format (e.g. /#/c/0x...)" }
+                            </label>
+                        </div>
+                        <p class="help">{ "Such links open directly on hosts which cannot fall back to the app for \
+                        other paths, such as some IPFS gateways." }</p>
+                    </div>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Images" }</h2>
+                    <div class="field">
+                        <label class="label">{ "Image Proxy" }</label>
+                        <div class="control">
+                            <input class={ classes!("input", invalid_proxy.then(|| "is-danger")) } type="text"
+                                placeholder="e.g. https://wsrv.nl/?url={url}&w={width}&output={format}"
+                                value={ image_proxy }
+                                oninput={ ctx.link().callback(|e: InputEvent| {
+                                    Message::ImageProxy(e.target_unchecked_into::<HtmlInputElement>().value())
+                                }) } />
+                        </div>
+                        if invalid_proxy {
+                            <p class="help is-danger">{ "The url must contain both {url} and {width}." }</p>
+                        }
+                        <p class="help">{ "Token images are requested via the proxy at the width displayed, rather than \
+                        at full size. Include {format} where the proxy converts images, so that AVIF or WebP is \
+                        requested where supported by the browser." }</p>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ self.settings.svg_object }
+                                    onchange={ ctx.link().callback(|e: Event| {
+                                        Message::SvgObject(e.target_unchecked_into::<HtmlInputElement>().checked())
+                                    }) } />
+                                { " Render SVG images as objects" }
+                            </label>
+                        </div>
+                        <p class="help">{ "SVG images relying on external fonts are then displayed as intended on the \
+                        token page. Scripts are removed from such images before they are rendered." }</p>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <button class="button is-small" onclick={ ctx.link().callback(|_| Message::ClearImageCache) }>
+                                { "Clear Cached Images" }
+                            </button>
+                        </div>
+                        <p class="help">{ "Images are cached once displayed without the proxy, so that previously viewed \
+                        tokens are displayed immediately and offline rather than requested again from their gateways." }</p>
+                    </div>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Display" }</h2>
+                    <div class="field is-horizontal">
+                        <div class="field-label is-normal">
+                            <label class="label">{ "Theme" }</label>
+                        </div>
+                        <div class="field-body">
+                            <div class="select">
+                                <select onchange={ ctx.link().callback(|e: Event| {
+                                    Message::Theme(match e.target_unchecked_into::<HtmlSelectElement>().value().as_str() {
+                                        "light" => storage::Theme::Light,
+                                        "dark" => storage::Theme::Dark,
+                                        _ => storage::Theme::System,
+                                    })
+                                }) }>
+                                    <option value="system" selected={ self.settings.theme == storage::Theme::System }>
+                                        { "System" }
+                                    </option>
+                                    <option value="light" selected={ self.settings.theme == storage::Theme::Light }>
+                                        { "Light" }
+                                    </option>
+                                    <option value="dark" selected={ self.settings.theme == storage::Theme::Dark }>
+                                        { "Dark" }
+                                    </option>
+                                </select>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="field is-horizontal">
+                        <div class="field-label is-normal">
+                            <label class="label">{ "Page Size" }</label>
+                        </div>
+                        <div class="field-body">
+                            <div class="select">
+                                <select onchange={ ctx.link().callback(|e: Event| {
+                                    let index = e.target_unchecked_into::<HtmlSelectElement>().selected_index();
+                                    Message::PageSize(PAGE_SIZES.get(index.max(0) as usize).copied()
+                                        .unwrap_or(config::PAGE_SIZE))
+                                }) }>
+                                    { for PAGE_SIZES.iter().map(|size| html! {
+                                        <option selected={ Some(*size) == self.settings.page_size
+                                            .or(Some(config::PAGE_SIZE)) }>
+                                            { format!("{size} tokens") }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="field is-horizontal">
+                        <div class="field-label is-normal">
+                            <label class="label">{ "Prefetch" }</label>
+                        </div>
+                        <div class="field-body">
+                            <div class="field">
+                                <div class="select">
+                                    <select onchange={ ctx.link().callback(|e: Event| {
+                                        Message::Prefetch(match e.target_unchecked_into::<HtmlSelectElement>().value().as_str() {
+                                            "always" => storage::Prefetch::Always,
+                                            "never" => storage::Prefetch::Never,
+                                            _ => storage::Prefetch::Unmetered,
+                                        })
+                                    }) }>
+                                        <option value="unmetered" selected={ self.settings.prefetch == storage::Prefetch::Unmetered }>
+                                            { "Unless on a metered connection" }
+                                        </option>
+                                        <option value="always" selected={ self.settings.prefetch == storage::Prefetch::Always }>
+                                            { "Always" }
+                                        </option>
+                                        <option value="never" selected={ self.settings.prefetch == storage::Prefetch::Never }>
+                                            { "Never" }
+                                        </option>
+                                    </select>
+                                </div>
+                                <p class="help">{ "Requests the metadata and images of the previous and next tokens whilst \
+                                viewing a token, so that moving between them is immediate." }</p>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ self.settings.infinite_scroll }
+                                    onchange={ ctx.link().callback(|e: Event| {
+                                        Message::InfiniteScroll(e.target_unchecked_into::<HtmlInputElement>().checked())
+                                    }) } />
+                                { " Load further tokens whilst scrolling, rather than paging" }
+                            </label>
+                        </div>
+                        <p class="help">{ "Tokens are then listed once indexed, rather than in place whilst indexing." }</p>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ self.settings.data_saver }
+                                    onchange={ ctx.link().callback(|e: Event| {
+                                        Message::DataSaver(e.target_unchecked_into::<HtmlInputElement>().checked())
+                                    }) } />
+                                { " Data saver" }
+                            </label>
+                        </div>
+                        <p class="help">
+                            { format!("Lists at most {} tokens per page, does not prefetch neighbouring tokens and only \
+                            indexes collections on request. Always applies whilst the browser requests reduced data usage.",
+                            config::DATA_SAVER_PAGE_SIZE) }
+                        </p>
+                    </div>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Video" }</h2>
+                    <p class="help">{ "How the videos of tokens are played on the token page. Collections and \
+                    galleries only ever show their poster images." }</p>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ video.autoplay }
+                                    onchange={ ctx.link().callback(move |e: Event| Message::Video(storage::VideoSettings {
+                                        autoplay: e.target_unchecked_into::<HtmlInputElement>().checked(),
+                                        ..video
+                                    })) } />
+                                { " Autoplay" }
+                            </label>
+                        </div>
+                        <p class="help">{ "Browsers typically only autoplay videos which are muted." }</p>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ video.muted }
+                                    onchange={ ctx.link().callback(move |e: Event| Message::Video(storage::VideoSettings {
+                                        muted: e.target_unchecked_into::<HtmlInputElement>().checked(),
+                                        ..video
+                                    })) } />
+                                { " Mute" }
+                            </label>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={ video.looped }
+                                    onchange={ ctx.link().callback(move |e: Event| Message::Video(storage::VideoSettings {
+                                        looped: e.target_unchecked_into::<HtmlInputElement>().checked(),
+                                        ..video
+                                    })) } />
+                                { " Loop" }
+                            </label>
+                        </div>
+                    </div>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Marketplaces" }</h2>
+                    <p class="help">{ "The marketplaces linked to from the token page, where they support the chain of \
+                    the collection." }</p>
+                    { for Marketplace::ALL.into_iter().map(|marketplace| html! {
+                        <div class="field">
+                            <div class="control">
+                                <label class="checkbox">
+                                    <input type="checkbox" checked={ !self.settings.hidden_marketplaces.contains(&marketplace) }
+                                        onchange={ ctx.link().callback(move |e: Event| Message::Marketplace(marketplace,
+                                            e.target_unchecked_into::<HtmlInputElement>().checked())) } />
+                                    { format!(" {}", marketplace.name()) }
+                                </label>
+                            </div>
+                        </div>
+                    }) }
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Network" }</h2>
+                    <div class="field">
+                        <label class="label">{ "Etherscan API Key" }</label>
+                        <div class="control">
+                            <input class="input" type="password" placeholder="None" autocomplete="off"
+                                value={ self.settings.api_key.clone().unwrap_or_default() }
+                                onchange={ ctx.link().callback(|e: Event| {
+                                    Message::ApiKey(e.target_unchecked_into::<HtmlInputElement>().value())
+                                }) } />
+                        </div>
+                        <p class="help">{ "Requests to etherscan.io (and the explorers of other chains) are throttled \
+                        without a key. Stored within this browser only, with any key supplied via a link taking \
+                        precedence for that session." }</p>
+                    </div>
+                    <div class="field">
+                        <label class="label">{ "Market Data API Key" }</label>
+                        <div class="control">
+                            <input class="input" type="password" placeholder="None" autocomplete="off"
+                                value={ self.settings.market_api_key.clone().unwrap_or_default() }
+                                onchange={ ctx.link().callback(|e: Event| {
+                                    Message::MarketApiKey(e.target_unchecked_into::<HtmlInputElement>().value())
+                                }) } />
+                        </div>
+                        <p class="help">{ "A reservoir.tools API key, with which the floor price and last sale are shown \
+                        on collections and tokens. Prices are omitted without a key." }</p>
+                    </div>
+                    <div class="field">
+                        <label class="label">{ "CORS Proxy" }</label>
+                        <div class="control">
+                            <input class="input" type="url" placeholder={ config::CORS_PROXY }
+                                value={ self.settings.cors_proxy.clone().unwrap_or_default() }
+                                onchange={ ctx.link().callback(|e: Event| {
+                                    Message::CorsProxy(e.target_unchecked_into::<HtmlInputElement>().value())
+                                }) } />
+                        </div>
+                        <p class="help">{ "Requests failing due to CORS are retried via the proxy, with the url of the \
+                        request appended." }</p>
+                    </div>
+                    <div class="field">
+                        <label class="label">{ "IPFS Gateway" }</label>
+                        <div class="control">
+                            <input class="input" type="text" placeholder={ config::IPFS_GATEWAY }
+                                value={ self.settings.ipfs_gateway.clone().unwrap_or_default() }
+                                onchange={ ctx.link().callback(|e: Event| {
+                                    Message::Gateway(e.target_unchecked_into::<HtmlInputElement>().value())
+                                }) } />
+                        </div>
+                        <p class="help">{ "The gateway host (e.g. cloudflare-ipfs.com) through which IPFS metadata and \
+                        images are requested, unless overridden for a collection within its diagnostics." }</p>
+                    </div>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Indexing" }</h2>
+                    <div class="field">
+                        <label class="label">{ "Metadata Requests per Hour" }</label>
+                        <div class="control">
+                            <input class="input" type="number" min="1" placeholder="Unlimited"
+                                value={ budget }
+                                onchange={ ctx.link().callback(|e: Event| {
+                                    Message::Budget(e.target_unchecked_into::<HtmlInputElement>().value())
+                                }) } />
+                        </div>
+                        <p class="help">{ format!("Indexing pauses once the budget is used, resuming as it allows. \
+                        Considerate of public IPFS gateways and mobile data, with a default of {} requests. Leave \
+                        empty for no limit.", DEFAULT_BUDGET.separate_with_commas()) }</p>
+                    </div>
+                </div>
+
+                <div class="box">
+                    <h2 class="subtitle">{ "Troubleshooting" }</h2>
+                    <p class="block">{ "Recent errors, along with details to include when reporting a bug, are \
+                    listed on the " }<Link<Route> to={ Route::Debug }>{ "debug page" }</Link<Route>>{ "." }</p>
+                    if let Some(data) = self.clearing.as_ref() {
+                        <article class="message is-danger">
+                            <div class="message-body content">
+                                <p>{ "The following will be removed from this browser, and cannot be undone:" }</p>
+                                <ul>
+                                    <li>{ format!("{} collections, along with {} indexed tokens", data.collections,
+                                        data.tokens.separate_with_commas()) }</li>
+                                    <li>{ format!("{} favorites and {} watched collections", data.favorites, data.watched) }</li>
+                                    <li>{ format!("{} recently viewed tokens", data.recently_viewed) }</li>
+                                    <li>{ format!("{} learned CORS and suspended hosts", data.hosts) }</li>
+                                    <li>{ format!("{} local and archived images, along with {} cached images", data.images,
+                                        data.cached_images.separate_with_commas()) }</li>
+                                    <li>{ format!("All settings ({} stored values in total)", data.keys) }</li>
+                                </ul>
+                                <div class="buttons mt-3">
+                                    <button class="button is-danger" onclick={ ctx.link().callback(|_| Message::ClearData) }>
+                                        { "Clear All Data" }
+                                    </button>
+                                    <button class="button" onclick={ ctx.link().callback(|_| Message::CancelClear) }>
+                                        { "Cancel" }
+                                    </button>
+                                </div>
+                            </div>
+                        </article>
+                    } else {
+                        <div class="field">
+                            <div class="control">
+                                <button class="button is-danger is-outlined"
+                                    onclick={ ctx.link().callback(|_| Message::SummariseData) }>
+                                    { "Clear All Data..." }
+                                </button>
+                            </div>
+                            <p class="help">{ "Removes everything the app holds within this browser, such as should \
+                            stale or corrupt data prevent it from working." }</p>
+                        </div>
+                    }
+                </div>
+            </section>
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::svg;
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub src: String,
+    #[prop_or_default]
+    pub alt: Option<String>,
+    #[prop_or_default]
+    pub onload: Option<Callback<Event>>,
+    #[prop_or_default]
+    pub onerror: Option<Callback<Event>>,
+}
+
+/// An SVG image rendered as an object rather than an image, so that any external resources it relies upon (such as
+/// fonts) are loaded. As objects run any scripts within the image, it is first requested and sanitised, being rendered
+/// as an image until then or should it not be readable (e.g. due to CORS).
+#[function_component(Svg)]
+pub fn object(props: &Properties) -> Html {
+    let sanitized = use_state(|| None::<String>);
+    {
+        let sanitized = sanitized.clone();
+        use_effect_with_deps(
+            move |src: &String| {
+                sanitized.set(None);
+                let src = src.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match markup(&src).await {
+                        Some(markup) => sanitized.set(Some(svg::data_uri(&svg::sanitize(&markup)))),
+                        None => {
+                            log::trace!("unable to read the svg image, so rendering as an image")
+                        }
+                    }
+                });
+                || ()
+            },
+            props.src.clone(),
+        );
+    }
+
+    match &*sanitized {
+        Some(data) => html! {
+            <object type="image/svg+xml" data={ data.clone() } aria-label={ props.alt.clone() }
+                onload={ props.onload.clone() } onerror={ props.onerror.clone() }></object>
+        },
+        None => html! {
+            <img src={ props.src.clone() } alt={ props.alt.clone() } onload={ props.onload.clone() }
+                onerror={ props.onerror.clone() } />
+        },
+    }
+}
+
+/// The markup of the image, decoded where inline or otherwise requested (typically from the browser cache).
+async fn markup(src: &str) -> Option<String> {
+    if let Some(markup) = workers::metadata::decode_data_uri(src) {
+        return Some(String::from_utf8_lossy(&markup).into_owned());
+    }
+    let response = gloo_net::http::Request::get(src).send().await.ok()?;
+    if !response.ok() {
+        return None;
+    }
+    response.text().await.ok()
+}
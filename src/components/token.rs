@@ -1,13 +1,123 @@
-use crate::models;
+use crate::components::market::Prices;
+use crate::components::svg::Svg;
+use crate::marketplace::Marketplace;
+use crate::media::{self, Media};
+use crate::{config, models, notifications, storage, svg, transition, uri, Address, Route};
+use bulma::components::Collapsible;
 use itertools::Itertools;
 use std::rc::Rc;
-use workers::{qr, Bridge, Bridged};
+use std::str::FromStr;
+use thousands::Separable;
+use wasm_bindgen::JsCast;
+use workers::market::TraitFloors;
+use workers::rarity::Rank;
+use workers::{market, qr, Bridge, Bridged};
 use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// The width (and height) of qr codes generated, in pixels, which are scaled down where displayed inline.
+const QR_CODE_SIZE: usize = 240;
+/// The script defining the `<model-viewer>` element, loaded once a token with a 3D model is first viewed.
+const MODEL_VIEWER: &str = "https://unpkg.com/@google/model-viewer@1.12.0/dist/model-viewer.min.js";
+
+/// Whether the device supports the Web Share api.
+fn can_share() -> bool {
+    web_sys::window().map_or(false, |window| {
+        js_sys::Reflect::has(&window.navigator(), &"share".into()).unwrap_or(false)
+    })
+}
+
+/// Opens the native share sheet of the device with the link, via the Web Share api.
+fn native_share(title: &str, url: &str) -> Result<(), wasm_bindgen::JsValue> {
+    let navigator = web_sys::window()
+        .ok_or_else(|| wasm_bindgen::JsValue::from_str("could not get window"))?
+        .navigator();
+    let share: js_sys::Function = js_sys::Reflect::get(&navigator, &"share".into())?.dyn_into()?;
+    let data = js_sys::Object::new();
+    js_sys::Reflect::set(&data, &"title".into(), &title.into())?;
+    js_sys::Reflect::set(&data, &"url".into(), &url.into())?;
+    // The promise is rejected should the user dismiss the share sheet, which is not an error
+    let _ = share.call1(&navigator, &data)?;
+    Ok(())
+}
+
+/// Detects the kind of content of the animation url of the token from its uri, or otherwise requests its content type.
+fn detect(ctx: &Context<Token>) -> Option<Media> {
+    let url = ctx.props().animation_url()?;
+    let media = Media::from_uri(&url);
+    if media.is_none() {
+        ctx.link().send_future(async move {
+            // The animation url was always assumed to be a video, so remains so where it cannot be detected
+            let media = content_type(&url)
+                .await
+                .and_then(|content_type| Media::from_content_type(&content_type))
+                .unwrap_or(Media::Video);
+            Message::Animation(url, media)
+        });
+    }
+    media
+}
+
+/// Requests the content type of the url via a `HEAD` request, without downloading its content.
+async fn content_type(url: &str) -> Option<String> {
+    let response = gloo_net::http::Request::new(url)
+        .method(gloo_net::http::Method::HEAD)
+        .send()
+        .await
+        .map_err(|e| log::trace!("unable to request the content type of {url}: {e:?}"))
+        .ok()?;
+    response.headers().get("content-type")
+}
+
+/// Loads the script defining the `<model-viewer>` element, unless already loaded.
+fn load_model_viewer() {
+    let document = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => return,
+    };
+    if document.get_element_by_id("model-viewer").is_some() {
+        return;
+    }
+    let script = document.create_element("script").and_then(|script| {
+        script.set_id("model-viewer");
+        script.set_attribute("type", "module")?;
+        script.set_attribute("src", MODEL_VIEWER)?;
+        document
+            .head()
+            .ok_or_else(|| wasm_bindgen::JsValue::from_str("could not get head"))?
+            .append_child(&script)
+    });
+    if let Err(e) = script {
+        log::error!("unable to load the model viewer: {e:?}")
+    }
+}
 
 pub struct Token {
     qr: Box<dyn Bridge<qr::Worker>>,
-    /// The qr code of the current url
+    /// The qr code of the link to the token
     qr_code: Option<String>,
+    /// Whether the options for sharing the token are shown.
+    sharing: bool,
+    favorite: bool,
+    /// Bridged only when market data is configured and the collection is sourced from a contract
+    market: Option<Box<dyn Bridge<market::Worker>>>,
+    trait_floors: Option<TraitFloors>,
+    /// The floor price of the collection and the last sale of the token, where a market data api key is configured.
+    prices: Option<market::Prices>,
+    attributes_open: bool,
+    metadata_open: bool,
+    /// Whether the image of the token could not be loaded.
+    image_failed: bool,
+    /// The rank of the token by rarity, along with the total number of ranked tokens, where ranked.
+    rank: Option<(Rank, usize)>,
+    video: storage::VideoSettings,
+    /// Whether SVG images are rendered as (sanitised) objects, so that any external fonts are loaded.
+    svg_object: bool,
+    /// The kind of content of the animation url, once detected.
+    animation: Option<Media>,
+    /// Whether the YouTube video of the token has been loaded, which is only embedded on request so that no requests
+    /// are made to YouTube otherwise.
+    youtube: bool,
 }
 
 #[derive(Debug)]
@@ -15,16 +125,52 @@ pub enum Message {
     // Qr Code
     GenerateQRCode,
     QRCode(String),
+    // Sharing
+    /// Shows (or hides) the options for sharing the token.
+    Share(bool),
+    /// Shares the link to the token via the native share sheet of the device.
+    NativeShare,
+    ToggleFavorite,
+    // Market Data
+    RequestTraitFloors,
+    TraitFloors(String, TraitFloors),
+    RequestPrices,
+    Prices(String, u32, market::Prices),
+    // Sections
+    ToggleAttributes(bool),
+    ToggleMetadata(bool),
+    // Media
+    Copy(String, &'static str),
+    ImageFailed,
+    /// The kind of content of the animation url has been detected from the response to a `HEAD` request.
+    Animation(String, Media),
+    LoadYouTube,
+    /// Attaches the image file as a local copy of the image of the token, or removes the local copy.
+    LocalImage(Option<web_sys::File>),
 }
 
 #[derive(Properties)]
 pub struct Properties {
+    /// The collection identifier.
+    pub collection: String,
     pub token: Rc<models::Token>,
+    /// The object url of an image uploaded locally, displayed in place of the image of the token.
+    #[prop_or_default]
+    pub local_image: Option<String>,
+    /// The object url of a copy of the image archived for offline viewing, displayed in place of the image of the token.
+    #[prop_or_default]
+    pub archived_image: Option<String>,
+    /// Handles the attaching (or removal) of a local copy of the image, which is only offered where handled.
+    #[prop_or_default]
+    pub onlocalimage: Option<Callback<Option<web_sys::File>>>,
 }
 
 impl PartialEq for Properties {
     fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.token, &other.token)
+        self.collection == other.collection
+            && Rc::ptr_eq(&self.token, &other.token)
+            && self.local_image == other.local_image
+            && self.archived_image == other.archived_image
     }
 }
 
@@ -35,25 +181,69 @@ impl Component for Token {
     fn create(ctx: &Context<Self>) -> Self {
         ctx.link().send_message(Message::GenerateQRCode);
 
+        // Use previously fetched trait floors and prices where possible
+        let collection = ctx.props().collection.as_str();
+        let mut market = None;
+        let mut trait_floors = None;
+        let mut prices = None;
+        if config::MARKET_DATA_API.is_some() && Address::from_str(collection).is_ok() {
+            trait_floors = storage::Market::trait_floors(collection);
+            prices = storage::Market::prices(collection, Some(ctx.props().token.id));
+            // Prices are only requested with a key, and are otherwise omitted
+            let request_prices = config::market_api_key().is_some();
+            if trait_floors.is_none() || request_prices {
+                market = Some(market::Worker::bridge(Rc::new({
+                    let link = ctx.link().clone();
+                    move |e: market::Response| match e {
+                        market::Response::TraitFloors(collection, floors) => {
+                            link.send_message(Self::Message::TraitFloors(collection, floors))
+                        }
+                        market::Response::Prices(collection, Some(token), prices) => {
+                            link.send_message(Self::Message::Prices(collection, token, prices))
+                        }
+                        _ => {}
+                    }
+                })));
+            }
+            if trait_floors.is_none() {
+                ctx.link().send_message(Message::RequestTraitFloors);
+            }
+            if prices.is_none() && request_prices {
+                ctx.link().send_message(Message::RequestPrices);
+            }
+        }
+
         Self {
             qr: qr::Worker::bridge(Rc::new({
                 let link = ctx.link().clone();
                 move |e: qr::Response| link.send_message(Self::Message::QRCode(e.qr_code))
             })),
             qr_code: None,
+            sharing: false,
+            favorite: storage::Favorites::contains(collection, ctx.props().token.id),
+            market,
+            trait_floors,
+            prices,
+            attributes_open: true,
+            metadata_open: false,
+            image_failed: false,
+            rank: storage::Rarity::rank(collection, ctx.props().token.id),
+            video: storage::Settings::get().video,
+            svg_object: storage::Settings::get().svg_object,
+            animation: detect(ctx),
+            youtube: false,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Message::GenerateQRCode => {
-                if let Some(location) = web_sys::window()
-                    .and_then(|window| window.document())
-                    .and_then(|document| document.location())
-                    .and_then(|location| location.href().ok())
-                {
+                if let Some(url) = ctx.props().share_url() {
                     log::trace!("generating qr code...");
-                    self.qr.send(workers::qr::Request { url: location });
+                    self.qr.send(workers::qr::Request {
+                        url,
+                        size: QR_CODE_SIZE,
+                    });
                 }
                 false
             }
@@ -62,37 +252,181 @@ impl Component for Token {
                 self.qr_code = Some(qr_code);
                 true
             }
+            // Sharing
+            Message::Share(sharing) => {
+                self.sharing = sharing;
+                true
+            }
+            Message::NativeShare => {
+                if let Some(url) = ctx.props().share_url() {
+                    if let Err(e) = native_share(&ctx.props().name(), &url) {
+                        log::error!("unable to share the token: {e:?}")
+                    }
+                }
+                false
+            }
+            Message::ToggleFavorite => {
+                self.favorite =
+                    storage::Favorites::toggle(&ctx.props().collection, ctx.props().token.id);
+                notifications::notify(
+                    if self.favorite {
+                        "Added to favorites"
+                    } else {
+                        "Removed from favorites"
+                    }
+                    .to_string(),
+                    None,
+                );
+                true
+            }
+            // Market Data
+            Message::RequestTraitFloors => {
+                if let (Some(market), Some(api)) = (self.market.as_mut(), config::MARKET_DATA_API) {
+                    log::trace!("requesting trait floors...");
+                    market.send(market::Request::TraitFloors {
+                        api: api.to_string(),
+                        collection: ctx.props().collection.clone(),
+                    });
+                }
+                false
+            }
+            Message::TraitFloors(collection, floors) => {
+                storage::Market::store_trait_floors(collection.as_str(), floors.clone());
+                if collection != ctx.props().collection {
+                    return false;
+                }
+                self.trait_floors = Some(floors);
+                true
+            }
+            Message::RequestPrices => {
+                if let (Some(market), Some(api), Some(api_key)) = (
+                    self.market.as_mut(),
+                    config::MARKET_DATA_API,
+                    config::market_api_key(),
+                ) {
+                    log::trace!("requesting prices...");
+                    market.send(market::Request::Prices {
+                        api: api.to_string(),
+                        api_key,
+                        collection: ctx.props().collection.clone(),
+                        token: Some(ctx.props().token.id),
+                    });
+                }
+                false
+            }
+            Message::Prices(collection, token, prices) => {
+                storage::Market::store_prices(collection.as_str(), Some(token), prices.clone());
+                if collection != ctx.props().collection || token != ctx.props().token.id {
+                    return false;
+                }
+                self.prices = Some(prices);
+                true
+            }
+            Message::ToggleAttributes(open) => {
+                self.attributes_open = open;
+                true
+            }
+            Message::ToggleMetadata(open) => {
+                self.metadata_open = open;
+                true
+            }
+            // Media
+            Message::Copy(value, label) => {
+                if let Some(clipboard) =
+                    web_sys::window().and_then(|window| window.navigator().clipboard())
+                {
+                    let _ = clipboard.write_text(&value);
+                    notifications::notify(format!("{label} copied to clipboard"), None);
+                }
+                false
+            }
+            Message::ImageFailed => {
+                self.image_failed = true;
+                true
+            }
+            Message::Animation(url, media) => {
+                // Ignore any detection completing once the token has changed
+                if ctx.props().animation_url().as_ref() != Some(&url) {
+                    return false;
+                }
+                self.animation = Some(media);
+                true
+            }
+            Message::LoadYouTube => {
+                self.youtube = true;
+                true
+            }
+            Message::LocalImage(file) => {
+                if let Some(onlocalimage) = ctx.props().onlocalimage.as_ref() {
+                    onlocalimage.emit(file)
+                }
+                false
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        ctx.link().send_message(Message::GenerateQRCode);
+        self.image_failed = false;
+        self.animation = detect(ctx);
+        self.youtube = false;
+        self.favorite =
+            storage::Favorites::contains(ctx.props().collection.as_str(), ctx.props().token.id);
+        self.rank = storage::Rarity::rank(ctx.props().collection.as_str(), ctx.props().token.id);
+        if self.market.is_some() {
+            self.prices = storage::Market::prices(
+                ctx.props().collection.as_str(),
+                Some(ctx.props().token.id),
+            );
+            if self.prices.is_none() {
+                ctx.link().send_message(Message::RequestPrices);
+            }
         }
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let props = ctx.props();
-        let image_onload = Callback::from(move |e: web_sys::Event| {
-            if let Some(figure) = e
-                .target_unchecked_into::<web_sys::HtmlElement>()
-                .offset_parent()
-            {
-                let _ = figure.class_list().remove_1("is-square");
-            }
-        });
+        let image = props.image();
+        let image_onload = {
+            let image = image.clone();
+            Callback::from(move |e: web_sys::Event| {
+                if let Some(figure) = e
+                    .target_unchecked_into::<web_sys::HtmlElement>()
+                    .offset_parent()
+                {
+                    let _ = figure.class_list().remove_1("is-square");
+                }
+                // Cache the image, so that it is displayed immediately (and offline) when viewed again
+                let image = image.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    storage::ImageCache::store(&image).await
+                });
+            })
+        };
 
         html! {
             if let Some(metadata) = props.token.metadata.as_ref() {
                 <div class="card columns">
-                if let Some((video, poster)) = props.video() {
+                if let Some((video, poster)) = props.video().filter(|_| self.animation == Some(Media::Video)) {
                     <div class="column">
-                        <figure class="image">
+                        <figure class={ classes!("image", transition::SHARED) }>
                             <video class="modal-button" data-target="nifty-image" controls={true}
-                                    poster={ poster.clone() }>
+                                    autoplay={ self.video.autoplay } muted={ self.video.muted }
+                                    loop={ self.video.looped } playsinline={true}
+                                    poster={ props.local_image.clone().unwrap_or(poster.clone()) }>
                                 <source src={ video.clone() } type="video/mp4" />
                             </video>
                         </figure>
+                        { self.actions(ctx) }
                         <div id="nifty-image" class="modal modal-fx-3dFlipHorizontal">
                             <div class="modal-background"></div>
                             <div class="modal-content">
                                 <p class="image">
+                                    // Never autoplayed, as the video is already playing behind the modal
                                     <video class="modal-button" data-target="nifty-image" controls={true}
-                                            poster={ poster }>
+                                            muted={ self.video.muted } loop={ self.video.looped }
+                                            poster={ props.local_image.clone().unwrap_or(poster) }>
                                         <source src={ video } type="video/mp4" />
                                     </video>
                                 </p>
@@ -103,15 +437,26 @@ impl Component for Token {
                 }
                 else {
                     <div class="column">
-                        <figure class="image is-square">
-                            <img src={ metadata.image.clone() } alt={ metadata.name.clone() } class="modal-button"
-                                 data-target="nifty-image" onload={ image_onload.clone() } />
-                        </figure>
+                        { self.animation(ctx, html! {
+                            <figure class={ classes!("image", "is-square", transition::SHARED) }>
+                                if self.svg_object && svg::is_svg(&image) {
+                                    <Svg src={ image.clone() } alt={ metadata.name.clone() }
+                                        onload={ image_onload.clone() }
+                                        onerror={ ctx.link().callback(|_| Message::ImageFailed) } />
+                                } else {
+                                    <img src={ image.clone() } alt={ metadata.name.clone() } class="modal-button"
+                                         data-target="nifty-image" onload={ image_onload.clone() }
+                                         onerror={ ctx.link().callback(|_| Message::ImageFailed) } />
+                                }
+                            </figure>
+                        }) }
+                        { self.actions(ctx) }
+                        { self.local_image(ctx) }
                         <div id="nifty-image" class="modal modal-fx-3dFlipHorizontal">
                             <div class="modal-background"></div>
                             <div class="modal-content">
                                 <p class="image">
-                                    <img src={ metadata.image.clone() } alt={ metadata.name.clone() } />
+                                    <img src={ image } alt={ metadata.name.clone() } />
                                 </p>
                             </div>
                             <button class="modal-close is-large" aria-label="close"></button>
@@ -122,7 +467,14 @@ impl Component for Token {
                         <div class="card-content">
                             <h1 class="title nifty-name">{ props.name() }</h1>
                             <div class="content">{ props.description() }</div>
-                            <div class="field is-grouped is-grouped-multiline">{ props.attributes() }</div>
+                            <Collapsible title="Attributes" open={ self.attributes_open }
+                                ontoggle={ ctx.link().callback(Message::ToggleAttributes) }>
+                                <div class="field is-grouped is-grouped-multiline">{ props.attributes(self.trait_floors.as_ref()) }</div>
+                            </Collapsible>
+                            <Collapsible title="Metadata" open={ self.metadata_open }
+                                ontoggle={ ctx.link().callback(Message::ToggleMetadata) }>
+                                <pre class="metadata">{ serde_json::to_string_pretty(metadata).unwrap_or_default() }</pre>
+                            </Collapsible>
                             if let Some(external_url) = &metadata.external_url {
                                 <div class="content">
                                     <a href={ external_url.to_string() } target="_blank">
@@ -130,6 +482,23 @@ impl Component for Token {
                                     </a>
                                 </div>
                             }
+                            if let Some(embed) = metadata.youtube_url.as_deref().and_then(media::youtube) {
+                                <div class="block">
+                                    if self.youtube {
+                                        <figure class="image is-16by9">
+                                            <iframe class="has-ratio" src={ format!("{embed}?autoplay=1") } title={ props.name() }
+                                                allow="autoplay; encrypted-media; fullscreen; picture-in-picture"
+                                                allowfullscreen={ true }></iframe>
+                                        </figure>
+                                    } else {
+                                        <button class="button" onclick={ ctx.link().callback(|_| Message::LoadYouTube) }>
+                                            <span class="icon"><i class="fa-brands fa-youtube"></i></span>
+                                            <span>{ "Load YouTube Video" }</span>
+                                        </button>
+                                        <p class="help">{ "The video is loaded from YouTube, which may then set cookies." }</p>
+                                    }
+                                </div>
+                            }
                             <table class="table">
                             <tbody>
                             if let Some(last_viewed) = &props.token.last_viewed {
@@ -150,16 +519,49 @@ impl Component for Token {
                                             <p class="title">{ props.total_attributes() }</p>
                                         </div>
                                     </div>
+                                    if let Some(prices) = self.prices.clone() {
+                                        <Prices { prices } token={true} />
+                                    }
+                                    if let Some((rank, ranked)) = self.rank.as_ref() {
+                                        <div class="level-item has-text-centered">
+                                            <Link<Route> to={ Route::CollectionRarity { id: props.collection.clone() } }>
+                                                <p class="heading">{"Rarity Rank"}</p>
+                                                <p class="title" title={ format!("Score: {:.2}", rank.score) }>
+                                                    { format!("#{}", rank.rank.separate_with_commas()) }
+                                                    <span class="is-size-6 has-text-grey">
+                                                        { format!(" / {}", ranked.separate_with_commas()) }
+                                                    </span>
+                                                </p>
+                                            </Link<Route>>
+                                        </div>
+                                    }
                                 </div>
                                 <div class="level-right">
                                     if let Some(qr_code) = self.qr_code.as_ref() {
-                                        <figure class="image is-qr-code level-item">
+                                        <figure class="image is-qr-code level-item"
+                                            onclick={ ctx.link().callback(|_| Message::Share(true)) }>
                                             <img src={ qr_code.clone() } alt={ metadata.name.clone() } />
                                         </figure>
                                     }
+                                    <div class="level-item">
+                                        <button onclick={ ctx.link().callback(|_| Message::ToggleFavorite) } class="button">
+                                            <span class="icon is-small has-tooltip-bottom"
+                                                data-tooltip={ if self.favorite { "Unfavorite" } else { "Favorite" } }>
+                                                <i class={ if self.favorite { "fa-solid fa-heart" } else { "fa-regular fa-heart" } }></i>
+                                            </span>
+                                        </button>
+                                    </div>
+                                    <div class="level-item">
+                                        <button onclick={ ctx.link().callback(|_| Message::Share(true)) } class="button">
+                                            <span class="icon is-small"><i class="fa-solid fa-share-nodes"></i></span>
+                                            <span>{ "Share" }</span>
+                                        </button>
+                                    </div>
                                 </div>
                             </div>
                         </footer>
+                        { self.marketplaces(ctx) }
+                        { self.share(ctx) }
                     </div>
                 </div>
             }
@@ -174,8 +576,252 @@ impl Component for Token {
     }
 }
 
+impl Token {
+    /// Renders the animation of the token in place of its image, other than audio (played beneath the image) and
+    /// videos (rendered along with their own modal). The image is rendered until the content of the animation has been
+    /// detected.
+    fn animation(&self, ctx: &Context<Self>, image: Html) -> Html {
+        let props = ctx.props();
+        let (url, media) = match props.animation_url().zip(self.animation) {
+            Some(animation) => animation,
+            None => return image,
+        };
+        let figure = classes!("image", "is-square", transition::SHARED);
+        match media {
+            Media::Video => image,
+            Media::Audio => html! {
+                <>
+                    { image }
+                    <audio class="is-fullwidth" src={ url } controls={ true } loop={ self.video.looped }
+                        preload="none"></audio>
+                </>
+            },
+            // Scripts run within an opaque origin, so the page cannot access the app or its storage
+            Media::Html => html! {
+                <figure class={ figure }>
+                    <iframe class="has-ratio" src={ url } title={ props.name() } sandbox="allow-scripts"
+                        allow="autoplay; fullscreen" referrerpolicy="no-referrer"></iframe>
+                </figure>
+            },
+            Media::Svg if self.svg_object => html! {
+                <figure class={ figure }>
+                    <Svg src={ url } alt={ props.name() } />
+                </figure>
+            },
+            Media::Svg | Media::Image => html! {
+                <figure class={ figure }>
+                    <img src={ uri::svg(&url).unwrap_or(url) } alt={ props.name() } />
+                </figure>
+            },
+            Media::Model => {
+                load_model_viewer();
+                html! {
+                    <figure class={ figure }>
+                        <model-viewer class="has-ratio" src={ url } poster={ props.image() } alt={ props.name() }
+                            camera-controls="" auto-rotate="" ar=""></model-viewer>
+                    </figure>
+                }
+            }
+        }
+    }
+
+    /// Links to the token on the marketplaces enabled within settings which support the chain of the collection.
+    fn marketplaces(&self, ctx: &Context<Self>) -> Html {
+        let (chain, address) = match models::Collection::parse_contract(&ctx.props().collection) {
+            Some(contract) => contract,
+            None => return Html::default(),
+        };
+        let hidden = storage::Settings::get().hidden_marketplaces;
+        let token = ctx.props().token.id;
+        let links: Vec<_> = Marketplace::ALL
+            .into_iter()
+            .filter(|marketplace| !hidden.contains(marketplace))
+            .filter_map(|marketplace| Some((marketplace, marketplace.url(chain, &address, token)?)))
+            .collect();
+        if links.is_empty() {
+            return Html::default();
+        }
+        html! {
+            <footer class="card-footer">
+                { for links.into_iter().map(|(marketplace, url)| html! {
+                    <a href={ url } target="_blank" rel="noopener noreferrer" class="card-footer-item">
+                        <span>{ marketplace.name() }</span>
+                        <span class="icon is-small ml-1"><i class="fa-solid fa-arrow-up-right-from-square"></i></span>
+                    </a>
+                }) }
+            </footer>
+        }
+    }
+
+    /// Flags an image displayed from a local copy, or offers to attach a local copy should the image fail to load.
+    fn local_image(&self, ctx: &Context<Self>) -> Html {
+        if ctx.props().onlocalimage.is_none() {
+            return Html::default();
+        }
+        if ctx.props().local_image.is_some() {
+            return html! {
+                <div class="field is-grouped is-grouped-centered">
+                    <div class="control">
+                        <span class="tag is-warning">{"Local copy"}</span>
+                    </div>
+                    <div class="control">
+                        <button class="button is-small is-danger is-outlined"
+                            onclick={ ctx.link().callback(|_| Message::LocalImage(None)) }>
+                            {"Remove local copy"}
+                        </button>
+                    </div>
+                </div>
+            };
+        }
+        if !self.image_failed {
+            return Html::default();
+        }
+
+        let onfile = ctx.link().batch_callback(|e: Event| {
+            e.target_unchecked_into::<web_sys::HtmlInputElement>()
+                .files()
+                .and_then(|files| files.get(0))
+                .map(|file| Message::LocalImage(Some(file)))
+        });
+        html! {
+            <article class="message is-warning">
+                <div class="message-body">
+                    <p>{"The image could not be loaded. Attach a local copy to keep displaying this token."}</p>
+                    <div class="file is-small mt-2">
+                        <label class="file-label">
+                            <input class="file-input" type="file" accept="image/*" onchange={ onfile } />
+                            <span class="file-cta">
+                                <span class="file-icon"><i class="fa-solid fa-upload"></i></span>
+                                <span class="file-label">{ "Choose an image..." }</span>
+                            </span>
+                        </label>
+                    </div>
+                </div>
+            </article>
+        }
+    }
+
+    /// The options for sharing the token: its qr code, a link which can be copied and the native share sheet of the
+    /// device where supported.
+    fn share(&self, ctx: &Context<Self>) -> Html {
+        let url = match ctx.props().share_url().filter(|_| self.sharing) {
+            Some(url) => url,
+            None => return Html::default(),
+        };
+        let close = ctx.link().callback(|_| Message::Share(false));
+        let copy = {
+            let url = url.clone();
+            ctx.link()
+                .callback(move |_| Message::Copy(url.clone(), "Link"))
+        };
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={ close.clone() }></div>
+                <div class="modal-card share">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ format!("Share {}", ctx.props().name()) }</p>
+                        <button onclick={ close } class="delete" aria-label="close"></button>
+                    </header>
+                    <section class="modal-card-body">
+                        if let Some(qr_code) = self.qr_code.as_ref() {
+                            <figure class="image is-qr-code is-large">
+                                <img src={ qr_code.clone() } alt={ url.clone() } />
+                            </figure>
+                        }
+                        <div class="field has-addons">
+                            <div class="control is-expanded">
+                                <input class="input" type="text" readonly={ true } value={ url }
+                                    onfocus={ Callback::from(|e: FocusEvent| {
+                                        e.target_unchecked_into::<web_sys::HtmlInputElement>().select()
+                                    }) } />
+                            </div>
+                            <div class="control">
+                                <button onclick={ copy } class="button">
+                                    <span class="icon is-small"><i class="fa-regular fa-clone"></i></span>
+                                    <span>{ "Copy" }</span>
+                                </button>
+                            </div>
+                        </div>
+                        if can_share() {
+                            <button onclick={ ctx.link().callback(|_| Message::NativeShare) } class="button is-primary is-fullwidth">
+                                <span class="icon is-small"><i class="fa-solid fa-share-from-square"></i></span>
+                                <span>{ "Share via…" }</span>
+                            </button>
+                        }
+                    </section>
+                </div>
+            </div>
+        }
+    }
+
+    /// Utility actions for the media of the token: opening the original in a new tab and copying its url, along with
+    /// its IPFS content identifier where applicable.
+    fn actions(&self, ctx: &Context<Self>) -> Html {
+        let url = match ctx.props().media() {
+            Some(url) => url,
+            None => return Html::default(),
+        };
+        let cid = uri::parse(&url).ok().and_then(|url| uri::cid(&url));
+        let copy_url = {
+            let url = url.clone();
+            ctx.link()
+                .callback(move |_| Message::Copy(url.clone(), "Image URL"))
+        };
+
+        html! {
+            <div class="buttons are-small is-centered media-actions">
+                // Browsers block navigating to data uris, as used by on-chain media
+                if !workers::metadata::is_data_uri(&url) {
+                    <a href={ url.clone() } target="_blank" rel="noopener noreferrer" class="button">
+                        <span class="icon is-small has-tooltip-bottom" data-tooltip="Open Original">
+                            <i class="fa-solid fa-up-right-from-square"></i>
+                        </span>
+                    </a>
+                }
+                <button onclick={ copy_url } class="button">
+                    <span class="icon is-small has-tooltip-bottom" data-tooltip="Copy Image URL">
+                        <i class="fa-solid fa-link"></i>
+                    </span>
+                </button>
+                if let Some(cid) = cid {
+                    <button onclick={ ctx.link().callback(move |_| Message::Copy(cid.clone(), "CID")) } class="button">
+                        <span class="icon is-small has-tooltip-bottom" data-tooltip="Copy IPFS CID">
+                            <i class="fa-solid fa-cube"></i>
+                        </span>
+                    </button>
+                }
+            </div>
+        }
+    }
+}
+
 impl Properties {
-    fn attributes(&self) -> Html {
+    /// The link to the token, for sharing.
+    fn share_url(&self) -> Option<String> {
+        Route::CollectionToken {
+            id: self.collection.clone(),
+            token: self.token.id,
+        }
+        .share_url()
+    }
+
+    /// The image of the token, preferring any local or archived copy. Inline SVG images are sanitised and re-encoded so
+    /// that the browser can display them.
+    fn image(&self) -> String {
+        self.local_image
+            .clone()
+            .or_else(|| self.archived_image.clone())
+            .unwrap_or_else(|| {
+                self.token
+                    .metadata
+                    .as_ref()
+                    .map_or(String::new(), |metadata| {
+                        uri::svg(&metadata.image).unwrap_or_else(|| metadata.image.clone())
+                    })
+            })
+    }
+
+    fn attributes(&self, trait_floors: Option<&TraitFloors>) -> Html {
         self.token
             .metadata
             .as_ref()
@@ -187,12 +833,23 @@ impl Properties {
                     .iter()
                     .sorted_by_key(|a| &a.0)
                     .map(|a| {
+                        // Link to the collection, filtered by the attribute
+                        let filter = models::Filter {
+                            trait_type: a.0.clone(),
+                            value: a.1.clone(),
+                        };
                         html! {
                             <div class="control">
-                                <div class="tags has-addons">
+                                <Link<Route, models::Filter> classes="tags has-addons"
+                                    to={ Route::Collection { id: self.collection.clone() } } query={ Some(filter) }>
                                     <span class="tag">{ &a.0 }</span>
                                     <span class="tag">{ &a.1 }</span>
-                                </div>
+                                    if let Some(floor) = trait_floors
+                                        .and_then(|floors| floors.get(&a.0))
+                                        .and_then(|values| values.get(&a.1)) {
+                                        <span class="tag is-floor">{ format!("Ξ {}", (floor * 1000.0).round() / 1000.0) }</span>
+                                    }
+                                </Link<Route, models::Filter>>
                             </div>
                         }
                     })
@@ -232,6 +889,23 @@ impl Properties {
             })
     }
 
+    /// The url of the original media of the token, preferring the animation over the image.
+    fn media(&self) -> Option<String> {
+        self.token.metadata.as_ref().map(|metadata| {
+            metadata
+                .animation_url
+                .clone()
+                .unwrap_or_else(|| metadata.image.clone())
+        })
+    }
+
+    fn animation_url(&self) -> Option<String> {
+        self.token
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.animation_url.clone())
+    }
+
     fn video(&self) -> Option<(String, String)> {
         self.token
             .metadata
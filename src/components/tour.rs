@@ -0,0 +1,306 @@
+use crate::tour::Tour;
+use crate::{components::collection, config, notifications, storage, Route};
+use gloo_timers::callback::Timeout;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+/// Copies a link to the tour to the clipboard.
+fn share(tour: &Tour) -> Callback<MouseEvent> {
+    let route = Route::Tour {
+        tour: tour.encode(),
+    };
+    Callback::from(move |_| {
+        if let (Some(url), Some(clipboard)) = (
+            route.share_url(),
+            web_sys::window().and_then(|window| window.navigator().clipboard()),
+        ) {
+            let _ = clipboard.write_text(&url);
+            notifications::notify("Link copied to clipboard".to_string(), None);
+        }
+    })
+}
+
+/// The tours saved, which can be played or shared, along with the recording of a new tour from the tokens
+/// subsequently viewed.
+#[function_component(Tours)]
+pub fn tours() -> Html {
+    let tours = use_state(storage::Tours::get);
+    let recording = use_state(storage::Tours::recording);
+    let name = use_state(String::new);
+
+    let onname = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            name.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+    let start = {
+        let name = name.clone();
+        let recording = recording.clone();
+        Callback::from(move |_| {
+            let tour = name.trim();
+            if tour.is_empty() {
+                return;
+            }
+            storage::Tours::start(tour.to_string());
+            recording.set(storage::Tours::recording());
+            name.set(String::new());
+        })
+    };
+    let stop = {
+        let tours = tours.clone();
+        let recording = recording.clone();
+        Callback::from(move |_| {
+            let message = match storage::Tours::stop() {
+                Some(tour) => format!("{} saved with {} stops", tour.name, tour.stops.len()),
+                None => "Tour discarded, as no tokens were viewed".to_string(),
+            };
+            notifications::notify(message, None);
+            recording.set(None);
+            tours.set(storage::Tours::get());
+        })
+    };
+    let remove = |name: String| {
+        let tours = tours.clone();
+        Callback::from(move |_| {
+            if let Some(removed) = storage::Tours::remove(&name) {
+                tours.set(storage::Tours::get());
+                let tours = tours.clone();
+                notifications::notify_with_action(format!("{name} removed"), "Undo", move || {
+                    storage::Tours::store(removed);
+                    tours.set(storage::Tours::get());
+                });
+            }
+        })
+    };
+
+    html! {
+        <section class="section is-fullheight">
+            <h1 class="title">{ "Tours" }</h1>
+            <div class="box">
+                if let Some(tour) = recording.as_ref() {
+                    <div class="level is-mobile">
+                        <div class="level-left">
+                            <div class="level-item">
+                                <span class="tag is-danger mr-2">{ "Recording" }</span>
+                                <strong>{ &tour.name }</strong>
+                            </div>
+                            <div class="level-item">
+                                <span class="tag">{ format!("{} stops", tour.stops.len()) }</span>
+                            </div>
+                        </div>
+                        <div class="level-right">
+                            <button onclick={ stop } class="button is-primary">{ "Stop & Save" }</button>
+                        </div>
+                    </div>
+                    <p class="help">{ "Each token viewed is added to the tour, in the order viewed." }</p>
+                } else {
+                    <div class="field has-addons">
+                        <div class="control is-expanded">
+                            <input class="input" type="text" placeholder="Name of the tour" value={ (*name).clone() }
+                                oninput={ onname } />
+                        </div>
+                        <div class="control">
+                            <button onclick={ start } class="button is-primary" disabled={ name.trim().is_empty() }>
+                                <span class="icon"><i class="fa-solid fa-circle"></i></span>
+                                <span>{ "Record" }</span>
+                            </button>
+                        </div>
+                    </div>
+                    <p class="help">{ "Records the tokens subsequently viewed as a tour, which can be replayed as a \
+                    slideshow or shared as a link." }</p>
+                }
+            </div>
+
+            if tours.is_empty() {
+                <p class="has-text-grey">{ "No tours have been saved." }</p>
+            } else {
+                <table class="table is-fullwidth">
+                    <tbody>
+                    { for tours.iter().map(|tour| html! {
+                        <tr>
+                            <td>{ &tour.name }</td>
+                            <td class="has-text-grey">{ format!("{} stops", tour.stops.len()) }</td>
+                            <td>
+                                <div class="buttons are-small is-right">
+                                    <Link<Route> classes="button is-primary" to={ Route::Tour { tour: tour.encode() } }>
+                                        <span class="icon"><i class="fa-solid fa-play"></i></span>
+                                        <span>{ "Play" }</span>
+                                    </Link<Route>>
+                                    <button onclick={ share(tour) } class="button">
+                                        <span class="icon"><i class="fa-solid fa-share-nodes"></i></span>
+                                        <span>{ "Share" }</span>
+                                    </button>
+                                    <button onclick={ remove(tour.name.clone()) } class="button is-danger is-outlined">
+                                        { "Remove" }
+                                    </button>
+                                </div>
+                            </td>
+                        </tr>
+                    }) }
+                    </tbody>
+                </table>
+            }
+        </section>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct PlayerProps {
+    /// The encoded tour.
+    pub tour: String,
+}
+
+/// Replays a tour as a slideshow, moving to the next stop at an interval whilst playing.
+#[function_component(Player)]
+pub fn player(props: &PlayerProps) -> Html {
+    let tour = Tour::decode(&props.tour);
+    let stops = tour.as_ref().map_or(0, |tour| tour.stops.len());
+    let current = use_state_eq(|| 0usize);
+    let playing = use_state_eq(|| false);
+    let saved = use_state_eq(|| false);
+
+    // Restart whenever another tour is opened
+    {
+        let current = current.clone();
+        let saved = saved.clone();
+        let tour = tour.clone();
+        use_effect_with_deps(
+            move |_| {
+                current.set(0);
+                saved.set(tour.map_or(false, |tour| storage::Tours::get().contains(&tour)));
+                || {}
+            },
+            props.tour.clone(),
+        );
+    }
+    {
+        let current = current.clone();
+        let playing = playing.clone();
+        use_effect_with_deps(
+            move |(play, stop)| {
+                let next = stop + 1;
+                let timeout = (*play && next < stops).then(|| {
+                    Timeout::new(config::TOUR_INTERVAL * 1_000, move || {
+                        current.set(next);
+                        if next + 1 == stops {
+                            playing.set(false);
+                        }
+                    })
+                });
+                move || drop(timeout)
+            },
+            (*playing, *current),
+        );
+    }
+
+    let tour = match tour {
+        Some(tour) => tour,
+        None => {
+            return html! {
+                <section class="section is-fullheight">
+                    <article class="message is-danger">
+                        <div class="message-body">
+                            { "The tour could not be read. Please check that the link is complete." }
+                        </div>
+                    </article>
+                </section>
+            }
+        }
+    };
+
+    let move_to = |stop: usize| {
+        let current = current.clone();
+        Callback::from(move |_| current.set(stop))
+    };
+    let toggle = {
+        let current = current.clone();
+        let playing = playing.clone();
+        Callback::from(move |_| {
+            // Play from the start once the tour has ended
+            if !*playing && *current + 1 >= stops {
+                current.set(0);
+            }
+            playing.set(!*playing)
+        })
+    };
+    let save = {
+        let tour = tour.clone();
+        let saved = saved.clone();
+        Callback::from(move |_| {
+            notifications::notify(format!("{} saved", tour.name), None);
+            storage::Tours::store(tour.clone());
+            saved.set(true);
+        })
+    };
+
+    html! {
+        <div id="tour">
+            <section class="section pb-0">
+                <div class="level is-mobile">
+                    <div class="level-left">
+                        <div class="level-item">
+                            <h1 class="title is-4">{ &tour.name }</h1>
+                        </div>
+                        if stops > 0 {
+                            <div class="level-item">
+                                <span class="tag">{ format!("Stop {} of {}", *current + 1, stops) }</span>
+                            </div>
+                        }
+                    </div>
+                    <div class="level-right">
+                        <div class="field has-addons">
+                            <div class="control">
+                                <button onclick={ move_to(current.saturating_sub(1)) } class="button is-primary"
+                                    disabled={ *current == 0 }>
+                                    <span class="icon is-small"><i class="fas fa-angle-left"></i></span>
+                                </button>
+                            </div>
+                            <div class="control">
+                                <button onclick={ toggle } class="button is-primary" disabled={ stops < 2 }>
+                                    <span class="icon is-small has-tooltip-bottom"
+                                        data-tooltip={ if *playing { "Pause" } else { "Play" } }>
+                                        <i class={ classes!("fa-solid", if *playing { "fa-pause" } else { "fa-play" }) }></i>
+                                    </span>
+                                </button>
+                            </div>
+                            <div class="control">
+                                <button onclick={ move_to(*current + 1) } class="button is-primary"
+                                    disabled={ *current + 1 >= stops }>
+                                    <span class="icon is-small"><i class="fas fa-angle-right"></i></span>
+                                </button>
+                            </div>
+                            if !*saved {
+                                <div class="control">
+                                    <button onclick={ save } class="button">
+                                        <span class="icon is-small has-tooltip-bottom" data-tooltip="Save">
+                                            <i class="fa-solid fa-floppy-disk"></i>
+                                        </span>
+                                    </button>
+                                </div>
+                            }
+                            <div class="control">
+                                <button onclick={ share(&tour) } class="button">
+                                    <span class="icon is-small has-tooltip-bottom" data-tooltip="Share">
+                                        <i class="fa-solid fa-share-nodes"></i>
+                                    </span>
+                                </button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </section>
+
+            if let Some(stop) = tour.stops.get(*current) {
+                // Recreated for each collection, as the token component only follows changes of token
+                <collection::token::Token key={ stop.collection.clone() } collection={ stop.collection.clone() }
+                    token={ stop.token } />
+            } else {
+                <section class="section">
+                    <p class="has-text-grey">{ "This tour has no stops." }</p>
+                </section>
+            }
+        </div>
+    }
+}
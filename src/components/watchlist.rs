@@ -0,0 +1,207 @@
+use crate::storage::Get;
+use crate::{config, storage, Address, Route};
+use gloo_timers::callback::Interval;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::str::FromStr;
+use workers::etherscan::TypeExtensions;
+use workers::{etherscan, market, Bridge, Bridged};
+use yew::prelude::*;
+
+/// Periodically checks the collections on the watchlist whilst the app is open, raising a notification when the
+/// total supply changes or the floor price moves beyond the configured threshold.
+pub struct Watcher {
+    /// The workers, bridged once there are collections to be checked.
+    etherscan: Option<Box<dyn Bridge<etherscan::Worker>>>,
+    market: Option<Box<dyn Bridge<market::Worker>>>,
+    /// The collections remaining to be checked, along with the collection currently being checked.
+    queue: VecDeque<Address>,
+    checking: Option<Address>,
+    _interval: Interval,
+}
+
+pub enum Message {
+    Check,
+    Next,
+    // Total Supply
+    Contract(Address),
+    TotalSupply(u32),
+    // Floor
+    Floor(String, f64),
+    // Ignore
+    None,
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Properties {
+    pub onnotify: Callback<storage::Notification>,
+}
+
+impl Component for Watcher {
+    type Message = Message;
+    type Properties = Properties;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        ctx.link().send_message(Message::Check);
+
+        Self {
+            etherscan: None,
+            market: None,
+            queue: VecDeque::new(),
+            checking: None,
+            _interval: Interval::new(config::WATCHLIST_INTERVAL * 60_000, {
+                let link = ctx.link().clone();
+                move || link.send_message(Message::Check)
+            }),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Check => {
+                // Ignore if previous check still in progress
+                if self.checking.is_some() {
+                    return false;
+                }
+
+                self.queue = storage::Watchlist::values()
+                    .keys()
+                    .filter_map(|collection| Address::from_str(collection).ok())
+                    .collect();
+                if self.queue.is_empty() {
+                    return false;
+                }
+
+                log::trace!("checking {} watched collections...", self.queue.len());
+                if self.etherscan.is_none() {
+                    self.etherscan = Some(etherscan::Worker::bridge(Rc::new({
+                        let link = ctx.link().clone();
+                        move |e: etherscan::Response| {
+                            link.send_message(match e {
+                                // Contract is returned when not yet cached by the worker, so request total supply again
+                                etherscan::Response::Contract(contract) => {
+                                    Message::Contract(contract.address)
+                                }
+                                etherscan::Response::TotalSupply(total_supply) => {
+                                    Message::TotalSupply(total_supply)
+                                }
+                                etherscan::Response::NoContract(_)
+                                | etherscan::Response::ContractFailed(..)
+                                | etherscan::Response::NoTotalSupply(_)
+                                | etherscan::Response::TotalSupplyFailed(_) => Message::Next,
+                                _ => Message::None,
+                            })
+                        }
+                    })));
+                }
+                if self.market.is_none() && config::MARKET_DATA_API.is_some() {
+                    self.market = Some(market::Worker::bridge(Rc::new({
+                        let link = ctx.link().clone();
+                        move |e: market::Response| {
+                            if let market::Response::Floor(collection, floor) = e {
+                                link.send_message(Message::Floor(collection, floor))
+                            }
+                        }
+                    })));
+                }
+                ctx.link().send_message(Message::Next);
+                false
+            }
+            Message::Next => {
+                self.checking = self.queue.pop_front();
+                if let Some(address) = self.checking {
+                    if let Some(etherscan) = self.etherscan.as_mut() {
+                        etherscan.send(etherscan::Request::TotalSupply(address));
+                    }
+                    if let (Some(market), Some(api)) =
+                        (self.market.as_mut(), config::MARKET_DATA_API)
+                    {
+                        market.send(market::Request::Floor {
+                            api: api.to_string(),
+                            collection: TypeExtensions::format(&address),
+                        });
+                    }
+                }
+                false
+            }
+            // Total Supply
+            Message::Contract(address) => {
+                if let Some(etherscan) = self.etherscan.as_mut() {
+                    if self.checking == Some(address) {
+                        etherscan.send(etherscan::Request::TotalSupply(address));
+                    }
+                }
+                false
+            }
+            Message::TotalSupply(total_supply) => {
+                if let Some(address) = self.checking {
+                    let id = TypeExtensions::format(&address);
+                    if let Some(mut watched) = storage::Watchlist::get(&id) {
+                        match watched.total_supply {
+                            Some(previous) if previous != total_supply => {
+                                let delta = total_supply as i64 - previous as i64;
+                                notify(
+                                    ctx,
+                                    &id,
+                                    format!(
+                                        "total supply changed from {previous} to {total_supply} ({delta:+})"
+                                    ),
+                                )
+                            }
+                            _ => {}
+                        }
+                        watched.total_supply = Some(total_supply);
+                        storage::Watchlist::store(&id, watched);
+                    }
+                }
+                ctx.link().send_message(Message::Next);
+                false
+            }
+            // Floor
+            Message::Floor(collection, floor) => {
+                if let Some(mut watched) = storage::Watchlist::get(&collection) {
+                    match watched.floor {
+                        Some(previous) if previous > 0.0 => {
+                            let change = (floor - previous) / previous;
+                            if change.abs() >= config::WATCHLIST_FLOOR_THRESHOLD {
+                                notify(
+                                    ctx,
+                                    &collection,
+                                    format!(
+                                        "floor price moved from Ξ {previous} to Ξ {floor} ({:+.0}%)",
+                                        change * 100.0
+                                    ),
+                                )
+                            } else {
+                                // Keep the previous floor as the baseline so gradual moves are still detected
+                                return false;
+                            }
+                        }
+                        _ => {}
+                    }
+                    watched.floor = Some(floor);
+                    storage::Watchlist::store(&collection, watched);
+                }
+                false
+            }
+            // Ignore
+            Message::None => false,
+        }
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        html! {}
+    }
+}
+
+fn notify(ctx: &Context<Watcher>, collection: &str, change: String) {
+    let name = storage::Collection::get(collection)
+        .and_then(|c| c.name().map(|name| name.to_string()))
+        .unwrap_or_else(|| collection.to_string());
+    ctx.props().onnotify.emit(storage::Notification::new(
+        format!("{name}: {change}"),
+        Some(Route::Collection {
+            id: collection.to_string(),
+        }),
+    ))
+}
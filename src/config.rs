@@ -1,127 +1,98 @@
-use once_cell::sync::Lazy;
+use crate::storage;
+use once_cell::sync::OnceCell;
+use workers::etherscan::Chain;
+use workers::metadata::Overrides;
+
+mod notable;
+
+pub use notable::COLLECTIONS;
 
 pub const CORS_PROXY: &str = "https://proxy.evilrobot.industries/";
-pub static COLLECTIONS: Lazy<Vec<(&str, &str, &str, Option<u32>)>> = Lazy::new(|| {
-    vec![
-        (
-            "Azuki",
-            "0xed5af388653567af2f388e6224dc7c4b3241c544",
-            "https://ikzttp.mypinata.cloud/ipfs/QmQFkLSQysj94s5GvTHPyzTxrawwtjgiiYS2TBLgrvw8CW/",
-            Some(10_000),
-        ),
-        (
-            "Beanz",
-            "0x306b1ea3ecdf94ab739f1910bbda052ed4a9f949",
-            "https://ikzttp.mypinata.cloud/ipfs/QmPZKyuRw4nQTD6S6R5HaNAXwoQVMj8YydDmad3rC985WZ/",
-            Some(19_950),
-        ),
-        (
-            "Bored Ape Chemistry Club",
-            "0x22c36bfdcef207f9c0cc941936eff94d4246d14a",
-            "https://ipfs.io/ipfs/QmdtARLUPQeqXrVcNzQuRqr9UCFoFvn76X9cdTczt4vqfw/",
-            None,
-        ),
-        (
-            "Bored Ape Kennel Club",
-            "0xba30e5f9bb24caa003e9f2f0497ad287fdf95623",
-            "https://ipfs.io/ipfs/QmTDcCdt3yb6mZitzWBmQr65AW6Wska295Dg9nbEYpSUDR/",
-            Some(9_602),
-        ),
-        (
-            "Bored Ape Yacht Club",
-            "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d",
-            "https://ipfs.io/ipfs/QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq/",
-            Some(10_000),
-        ),
-        (
-            "Clone X",
-            "0x49cf6f5d44e70224e2e23fdcdd2c053f30ada28b",
-            "https://clonex-assets.rtfkt.com/",
-            Some(19_311),
-        ),
-        (
-            "Cool Cats NFT",
-            "0x1a92f7381b9f03921564a437210bb9396471050c",
-            "https://api.coolcatsnft.com/cat/",
-            Some(9_941),
-        ),
-        (
-            "CrypToadz by GREMPLIN",
-            "0x1cb1a5e65610aeff2551a50f76a87a7d3fb649c6",
-            "https://arweave.net/OVAmf1xgB6atP0uZg1U0fMd0Lw6DlsVqdvab-WTXZ1Q/",
-            Some(7_025),
-        ),
-        (
-            "DeadFellaz",
-            "0x2acab3dea77832c09420663b0e1cb386031ba17b",
-            "https://api.deadfellaz.io/traits/",
-            Some(10_000),
-        ),
-        (
-            "Doodles",
-            "0x8a90cab2b38dba80c64b7734e58ee1db38b8992e",
-            "https://ipfs.io/ipfs/QmPMc4tcBsMqLRuCQtPmPe84bpSjrC3Ky7t3JWuHXYB4aS/",
-            Some(10_000),
-        ),
-        (
-            "Hape Prime",
-            "0x4db1f25d3d98600140dfc18deb7515be5bd293af",
-            "https://meta.hapeprime.com/",
-            Some(8_192),
-        ),
-        (
-            "Meebits",
-            "0x7bd29408f11d2bfc23c34f18275bbf23bb716bc7",
-            "https://meebits.larvalabs.com/meebit/",
-            Some(20_000),
-        ),
-        (
-            "MekaVerse",
-            "0x9a534628b4062e123ce7ee2222ec20b86e16ca8f",
-            "https://ipfs.io/ipfs/Qmcob1MaPTXUZt5MztHEgsYhrf7R6G7wV8hpcweL8nEfgU/meka/",
-            Some(8_888),
-        ),
-        (
-            "Moonbirds",
-            "0x23581767a106ae21c074b2276d25e5c3e136a68b",
-            "https://live---metadata-5covpqijaa-uc.a.run.app/metadata/",
-            Some(10_000),
-        ),
-        (
-            "Mutant Ape Yacht Club",
-            "0x60e4d786628fea6478f785a6d7e704777c86a7c6",
-            "https://boredapeyachtclub.com/api/mutants/",
-            Some(19_423),
-        ),
-        (
-            "ON1 Force",
-            "0x3bf2922f4520a8ba0c2efc3d2a1539678dad5e9d",
-            "https://ipfs.io/ipfs/QmXgSuLPGuxxRuAana7JdoWmaS25oAcXv3x2pYMN9kVfg3/",
-            Some(7_777),
-        ),
-        (
-            "Otherdeed for Otherside",
-            "0x34d85c9cdeb23fa97cb08333b511ac86e1c4e258",
-            "https://api.otherside.xyz/lands/",
-            Some(100_000),
-        ),
-        (
-            "Pudgy Penguins",
-            "0xbd3531da5cf5857e7cfaa92426877b022e612cf8",
-            "https://ipfs.io/ipfs/QmWXJXRdExse2YHRY21Wvh4pjRxNRQcWVhcKw4DLVnqGqs/",
-            Some(8888),
-        ),
-        (
-            "VeeFriends",
-            "0xa3aee8bce55beea1951ef834b99f3ac60d1abeeb",
-            "https://erc721.veefriends.com/api/metadata/0xa3aee8bce55beea1951ef834b99f3ac60d1abeeb/",
-            Some(10_255),
-        ),
-        (
-            "World of Women",
-            "0xe785e82358879f061bc3dcac6f0444462d4b5330",
-            "https://wow-prod-nftribe.s3.eu-west-2.amazonaws.com/t/",
-            Some(10_000),
-        ),
-    ]
-});
+/// The gateway to which IPFS (`ipfs://`) uris are converted, unless configured otherwise within settings.
+pub const IPFS_GATEWAY: &str = "ipfs.io";
+/// The number of tokens listed per page, unless configured otherwise within settings.
+pub const PAGE_SIZE: usize = 25;
+/// The maximum number of tokens listed per page whilst saving data.
+pub const DATA_SAVER_PAGE_SIZE: usize = 10;
+/// The market data api used for pricing, which can be set to `None` to disable market data.
+pub const MARKET_DATA_API: Option<&str> = Some("https://api.reservoir.tools/");
+/// The duration (in minutes) for which market data is cached.
+pub const MARKET_DATA_TTL: i64 = 15;
+/// The number of days for which the tokens viewed are kept within the history.
+pub const HISTORY_DAYS: i64 = 30;
+/// The duration (in hours) for which contracts and the results of their calls (e.g. the total supply) are cached.
+pub const CONTRACT_TTL: i64 = 24;
+/// The interval (in seconds) at which the gas price is polled.
+pub const GAS_INTERVAL: u32 = 60;
+/// The interval (in minutes) at which the collections on the watchlist are checked for changes.
+pub const WATCHLIST_INTERVAL: u32 = 5;
+/// The interval (in seconds) at which a tour being played moves to its next stop.
+pub const TOUR_INTERVAL: u32 = 8;
+/// The relative floor price move (e.g. 0.1 for 10%) beyond which a watched collection raises a notification.
+pub const WATCHLIST_FLOOR_THRESHOLD: f64 = 0.1;
+/// The etherscan-compatible api of the block explorer used for each chain.
+pub fn api(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Ethereum => "https://api.etherscan.io/api",
+        Chain::Polygon => "https://api.polygonscan.com/api",
+        Chain::Arbitrum => "https://api.arbiscan.io/api",
+        Chain::Optimism => "https://api-optimistic.etherscan.io/api",
+        Chain::Bsc => "https://api.bscscan.com/api",
+    }
+}
+/// An etherscan.io API key supplied via the `apikey` query string parameter, used for the current session only and
+/// never persisted.
+pub static SESSION_API_KEY: OnceCell<String> = OnceCell::new();
+
+/// The etherscan.io API key, being any supplied for the current session in preference to that configured within
+/// settings.
+pub fn api_key() -> Option<String> {
+    SESSION_API_KEY
+        .get()
+        .cloned()
+        .or_else(|| storage::Settings::get().api_key)
+}
+
+/// The market data API key configured within settings, without which prices are not requested.
+pub fn market_api_key() -> Option<String> {
+    MARKET_DATA_API.and(storage::Settings::get().market_api_key)
+}
+
+/// Whether the browser requests that data usage is reduced (`navigator.connection.saveData`), such as within a data
+/// saver mode on mobile.
+pub fn save_data() -> bool {
+    web_sys::window()
+        .and_then(|window| js_sys::Reflect::get(&window.navigator(), &"connection".into()).ok())
+        .filter(|connection| connection.is_object())
+        .and_then(|connection| js_sys::Reflect::get(&connection, &"saveData".into()).ok())
+        .and_then(|save_data| save_data.as_bool())
+        .unwrap_or(false)
+}
+
+/// Whether the connection is likely metered, being cellular (`navigator.connection.type`) where reported by the
+/// browser.
+pub fn metered() -> bool {
+    web_sys::window()
+        .and_then(|window| js_sys::Reflect::get(&window.navigator(), &"connection".into()).ok())
+        .filter(|connection| connection.is_object())
+        .and_then(|connection| js_sys::Reflect::get(&connection, &"type".into()).ok())
+        .and_then(|connection_type| connection_type.as_string())
+        .map_or(false, |connection_type| connection_type == "cellular")
+}
+
+/// The CORS proxy through which requests failing due to CORS are retried.
+pub fn cors_proxy() -> String {
+    storage::Settings::get()
+        .cors_proxy
+        .unwrap_or_else(|| CORS_PROXY.to_string())
+}
+
+/// The overrides with which the metadata of a collection is requested, falling back to the IPFS gateway configured
+/// within settings where the collection has none of its own.
+pub fn overrides(overrides: &Overrides) -> Overrides {
+    let mut overrides = overrides.clone();
+    if overrides.gateway.is_none() {
+        overrides.gateway = storage::Settings::get().ipfs_gateway;
+    }
+    overrides
+}
@@ -0,0 +1,58 @@
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Formats the rows as comma-separated values, quoting any fields containing delimiters, quotes or line breaks.
+pub fn csv(rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    rows.into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| {
+                    if field.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field.clone()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+/// Downloads the content as a file, via a temporary object url.
+pub fn download(file_name: &str, mime_type: &str, content: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("could not get document"))?;
+
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(
+        &js_sys::Array::of1(&JsValue::from_str(content)),
+        &options,
+    )?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<web_sys::HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::csv;
+
+    #[test]
+    fn quotes_fields_containing_delimiters() {
+        let rows = vec![
+            vec!["token".to_string(), "name".to_string()],
+            vec!["1".to_string(), "Hat, \"Gold\"".to_string()],
+        ];
+        assert_eq!("token,name\r\n1,\"Hat, \"\"Gold\"\"\"", csv(rows));
+    }
+}
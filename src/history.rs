@@ -0,0 +1,147 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A token viewed at a point in time, recorded so that the tokens viewed can be revisited by day.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Visit {
+    #[serde(rename = "c")]
+    pub collection: String,
+    #[serde(rename = "t")]
+    pub token: u32,
+    #[serde(rename = "n")]
+    pub name: String,
+    #[serde(rename = "i")]
+    pub image: String,
+    #[serde(rename = "v")]
+    pub viewed: DateTime<Utc>,
+}
+
+/// The visits of a day, most recent first, with each token listed once.
+#[derive(Debug, PartialEq)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub visits: Vec<Visit>,
+}
+
+impl Day {
+    pub fn collections(&self) -> usize {
+        self.visits
+            .iter()
+            .map(|visit| visit.collection.as_str())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Summarises the day relative to today, e.g. "Yesterday you viewed 14 tokens across 3 collections".
+    pub fn describe(&self, today: NaiveDate) -> String {
+        let day = if self.date == today {
+            "Today".to_string()
+        } else if self.date == today - Duration::days(1) {
+            "Yesterday".to_string()
+        } else {
+            self.date.format("On %A %-d %B").to_string()
+        };
+        let tokens = self.visits.len();
+        let collections = self.collections();
+        format!(
+            "{day} you viewed {tokens} token{} {} {collections} collection{}",
+            if tokens == 1 { "" } else { "s" },
+            if collections == 1 { "in" } else { "across" },
+            if collections == 1 { "" } else { "s" },
+        )
+    }
+}
+
+/// Groups the visits (in the order visited) by the day on which they were viewed within the timezone, most recent day
+/// first.
+pub fn days<Tz: TimeZone>(visits: &[Visit], timezone: &Tz) -> Vec<Day> {
+    let mut days: Vec<Day> = Vec::new();
+    let mut seen = HashSet::new();
+    for visit in visits.iter().rev() {
+        let date = visit.viewed.with_timezone(timezone).date().naive_local();
+        if days.last().map_or(true, |day| day.date != date) {
+            days.push(Day {
+                date,
+                visits: Vec::new(),
+            });
+            seen.clear();
+        }
+        // Only the most recent visit of a token within the day is listed
+        if seen.insert((visit.collection.as_str(), visit.token)) {
+            if let Some(day) = days.last_mut() {
+                day.visits.push(visit.clone());
+            }
+        }
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{days, Visit};
+    use chrono::{NaiveDate, TimeZone, Timelike, Utc};
+
+    fn visit(collection: &str, token: u32, day: u32, hour: u32) -> Visit {
+        Visit {
+            collection: collection.to_string(),
+            token,
+            name: format!("#{token}"),
+            image: String::new(),
+            viewed: Utc.ymd(2022, 10, day).and_hms(hour, 0, 0),
+        }
+    }
+
+    #[test]
+    fn groups_visits_by_day() {
+        let visits = vec![
+            visit("azuki", 1, 10, 9),
+            visit("azuki", 2, 11, 9),
+            visit("doodles", 7, 11, 10),
+            visit("azuki", 2, 11, 11),
+            visit("azuki", 3, 12, 9),
+        ];
+        let days = days(&visits, &Utc);
+        assert_eq!(
+            vec![
+                NaiveDate::from_ymd(2022, 10, 12),
+                NaiveDate::from_ymd(2022, 10, 11),
+                NaiveDate::from_ymd(2022, 10, 10)
+            ],
+            days.iter().map(|day| day.date).collect::<Vec<_>>()
+        );
+        // Revisited tokens are listed once, as of the most recent visit
+        assert_eq!(
+            vec![("azuki", 2, 11), ("doodles", 7, 10)],
+            days[1]
+                .visits
+                .iter()
+                .map(|v| (v.collection.as_str(), v.token, v.viewed.time().hour()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn describes_days() {
+        let visits = vec![
+            visit("azuki", 1, 10, 9),
+            visit("azuki", 2, 11, 9),
+            visit("doodles", 7, 11, 10),
+            visit("azuki", 3, 12, 9),
+        ];
+        let days = days(&visits, &Utc);
+        let today = NaiveDate::from_ymd(2022, 10, 12);
+        assert_eq!(
+            "Today you viewed 1 token in 1 collection",
+            days[0].describe(today)
+        );
+        assert_eq!(
+            "Yesterday you viewed 2 tokens across 2 collections",
+            days[1].describe(today)
+        );
+        assert_eq!(
+            "On Monday 10 October you viewed 1 token in 1 collection",
+            days[2].describe(today)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+/// The widths at which images are requested from the image proxy.
+pub const WIDTHS: [u32; 4] = [200, 400, 800, 1200];
+
+/// The formats requested from an image proxy supporting format conversion, in order of preference.
+pub const FORMATS: [(&str, &str); 2] = [("avif", "image/avif"), ("webp", "image/webp")];
+
+/// An image proxy, configured as a url template containing `{url}` and `{width}` placeholders, along with an optional
+/// `{format}` placeholder where the proxy converts images to the requested format (e.g.
+/// `https://wsrv.nl/?url={url}&w={width}&output={format}`).
+pub struct Proxy<'a> {
+    template: &'a str,
+}
+
+impl<'a> Proxy<'a> {
+    /// Parses the template, provided it contains the required placeholders.
+    pub fn parse(template: &'a str) -> Option<Proxy<'a>> {
+        let template = template.trim();
+        (template.contains("{url}") && template.contains("{width}")).then(|| Proxy { template })
+    }
+
+    /// Whether the proxy converts images to the format requested.
+    pub fn formats(&self) -> bool {
+        self.template.contains("{format}")
+    }
+
+    /// Returns the url of the image at the width (and format) via the proxy.
+    pub fn url(&self, image: &str, width: u32, format: Option<&str>) -> String {
+        self.template
+            .replace("{url}", &encode(image))
+            .replace("{width}", &width.to_string())
+            .replace("{format}", format.unwrap_or_default())
+    }
+
+    /// Returns the source set of the image at each width, should the image be served via the proxy.
+    pub fn srcset(&self, image: &str, format: Option<&str>) -> Option<String> {
+        // Only remote images can be fetched by the proxy (e.g. not data uris)
+        if !image.starts_with("https://") && !image.starts_with("http://") {
+            return None;
+        }
+        Some(
+            WIDTHS
+                .iter()
+                .map(|width| format!("{} {width}w", self.url(image, *width, format)))
+                .collect::<Vec<String>>()
+                .join(", "),
+        )
+    }
+}
+
+/// Percent-encodes the url for inclusion within the query string of the proxy url.
+fn encode(url: &str) -> String {
+    url.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Whether the image may be animated, per the extension or media type of its source, being those formats (GIF, APNG and
+/// WebP) which support animation.
+pub fn may_be_animated(src: &str) -> bool {
+    let src = src.to_ascii_lowercase();
+    if let Some(media_type) = src.strip_prefix("data:") {
+        return ["image/gif", "image/png", "image/apng", "image/webp"]
+            .iter()
+            .any(|m| media_type.starts_with(m));
+    }
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+    [".gif", ".png", ".apng", ".webp"]
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+/// Whether the image is animated, having more than one frame.
+pub fn animated(bytes: &[u8]) -> bool {
+    match bytes {
+        [b'G', b'I', b'F', b'8', ..] => gif_frames(bytes) > 1,
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', ..] => apng(&bytes[8..]),
+        // An extended WebP header flags any animation
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', b'V', b'P', b'8', b'X', _, _, _, _, flags, ..] => {
+            flags & 0x02 != 0
+        }
+        _ => false,
+    }
+}
+
+/// Counts the frames of the GIF, up to the second.
+fn gif_frames(bytes: &[u8]) -> usize {
+    // Skip the header, logical screen descriptor and any global color table
+    let mut i = 13 + color_table(bytes.get(10));
+    let mut frames = 0;
+    while frames < 2 {
+        match bytes.get(i) {
+            // Extension: label followed by data sub-blocks
+            Some(0x21) => i = sub_blocks(bytes, i + 2),
+            // Image descriptor: any local color table, then the minimum code size followed by data sub-blocks
+            Some(0x2C) => {
+                frames += 1;
+                i = sub_blocks(bytes, i + 10 + color_table(bytes.get(i + 9)) + 1);
+            }
+            // Trailer, or truncated
+            _ => break,
+        }
+    }
+    frames
+}
+
+/// The size of the color table flagged within the packed field of a descriptor.
+fn color_table(packed: Option<&u8>) -> usize {
+    packed
+        .filter(|packed| *packed & 0x80 != 0)
+        .map_or(0, |packed| 3 << ((packed & 0x07) + 1))
+}
+
+/// The position following the data sub-blocks starting at the position, terminated by an empty block.
+fn sub_blocks(bytes: &[u8], mut i: usize) -> usize {
+    while let Some(size) = bytes.get(i).filter(|size| **size != 0) {
+        i += *size as usize + 1;
+    }
+    i + 1
+}
+
+/// Whether the chunks of the PNG include an animation control chunk (ahead of the image data) of more than one frame.
+fn apng(mut chunks: &[u8]) -> bool {
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+        match &chunks[4..8] {
+            b"acTL" => {
+                return chunks.get(8..12).map_or(false, |frames| {
+                    u32::from_be_bytes([frames[0], frames[1], frames[2], frames[3]]) > 1
+                })
+            }
+            b"IDAT" => return false,
+            _ => match chunks.get(12 + length..) {
+                Some(remaining) => chunks = remaining,
+                None => return false,
+            },
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{animated, may_be_animated, Proxy};
+
+    #[test]
+    fn requires_placeholders() {
+        assert!(Proxy::parse("https://wsrv.nl/?url={url}").is_none());
+        assert!(Proxy::parse(" https://wsrv.nl/?url={url}&w={width} ").is_some());
+    }
+
+    #[test]
+    fn builds_srcset() {
+        let proxy = Proxy::parse("https://wsrv.nl/?url={url}&w={width}&output={format}").unwrap();
+        assert!(proxy.formats());
+        assert_eq!(
+            Some(
+                "https://wsrv.nl/?url=https%3A%2F%2Fipfs.io%2Fipfs%2Fcid%2F1.png&w=200&output=webp 200w, \
+                https://wsrv.nl/?url=https%3A%2F%2Fipfs.io%2Fipfs%2Fcid%2F1.png&w=400&output=webp 400w, \
+                https://wsrv.nl/?url=https%3A%2F%2Fipfs.io%2Fipfs%2Fcid%2F1.png&w=800&output=webp 800w, \
+                https://wsrv.nl/?url=https%3A%2F%2Fipfs.io%2Fipfs%2Fcid%2F1.png&w=1200&output=webp 1200w"
+                    .to_string()
+            ),
+            proxy.srcset("https://ipfs.io/ipfs/cid/1.png", Some("webp"))
+        );
+    }
+
+    #[test]
+    fn ignores_data_uris() {
+        let proxy = Proxy::parse("https://proxy.example.com/{width}/{url}").unwrap();
+        assert!(!proxy.formats());
+        assert!(proxy
+            .srcset("data:image/svg+xml;base64,PHN2Zz4=", None)
+            .is_none());
+    }
+
+    #[test]
+    fn detects_animated_gif() {
+        // Header, logical screen descriptor without a global color table, then each frame (a graphic control
+        // extension followed by an image descriptor and its data)
+        let mut gif = b"GIF89a\x01\x00\x01\x00\x00\x00\x00".to_vec();
+        let frame = b"\x21\xF9\x04\x00\x0A\x00\x00\x00\x2C\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00";
+        gif.extend_from_slice(frame);
+        assert!(!animated(&[gif.as_slice(), &b"\x3B"[..]].concat()));
+        gif.extend_from_slice(frame);
+        assert!(animated(&[gif.as_slice(), &b"\x3B"[..]].concat()));
+    }
+
+    #[test]
+    fn detects_animated_png() {
+        let chunk = |kind: &[u8], data: &[u8]| {
+            [
+                &(data.len() as u32).to_be_bytes()[..],
+                kind,
+                data,
+                &[0u8; 4][..],
+            ]
+            .concat()
+        };
+        let png = |chunks: &[Vec<u8>]| [b"\x89PNG\r\n\x1A\n".to_vec(), chunks.concat()].concat();
+        let header = chunk(b"IHDR", &[0; 13]);
+        let data = chunk(b"IDAT", &[0; 4]);
+        assert!(!animated(&png(&[header.clone(), data.clone()])));
+        assert!(animated(&png(&[
+            header,
+            chunk(b"acTL", &[0, 0, 0, 2, 0, 0, 0, 0]),
+            data
+        ])));
+    }
+
+    #[test]
+    fn checks_formats_supporting_animation() {
+        assert!(may_be_animated("https://ipfs.io/ipfs/cid/1.GIF?v=2"));
+        assert!(may_be_animated("data:image/png;base64,iVBORw0KGgo="));
+        assert!(!may_be_animated("https://ipfs.io/ipfs/cid/1.jpg"));
+        assert!(!may_be_animated("data:image/svg+xml;base64,PHN2Zz4="));
+    }
+}
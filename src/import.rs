@@ -0,0 +1,122 @@
+use crate::{models, Address};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Extracts the distinct contract addresses from a list supplied either as JSON (an array of addresses, or of objects
+/// with an `address` field) or as comma/line-separated values, in the order first listed. Any values which are not
+/// valid addresses (e.g. headers or names) are ignored.
+pub fn addresses(input: &str) -> Vec<Address> {
+    let values: Vec<String> = match serde_json::from_str::<Value>(input) {
+        Ok(Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::String(address) => Some(address),
+                Value::Object(mut item) => item
+                    .remove("address")
+                    .and_then(|address| address.as_str().map(|address| address.to_string())),
+                _ => None,
+            })
+            .collect(),
+        _ => input
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .map(|field| field.trim_matches('"').to_string())
+            .collect(),
+    };
+
+    let mut addresses = Vec::new();
+    for value in values {
+        if let Ok(address) = Address::from_str(value.trim()) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    addresses
+}
+
+/// An entry of an imported list: a favorite token, or a watched collection where no token is listed.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    /// The collection identifier, qualified by its chain where not Ethereum.
+    pub collection: String,
+    pub token: Option<u32>,
+}
+
+/// Extracts the distinct entries from comma-separated values of `contract,tokenId`, in the order first listed. The
+/// contract may be qualified by its chain (e.g. `polygon:0x...`), with the token identifier left empty for watched
+/// collections. Any rows which are not valid entries (e.g. headers) are ignored.
+pub fn entries(input: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for row in input.lines() {
+        let mut fields = row
+            .split(|c: char| c == ',' || c == ';' || c == '\t')
+            .map(|field| field.trim().trim_matches('"').trim());
+        let collection = match fields.next().and_then(models::Collection::parse_contract) {
+            Some((chain, address)) => models::Collection::contract_id(chain, &address),
+            None => continue,
+        };
+        let token = match fields.next().filter(|token| !token.is_empty()) {
+            Some(token) => match u32::from_str(token) {
+                Ok(token) => Some(token),
+                Err(_) => continue,
+            },
+            None => None,
+        };
+        let entry = Entry { collection, token };
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::{addresses, entries, Entry};
+    use crate::Address;
+    use std::str::FromStr;
+
+    const AZUKI: &str = "0xed5af388653567af2f388e6224dc7c4b3241c544";
+    const BAYC: &str = "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d";
+
+    #[test]
+    fn imports_csv() {
+        let input = format!("address,name\r\n{AZUKI},Azuki\r\n{BAYC},BAYC\r\n{AZUKI},Azuki");
+        assert_eq!(
+            vec![
+                Address::from_str(AZUKI).unwrap(),
+                Address::from_str(BAYC).unwrap()
+            ],
+            addresses(&input)
+        );
+    }
+
+    #[test]
+    fn imports_entries() {
+        let input = format!(
+            "contract,tokenId\r\n{AZUKI},1\r\n\"{BAYC}\",\"42\"\r\npolygon:{AZUKI},7\r\n{BAYC},\r\n{AZUKI},1\r\n{AZUKI},x"
+        );
+        let entry = |collection: String, token| Entry { collection, token };
+        assert_eq!(
+            vec![
+                entry(AZUKI.to_string(), Some(1)),
+                entry(BAYC.to_string(), Some(42)),
+                entry(format!("polygon:{AZUKI}"), Some(7)),
+                entry(BAYC.to_string(), None),
+            ],
+            entries(&input)
+        );
+    }
+
+    #[test]
+    fn imports_json() {
+        let input = format!("[\"{AZUKI}\", {{ \"address\": \"{BAYC}\", \"name\": \"BAYC\" }}, 1]");
+        assert_eq!(
+            vec![
+                Address::from_str(AZUKI).unwrap(),
+                Address::from_str(BAYC).unwrap()
+            ],
+            addresses(&input)
+        );
+    }
+}
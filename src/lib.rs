@@ -1,60 +1,228 @@
 use serde::{Deserialize, Serialize};
 use std::rc::Rc;
-use workers::{etherscan, metadata, Bridge, Bridged};
+use std::str::FromStr;
+use workers::{etherscan, indexer, metadata, Bridge, Bridged};
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+mod clock;
 mod components;
 mod config;
+mod export;
+mod history;
+mod image;
+mod import;
+pub mod logging;
+mod marketplace;
+mod media;
 mod models;
 mod notifications;
+mod redirect;
+mod search;
+mod snapshot;
 mod storage;
+mod svg;
+mod tour;
+mod transition;
 mod uri;
+mod wallet;
 
 extern crate core;
 
 type Address = workers::etherscan::Address;
 
 pub struct App {
-    _etherscan: Box<dyn Bridge<etherscan::Worker>>,
-    _metadata: Box<dyn Bridge<metadata::Worker>>,
+    unread: usize,
+}
+
+pub enum Message {
+    Notify(storage::Notification),
+    Read,
 }
 
 impl Component for App {
-    type Message = ();
+    type Message = Message;
     type Properties = ();
 
     fn create(_ctx: &Context<Self>) -> Self {
+        if let Some(api_key) = take_api_key() {
+            log::info!("using session api key");
+            let _ = config::SESSION_API_KEY.set(api_key);
+        }
+        let settings = storage::Settings::get();
+        components::settings::apply_theme(settings.theme);
+        uri::set_gateway(settings.ipfs_gateway.as_deref());
         if let Err(e) = yew_router_qs::try_route_from_query_string() {
             log::error!("{:?}", e)
         }
+        register_service_worker();
 
         Self {
-            // Declare workers 'globally' so not disposed when navigating between components which rely on them
-            _etherscan: etherscan::Worker::bridge(Rc::new(move |_: etherscan::Response| {})),
-            _metadata: metadata::Worker::bridge(Rc::new(move |_: metadata::Response| {})),
+            unread: storage::Notifications::unread(),
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::Notify(notification) => {
+                notifications::notify(notification.message.clone(), None);
+                storage::Notifications::store(notification);
+            }
+            Message::Read => {}
+        }
+        self.unread = storage::Notifications::unread();
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
         html! {
             <BrowserRouter>
-                <components::Navigation />
+                <Workers />
+                <components::Navigation unread={ self.unread } onread={ ctx.link().callback(|_| Message::Read) } />
                 <main>
+                    <components::StorageRestricted />
+                    <components::Offline />
                     <Switch<Route> render={Switch::render(switch)} />
                 </main>
                 <components::Footer />
+                <components::watchlist::Watcher onnotify={ ctx.link().callback(Message::Notify) } />
             </BrowserRouter>
         }
     }
 }
 
+/// Takes an etherscan.io API key from the `apikey` query string parameter, removing it from the address bar so that it
+/// is not inadvertently shared or bookmarked.
+fn take_api_key() -> Option<String> {
+    let window = web_sys::window()?;
+    let url = web_sys::Url::new(&window.location().href().ok()?).ok()?;
+    let api_key = url
+        .search_params()
+        .get("apikey")
+        .filter(|api_key| !api_key.is_empty())?;
+    url.search_params().delete("apikey");
+    if let Err(e) = window.history().and_then(|history| {
+        history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&url.href()))
+    }) {
+        log::error!("unable to remove the api key from the url: {e:?}")
+    }
+    Some(api_key)
+}
+
+/// Registers the service worker, which caches the app so that it (along with any collections already indexed) can be
+/// browsed offline.
+fn register_service_worker() {
+    let navigator = match web_sys::window() {
+        Some(window) => window.navigator(),
+        None => return,
+    };
+    // Service workers are only available within secure contexts
+    if !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        log::trace!("service workers unavailable, so the app is not cached for offline use");
+        return;
+    }
+    let registration = navigator.service_worker().register("/service-worker.js");
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(e) = wasm_bindgen_futures::JsFuture::from(registration).await {
+            log::warn!("unable to register the service worker: {e:?}")
+        }
+    });
+}
+
+/// Keeps the shared workers alive when navigating between components which rely on them. Each worker is only bridged
+/// (and its script loaded) once a route requiring it is first visited.
+#[function_component(Workers)]
+fn workers() -> Html {
+    let route = use_route::<Route>();
+    let bridges = use_mut_ref(Bridges::default);
+
+    if let Some(route) = route {
+        let mut bridges = bridges.borrow_mut();
+        if route.requires_etherscan() && bridges.etherscan.is_none() {
+            log::trace!("bridging etherscan worker...");
+            // Persist contracts as they are resolved, so that they are not requested again on subsequent visits
+            let mut bridge = etherscan::Worker::bridge(Rc::new(move |e: etherscan::Response| {
+                if let etherscan::Response::Cached(chain, address, contract) = e {
+                    storage::Contract::store(chain, &address, contract)
+                }
+            }));
+            bridge.send(etherscan::Request::Persist);
+            bridges.etherscan = Some(bridge);
+        }
+        if route.requires_metadata() && bridges.metadata.is_none() {
+            log::trace!("bridging metadata worker...");
+            let mut bridge = metadata::Worker::bridge(Rc::new(move |e: metadata::Response| {
+                if let metadata::Response::Hosts(hosts) = e {
+                    storage::Hosts::store(&hosts)
+                }
+            }));
+            // Load the hosts learned in previous sessions, persisting any subsequent changes
+            bridge.send(metadata::Request::Hosts(storage::Hosts::get()));
+            bridges.metadata = Some(bridge);
+        }
+        if route.requires_metadata() && bridges.indexer.is_none() {
+            log::trace!("bridging indexer worker...");
+            // Persist tokens (and any redirects) as they are indexed, so that indexing continues whilst navigating between collections,
+            // along with the hosts learned whilst indexing
+            let mut bridge =
+                indexer::Worker::bridge(Rc::new(move |e: indexer::Response| match e {
+                    indexer::Response::Indexed(collection, tokens) => {
+                        let tokens: Vec<models::Token> = tokens
+                            .into_iter()
+                            .map(|(token, metadata)| models::Token::new(token, metadata))
+                            .collect();
+                        storage::Token::store_many(&collection, tokens.iter());
+                    }
+                    indexer::Response::Redirected(collection, redirects) => {
+                        storage::Redirects::store(&collection, redirects)
+                    }
+                    indexer::Response::Cursor(collection, cursor) => {
+                        storage::Cursor::store(&collection, &cursor)
+                    }
+                    indexer::Response::Hosts(hosts) => storage::Hosts::store(&hosts),
+                    indexer::Response::Progress(_) => {}
+                }));
+            bridge.send(indexer::Request::Persist(storage::Hosts::get()));
+            bridges.indexer = Some(bridge);
+        }
+        // Applied on each change of route, so that any change within settings applies once navigating away
+        let settings = storage::Settings::get();
+        if let Some(etherscan) = bridges.etherscan.as_mut() {
+            etherscan.send(etherscan::Request::ApiKey(
+                config::api_key().unwrap_or_default(),
+            ));
+        }
+        if let Some(indexer) = bridges.indexer.as_mut() {
+            indexer.send(indexer::Request::Budget(settings.budget));
+        }
+    }
+
+    html! {}
+}
+
+#[derive(Default)]
+struct Bridges {
+    etherscan: Option<Box<dyn Bridge<etherscan::Worker>>>,
+    metadata: Option<Box<dyn Bridge<metadata::Worker>>>,
+    indexer: Option<Box<dyn Bridge<indexer::Worker>>>,
+}
+
 #[derive(Routable, Eq, Hash, PartialEq, Clone, Debug, Deserialize, Serialize)]
 pub enum Route {
     #[at("/a/:address")]
     Address { address: String },
     #[at("/c/:id")]
     Collection { id: String },
+    #[at("/c/:id/diagnostics")]
+    CollectionDiagnostics {
+        /// The collection identifier.
+        id: String,
+    },
+    #[at("/c/:id/rarity")]
+    CollectionRarity {
+        /// The collection identifier.
+        id: String,
+    },
     #[at("/c/:id/:token")]
     CollectionToken {
         /// The collection identifier.
@@ -62,8 +230,44 @@ pub enum Route {
         /// The token identifier.
         token: u32,
     },
+    /// A collection on a chain other than Ethereum, redirecting to its chain-qualified collection identifier.
+    #[at("/:chain/c/:id")]
+    ChainCollection {
+        /// The chain identifier (e.g. `polygon`).
+        chain: String,
+        /// The contract address.
+        id: String,
+    },
+    #[at("/:chain/c/:id/:token")]
+    ChainCollectionToken {
+        /// The chain identifier (e.g. `polygon`).
+        chain: String,
+        /// The contract address.
+        id: String,
+        /// The token identifier.
+        token: u32,
+    },
+    /// The errors of the app, for inclusion within bug reports.
+    #[at("/debug")]
+    Debug,
+    #[at("/favorites")]
+    Favorites,
+    #[at("/gallery")]
+    Gallery,
+    #[at("/history")]
+    History,
     #[at("/")]
     Home,
+    #[at("/settings")]
+    Settings,
+    #[at("/tours")]
+    Tours,
+    /// A tour, replayed as a slideshow.
+    #[at("/tours/:tour")]
+    Tour {
+        /// The encoded tour.
+        tour: String,
+    },
     #[not_found]
     #[at("/404")]
     NotFound,
@@ -72,6 +276,43 @@ pub enum Route {
 }
 
 impl Route {
+    /// The absolute url of the route, for sharing. Uses the hash-based format where configured, which the bootstrap
+    /// within index.html rewrites to the route.
+    pub(crate) fn share_url(&self) -> Option<String> {
+        let origin = web_sys::window()?.location().origin().ok()?;
+        Some(if storage::Settings::get().hash_links {
+            format!("{origin}/#{}", self.to_path())
+        } else {
+            format!("{origin}{}", self.to_path())
+        })
+    }
+
+    fn requires_etherscan(&self) -> bool {
+        matches!(
+            self,
+            Route::Address { .. }
+                | Route::Collection { .. }
+                | Route::CollectionDiagnostics { .. }
+                | Route::CollectionToken { .. }
+                | Route::Gallery
+                | Route::Tour { .. }
+        )
+    }
+
+    fn requires_metadata(&self) -> bool {
+        matches!(
+            self,
+            Route::Collection { .. } | Route::CollectionToken { .. } | Route::Tour { .. }
+        )
+    }
+
+    /// Qualifies the contract address with the chain, as a collection identifier.
+    fn chain(chain: &str, address: &str) -> Option<String> {
+        let chain = etherscan::Chain::from_str(chain).ok()?;
+        let address = Address::from_str(address).ok()?;
+        Some(models::Collection::contract_id(chain, &address))
+    }
+
     fn token(token: &models::Token, collection: String) -> Route {
         Route::CollectionToken {
             id: collection,
@@ -83,17 +324,54 @@ impl Route {
 fn switch(routes: &Route) -> Html {
     match routes.clone() {
         Route::Address { address } => {
-            html! { <components::address::Address { address } /> }
+            let api_key = config::api_key();
+            html! { <components::address::Address { address } { api_key } /> }
         }
         Route::Collection { id } => {
-            html! { <components::collection::Collection { id } /> }
+            let api_key = config::api_key();
+            html! { <components::collection::Collection { id } { api_key } /> }
+        }
+        Route::CollectionDiagnostics { id } => {
+            html! { <components::collection::diagnostics::Diagnostics { id } /> }
+        }
+        Route::CollectionRarity { id } => {
+            html! { <components::collection::rarity::Rarity { id } /> }
         }
         Route::CollectionToken { id, token } => {
             html! { <components::collection::token::Token collection={ id } { token } /> }
         }
+        Route::ChainCollection { chain, id } => match Route::chain(&chain, &id) {
+            Some(id) => html! { <Redirect<Route> to={ Route::Collection { id } } /> },
+            None => html! { <components::NotFound /> },
+        },
+        Route::ChainCollectionToken { chain, id, token } => match Route::chain(&chain, &id) {
+            Some(id) => html! { <Redirect<Route> to={ Route::CollectionToken { id, token } } /> },
+            None => html! { <components::NotFound /> },
+        },
+        Route::Debug => {
+            html! { <components::debug::Debug /> }
+        }
+        Route::Favorites => {
+            html! { <components::favorites::Favorites /> }
+        }
+        Route::Gallery => {
+            html! { <components::gallery::Gallery /> }
+        }
+        Route::History => {
+            html! { <components::history::History /> }
+        }
         Route::Home => {
             html! { <components::Home /> }
         }
+        Route::Settings => {
+            html! { <components::settings::Settings /> }
+        }
+        Route::Tours => {
+            html! { <components::tour::Tours /> }
+        }
+        Route::Tour { tour } => {
+            html! { <components::tour::Player { tour } /> }
+        }
         Route::NotFound => {
             html! { <components::NotFound /> }
         } // Route::Token { uri } => {
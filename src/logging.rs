@@ -0,0 +1,316 @@
+//! Structured logging of the app. Events are written to the console and retained (up to a limit) so that they can be
+//! viewed within the app and attached to bug reports. Events may include fields by convention, such as the
+//! `collection`, `token` and `duration` (in milliseconds) to which they pertain, e.g.
+//! `log::trace!(collection = id, token = token; "requesting metadata...")`.
+//!
+//! Errors (along with any panics) are also retained separately and persisted, so that those of previous sessions can
+//! be included within bug reports from the debug page.
+use crate::{clock, storage};
+use chrono::{DateTime, Utc};
+use log::{kv, Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::panic::PanicInfo;
+
+/// The maximum number of events retained.
+const MAX_EVENTS: usize = 1000;
+/// The maximum number of errors retained.
+const MAX_ERRORS: usize = 50;
+/// The crate name, omitted from the modules of events.
+const CRATE: &str = "nifty_gallery::";
+
+thread_local! {
+    static EVENTS: RefCell<VecDeque<Event>> = RefCell::new(VecDeque::new());
+    static FILTERS: RefCell<BTreeMap<String, LevelFilter>> = RefCell::new(BTreeMap::new());
+    static ERRORS: RefCell<VecDeque<Error>> = RefCell::new(VecDeque::new());
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    pub level: Level,
+    /// The module from which the event was logged.
+    pub module: String,
+    pub message: String,
+    /// The fields of the event (e.g. the collection, token or duration), in the order logged.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Event {
+    /// Gets the value of the field.
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {} {}",
+            self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            self.level,
+            self.module,
+            self.message
+        )?;
+        for (key, value) in self.fields.iter() {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error logged by the app, or a panic, retained across sessions.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Error {
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    /// The module from which the error was logged, or `panic`.
+    #[serde(rename = "s")]
+    pub source: String,
+    /// The message, including any fields of the event.
+    #[serde(rename = "m")]
+    pub message: String,
+}
+
+impl From<&Event> for Error {
+    fn from(event: &Event) -> Self {
+        let mut message = event.message.clone();
+        for (key, value) in event.fields.iter() {
+            let _ = write!(message, " {key}={value}");
+        }
+        Error {
+            timestamp: event.timestamp,
+            source: event.module.clone(),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            self.source,
+            self.message
+        )
+    }
+}
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let mut fields = Fields(Vec::new());
+        if let Err(e) = record.key_values().visit(&mut fields) {
+            web_sys::console::error_1(
+                &format!("unable to read the fields of the event: {e}").into(),
+            );
+        }
+        let event = Event {
+            timestamp: clock::now(),
+            level: record.level(),
+            module: record
+                .target()
+                .strip_prefix(CRATE)
+                .unwrap_or(record.target())
+                .to_string(),
+            message: record.args().to_string(),
+            fields: fields.0,
+        };
+
+        let line = event.to_string().into();
+        match event.level {
+            Level::Error => web_sys::console::error_1(&line),
+            Level::Warn => web_sys::console::warn_1(&line),
+            Level::Info => web_sys::console::info_1(&line),
+            Level::Debug | Level::Trace => web_sys::console::debug_1(&line),
+        }
+
+        if event.level == Level::Error {
+            record(Error::from(&event));
+        }
+        EVENTS.with(|events| {
+            let mut events = events.borrow_mut();
+            if events.len() == MAX_EVENTS {
+                events.pop_front();
+            }
+            events.push_back(event);
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+struct Fields(Vec<(String, String)>);
+
+impl<'kvs> kv::Visitor<'kvs> for Fields {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+/// Initialises logging, restoring the module filters configured and the errors retained in previous sessions.
+pub fn init() {
+    FILTERS.with(|filters| *filters.borrow_mut() = storage::LogFilters::get());
+    ERRORS.with(|errors| *errors.borrow_mut() = storage::Errors::get().into());
+    match log::set_logger(&Logger) {
+        Ok(_) => log::set_max_level(LevelFilter::Trace),
+        Err(e) => web_sys::console::error_1(&format!("unable to initialise logging: {e}").into()),
+    }
+}
+
+/// The retained events, oldest first.
+pub fn events() -> Vec<Event> {
+    EVENTS.with(|events| events.borrow().iter().cloned().collect())
+}
+
+pub fn clear() {
+    EVENTS.with(|events| events.borrow_mut().clear())
+}
+
+/// The retained errors, oldest first.
+pub fn errors() -> Vec<Error> {
+    ERRORS.with(|errors| errors.borrow().iter().cloned().collect())
+}
+
+pub fn clear_errors() {
+    ERRORS.with(|errors| errors.borrow_mut().clear());
+    storage::Errors::store(&VecDeque::new());
+}
+
+/// Records the panic as an error before writing it to the console, for use as the panic hook of the app.
+pub fn panicked(info: &PanicInfo) {
+    record(Error {
+        timestamp: clock::now(),
+        source: "panic".to_string(),
+        message: info.to_string(),
+    });
+    console_error_panic_hook::hook(info);
+}
+
+fn record(error: Error) {
+    let errors = ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        retain(&mut errors, error);
+        errors.clone()
+    });
+    storage::Errors::store(&errors);
+}
+
+fn retain(errors: &mut VecDeque<Error>, error: Error) {
+    while errors.len() >= MAX_ERRORS {
+        errors.pop_front();
+    }
+    errors.push_back(error);
+}
+
+/// Formats the events as text, one per line, for attaching to a bug report.
+pub fn export<'a>(events: impl IntoIterator<Item = &'a Event>) -> String {
+    events.into_iter().fold(String::new(), |mut text, event| {
+        let _ = writeln!(text, "{event}");
+        text
+    })
+}
+
+/// The level filter of each module, for modules whose level differs from the default.
+pub fn filters() -> BTreeMap<String, LevelFilter> {
+    FILTERS.with(|filters| filters.borrow().clone())
+}
+
+/// Sets the level filter of the module (and its submodules), or restores the default where none.
+pub fn set_filter(module: &str, level: Option<LevelFilter>) {
+    let filters = FILTERS.with(|filters| {
+        let mut filters = filters.borrow_mut();
+        match level {
+            Some(level) => filters.insert(module.to_string(), level),
+            None => filters.remove(module),
+        };
+        filters.clone()
+    });
+    // Stored once released, as any error storing the filters is itself logged
+    storage::LogFilters::store(&filters);
+}
+
+/// The level of the target, per the filter of its most specific module.
+fn level(target: &str) -> LevelFilter {
+    let module = target.strip_prefix(CRATE).unwrap_or(target);
+    FILTERS.with(|filters| filter(&filters.borrow(), module))
+}
+
+fn filter(filters: &BTreeMap<String, LevelFilter>, module: &str) -> LevelFilter {
+    filters
+        .iter()
+        .filter(|(m, _)| {
+            module == m.as_str()
+                || module
+                    .strip_prefix(m.as_str())
+                    .map_or(false, |rest| rest.starts_with("::"))
+        })
+        .max_by_key(|(m, _)| m.len())
+        .map_or(LevelFilter::Trace, |(_, level)| *level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter, retain, Error, MAX_ERRORS};
+    use chrono::Utc;
+    use log::LevelFilter;
+    use std::collections::{BTreeMap, VecDeque};
+
+    #[test]
+    fn applies_most_specific_filter() {
+        let filters = BTreeMap::from([
+            ("components".to_string(), LevelFilter::Warn),
+            (
+                "components::collection::token".to_string(),
+                LevelFilter::Debug,
+            ),
+        ]);
+        assert_eq!(LevelFilter::Trace, filter(&filters, "storage"));
+        assert_eq!(
+            LevelFilter::Warn,
+            filter(&filters, "components::collection")
+        );
+        assert_eq!(
+            LevelFilter::Debug,
+            filter(&filters, "components::collection::token")
+        );
+        // Only whole module names match
+        assert_eq!(LevelFilter::Trace, filter(&filters, "componentsx"));
+    }
+
+    #[test]
+    fn retains_most_recent_errors() {
+        let mut errors = VecDeque::new();
+        for i in 0..MAX_ERRORS + 5 {
+            retain(
+                &mut errors,
+                Error {
+                    timestamp: Utc::now(),
+                    source: "storage".to_string(),
+                    message: i.to_string(),
+                },
+            );
+        }
+        assert_eq!(MAX_ERRORS, errors.len());
+        assert_eq!("5", errors[0].message);
+        assert_eq!((MAX_ERRORS + 4).to_string(), errors[MAX_ERRORS - 1].message);
+    }
+}
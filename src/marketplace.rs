@@ -0,0 +1,102 @@
+use crate::Address;
+use serde::{Deserialize, Serialize};
+use workers::etherscan::{Chain, TypeExtensions};
+
+/// A marketplace on which tokens are listed, linked to from the token page.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Marketplace {
+    OpenSea,
+    LooksRare,
+    Blur,
+    Rarible,
+}
+
+impl Marketplace {
+    pub const ALL: [Marketplace; 4] = [
+        Marketplace::OpenSea,
+        Marketplace::LooksRare,
+        Marketplace::Blur,
+        Marketplace::Rarible,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Marketplace::OpenSea => "OpenSea",
+            Marketplace::LooksRare => "LooksRare",
+            Marketplace::Blur => "Blur",
+            Marketplace::Rarible => "Rarible",
+        }
+    }
+
+    /// The url of the token on the marketplace, where the marketplace supports the chain of the contract.
+    pub fn url(&self, chain: Chain, address: &Address, token: u32) -> Option<String> {
+        let address = TypeExtensions::format(address);
+        match (self, chain) {
+            (Marketplace::OpenSea, chain) => {
+                let chain = match chain {
+                    Chain::Ethereum => "ethereum",
+                    Chain::Polygon => "matic",
+                    Chain::Arbitrum => "arbitrum",
+                    Chain::Optimism => "optimism",
+                    Chain::Bsc => "bsc",
+                };
+                Some(format!(
+                    "https://opensea.io/assets/{chain}/{address}/{token}"
+                ))
+            }
+            (Marketplace::LooksRare, Chain::Ethereum) => Some(format!(
+                "https://looksrare.org/collections/{address}/{token}"
+            )),
+            (Marketplace::Blur, Chain::Ethereum) => {
+                Some(format!("https://blur.io/asset/{address}/{token}"))
+            }
+            (Marketplace::Rarible, Chain::Ethereum) => {
+                Some(format!("https://rarible.com/token/{address}:{token}"))
+            }
+            (Marketplace::Rarible, Chain::Polygon) => Some(format!(
+                "https://rarible.com/token/polygon/{address}:{token}"
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Marketplace;
+    use crate::Address;
+    use std::str::FromStr;
+    use workers::etherscan::Chain;
+
+    const BAYC: &str = "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d";
+
+    #[test]
+    fn builds_urls() {
+        let address = Address::from_str(BAYC).unwrap();
+        assert_eq!(
+            Some(format!("https://opensea.io/assets/ethereum/{BAYC}/1")),
+            Marketplace::OpenSea.url(Chain::Ethereum, &address, 1)
+        );
+        assert_eq!(
+            Some(format!("https://opensea.io/assets/matic/{BAYC}/1")),
+            Marketplace::OpenSea.url(Chain::Polygon, &address, 1)
+        );
+        assert_eq!(
+            Some(format!("https://rarible.com/token/polygon/{BAYC}:1")),
+            Marketplace::Rarible.url(Chain::Polygon, &address, 1)
+        );
+    }
+
+    #[test]
+    fn omits_unsupported_chains() {
+        let address = Address::from_str(BAYC).unwrap();
+        for marketplace in [
+            Marketplace::LooksRare,
+            Marketplace::Blur,
+            Marketplace::Rarible,
+        ] {
+            assert!(marketplace.url(Chain::Ethereum, &address, 1).is_some());
+            assert_eq!(None, marketplace.url(Chain::Bsc, &address, 1));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+/// The kind of content of the `animation_url` of a token, which determines how it is rendered on the token page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Media {
+    Video,
+    Audio,
+    /// A page, rendered within a sandboxed frame.
+    Html,
+    Svg,
+    /// A 3D model (GLB or glTF), rendered via `<model-viewer>`.
+    Model,
+    /// An image, such as an animated GIF.
+    Image,
+}
+
+impl Media {
+    /// Detects the content from the media type of a data uri, or otherwise the extension of the path of the uri.
+    pub fn from_uri(uri: &str) -> Option<Media> {
+        if workers::metadata::is_data_uri(uri) {
+            let media_type = uri[5..].split([';', ',']).next()?;
+            return Media::from_content_type(media_type);
+        }
+        let path = uri.split(['?', '#']).next()?.to_ascii_lowercase();
+        let extension = path.rsplit_once('.')?.1;
+        Some(match extension {
+            "mp4" | "m4v" | "webm" | "mov" | "ogv" => Media::Video,
+            "mp3" | "wav" | "ogg" | "oga" | "flac" | "m4a" | "aac" => Media::Audio,
+            "html" | "htm" => Media::Html,
+            "svg" => Media::Svg,
+            "glb" | "gltf" => Media::Model,
+            "gif" | "png" | "jpg" | "jpeg" | "webp" | "avif" | "apng" => Media::Image,
+            _ => return None,
+        })
+    }
+
+    /// Detects the content from its media type (e.g. the `Content-Type` header of a response), ignoring any parameters.
+    pub fn from_content_type(content_type: &str) -> Option<Media> {
+        let content_type = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match content_type.as_str() {
+            "text/html" | "application/xhtml+xml" => Some(Media::Html),
+            "image/svg+xml" => Some(Media::Svg),
+            "model/gltf-binary" | "model/gltf+json" => Some(Media::Model),
+            _ => match content_type.split_once('/')?.0 {
+                "video" => Some(Media::Video),
+                "audio" => Some(Media::Audio),
+                "image" => Some(Media::Image),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// The url at which the YouTube video (e.g. `https://www.youtube.com/watch?v=<id>` or `https://youtu.be/<id>`) is
+/// embedded, via the privacy-enhanced domain which does not set cookies until played.
+pub fn youtube(url: &str) -> Option<String> {
+    let url = workers::Url::parse(url.trim()).ok()?;
+    let host = url
+        .host_str()?
+        .trim_start_matches("www.")
+        .trim_start_matches("m.");
+    let mut segments = url.path_segments()?;
+    let id = match host {
+        "youtu.be" => segments.next().map(str::to_string),
+        "youtube.com" | "youtube-nocookie.com" => match segments.next() {
+            Some("watch") => url
+                .query_pairs()
+                .find(|(key, _)| key == "v")
+                .map(|(_, id)| id.into_owned()),
+            Some("embed" | "shorts" | "live" | "v") => segments.next().map(str::to_string),
+            _ => None,
+        },
+        _ => None,
+    }?;
+    // Identifiers only ever contain url-safe base64 characters
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| format!("https://www.youtube-nocookie.com/embed/{id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{youtube, Media};
+
+    #[test]
+    fn detects_media_from_uri() {
+        assert_eq!(
+            Some(Media::Video),
+            Media::from_uri("https://ipfs.io/ipfs/Qm/1.MP4")
+        );
+        assert_eq!(
+            Some(Media::Html),
+            Media::from_uri("https://example.com/index.html?token=1#start")
+        );
+        assert_eq!(
+            Some(Media::Model),
+            Media::from_uri("ar://3U2WUoK3bq7pcFkLtmYTjGJfL3pV5PvzRr6kNcAgRFQ/1.glb")
+        );
+        assert_eq!(
+            Some(Media::Html),
+            Media::from_uri("data:text/html;base64,PGh0bWw+PC9odG1sPg==")
+        );
+        assert_eq!(
+            Some(Media::Svg),
+            Media::from_uri("data:image/svg+xml;utf8,<svg></svg>")
+        );
+        // Content served without an extension is detected via its content type instead
+        assert_eq!(
+            None,
+            Media::from_uri("https://ipfs.io/ipfs/QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq")
+        );
+    }
+
+    #[test]
+    fn detects_media_from_content_type() {
+        assert_eq!(
+            Some(Media::Html),
+            Media::from_content_type("text/html; charset=utf-8")
+        );
+        assert_eq!(Some(Media::Audio), Media::from_content_type("audio/mpeg"));
+        assert_eq!(
+            Some(Media::Model),
+            Media::from_content_type("model/gltf-binary")
+        );
+        assert_eq!(None, Media::from_content_type("application/octet-stream"));
+    }
+
+    #[test]
+    fn embeds_youtube_videos() {
+        let embed = Some("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ".to_string());
+        assert_eq!(
+            embed,
+            youtube("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42s")
+        );
+        assert_eq!(embed, youtube("https://youtu.be/dQw4w9WgXcQ"));
+        assert_eq!(embed, youtube("https://m.youtube.com/shorts/dQw4w9WgXcQ"));
+        assert_eq!(None, youtube("https://www.youtube.com/channel/UC"));
+        assert_eq!(None, youtube("https://vimeo.com/76979871"));
+        assert_eq!(None, youtube("https://youtu.be/<script>"));
+    }
+}
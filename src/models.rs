@@ -1,9 +1,10 @@
 use crate::Address;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
-use workers::etherscan::TypeExtensions;
-use workers::metadata::Metadata;
+use workers::etherscan::{Chain, TypeExtensions};
+use workers::metadata::{self, Metadata, Overrides};
 use workers::Url;
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -23,6 +24,12 @@ pub enum Collection {
         total_supply: Option<u32>,
         #[serde(rename = "lv")]
         last_viewed: Option<DateTime<Utc>>,
+        #[serde(rename = "o", default)]
+        overrides: Overrides,
+        #[serde(rename = "ps", default)]
+        position: Position,
+        #[serde(rename = "ch", default)]
+        chain: Chain,
     },
     /// Collection is sourced from url
     #[serde(rename = "u")]
@@ -37,6 +44,10 @@ pub enum Collection {
         total_supply: Option<u32>,
         #[serde(rename = "lv")]
         last_viewed: Option<DateTime<Utc>>,
+        #[serde(rename = "o", default)]
+        overrides: Overrides,
+        #[serde(rename = "ps", default)]
+        position: Position,
     },
 }
 
@@ -53,6 +64,28 @@ impl Collection {
             start_token: 0,
             total_supply,
             last_viewed: None,
+            overrides: Overrides::default(),
+            position: Position::default(),
+            chain: Chain::default(),
+        }
+    }
+
+    /// The identifier of a collection sourced from a smart contract, qualified by its chain unless on Ethereum.
+    pub fn contract_id(chain: Chain, address: &Address) -> String {
+        match chain {
+            Chain::Ethereum => TypeExtensions::format(address),
+            chain => format!("{chain}:{}", TypeExtensions::format(address)),
+        }
+    }
+
+    /// Parses a collection identifier as a (chain-qualified) contract address.
+    pub fn parse_contract(id: &str) -> Option<(Chain, Address)> {
+        match id.split_once(':') {
+            Some((chain, address)) => Some((
+                Chain::from_str(chain).ok()?,
+                Address::from_str(address).ok()?,
+            )),
+            None => Some((Chain::Ethereum, Address::from_str(id).ok()?)),
         }
     }
 
@@ -65,10 +98,15 @@ impl Collection {
 
     pub fn set_last_viewed(&mut self) {
         match self {
-            Collection::Contract { last_viewed, .. } => {
-                *last_viewed = Some(chrono::offset::Utc::now())
-            }
-            Collection::Url { last_viewed, .. } => *last_viewed = Some(chrono::offset::Utc::now()),
+            Collection::Contract { last_viewed, .. } => *last_viewed = Some(crate::clock::now()),
+            Collection::Url { last_viewed, .. } => *last_viewed = Some(crate::clock::now()),
+        }
+    }
+
+    pub fn set_start_token(&mut self, value: u32) {
+        match self {
+            Collection::Contract { start_token, .. } => *start_token = value,
+            Collection::Url { start_token, .. } => *start_token = value,
         }
     }
 
@@ -95,11 +133,18 @@ impl Collection {
 
     pub fn id(&self) -> String {
         match self {
-            Collection::Contract { address, .. } => TypeExtensions::format(address),
+            Collection::Contract { address, chain, .. } => Collection::contract_id(*chain, address),
             Collection::Url { id, .. } => id.clone(),
         }
     }
 
+    pub fn chain(&self) -> Chain {
+        match self {
+            Collection::Contract { chain, .. } => *chain,
+            Collection::Url { .. } => Chain::default(),
+        }
+    }
+
     pub fn last_viewed(&self) -> &Option<DateTime<Utc>> {
         match self {
             Collection::Contract { last_viewed, .. } => last_viewed,
@@ -114,6 +159,42 @@ impl Collection {
         }
     }
 
+    pub fn overrides(&self) -> &Overrides {
+        match self {
+            Collection::Contract { overrides, .. } => overrides,
+            Collection::Url { overrides, .. } => overrides,
+        }
+    }
+
+    pub fn set_overrides(&mut self, value: Overrides) {
+        match self {
+            Collection::Contract { overrides, .. } => *overrides = value,
+            Collection::Url { overrides, .. } => *overrides = value,
+        }
+    }
+
+    /// The position last viewed within the collection.
+    pub fn position(&self) -> &Position {
+        match self {
+            Collection::Contract { position, .. } => position,
+            Collection::Url { position, .. } => position,
+        }
+    }
+
+    pub fn set_last_page(&mut self, page: usize) {
+        match self {
+            Collection::Contract { position, .. } => position.page = Some(page),
+            Collection::Url { position, .. } => position.page = Some(page),
+        }
+    }
+
+    pub fn set_last_token(&mut self, token: u32) {
+        match self {
+            Collection::Contract { position, .. } => position.token = Some(token),
+            Collection::Url { position, .. } => position.token = Some(token),
+        }
+    }
+
     pub fn start_token(&self) -> &u32 {
         match self {
             Collection::Contract { start_token, .. } => start_token,
@@ -130,14 +211,75 @@ impl Collection {
 
     pub(crate) fn url(&self, token: u32) -> Option<String> {
         self.base_uri().as_ref().map(|base_uri| {
-            base_uri
-                .join(token.to_string().as_str())
-                .expect("unable to create token metadata request url")
-                .to_string()
+            // ERC-1155 templates contain a placeholder for the token, rather than the token being appended
+            metadata::substitute(base_uri.as_str(), token).unwrap_or_else(|| {
+                base_uri
+                    .join(token.to_string().as_str())
+                    .expect("unable to create token metadata request url")
+                    .to_string()
+            })
         })
     }
 }
 
+/// The archiving of a collection for offline viewing, with the images of its tokens downloaded once indexed.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Archive {
+    #[serde(rename = "s")]
+    pub started: DateTime<Utc>,
+    /// When every token of the collection was indexed and its image archived (or found to be unavailable).
+    #[serde(rename = "c")]
+    pub completed: Option<DateTime<Utc>>,
+    /// The tokens whose images have been archived, along with the total size of the images (in bytes).
+    #[serde(rename = "a")]
+    pub archived: BTreeSet<u32>,
+    #[serde(rename = "b")]
+    pub bytes: u64,
+    /// The tokens whose images could not be downloaded.
+    #[serde(rename = "f")]
+    pub failed: BTreeSet<u32>,
+    /// The content hashes of the metadata and image of each token archived, against which the local copies (or the
+    /// source) can later be verified.
+    #[serde(rename = "h", default)]
+    pub hashes: BTreeMap<u32, Hashes>,
+}
+
+/// The SHA-256 hashes (as hex) of the metadata and image of an archived token.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Hashes {
+    #[serde(rename = "m")]
+    pub metadata: String,
+    #[serde(rename = "i")]
+    pub image: String,
+}
+
+impl Archive {
+    pub fn start() -> Archive {
+        Archive {
+            started: crate::clock::now(),
+            completed: None,
+            archived: BTreeSet::new(),
+            bytes: 0,
+            failed: BTreeSet::new(),
+            hashes: BTreeMap::new(),
+        }
+    }
+
+    /// Whether the image of the token has been archived, or found to be unavailable.
+    pub fn contains(&self, token: u32) -> bool {
+        self.archived.contains(&token) || self.failed.contains(&token)
+    }
+}
+
+/// The page and token last viewed within a collection, so that viewing can be continued.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Position {
+    #[serde(rename = "p")]
+    pub page: Option<usize>,
+    #[serde(rename = "t")]
+    pub token: Option<u32>,
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct Token {
     #[serde(rename = "i")]
@@ -157,3 +299,72 @@ impl Token {
         }
     }
 }
+
+/// Filters the tokens of a collection to those with the specified attribute value, supplied as query parameters on
+/// the collection route.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Filter {
+    #[serde(rename = "trait")]
+    pub trait_type: String,
+    pub value: String,
+}
+
+impl Filter {
+    pub fn matches(&self, token: &Token) -> bool {
+        token.metadata.as_ref().map_or(false, |metadata| {
+            metadata.attributes.iter().any(|attribute| {
+                let (trait_type, value) = attribute.map();
+                trait_type == self.trait_type && value == self.value
+            })
+        })
+    }
+}
+
+/// The order in which the tokens of a collection are listed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Sort {
+    #[serde(rename = "b")]
+    pub by: SortBy,
+    #[serde(rename = "d")]
+    pub descending: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SortBy {
+    Id,
+    Name,
+    Rarity,
+    RecentlyViewed,
+}
+
+impl SortBy {
+    pub const ALL: [SortBy; 4] = [
+        SortBy::Id,
+        SortBy::Name,
+        SortBy::Rarity,
+        SortBy::RecentlyViewed,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortBy::Id => "Token ID",
+            SortBy::Name => "Name",
+            SortBy::Rarity => "Rarity",
+            SortBy::RecentlyViewed => "Recently Viewed",
+        }
+    }
+
+    /// The order in which tokens are initially sorted, such as the most recently viewed first.
+    pub fn sort(self) -> Sort {
+        Sort {
+            by: self,
+            descending: self == SortBy::RecentlyViewed,
+        }
+    }
+}
+
+impl Default for SortBy {
+    fn default() -> Self {
+        SortBy::Id
+    }
+}
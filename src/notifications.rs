@@ -1,5 +1,5 @@
 use bulma::toast::Animate;
-use bulma::{toast, toast::Position};
+use bulma::{toast, toast::Action, toast::Position};
 
 pub type Color = bulma::toast::Color;
 
@@ -7,10 +7,33 @@ pub(crate) fn notify(message: String, color: Option<Color>) {
     notify_extra_classes(message, color, None)
 }
 
+/// Displays a notification with an action (e.g. undo), invoking the callback should the action be clicked before the
+/// notification is dismissed.
+pub(crate) fn notify_with_action(message: String, label: &str, callback: impl FnOnce() + 'static) {
+    show(
+        message,
+        None,
+        None,
+        Some(Action {
+            label: label.to_string(),
+            callback: Box::new(callback),
+        }),
+    )
+}
+
 pub(crate) fn notify_extra_classes(
     message: String,
     color: Option<Color>,
     extra_classes: Option<String>,
+) {
+    show(message, color, extra_classes, None)
+}
+
+fn show(
+    message: String,
+    color: Option<Color>,
+    extra_classes: Option<String>,
+    action: Option<Action>,
 ) {
     toast::toast(
         message,
@@ -26,5 +49,6 @@ pub(crate) fn notify_extra_classes(
             out: "flipOutY".to_string(),
         }),
         extra_classes,
+        action,
     );
 }
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+/// The number of tokens which must have been redirected before the collection is considered to consistently redirect.
+pub const MIN_REDIRECTS: usize = 10;
+
+/// The base uri to which requests for the metadata of the collection are consistently redirected (e.g. from an api to
+/// IPFS), given the final url of each token whose request was redirected. Only determined once enough tokens have been
+/// redirected, each to the same base uri followed by its token identifier, so that the base uri of the collection can
+/// be rewritten to skip the redirect.
+pub fn target(base_uri: &str, redirects: &BTreeMap<u32, String>) -> Option<String> {
+    if redirects.len() < MIN_REDIRECTS {
+        return None;
+    }
+    let mut target = None;
+    for (token, url) in redirects.iter() {
+        let base = url.strip_suffix(&token.to_string())?;
+        match target {
+            None => target = Some(base),
+            Some(target) if target != base => return None,
+            Some(_) => {}
+        }
+    }
+    target
+        .filter(|target| *target != base_uri && target.ends_with('/'))
+        .map(|target| target.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{target, MIN_REDIRECTS};
+    use std::collections::BTreeMap;
+
+    const BASE_URI: &str = "https://api.project.com/token/";
+    const TARGET: &str = "https://ipfs.io/ipfs/QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq/";
+
+    fn redirects(count: usize) -> BTreeMap<u32, String> {
+        (1..=count as u32)
+            .map(|token| (token, format!("{TARGET}{token}")))
+            .collect()
+    }
+
+    #[test]
+    fn detects_consistent_redirects() {
+        assert_eq!(
+            Some(TARGET.to_string()),
+            target(BASE_URI, &redirects(MIN_REDIRECTS))
+        );
+        // Too few tokens redirected to be sure
+        assert_eq!(None, target(BASE_URI, &redirects(MIN_REDIRECTS - 1)));
+        // Already the base uri
+        assert_eq!(None, target(TARGET, &redirects(MIN_REDIRECTS)));
+    }
+
+    #[test]
+    fn ignores_inconsistent_redirects() {
+        let mut different = redirects(MIN_REDIRECTS);
+        different.insert(100, "https://cloudflare-ipfs.com/ipfs/Qm/100".to_string());
+        assert_eq!(None, target(BASE_URI, &different));
+
+        // Redirected to a url not ending with the token (e.g. a file extension), so the base uri cannot be rewritten
+        let mut extension = redirects(MIN_REDIRECTS);
+        extension.insert(100, format!("{TARGET}100.json"));
+        assert_eq!(None, target(BASE_URI, &extension));
+    }
+}
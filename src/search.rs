@@ -0,0 +1,111 @@
+use crate::models;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The minimum length of a (non-numeric) term, below which terms are too common to be worth indexing.
+const MIN_TERM_LENGTH: usize = 2;
+
+/// An inverted index of the terms within the metadata of the tokens of a collection: the tokens containing each term.
+pub type Index = BTreeMap<String, BTreeSet<u32>>;
+
+/// Splits the text into lower-cased, alphanumeric terms.
+pub fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| {
+            term.chars().count() >= MIN_TERM_LENGTH || term.chars().all(|c| c.is_numeric())
+        })
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+}
+
+/// Adds the name, description and attribute values of the token to the index.
+pub fn index(index: &mut Index, token: &models::Token) {
+    let metadata = match token.metadata.as_ref() {
+        Some(metadata) => metadata,
+        None => return,
+    };
+    let mut text = Vec::new();
+    text.extend(metadata.name.as_deref());
+    text.extend(metadata.description.as_deref());
+    let attributes: Vec<(String, String)> = metadata.attributes.iter().map(|a| a.map()).collect();
+    text.extend(attributes.iter().map(|(_, value)| value.as_str()));
+
+    for term in text.into_iter().flat_map(terms) {
+        index.entry(term).or_default().insert(token.id);
+    }
+}
+
+/// Removes the token from the index, along with any terms no longer contained by any token.
+pub fn unindex(index: &mut Index, token: u32) {
+    for tokens in index.values_mut() {
+        tokens.remove(&token);
+    }
+    index.retain(|_, tokens| !tokens.is_empty());
+}
+
+/// Finds the tokens containing every term of the query, with the last term matched as a prefix so that results are
+/// returned whilst typing.
+pub fn query(index: &Index, query: &str) -> BTreeSet<u32> {
+    let terms: Vec<String> = terms(query).collect();
+    let mut matches: Option<BTreeSet<u32>> = None;
+    for (i, term) in terms.iter().enumerate() {
+        let tokens: BTreeSet<u32> = if i == terms.len() - 1 {
+            index
+                .range(term.clone()..)
+                .take_while(|(t, _)| t.starts_with(term.as_str()))
+                .flat_map(|(_, tokens)| tokens.iter().copied())
+                .collect()
+        } else {
+            index.get(term).cloned().unwrap_or_default()
+        };
+        matches = Some(match matches {
+            Some(matches) => matches.intersection(&tokens).copied().collect(),
+            None => tokens,
+        });
+    }
+    matches.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{query, terms, unindex, Index};
+    use std::collections::BTreeSet;
+
+    fn index() -> Index {
+        let mut index = Index::new();
+        for (term, tokens) in [
+            ("blue", vec![1, 2]),
+            ("bluebird", vec![3]),
+            ("laser", vec![2, 3]),
+            ("eyes", vec![2]),
+        ] {
+            index.insert(term.to_string(), tokens.into_iter().collect());
+        }
+        index
+    }
+
+    #[test]
+    fn splits_terms() {
+        assert_eq!(
+            vec!["azuki", "1234", "laser", "eyes", "7"],
+            terms("Azuki #1234 - Laser-Eyes, a 7").collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn matches_all_terms() {
+        let index = index();
+        assert_eq!(BTreeSet::from([1, 2, 3]), query(&index, "blu"));
+        assert_eq!(BTreeSet::from([2]), query(&index, "Laser EYES"));
+        assert_eq!(BTreeSet::from([2]), query(&index, "blue las"));
+        assert!(query(&index, "red").is_empty());
+        assert!(query(&index, "").is_empty());
+    }
+
+    #[test]
+    fn unindexes_token() {
+        let mut index = index();
+        unindex(&mut index, 3);
+        assert!(!index.contains_key("bluebird"));
+        assert_eq!(Some(&BTreeSet::from([2])), index.get("laser"));
+    }
+}
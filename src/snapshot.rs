@@ -0,0 +1,99 @@
+use crate::{clock, models};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use workers::metadata::Metadata;
+
+/// The indexed state of a collection frozen at a point in time, as a hash of the metadata of each token, against which
+/// the current state can later be compared (e.g. to audit collections whose metadata is mutable).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    #[serde(rename = "t")]
+    pub taken: DateTime<Utc>,
+    #[serde(rename = "h")]
+    pub hashes: BTreeMap<u32, u64>,
+}
+
+/// The tokens changed, added and removed between two snapshots.
+#[derive(Debug, Default, PartialEq)]
+pub struct Diff {
+    pub changed: Vec<u32>,
+    pub added: Vec<u32>,
+    pub removed: Vec<u32>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the tokens, ignoring any without metadata.
+    pub fn take<'a>(tokens: impl IntoIterator<Item = &'a models::Token>) -> Snapshot {
+        Snapshot {
+            taken: clock::now(),
+            hashes: tokens
+                .into_iter()
+                .filter_map(|token| token.metadata.as_ref().map(|m| (token.id, hash(m))))
+                .collect(),
+        }
+    }
+
+    /// Compares the snapshot with a later snapshot.
+    pub fn compare(&self, later: &Snapshot) -> Diff {
+        let mut diff = Diff::default();
+        for (token, hash) in later.hashes.iter() {
+            match self.hashes.get(token) {
+                Some(previous) if previous != hash => diff.changed.push(*token),
+                Some(_) => {}
+                None => diff.added.push(*token),
+            }
+        }
+        diff.removed = self
+            .hashes
+            .keys()
+            .filter(|token| !later.hashes.contains_key(token))
+            .copied()
+            .collect();
+        diff
+    }
+}
+
+/// Hashes the metadata (FNV-1a), so that the hash remains stable between sessions and releases.
+fn hash(metadata: &Metadata) -> u64 {
+    let bytes = serde_json::to_vec(metadata).unwrap_or_default();
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diff, Snapshot};
+    use crate::{clock, models};
+    use workers::metadata;
+
+    fn token(id: u32, name: &str) -> models::Token {
+        let json = format!(r#"{{ "name": "{name}", "image": "ipfs://image", "attributes": [] }}"#);
+        models::Token::new(
+            id,
+            metadata::parse(&json).expect("unable to parse metadata"),
+        )
+    }
+
+    #[test]
+    fn compares_snapshots() {
+        let before = Snapshot::take(&[token(1, "One"), token(2, "Two"), token(3, "Three")]);
+        let after = Snapshot::take(&[token(1, "One"), token(2, "Deux"), token(4, "Four")]);
+        assert_eq!(
+            Diff {
+                changed: vec![2],
+                added: vec![4],
+                removed: vec![3],
+            },
+            before.compare(&after)
+        );
+        assert!(after.compare(&after).is_empty());
+    }
+}
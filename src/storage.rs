@@ -1,9 +1,28 @@
-use crate::{models, Address, Route};
-use gloo_storage::{LocalStorage, Storage};
-use indexmap::IndexSet;
+use crate::history::Visit;
+use crate::marketplace::Marketplace;
+use crate::snapshot::Snapshot as CollectionSnapshot;
+use crate::tour::Tour;
+use crate::{clock, models, search, Address, Route};
+use chrono::{DateTime, Duration, Utc};
+use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeSet, HashSet};
-use workers::etherscan::TypeExtensions;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{self, AtomicBool};
+use wasm_bindgen::{JsCast, JsValue};
+use workers::etherscan::{Cached as CachedContract, Chain, TypeExtensions};
+use workers::indexer::Cursor as IndexerCursor;
+use workers::market::{Details, Prices, TraitFloors};
+use workers::metadata::Hosts as MetadataHosts;
+use workers::rarity::{Rank, Rarity as CollectionRarity};
+
+mod cache;
+mod idb;
+mod local;
+
+pub use local::is_persistent;
+use local::LocalStorage;
 
 pub trait Get<I, T> {
     fn get(id: I) -> T;
@@ -13,6 +32,68 @@ pub trait All<T> {
     fn get() -> T;
 }
 
+/// Collections archived for offline viewing, with the image of each token stored as a blob within IndexedDB.
+pub struct Archive {}
+
+impl Archive {
+    const ARCHIVE: &'static str = "AR";
+
+    pub fn get(collection: &str) -> Option<models::Archive> {
+        LocalStorage::get(format!("{}:{collection}", Self::ARCHIVE)).ok()
+    }
+
+    pub fn store(collection: &str, archive: &models::Archive) {
+        if let Err(e) = LocalStorage::set(format!("{}:{collection}", Self::ARCHIVE), archive) {
+            log::error!("An error occurred whilst storing the archive: {:?}", e)
+        }
+    }
+
+    /// Removes the archive of the collection, along with its archived images.
+    pub async fn remove(collection: &str) {
+        LocalStorage::delete(format!("{}:{collection}", Self::ARCHIVE));
+        if let Err(e) = idb::delete_all(idb::ARCHIVE, &format!("{collection}:")).await {
+            log::error!(
+                "An error occurred whilst removing the archived images: {:?}",
+                e
+            )
+        }
+    }
+
+    pub async fn image(collection: &str, token: u32) -> Option<web_sys::Blob> {
+        match idb::get(idb::ARCHIVE, &Self::key(collection, token)).await {
+            Ok(value) => value.dyn_into().ok(),
+            Err(e) => {
+                log::error!(
+                    "An error occurred whilst loading the archived image: {:?}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Stores the image, returning whether storage is full should it not be stored.
+    pub async fn store_image(
+        collection: &str,
+        token: u32,
+        image: &web_sys::Blob,
+    ) -> Result<(), bool> {
+        idb::put(idb::ARCHIVE, &Self::key(collection, token), image)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "An error occurred whilst storing the archived image: {:?}",
+                    e
+                );
+                idb::is_quota_exceeded(&e)
+            })
+    }
+
+    fn key(collection: &str, token: u32) -> String {
+        format!("{collection}:{token}")
+    }
+}
+
 impl Get<&Address, Option<models::Collection>> for Collection {
     fn get(id: &Address) -> Option<models::Collection> {
         LocalStorage::get(format!(
@@ -49,17 +130,23 @@ impl Collection {
     const COLLECTION: &'static str = "C";
     const COLLECTIONS: &'static str = "CS";
 
+    /// The name of the collection where stored and named, otherwise its identifier.
+    pub fn name(id: &str) -> String {
+        <Collection as Get<&str, Option<models::Collection>>>::get(id)
+            .and_then(|collection| collection.name().map(|name| name.to_string()))
+            .unwrap_or_else(|| id.to_string())
+    }
+
     pub fn contains(collection: &crate::models::Collection) -> bool {
         let collection: gloo_storage::Result<models::Collection> =
             LocalStorage::get(format!("{}:{}", Self::COLLECTION, collection.id()));
         collection.is_ok()
     }
 
-    pub fn store(collection: crate::models::Collection) {
+    pub fn store(collection: &crate::models::Collection) {
         // Store individual item
         let id = collection.id();
-        if let Err(e) = LocalStorage::set(format!("{}:{id}", Self::COLLECTION), collection.clone())
-        {
+        if let Err(e) = LocalStorage::set(format!("{}:{id}", Self::COLLECTION), collection) {
             log::error!("An error occurred whilst storing the collection: {:?}", e)
         }
 
@@ -73,11 +160,681 @@ impl Collection {
     }
 }
 
+/// The contracts of collections along with the results of their calls, so that repeat visits do not request them from
+/// the api again.
+pub struct Contract {}
+
+impl Contract {
+    const CONTRACT: &'static str = "EC";
+
+    /// Gets the contract at the address on the chain, provided it was cached within the contract ttl.
+    pub fn get(chain: Chain, address: &Address) -> Option<CachedContract> {
+        LocalStorage::get(Self::key(chain, address))
+            .ok()
+            .filter(|item: &CachedItem<CachedContract>| {
+                clock::now() - item.fetched < Duration::hours(crate::config::CONTRACT_TTL)
+            })
+            .map(|item| item.data)
+    }
+
+    pub fn store(chain: Chain, address: &Address, contract: CachedContract) {
+        if let Err(e) = LocalStorage::set(
+            Self::key(chain, address),
+            CachedItem {
+                data: contract,
+                fetched: clock::now(),
+            },
+        ) {
+            log::error!("An error occurred whilst storing the contract: {:?}", e)
+        }
+    }
+
+    fn key(chain: Chain, address: &Address) -> String {
+        format!(
+            "{}:{}",
+            Self::CONTRACT,
+            models::Collection::contract_id(chain, address)
+        )
+    }
+}
+
+/// The cursor from which the indexing of a collection resumes, should it be interrupted.
+pub struct Cursor {}
+
+impl Cursor {
+    const CURSOR: &'static str = "IC";
+
+    pub fn get(collection: &str) -> Option<IndexerCursor> {
+        LocalStorage::get(format!("{}:{collection}", Self::CURSOR)).ok()
+    }
+
+    pub fn store(collection: &str, cursor: &IndexerCursor) {
+        if let Err(e) = LocalStorage::set(format!("{}:{collection}", Self::CURSOR), cursor) {
+            log::error!("An error occurred whilst storing the cursor: {:?}", e)
+        }
+    }
+
+    pub fn remove(collection: &str) {
+        LocalStorage::delete(format!("{}:{collection}", Self::CURSOR))
+    }
+}
+
+/// The data held by the app within the browser, summarised ahead of clearing it all (e.g. should it have become stale
+/// or corrupt).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Data {
+    pub collections: usize,
+    pub tokens: usize,
+    pub favorites: usize,
+    pub watched: usize,
+    pub recently_viewed: usize,
+    /// The hosts learned to require the CORS proxy, or suspended following repeated failures.
+    pub hosts: usize,
+    /// The images uploaded locally or archived for offline viewing.
+    pub images: usize,
+    pub cached_images: usize,
+    /// The number of keys within local storage, including the settings.
+    pub keys: usize,
+}
+
+impl Data {
+    pub async fn summarise() -> Data {
+        let count = |store: &'static str| async move {
+            idb::count(store)
+                .await
+                .map_err(|e| log::error!("An error occurred whilst counting the {store}: {:?}", e))
+                .unwrap_or_default() as usize
+        };
+        let hosts = Hosts::get();
+        Data {
+            collections: LocalStorage::get::<HashSet<String>>(Collection::COLLECTIONS)
+                .map_or(0, |collections| collections.len()),
+            tokens: count(idb::TOKENS).await,
+            favorites: Favorites::values().len(),
+            watched: Watchlist::values().len(),
+            recently_viewed: RecentlyViewed::history().len(),
+            hosts: hosts.cors.len() + hosts.suspended.len(),
+            images: count(idb::IMAGES).await + count(idb::ARCHIVE).await,
+            cached_images: cache::len().await.unwrap_or_default() as usize,
+            keys: LocalStorage::len(),
+        }
+    }
+
+    /// Clears every key within local storage, the indexed tokens and images within IndexedDB, and the cached images.
+    pub async fn clear() {
+        log::info!("clearing all data...");
+        LocalStorage::clear();
+        TOKENS.with(|tokens| tokens.borrow_mut().clear());
+        if let Err(e) = idb::delete_database().await {
+            log::error!("An error occurred whilst deleting the database: {:?}", e)
+        }
+        ImageCache::clear().await;
+    }
+}
+
+/// The most recent errors (and panics) of the app, retained across sessions for inclusion within bug reports.
+pub struct Errors {}
+
+impl Errors {
+    const ERRORS: &'static str = "ER";
+
+    pub fn get() -> Vec<crate::logging::Error> {
+        LocalStorage::get(Self::ERRORS).unwrap_or_default()
+    }
+
+    pub fn store(errors: &VecDeque<crate::logging::Error>) {
+        // Written to the console rather than logged, as logging the error would itself attempt to store the errors
+        if let Err(e) = LocalStorage::set(Self::ERRORS, errors) {
+            web_sys::console::error_1(
+                &format!("An error occurred whilst storing the errors: {:?}", e).into(),
+            )
+        }
+    }
+}
+
+/// The tokens marked as favorites, in the order marked.
+pub struct Favorites {}
+
+impl Favorites {
+    const FAVORITES: &'static str = "FV";
+    const COLLECTIONS: &'static str = "FVC";
+
+    pub fn values() -> IndexSet<Favorite> {
+        LocalStorage::get(Self::FAVORITES).unwrap_or_default()
+    }
+
+    pub fn contains(collection: &str, token: u32) -> bool {
+        Self::values().contains(&Favorite::new(collection, token))
+    }
+
+    /// Marks the token as a favorite, or unmarks it where already a favorite, returning whether now a favorite.
+    pub fn toggle(collection: &str, token: u32) -> bool {
+        let mut favorites = Self::values();
+        let favorite = Favorite::new(collection, token);
+        let added = !favorites.shift_remove(&favorite);
+        if added {
+            favorites.insert(favorite);
+        }
+        Self::set(&favorites);
+        added
+    }
+
+    /// Adds the tokens as favorites, returning the number not already favorites.
+    pub fn store_many(favorites: impl IntoIterator<Item = Favorite>) -> usize {
+        let mut values = Self::values();
+        let count = values.len();
+        values.extend(favorites);
+        Self::set(&values);
+        values.len() - count
+    }
+
+    fn set(favorites: &IndexSet<Favorite>) {
+        if let Err(e) = LocalStorage::set(Self::FAVORITES, favorites) {
+            log::error!("An error occurred whilst storing the favorites: {:?}", e)
+        }
+    }
+
+    /// Gets the identifiers of the collections marked as favorites, in the order marked.
+    pub fn collections() -> IndexSet<String> {
+        LocalStorage::get(Self::COLLECTIONS).unwrap_or_default()
+    }
+
+    pub fn contains_collection(collection: &str) -> bool {
+        Self::collections().contains(collection)
+    }
+
+    /// Marks the collection as a favorite, or unmarks it where already a favorite, returning whether now a favorite.
+    pub fn toggle_collection(collection: &str) -> bool {
+        let mut collections = Self::collections();
+        let added = !collections.shift_remove(collection);
+        if added {
+            collections.insert(collection.to_string());
+        }
+        if let Err(e) = LocalStorage::set(Self::COLLECTIONS, collections) {
+            log::error!(
+                "An error occurred whilst storing the favorite collections: {:?}",
+                e
+            )
+        }
+        added
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+pub struct Favorite {
+    #[serde(rename = "c")]
+    pub collection: String,
+    #[serde(rename = "t")]
+    pub token: u32,
+}
+
+impl Favorite {
+    pub fn new(collection: &str, token: u32) -> Self {
+        Self {
+            collection: collection.to_string(),
+            token,
+        }
+    }
+}
+
+/// The knowledge of hosts learned by the metadata worker (e.g. those requiring the CORS proxy), persisted so that it
+/// need not be re-discovered each session.
+pub struct Hosts {}
+
+impl Hosts {
+    const HOSTS: &'static str = "H";
+
+    pub fn get() -> MetadataHosts {
+        LocalStorage::get(Self::HOSTS).unwrap_or_default()
+    }
+
+    pub fn store(hosts: &MetadataHosts) {
+        if let Err(e) = LocalStorage::set(Self::HOSTS, hosts) {
+            log::error!("An error occurred whilst storing the hosts: {:?}", e)
+        }
+    }
+}
+
+/// Images previously displayed, held within the Cache API so that they are displayed immediately (and offline) when
+/// viewed again, rather than requested again from their gateways.
+pub struct ImageCache {}
+
+impl ImageCache {
+    pub async fn get(url: &str) -> Option<web_sys::Blob> {
+        if !Self::is_cacheable(url) {
+            return None;
+        }
+        // The Cache API is unavailable outside of secure contexts, so failures are expected
+        cache::get(url)
+            .await
+            .map_err(|e| log::trace!("unable to load the cached image: {:?}", e))
+            .ok()
+            .flatten()
+    }
+
+    /// Caches the image, which is requested again (typically from the browser cache) once displayed.
+    pub async fn store(url: &str) {
+        if !Self::is_cacheable(url) {
+            return;
+        }
+        // Images served without CORS headers cannot be read, so cannot be cached
+        if let Err(e) = cache::put(url).await {
+            log::trace!("unable to cache the image: {:?}", e)
+        }
+    }
+
+    pub async fn clear() {
+        if let Err(e) = cache::clear().await {
+            log::error!(
+                "An error occurred whilst clearing the cached images: {:?}",
+                e
+            )
+        }
+    }
+
+    /// Whether the image is requested from a url, rather than being inline or a local copy.
+    fn is_cacheable(url: &str) -> bool {
+        url.get(..4)
+            .map_or(false, |scheme| scheme.eq_ignore_ascii_case("http"))
+    }
+}
+
+/// Images uploaded locally as overrides for tokens whose images can no longer be loaded (e.g. where the gateway has
+/// gone), stored as blobs within IndexedDB.
+pub struct LocalImage {}
+
+impl LocalImage {
+    pub async fn get(collection: &str, token: u32) -> Option<web_sys::Blob> {
+        match idb::get(idb::IMAGES, &Self::key(collection, token)).await {
+            Ok(value) => value.dyn_into().ok(),
+            Err(e) => {
+                log::error!("An error occurred whilst loading the local image: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Stores the image, returning whether it was stored.
+    pub async fn store(collection: &str, token: u32, image: &web_sys::Blob) -> bool {
+        match idb::put(idb::IMAGES, &Self::key(collection, token), image).await {
+            Ok(_) => true,
+            Err(e) => {
+                log::error!("An error occurred whilst storing the local image: {:?}", e);
+                if idb::is_quota_exceeded(&e) {
+                    crate::notifications::notify(
+                        "Storage is full: the image could not be kept. Free up some space for this site within the browser to keep it.".to_string(),
+                        Some(crate::notifications::Color::Danger),
+                    )
+                }
+                false
+            }
+        }
+    }
+
+    pub async fn remove(collection: &str, token: u32) {
+        if let Err(e) = idb::delete(idb::IMAGES, &Self::key(collection, token)).await {
+            log::error!("An error occurred whilst removing the local image: {:?}", e)
+        }
+    }
+
+    fn key(collection: &str, token: u32) -> String {
+        format!("{collection}:{token}")
+    }
+}
+
+/// Corrected metadata documents for tokens whose metadata could not be parsed, stored locally as overrides so that
+/// the tokens still render whilst the upstream source remains broken.
+pub struct LocalMetadata {}
+
+impl LocalMetadata {
+    const LOCAL_METADATA: &'static str = "LM";
+
+    /// Gets the corrected metadata document of the token, as raw JSON.
+    pub fn get(collection: &str, token: u32) -> Option<String> {
+        LocalStorage::get(Self::key(collection, token)).ok()
+    }
+
+    pub fn store(collection: &str, token: u32, json: &str) {
+        if let Err(e) = LocalStorage::set(Self::key(collection, token), json) {
+            log::error!(
+                "An error occurred whilst storing the local metadata: {:?}",
+                e
+            )
+        }
+    }
+
+    pub fn remove(collection: &str, token: u32) {
+        LocalStorage::delete(Self::key(collection, token))
+    }
+
+    fn key(collection: &str, token: u32) -> String {
+        format!("{}:{collection}:{token}", Self::LOCAL_METADATA)
+    }
+}
+
+/// The level filter of each module whose logging differs from the default.
+pub struct LogFilters {}
+
+impl LogFilters {
+    const LOG_FILTERS: &'static str = "LF";
+
+    pub fn get() -> BTreeMap<String, log::LevelFilter> {
+        let filters: BTreeMap<String, String> =
+            LocalStorage::get(Self::LOG_FILTERS).unwrap_or_default();
+        filters
+            .into_iter()
+            .filter_map(|(module, level)| Some((module, level.parse().ok()?)))
+            .collect()
+    }
+
+    pub fn store(filters: &BTreeMap<String, log::LevelFilter>) {
+        let filters: BTreeMap<&String, String> = filters
+            .iter()
+            .map(|(module, level)| (module, level.to_string()))
+            .collect();
+        if let Err(e) = LocalStorage::set(Self::LOG_FILTERS, filters) {
+            log::error!("An error occurred whilst storing the log filters: {:?}", e)
+        }
+    }
+}
+
+pub struct Market {}
+
+impl Market {
+    const DETAILS: &'static str = "MD";
+    const PRICES: &'static str = "MP";
+    const TRAIT_FLOORS: &'static str = "MTF";
+
+    /// Gets the market details of the collection, provided they were fetched within the market data ttl.
+    pub fn details(collection: &str) -> Option<Details> {
+        LocalStorage::get(format!("{}:{collection}", Self::DETAILS))
+            .ok()
+            .filter(|item: &CachedItem<Details>| {
+                clock::now() - item.fetched < Duration::minutes(crate::config::MARKET_DATA_TTL)
+            })
+            .map(|item| item.data)
+    }
+
+    pub fn store_details(collection: &str, details: Details) {
+        if let Err(e) = LocalStorage::set(
+            format!("{}:{collection}", Self::DETAILS),
+            CachedItem {
+                data: details,
+                fetched: clock::now(),
+            },
+        ) {
+            log::error!("An error occurred whilst storing the details: {:?}", e)
+        }
+    }
+
+    /// Gets the prices of the collection, or of the token where specified, provided they were fetched within the market
+    /// data ttl.
+    pub fn prices(collection: &str, token: Option<u32>) -> Option<Prices> {
+        LocalStorage::get(Self::prices_key(collection, token))
+            .ok()
+            .filter(|item: &CachedItem<Prices>| {
+                clock::now() - item.fetched < Duration::minutes(crate::config::MARKET_DATA_TTL)
+            })
+            .map(|item| item.data)
+    }
+
+    pub fn store_prices(collection: &str, token: Option<u32>, prices: Prices) {
+        if let Err(e) = LocalStorage::set(
+            Self::prices_key(collection, token),
+            CachedItem {
+                data: prices,
+                fetched: clock::now(),
+            },
+        ) {
+            log::error!("An error occurred whilst storing the prices: {:?}", e)
+        }
+    }
+
+    fn prices_key(collection: &str, token: Option<u32>) -> String {
+        match token {
+            Some(token) => format!("{}:{collection}:{token}", Self::PRICES),
+            None => format!("{}:{collection}", Self::PRICES),
+        }
+    }
+
+    /// Gets the trait floor prices of the collection, provided they were fetched within the market data ttl.
+    pub fn trait_floors(collection: &str) -> Option<TraitFloors> {
+        LocalStorage::get(format!("{}:{collection}", Self::TRAIT_FLOORS))
+            .ok()
+            .filter(|item: &CachedItem<TraitFloors>| {
+                clock::now() - item.fetched < Duration::minutes(crate::config::MARKET_DATA_TTL)
+            })
+            .map(|item| item.data)
+    }
+
+    pub fn store_trait_floors(collection: &str, floors: TraitFloors) {
+        if let Err(e) = LocalStorage::set(
+            format!("{}:{collection}", Self::TRAIT_FLOORS),
+            CachedItem {
+                data: floors,
+                fetched: clock::now(),
+            },
+        ) {
+            log::error!("An error occurred whilst storing the trait floors: {:?}", e)
+        }
+    }
+}
+
+/// Data cached from an api, along with when it was fetched so that it can expire.
+#[derive(Deserialize, Serialize)]
+struct CachedItem<T> {
+    #[serde(rename = "d")]
+    data: T,
+    #[serde(rename = "f")]
+    fetched: DateTime<Utc>,
+}
+
+/// The notable collections featured within search, seeded from the configured defaults and curated via settings.
+pub struct Notable {}
+
+impl Notable {
+    const NOTABLE: &'static str = "NC";
+
+    /// Gets the identifiers of the notable collections, in display order.
+    pub fn values() -> Vec<String> {
+        LocalStorage::get(Self::NOTABLE).unwrap_or_else(|_| Self::reset())
+    }
+
+    pub fn store(collections: &[String]) {
+        if let Err(e) = LocalStorage::set(Self::NOTABLE, collections) {
+            log::error!(
+                "An error occurred whilst storing the notable collections: {:?}",
+                e
+            )
+        }
+    }
+
+    /// Restores the configured defaults, storing the details of each collection where not already stored.
+    pub fn reset() -> Vec<String> {
+        let collections: Vec<String> = crate::config::COLLECTIONS
+            .iter()
+            .map(|(name, address, base_uri, total_supply)| {
+                let collection = models::Collection::new(address, name, base_uri, *total_supply);
+                if !Collection::contains(&collection) {
+                    Collection::store(&collection);
+                }
+                collection.id()
+            })
+            .collect();
+        Self::store(&collections);
+        collections
+    }
+}
+
+pub struct Notifications {}
+
+impl Notifications {
+    const NOTIFICATIONS: &'static str = "N";
+    const MAX_ITEMS: usize = 50;
+
+    /// Gets the notifications, most recent first.
+    pub fn values() -> Vec<Notification> {
+        LocalStorage::get(Self::NOTIFICATIONS).unwrap_or_default()
+    }
+
+    pub fn unread() -> usize {
+        Self::values().iter().filter(|n| !n.read).count()
+    }
+
+    pub fn store(notification: Notification) {
+        let mut notifications = Self::values();
+        notifications.insert(0, notification);
+        notifications.truncate(Self::MAX_ITEMS);
+        Self::set(&notifications)
+    }
+
+    pub fn mark_read() {
+        let mut notifications = Self::values();
+        notifications.iter_mut().for_each(|n| n.read = true);
+        Self::set(&notifications)
+    }
+
+    /// Clears the notifications, returning those removed so that they can be restored.
+    pub fn clear() -> Vec<Notification> {
+        let notifications = Self::values();
+        LocalStorage::delete(Self::NOTIFICATIONS);
+        notifications
+    }
+
+    /// Restores previously cleared notifications, after any raised since.
+    pub fn restore(mut cleared: Vec<Notification>) {
+        let mut notifications = Self::values();
+        notifications.append(&mut cleared);
+        notifications.truncate(Self::MAX_ITEMS);
+        Self::set(&notifications)
+    }
+
+    fn set(notifications: &[Notification]) {
+        if let Err(e) = LocalStorage::set(Self::NOTIFICATIONS, notifications) {
+            log::error!(
+                "An error occurred whilst storing the notifications: {:?}",
+                e
+            )
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct Notification {
+    #[serde(rename = "m")]
+    pub message: String,
+    #[serde(rename = "r")]
+    pub route: Option<Route>,
+    #[serde(rename = "t")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "rd")]
+    pub read: bool,
+}
+
+impl Notification {
+    pub fn new(message: String, route: Option<Route>) -> Self {
+        Self {
+            message,
+            route,
+            timestamp: clock::now(),
+            read: false,
+        }
+    }
+}
+
+pub struct Preferences {}
+
+impl Preferences {
+    const PREFERENCES: &'static str = "P";
+
+    /// Gets the view preferences for the collection, falling back to defaults when none have been stored.
+    pub fn get(collection: &str) -> ViewPreferences {
+        LocalStorage::get(format!("{}:{collection}", Self::PREFERENCES)).unwrap_or_default()
+    }
+
+    pub fn store(collection: &str, preferences: &ViewPreferences) {
+        if let Err(e) =
+            LocalStorage::set(format!("{}:{collection}", Self::PREFERENCES), preferences)
+        {
+            log::error!("An error occurred whilst storing the preferences: {:?}", e)
+        }
+    }
+}
+
+/// The preferences used when viewing a collection.
+#[derive(Default, Deserialize, Serialize)]
+pub struct ViewPreferences {
+    #[serde(rename = "f", default)]
+    pub filter: Option<models::Filter>,
+    #[serde(rename = "s", default)]
+    pub sort: models::Sort,
+}
+
+pub struct Rarity {}
+
+impl Rarity {
+    const RARITY: &'static str = "R";
+
+    pub fn get(collection: &str) -> Option<CollectionRarity> {
+        LocalStorage::get(format!("{}:{collection}", Self::RARITY)).ok()
+    }
+
+    /// Returns the requested page of ranked tokens, along with the total number of ranked tokens.
+    pub fn page(
+        collection: &str,
+        page: usize,
+        page_size: usize,
+    ) -> (Vec<(Rank, Option<models::Token>)>, usize) {
+        Rarity::get(collection).map_or((Vec::new(), 0), |rarity| {
+            (
+                rarity
+                    .ranks
+                    .iter()
+                    .skip(page * page_size)
+                    .take(page_size)
+                    .map(|rank| (rank.clone(), Token::get(collection, rank.token)))
+                    .collect(),
+                rarity.ranks.len(),
+            )
+        })
+    }
+
+    /// The rank of each ranked token.
+    pub fn ranks(collection: &str) -> HashMap<u32, u32> {
+        Rarity::get(collection).map_or(HashMap::new(), |rarity| {
+            rarity
+                .ranks
+                .iter()
+                .map(|rank| (rank.token, rank.rank))
+                .collect()
+        })
+    }
+
+    /// The rank of the token, along with the total number of ranked tokens.
+    pub fn rank(collection: &str, token: u32) -> Option<(Rank, usize)> {
+        let rarity = Rarity::get(collection)?;
+        let rank = rarity
+            .ranks
+            .iter()
+            .find(|rank| rank.token == token)?
+            .clone();
+        Some((rank, rarity.ranks.len()))
+    }
+
+    pub fn store(collection: &str, rarity: &CollectionRarity) {
+        if let Err(e) = LocalStorage::set(format!("{}:{collection}", Self::RARITY), rarity) {
+            log::error!("An error occurred whilst storing the rarity: {:?}", e)
+        }
+    }
+}
+
 pub struct RecentlyViewed {}
 
 impl RecentlyViewed {
     const STORAGE_KEY: &'static str = "RV";
+    const HISTORY: &'static str = "RVH";
     const MAX_ITEMS: usize = 10;
+    const MAX_VISITS: usize = 1_000;
 
     fn data() -> gloo_storage::Result<IndexSet<RecentlyViewedItem>> {
         LocalStorage::get(Self::STORAGE_KEY)
@@ -101,6 +858,70 @@ impl RecentlyViewed {
     pub fn values() -> Option<IndexSet<RecentlyViewedItem>> {
         Self::data().ok()
     }
+
+    /// Clears the recently viewed items, returning those removed so that they can be restored.
+    pub fn clear() -> IndexSet<RecentlyViewedItem> {
+        let data = Self::data().unwrap_or_default();
+        LocalStorage::delete(Self::STORAGE_KEY);
+        data
+    }
+
+    /// Restores previously cleared items, preceding any viewed since.
+    pub fn restore(cleared: IndexSet<RecentlyViewedItem>) {
+        let mut data = cleared;
+        for item in Self::data().unwrap_or_default() {
+            data.shift_remove(&item);
+            data.insert(item);
+        }
+        while data.len() > Self::MAX_ITEMS {
+            data.shift_remove_index(0);
+        }
+        if let Err(e) = LocalStorage::set(Self::STORAGE_KEY, data) {
+            log::error!("an error occurred whilst storing the item: {:?}", e)
+        }
+    }
+
+    /// Gets the visits within the history, in the order visited.
+    pub fn history() -> Vec<Visit> {
+        LocalStorage::get(Self::HISTORY).unwrap_or_default()
+    }
+
+    /// Records a visit within the history, unless the token is already the last visited. Visits older than the
+    /// configured number of days are removed.
+    pub fn record(visit: Visit) {
+        let mut visits = Self::history();
+        if visits.last().map_or(false, |last| {
+            last.collection == visit.collection && last.token == visit.token
+        }) {
+            return;
+        }
+        let expired = visit.viewed - Duration::days(crate::config::HISTORY_DAYS);
+        visits.retain(|visit| visit.viewed > expired);
+        visits.push(visit);
+        if visits.len() > Self::MAX_VISITS {
+            visits.drain(..visits.len() - Self::MAX_VISITS);
+        }
+        Self::set_history(&visits)
+    }
+
+    /// Clears the history, returning the visits removed so that they can be restored.
+    pub fn clear_history() -> Vec<Visit> {
+        let visits = Self::history();
+        LocalStorage::delete(Self::HISTORY);
+        visits
+    }
+
+    /// Restores previously cleared visits, preceding any visited since.
+    pub fn restore_history(mut cleared: Vec<Visit>) {
+        cleared.extend(Self::history());
+        Self::set_history(&cleared)
+    }
+
+    fn set_history(visits: &[Visit]) {
+        if let Err(e) = LocalStorage::set(Self::HISTORY, visits) {
+            log::error!("An error occurred whilst storing the history: {:?}", e)
+        }
+    }
 }
 
 #[derive(Eq, Hash, PartialEq, Deserialize, Serialize)]
@@ -110,55 +931,871 @@ pub struct RecentlyViewedItem {
     pub route: Route,
 }
 
+/// The settings of the app.
+pub struct Settings {}
+
+impl Settings {
+    const SETTINGS: &'static str = "S";
+
+    pub fn get() -> AppSettings {
+        LocalStorage::get(Self::SETTINGS).unwrap_or_default()
+    }
+
+    pub fn store(settings: &AppSettings) {
+        if let Err(e) = LocalStorage::set(Self::SETTINGS, settings) {
+            log::error!("An error occurred whilst storing the settings: {:?}", e)
+        }
+    }
+}
+
+#[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+pub struct AppSettings {
+    #[serde(rename = "n", default)]
+    pub navigation: Navigation,
+    #[serde(rename = "sp", default)]
+    pub start_page: StartPage,
+    /// Whether shared links use the hash-based format (e.g. `/#/c/0x...`), which opens on any host without relying on
+    /// a fallback to the app.
+    #[serde(rename = "hl", default)]
+    pub hash_links: bool,
+    /// The url template of an image proxy, through which token images are requested at the width displayed.
+    #[serde(rename = "ip", default)]
+    pub image_proxy: Option<String>,
+    /// Whether SVG images are rendered as (sanitised) objects on the token page, loading any external fonts they rely
+    /// upon, rather than as images.
+    #[serde(rename = "so", default)]
+    pub svg_object: bool,
+    /// The number of metadata requests which may be made whilst indexing collections.
+    #[serde(rename = "b", default)]
+    pub budget: workers::indexer::Budget,
+    /// An etherscan.io API key, used whenever no key has been supplied for the session.
+    #[serde(rename = "ak", default)]
+    pub api_key: Option<String>,
+    /// A market data API key, with which prices are requested.
+    #[serde(rename = "mk", default)]
+    pub market_api_key: Option<String>,
+    /// The CORS proxy through which requests failing due to CORS are retried, in place of the default.
+    #[serde(rename = "cp", default)]
+    pub cors_proxy: Option<String>,
+    /// The IPFS gateway host (e.g. `cloudflare-ipfs.com`) to which IPFS uris are converted, in place of the default.
+    #[serde(rename = "ig", default)]
+    pub ipfs_gateway: Option<String>,
+    /// The number of tokens listed per page, in place of the default.
+    #[serde(rename = "ps", default)]
+    pub page_size: Option<usize>,
+    #[serde(rename = "t", default)]
+    pub theme: Theme,
+    #[serde(rename = "v", default)]
+    pub video: VideoSettings,
+    /// Whether further tokens of a collection are loaded whilst scrolling, rather than paged through.
+    #[serde(rename = "is", default)]
+    pub infinite_scroll: bool,
+    /// The marketplaces whose links are hidden from the token page.
+    #[serde(rename = "hm", default)]
+    pub hidden_marketplaces: BTreeSet<Marketplace>,
+    /// Whether data usage is reduced regardless of the browser, by listing fewer tokens per page, not prefetching
+    /// neighbouring tokens and only indexing collections on request.
+    #[serde(rename = "ds", default)]
+    pub data_saver: bool,
+    /// How the tokens of a collection are laid out.
+    #[serde(rename = "l", default)]
+    pub layout: Layout,
+    #[serde(rename = "pf", default)]
+    pub prefetch: Prefetch,
+}
+
+impl AppSettings {
+    /// The number of tokens listed per page, reduced whilst saving data.
+    pub fn page_size(&self) -> usize {
+        let page_size = self.page_size.unwrap_or(crate::config::PAGE_SIZE);
+        match self.saving_data() {
+            true => page_size.min(crate::config::DATA_SAVER_PAGE_SIZE),
+            false => page_size,
+        }
+    }
+
+    /// Whether data usage is reduced, either as configured or as requested by the browser.
+    pub fn saving_data(&self) -> bool {
+        self.data_saver || crate::config::save_data()
+    }
+
+    /// Whether the neighbouring tokens are prefetched whilst viewing a token, which they never are whilst saving data.
+    pub fn prefetches(&self) -> bool {
+        !self.saving_data()
+            && match self.prefetch {
+                Prefetch::Always => true,
+                Prefetch::Unmetered => !crate::config::metered(),
+                Prefetch::Never => false,
+            }
+    }
+}
+
+/// How the video (`animation_url`) of a token is played on the token page. Grids only ever show the poster image.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Serialize)]
+pub struct VideoSettings {
+    #[serde(rename = "a", default)]
+    pub autoplay: bool,
+    #[serde(rename = "m", default)]
+    pub muted: bool,
+    #[serde(rename = "l", default)]
+    pub looped: bool,
+}
+
+/// The colour scheme of the app.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Theme {
+    /// Follows the preference of the operating system.
+    System,
+    Light,
+    Dark,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::Light
+    }
+}
+
+/// How the tokens of a collection are laid out: as a grid of cards of a size, or as a list.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Layout {
+    Small,
+    Medium,
+    Large,
+    /// A table of the name and key attributes of each token.
+    List,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 4] = [Layout::Small, Layout::Medium, Layout::Large, Layout::List];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Layout::Small => "Small",
+            Layout::Medium => "Medium",
+            Layout::Large => "Large",
+            Layout::List => "List",
+        }
+    }
+
+    /// The class of the column of each card within the grid.
+    pub fn column(&self) -> &'static str {
+        match self {
+            Layout::Small => "is-2",
+            Layout::Medium | Layout::List => "is-one-fifth",
+            Layout::Large => "is-one-third",
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::Medium
+    }
+}
+
+/// The page at which a previously viewed collection is opened.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum StartPage {
+    First,
+    /// The page last viewed, where remembered.
+    Last,
+}
+
+impl Default for StartPage {
+    fn default() -> Self {
+        StartPage::First
+    }
+}
+
+/// When the neighbouring tokens (metadata and images) are prefetched whilst viewing a token, so that moving to the
+/// previous/next token is immediate.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Prefetch {
+    Always,
+    /// Only whilst on a connection which is not metered (e.g. cellular).
+    Unmetered,
+    Never,
+}
+
+impl Default for Prefetch {
+    fn default() -> Self {
+        Prefetch::Unmetered
+    }
+}
+
+/// How moving to the previous/next token is recorded within the browser history.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub enum Navigation {
+    /// Each token is added to the history.
+    Push,
+    /// The current token is replaced, so that the history returns to wherever the tokens were first navigated from.
+    Replace,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Navigation::Replace
+    }
+}
+
+/// The snapshot of the indexed state of each collection, against which the current state can be compared.
+pub struct Snapshot {}
+
+impl Snapshot {
+    const SNAPSHOT: &'static str = "SN";
+
+    pub fn get(collection: &str) -> Option<CollectionSnapshot> {
+        LocalStorage::get(format!("{}:{collection}", Self::SNAPSHOT)).ok()
+    }
+
+    pub fn store(collection: &str, snapshot: &CollectionSnapshot) {
+        if let Err(e) = LocalStorage::set(format!("{}:{collection}", Self::SNAPSHOT), snapshot) {
+            log::error!("An error occurred whilst storing the snapshot: {:?}", e)
+        }
+    }
+}
+
+/// The final url of tokens whose metadata requests were redirected, by which a collection consistently redirecting
+/// elsewhere is detected.
+pub struct Redirects {}
+
+impl Redirects {
+    const REDIRECTS: &'static str = "RD";
+    /// The maximum number of redirects retained per collection, being more than enough to detect consistency.
+    const MAX_ITEMS: usize = 100;
+
+    pub fn get(collection: &str) -> BTreeMap<u32, String> {
+        LocalStorage::get(format!("{}:{collection}", Self::REDIRECTS)).unwrap_or_default()
+    }
+
+    pub fn store(collection: &str, redirects: impl IntoIterator<Item = (u32, String)>) {
+        let mut data = Self::get(collection);
+        for (token, url) in redirects {
+            if data.len() < Self::MAX_ITEMS || data.contains_key(&token) {
+                data.insert(token, url);
+            }
+        }
+        if let Err(e) = LocalStorage::set(format!("{}:{collection}", Self::REDIRECTS), data) {
+            log::error!("An error occurred whilst storing the redirects: {:?}", e)
+        }
+    }
+
+    pub fn clear(collection: &str) {
+        LocalStorage::delete(format!("{}:{collection}", Self::REDIRECTS))
+    }
+}
+
 pub struct Token {}
 
+/// The trait index of a collection: the tokens having each value of each trait.
+pub type Traits = BTreeMap<String, BTreeMap<String, BTreeSet<u32>>>;
+
+/// The indexed tokens of a collection, loaded from IndexedDB, along with the indexes derived from them.
+#[derive(Default)]
+struct Tokens {
+    loaded: bool,
+    tokens: BTreeMap<u32, models::Token>,
+    traits: Traits,
+    search: search::Index,
+}
+
+impl Tokens {
+    fn insert(&mut self, token: models::Token) {
+        // Tokens stored again (e.g. refreshed) may no longer have the same attributes
+        if self.tokens.contains_key(&token.id) {
+            Token::unindex(&mut self.traits, token.id);
+            search::unindex(&mut self.search, token.id);
+        }
+        Token::index(&mut self.traits, &token);
+        search::index(&mut self.search, &token);
+        self.tokens.insert(token.id, token);
+    }
+}
+
+thread_local! {
+    static TOKENS: RefCell<HashMap<String, Tokens>> = RefCell::new(HashMap::new());
+}
+
+/// Whether the user has been notified that the storage quota has been exceeded, so that they are only notified once.
+static QUOTA_EXCEEDED: AtomicBool = AtomicBool::new(false);
+
 impl Token {
+    // Keys of tokens previously stored within local storage, migrated to IndexedDB on load
     const TOKEN: &'static str = "T";
     const COLLECTION_TOKENS: &'static str = "CT";
+    const TRAITS: &'static str = "TI";
+    const SEARCH: &'static str = "SI";
+
+    /// Loads the indexed tokens of the collection from IndexedDB, migrating any tokens previously stored within local
+    /// storage. Must be awaited before the tokens of the collection are accessed, with subsequent calls returning
+    /// immediately.
+    pub async fn load(collection: &str) {
+        if Token::loaded(collection) {
+            return;
+        }
 
-    pub fn page(collection: &str, page: usize, page_size: usize) -> (Vec<models::Token>, usize) {
-        let tokens = Token::collection(collection);
-        (
-            tokens
+        let started = workers::clock::now();
+        let mut tokens = Token::legacy(collection);
+        if !tokens.is_empty() {
+            log::info!(collection = collection; "migrating {} tokens to IndexedDB...", tokens.len());
+            match idb::put_all(idb::TOKENS, Token::entries(collection, tokens.iter())).await {
+                Ok(()) => Token::remove_legacy(collection, tokens.iter().map(|token| token.id)),
+                Err(e) => Token::failed(e),
+            }
+        }
+        match idb::get_all(idb::TOKENS, &format!("{collection}:")).await {
+            Ok(values) => tokens.extend(values.iter().filter_map(|value| binary::decode(value))),
+            Err(e) => log::error!("An error occurred whilst loading the tokens: {:?}", e),
+        }
+
+        TOKENS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let cached = cache.entry(collection.to_string()).or_default();
+            if cached.loaded {
+                return;
+            }
+            for token in tokens {
+                // Tokens stored whilst loading are more recent
+                if !cached.tokens.contains_key(&token.id) {
+                    cached.insert(token);
+                }
+            }
+            cached.loaded = true;
+        });
+        log::debug!(collection = collection, duration = workers::clock::now() - started;
+            "loaded {} tokens", Token::count(collection));
+    }
+
+    /// Whether the tokens of the collection have been loaded.
+    pub fn loaded(collection: &str) -> bool {
+        TOKENS.with(|cache| {
+            cache
+                .borrow()
+                .get(collection)
+                .map_or(false, |cached| cached.loaded)
+        })
+    }
+
+    fn with<R>(collection: &str, f: impl FnOnce(&Tokens) -> R) -> R {
+        TOKENS.with(|cache| match cache.borrow().get(collection) {
+            Some(cached) => f(cached),
+            None => f(&Tokens::default()),
+        })
+    }
+
+    /// Returns the requested page of tokens in the order specified, along with the total number of tokens.
+    pub fn page(
+        collection: &str,
+        sort: models::Sort,
+        page: usize,
+        page_size: usize,
+    ) -> (Vec<models::Token>, usize) {
+        let ranks = Token::ranks(collection, sort);
+        Token::with(collection, |cached| {
+            (
+                Token::ordered(cached.tokens.values(), sort, &ranks)
+                    .skip(page * page_size)
+                    .take(page_size)
+                    .cloned()
+                    .collect(),
+                cached.tokens.len(),
+            )
+        })
+    }
+
+    /// Returns the indexed tokens within the range of token identifiers.
+    pub fn range(collection: &str, range: std::ops::Range<u32>) -> Vec<models::Token> {
+        Token::with(collection, |cached| {
+            cached
+                .tokens
+                .range(range)
+                .map(|(_, token)| token.clone())
+                .collect()
+        })
+    }
+
+    /// Returns the requested page of tokens matching the filter in the order specified, along with the total number of
+    /// matching tokens.
+    pub fn filter(
+        collection: &str,
+        filter: &models::Filter,
+        sort: models::Sort,
+        page: usize,
+        page_size: usize,
+    ) -> (Vec<models::Token>, usize) {
+        let ranks = Token::ranks(collection, sort);
+        Token::with(collection, |cached| {
+            let tokens = match cached
+                .traits
+                .get(&filter.trait_type)
+                .and_then(|values| values.get(&filter.value))
+            {
+                Some(tokens) => tokens,
+                None => return (Vec::new(), 0),
+            };
+            let matching = tokens
                 .iter()
-                .skip(page * page_size)
-                .take(page_size)
-                .map(|token| Token::get(collection, *token))
-                .filter(|t| t.is_some())
-                .map(|t| t.unwrap())
-                .collect(),
-            tokens.len(),
-        )
+                .filter_map(move |token| cached.tokens.get(token));
+            (
+                Token::ordered(matching, sort, &ranks)
+                    .skip(page * page_size)
+                    .take(page_size)
+                    .cloned()
+                    .collect(),
+                tokens.len(),
+            )
+        })
     }
 
-    fn collection(collection: &str) -> BTreeSet<u32> {
-        LocalStorage::get(format!("{}:{collection}", Self::COLLECTION_TOKENS))
-            .unwrap_or_else(|_| BTreeSet::new())
+    /// Orders the tokens (supplied in token identifier order) by reference, so that only those paged are cloned. Tokens
+    /// lacking the value by which they are sorted (e.g. unnamed, unranked or never viewed) are listed last.
+    fn ordered<'a>(
+        tokens: impl DoubleEndedIterator<Item = &'a models::Token> + 'a,
+        sort: models::Sort,
+        ranks: &HashMap<u32, u32>,
+    ) -> Box<dyn Iterator<Item = &'a models::Token> + 'a> {
+        fn by<'a, K: Ord>(
+            tokens: impl Iterator<Item = &'a models::Token>,
+            descending: bool,
+            key: impl Fn(&models::Token) -> Option<K>,
+        ) -> Vec<&'a models::Token> {
+            let mut keyed: Vec<(Option<K>, &models::Token)> =
+                tokens.map(|token| (key(token), token)).collect();
+            keyed.sort_by(|(a, x), (b, y)| {
+                match (a, b) {
+                    (Some(a), Some(b)) if descending => b.cmp(a),
+                    (Some(a), Some(b)) => a.cmp(b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+                .then(x.id.cmp(&y.id))
+            });
+            keyed.into_iter().map(|(_, token)| token).collect()
+        }
+
+        let descending = sort.descending;
+        match sort.by {
+            models::SortBy::Id if descending => Box::new(tokens.rev()),
+            models::SortBy::Id => Box::new(tokens),
+            models::SortBy::Name => Box::new(
+                by(tokens, descending, |token| {
+                    token
+                        .metadata
+                        .as_ref()
+                        .and_then(|metadata| metadata.name.as_ref())
+                        .map(|name| name.to_lowercase())
+                })
+                .into_iter(),
+            ),
+            models::SortBy::Rarity => {
+                Box::new(by(tokens, descending, |token| ranks.get(&token.id).copied()).into_iter())
+            }
+            models::SortBy::RecentlyViewed => {
+                Box::new(by(tokens, descending, |token| token.last_viewed).into_iter())
+            }
+        }
     }
 
-    pub fn get(collection: &str, token: u32) -> Option<models::Token> {
-        LocalStorage::get(format!("{}:{collection}:{token}", Self::TOKEN)).ok()
+    /// The ranks of the tokens of the collection, where sorted by rarity.
+    fn ranks(collection: &str, sort: models::Sort) -> HashMap<u32, u32> {
+        match sort.by {
+            models::SortBy::Rarity => Rarity::ranks(collection),
+            _ => HashMap::new(),
+        }
+    }
+
+    /// Returns the trait index of the collection.
+    pub fn traits(collection: &str) -> Traits {
+        Token::with(collection, |cached| cached.traits.clone())
+    }
+
+    /// Returns the search index of the collection.
+    pub fn search(collection: &str) -> search::Index {
+        Token::with(collection, |cached| cached.search.clone())
     }
 
-    pub fn store(collection: &str, token: models::Token) -> usize {
-        let id = token.id;
-        if let Err(e) = LocalStorage::set(format!("{}:{collection}:{}", Self::TOKEN, id), token) {
-            log::error!("An error occurred whilst storing the token: {:?}", e)
+    /// Adds the attributes of the token to the trait index.
+    fn index(traits: &mut Traits, token: &models::Token) {
+        if let Some(metadata) = token.metadata.as_ref() {
+            for attribute in &metadata.attributes {
+                let (trait_type, value) = attribute.map();
+                traits
+                    .entry(trait_type)
+                    .or_default()
+                    .entry(value)
+                    .or_default()
+                    .insert(token.id);
+            }
         }
+    }
 
-        // Add to collection
-        let mut collection_tokens = Token::collection(collection);
-        collection_tokens.insert(id);
-        let total = collection_tokens.len();
-        if let Err(e) = LocalStorage::set(
-            format!("{}:{collection}", Self::COLLECTION_TOKENS),
-            collection_tokens,
-        ) {
-            log::error!(
-                "An error occurred whilst storing the collection tokens: {:?}",
-                e
+    /// Removes the token from the trait index, along with any values no longer held by any token.
+    fn unindex(traits: &mut Traits, token: u32) {
+        for values in traits.values_mut() {
+            for tokens in values.values_mut() {
+                tokens.remove(&token);
+            }
+            values.retain(|_, tokens| !tokens.is_empty());
+        }
+        traits.retain(|_, values| !values.is_empty());
+    }
+
+    pub fn all(collection: &str) -> Vec<models::Token> {
+        Token::with(collection, |cached| {
+            cached.tokens.values().cloned().collect()
+        })
+    }
+
+    /// Returns the identifiers of the indexed tokens of the collection.
+    pub fn ids(collection: &str) -> Vec<u32> {
+        Token::with(collection, |cached| cached.tokens.keys().copied().collect())
+    }
+
+    pub fn count(collection: &str) -> usize {
+        Token::with(collection, |cached| cached.tokens.len())
+    }
+
+    /// Returns the (one-based) position of the token amongst the indexed tokens of the collection, along with the
+    /// total number of indexed tokens.
+    pub fn position(collection: &str, token: u32) -> Option<(usize, usize)> {
+        Token::with(collection, |cached| {
+            cached.tokens.contains_key(&token).then(|| {
+                (
+                    cached.tokens.range(..token).count() + 1,
+                    cached.tokens.len(),
+                )
+            })
+        })
+    }
+
+    pub fn get(collection: &str, token: u32) -> Option<models::Token> {
+        Token::with(collection, |cached| cached.tokens.get(&token).cloned())
+    }
+
+    pub fn store(collection: &str, token: &models::Token) -> usize {
+        Token::store_many(collection, [token])
+    }
+
+    /// Stores the tokens, persisting the whole batch within a single transaction rather than per token. Returns the
+    /// total number of tokens stored for the collection.
+    pub fn store_many<'a>(
+        collection: &str,
+        tokens: impl IntoIterator<Item = &'a models::Token>,
+    ) -> usize {
+        let tokens: Vec<&models::Token> = tokens.into_iter().collect();
+        let entries = Token::entries(collection, tokens.iter().copied());
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = idb::put_all(idb::TOKENS, entries).await {
+                Token::failed(e)
+            }
+        });
+
+        TOKENS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let cached = cache.entry(collection.to_string()).or_default();
+            for token in tokens {
+                cached.insert(token.clone());
+            }
+            cached.tokens.len()
+        })
+    }
+
+    /// Encodes the tokens as IndexedDB entries, keyed by collection and token identifier.
+    fn entries<'a>(
+        collection: &str,
+        tokens: impl IntoIterator<Item = &'a models::Token>,
+    ) -> Vec<(String, String)> {
+        tokens
+            .into_iter()
+            .filter_map(|token| match binary::encode(token) {
+                Some(value) => Some((format!("{collection}:{}", token.id), value)),
+                None => {
+                    log::error!("An error occurred whilst encoding the token");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Logs the failure to persist tokens, notifying the user (once) should the storage quota have been exceeded.
+    fn failed(e: JsValue) {
+        if !idb::is_quota_exceeded(&e) {
+            log::error!("An error occurred whilst storing the tokens: {:?}", e);
+            return;
+        }
+        log::error!("The storage quota has been exceeded whilst storing the tokens");
+        if !QUOTA_EXCEEDED.swap(true, atomic::Ordering::Relaxed) {
+            crate::notifications::notify(
+                "Storage is full: newly indexed tokens will not be kept once the page is closed. Free up some space for this site within the browser to keep them.".to_string(),
+                Some(crate::notifications::Color::Danger),
             )
         }
-        total
+    }
+
+    /// Gets the tokens of the collection previously stored within local storage.
+    fn legacy(collection: &str) -> Vec<models::Token> {
+        let tokens: BTreeSet<u32> =
+            LocalStorage::get(format!("{}:{collection}", Self::COLLECTION_TOKENS))
+                .unwrap_or_else(|_| BTreeSet::new());
+        tokens
+            .into_iter()
+            .filter_map(|token| {
+                let key = format!("{}:{collection}:{token}", Self::TOKEN);
+                let value = LocalStorage::get_raw(&key)?;
+                // Tokens were previously stored as JSON, prior to the binary format
+                binary::decode(&value).or_else(|| serde_json::from_str(&value).ok())
+            })
+            .collect()
+    }
+
+    /// Removes the migrated tokens of the collection from local storage, along with the indexes stored alongside.
+    fn remove_legacy(collection: &str, tokens: impl Iterator<Item = u32>) {
+        for token in tokens {
+            LocalStorage::delete(format!("{}:{collection}:{token}", Self::TOKEN));
+        }
+        for key in [Self::COLLECTION_TOKENS, Self::TRAITS, Self::SEARCH] {
+            LocalStorage::delete(format!("{key}:{collection}"));
+        }
+    }
+}
+
+/// The tours saved, along with any tour being recorded.
+pub struct Tours {}
+
+impl Tours {
+    const TOURS: &'static str = "TR";
+    const RECORDING: &'static str = "TRR";
+
+    pub fn get() -> Vec<Tour> {
+        LocalStorage::get(Self::TOURS).unwrap_or_default()
+    }
+
+    /// Stores the tour, replacing any saved with the same name.
+    pub fn store(tour: Tour) {
+        let mut tours = Self::get();
+        match tours.iter_mut().find(|t| t.name == tour.name) {
+            Some(existing) => *existing = tour,
+            None => tours.push(tour),
+        }
+        Self::set(&tours)
+    }
+
+    /// Removes the tour, returning it so that it can be restored.
+    pub fn remove(name: &str) -> Option<Tour> {
+        let mut tours = Self::get();
+        let index = tours.iter().position(|tour| tour.name == name)?;
+        let removed = tours.remove(index);
+        Self::set(&tours);
+        Some(removed)
+    }
+
+    fn set(tours: &[Tour]) {
+        if let Err(e) = LocalStorage::set(Self::TOURS, tours) {
+            log::error!("An error occurred whilst storing the tours: {:?}", e)
+        }
+    }
+
+    /// The tour being recorded, if any.
+    pub fn recording() -> Option<Tour> {
+        LocalStorage::get(Self::RECORDING).ok()
+    }
+
+    /// Starts recording a tour, with each token subsequently viewed added as a stop.
+    pub fn start(name: String) {
+        if let Err(e) = LocalStorage::set(Self::RECORDING, Tour::new(name)) {
+            log::error!("An error occurred whilst storing the recording: {:?}", e)
+        }
+    }
+
+    /// Adds the token to the tour being recorded, if any.
+    pub fn record(collection: &str, token: u32) {
+        if let Some(mut tour) = Self::recording() {
+            if tour.record(collection, token) {
+                if let Err(e) = LocalStorage::set(Self::RECORDING, tour) {
+                    log::error!("An error occurred whilst storing the recording: {:?}", e)
+                }
+            }
+        }
+    }
+
+    /// Stops recording, saving the tour recorded where it has any stops.
+    pub fn stop() -> Option<Tour> {
+        let tour = Self::recording()?;
+        LocalStorage::delete(Self::RECORDING);
+        if tour.stops.is_empty() {
+            return None;
+        }
+        Self::store(tour.clone());
+        Some(tour)
+    }
+}
+
+pub struct Watchlist {}
+
+impl Watchlist {
+    const WATCHLIST: &'static str = "W";
+
+    /// Gets the watched collections, along with the values last observed for each.
+    pub fn values() -> IndexMap<String, Watched> {
+        LocalStorage::get(Self::WATCHLIST).unwrap_or_default()
+    }
+
+    pub fn contains(collection: &str) -> bool {
+        Self::values().contains_key(collection)
+    }
+
+    pub fn get(collection: &str) -> Option<Watched> {
+        Self::values().remove(collection)
+    }
+
+    /// Adds the collection to the watchlist, or updates the values last observed when already watched.
+    pub fn store(collection: &str, watched: Watched) {
+        let mut watchlist = Self::values();
+        watchlist.insert(collection.to_string(), watched);
+        Self::set(&watchlist)
+    }
+
+    /// Removes the collection from the watchlist, returning its values so that it can be restored.
+    pub fn remove(collection: &str) -> Option<Watched> {
+        let mut watchlist = Self::values();
+        let watched = watchlist.shift_remove(collection);
+        Self::set(&watchlist);
+        watched
+    }
+
+    fn set(watchlist: &IndexMap<String, Watched>) {
+        if let Err(e) = LocalStorage::set(Self::WATCHLIST, watchlist) {
+            log::error!("An error occurred whilst storing the watchlist: {:?}", e)
+        }
+    }
+}
+
+/// The values last observed for a watched collection.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Watched {
+    #[serde(rename = "ts")]
+    pub total_supply: Option<u32>,
+    #[serde(rename = "fp")]
+    pub floor: Option<f64>,
+}
+
+/// A compact encoding (bincode, compressed and base64-encoded) for items which are too bulky or slow to parse as JSON.
+mod binary {
+    use flate2::read::DeflateDecoder;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::io::{Read, Write};
+
+    /// Prefixes encoded values, distinguishing them from values stored in the previous (JSON) format.
+    const PREFIX: &str = "b:";
+
+    pub fn encode<T: Serialize>(value: &T) -> Option<String> {
+        let bytes = bincode::serialize(value).ok()?;
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes).ok()?;
+        Some(format!(
+            "{PREFIX}{}",
+            base64::encode(encoder.finish().ok()?)
+        ))
+    }
+
+    pub fn decode<T: DeserializeOwned>(value: &str) -> Option<T> {
+        let compressed = base64::decode(value.strip_prefix(PREFIX)?).ok()?;
+        let mut bytes = Vec::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .read_to_end(&mut bytes)
+            .ok()?;
+        bincode::deserialize(&bytes).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{Sort, SortBy, Token};
+    use crate::storage::binary::{decode, encode};
+    use crate::storage::{Token as Storage, Traits};
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn encodes_token() {
+        let token = Token {
+            id: 1,
+            metadata: None,
+            last_viewed: None,
+        };
+        let encoded = encode(&token).expect("could not encode token");
+        assert!(encoded.starts_with("b:"));
+        let decoded: Token = decode(&encoded).expect("could not decode token");
+        assert_eq!(token.id, decoded.id);
+    }
+
+    #[test]
+    fn ignores_json() {
+        assert!(decode::<Token>(r#"{"i":1,"m":null,"lv":null}"#).is_none());
+    }
+
+    #[test]
+    fn unindexes_token() {
+        let mut traits = Traits::new();
+        let background = traits.entry("Background".to_string()).or_default();
+        background.insert("Blue".to_string(), BTreeSet::from([1, 2]));
+        background.insert("Red".to_string(), BTreeSet::from([3]));
+
+        Storage::unindex(&mut traits, 3);
+        assert_eq!(
+            Some(&BTreeSet::from([1, 2])),
+            traits["Background"].get("Blue")
+        );
+        assert!(!traits["Background"].contains_key("Red"));
+
+        Storage::unindex(&mut traits, 1);
+        Storage::unindex(&mut traits, 2);
+        assert!(traits.is_empty());
+    }
+
+    #[test]
+    fn orders_tokens() {
+        let tokens: Vec<Token> = (1..=4)
+            .map(|id| Token {
+                id,
+                metadata: None,
+                last_viewed: None,
+            })
+            .collect();
+        let ids = |sort: Sort, ranks: &HashMap<u32, u32>| -> Vec<u32> {
+            Storage::ordered(tokens.iter(), sort, ranks)
+                .map(|token| token.id)
+                .collect()
+        };
+
+        assert_eq!(vec![1, 2, 3, 4], ids(Sort::default(), &HashMap::new()));
+        assert_eq!(
+            vec![4, 3, 2, 1],
+            ids(
+                Sort {
+                    by: SortBy::Id,
+                    descending: true
+                },
+                &HashMap::new()
+            )
+        );
+
+        // Unranked tokens are listed last, whichever the direction
+        let ranks = HashMap::from([(2, 2), (3, 1), (4, 3)]);
+        assert_eq!(vec![3, 2, 4, 1], ids(SortBy::Rarity.sort(), &ranks));
+        assert_eq!(
+            vec![4, 2, 3, 1],
+            ids(
+                Sort {
+                    by: SortBy::Rarity,
+                    descending: true
+                },
+                &ranks
+            )
+        );
     }
 }
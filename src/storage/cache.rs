@@ -0,0 +1,70 @@
+//! A minimal wrapper of the Cache API, holding the responses of images keyed by their url.
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Cache, Response};
+
+const CACHE: &str = "nifty-gallery-images";
+/// The maximum number of images cached, beyond which the least recently cached are evicted.
+const MAX_ENTRIES: u32 = 1_000;
+
+/// Gets the cached image at the url, resolving with none where not cached.
+pub async fn get(url: &str) -> Result<Option<web_sys::Blob>, JsValue> {
+    let cache = open().await?;
+    let response = JsFuture::from(cache.match_with_str(url)).await?;
+    if response.is_undefined() {
+        return Ok(None);
+    }
+    let response: Response = response.dyn_into()?;
+    let blob = JsFuture::from(response.blob()?).await?;
+    Ok(Some(blob.dyn_into()?))
+}
+
+/// Requests the image at the url (typically from the browser cache, having just been displayed) and caches the
+/// response, evicting the least recently cached images beyond the maximum.
+pub async fn put(url: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("could not get window"))?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    // Only readable responses are cached, as those served without CORS headers cannot be displayed from the cache
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "unable to cache the image: {}",
+            response.status()
+        )));
+    }
+    let cache = open().await?;
+    JsFuture::from(cache.put_with_str(url, &response)).await?;
+
+    let keys: js_sys::Array = JsFuture::from(cache.keys()).await?.unchecked_into();
+    for request in keys
+        .iter()
+        .take(keys.length().saturating_sub(MAX_ENTRIES) as usize)
+    {
+        JsFuture::from(cache.delete_with_request(&request.unchecked_into())).await?;
+    }
+    Ok(())
+}
+
+/// The number of images cached.
+pub async fn len() -> Result<u32, JsValue> {
+    let cache = open().await?;
+    let keys: js_sys::Array = JsFuture::from(cache.keys()).await?.unchecked_into();
+    Ok(keys.length())
+}
+
+/// Deletes the cache, along with every image within it.
+pub async fn clear() -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("could not get window"))?;
+    JsFuture::from(window.caches()?.delete(CACHE))
+        .await
+        .map(|_| ())
+}
+
+async fn open() -> Result<Cache, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("could not get window"))?;
+    // The Cache API is only available within secure contexts
+    JsFuture::from(window.caches()?.open(CACHE))
+        .await?
+        .dyn_into()
+}
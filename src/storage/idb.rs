@@ -0,0 +1,205 @@
+//! A minimal IndexedDB store of values (strings or blobs), keyed by string.
+use std::cell::RefCell;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbKeyRange, IdbOpenDbRequest, IdbRequest, IdbTransaction, IdbTransactionMode,
+};
+
+const DATABASE: &str = "nifty-gallery";
+const VERSION: u32 = 3;
+pub const TOKENS: &str = "tokens";
+/// Images uploaded locally as overrides for those of tokens.
+pub const IMAGES: &str = "images";
+/// Images of the tokens of collections archived for offline viewing.
+pub const ARCHIVE: &str = "archive";
+const STORES: [&str; 3] = [TOKENS, IMAGES, ARCHIVE];
+
+thread_local! {
+    static DB: RefCell<Option<IdbDatabase>> = RefCell::new(None);
+}
+
+/// Gets the values of the store whose keys start with the prefix.
+pub async fn get_all(store: &str, prefix: &str) -> Result<Vec<String>, JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str(store)?;
+    let range = IdbKeyRange::bound(
+        &JsValue::from_str(prefix),
+        &JsValue::from_str(&format!("{prefix}\u{ffff}")),
+    )?;
+    let request = transaction.object_store(store)?.get_all_with_key(&range)?;
+    let values: js_sys::Array = request_future(&request).await?.unchecked_into();
+    Ok(values
+        .iter()
+        .filter_map(|value| value.as_string())
+        .collect())
+}
+
+/// Puts the values into the store within a single transaction, resolving once the transaction completes.
+pub async fn put_all(store: &str, entries: Vec<(String, String)>) -> Result<(), JsValue> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let db = open().await?;
+    let transaction = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    let object_store = transaction.object_store(store)?;
+    for (key, value) in entries {
+        object_store.put_with_key(&JsValue::from_str(&value), &JsValue::from_str(&key))?;
+    }
+    transaction_future(&transaction).await.map(|_| ())
+}
+
+/// Gets the value of the key within the store, resolving with undefined where not found.
+pub async fn get(store: &str, key: &str) -> Result<JsValue, JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str(store)?;
+    let request = transaction
+        .object_store(store)?
+        .get(&JsValue::from_str(key))?;
+    request_future(&request).await
+}
+
+/// Puts the value into the store, resolving once the transaction completes.
+pub async fn put(store: &str, key: &str, value: &JsValue) -> Result<(), JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    transaction
+        .object_store(store)?
+        .put_with_key(value, &JsValue::from_str(key))?;
+    transaction_future(&transaction).await.map(|_| ())
+}
+
+/// Deletes the key from the store, resolving once the transaction completes.
+pub async fn delete(store: &str, key: &str) -> Result<(), JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    transaction
+        .object_store(store)?
+        .delete(&JsValue::from_str(key))?;
+    transaction_future(&transaction).await.map(|_| ())
+}
+
+/// Deletes the keys of the store which start with the prefix, resolving once the transaction completes.
+pub async fn delete_all(store: &str, prefix: &str) -> Result<(), JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    let range = IdbKeyRange::bound(
+        &JsValue::from_str(prefix),
+        &JsValue::from_str(&format!("{prefix}\u{ffff}")),
+    )?;
+    transaction.object_store(store)?.delete(&range)?;
+    transaction_future(&transaction).await.map(|_| ())
+}
+
+/// Counts the values of the store.
+pub async fn count(store: &str) -> Result<u32, JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str(store)?;
+    let request = transaction.object_store(store)?.count()?;
+    Ok(request_future(&request).await?.as_f64().unwrap_or_default() as u32)
+}
+
+/// Deletes the database along with every store within it, which is created again when next opened.
+pub async fn delete_database() -> Result<(), JsValue> {
+    // The database is only deleted once every connection to it has been closed
+    if let Some(db) = DB.with(|db| db.borrow_mut().take()) {
+        db.close();
+    }
+    let request = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("could not get window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB is not supported"))?
+        .delete_database(DATABASE)?;
+    request_future(&request).await.map(|_| ())
+}
+
+/// Whether the error signifies that the storage quota of the origin has been exceeded.
+pub fn is_quota_exceeded(error: &JsValue) -> bool {
+    error
+        .dyn_ref::<web_sys::DomException>()
+        .map_or(false, |e| e.name() == "QuotaExceededError")
+}
+
+/// Opens the database, creating any stores added since the version last opened.
+async fn open() -> Result<IdbDatabase, JsValue> {
+    if let Some(db) = DB.with(|db| db.borrow().clone()) {
+        return Ok(db);
+    }
+
+    let factory = web_sys::window()
+        .ok_or_else(|| JsValue::from_str("could not get window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from_str("IndexedDB is not supported"))?;
+    let request = factory.open_with_u32(DATABASE, VERSION)?;
+    let upgrade = Closure::once_into_js(move |event: web_sys::Event| {
+        let db = event
+            .target()
+            .and_then(|target| target.dyn_into::<IdbOpenDbRequest>().ok())
+            .and_then(|request| request.result().ok())
+            .map(|db| db.unchecked_into::<IdbDatabase>());
+        if let Some(db) = db {
+            let existing = db.object_store_names();
+            for store in STORES.iter().filter(|store| !existing.contains(store)) {
+                if let Err(e) = db.create_object_store(store) {
+                    log::error!("unable to create the {store} store: {e:?}")
+                }
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+
+    let db: IdbDatabase = request_future(&request).await?.unchecked_into();
+    DB.with(|cached| *cached.borrow_mut() = Some(db.clone()));
+    Ok(db)
+}
+
+/// Resolves with the result of the request, or rejects with its error.
+fn request_future(request: &IdbRequest) -> JsFuture {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let success = Closure::once_into_js(move |event: web_sys::Event| {
+            let result = event
+                .target()
+                .and_then(|target| target.dyn_into::<IdbRequest>().ok())
+                .and_then(|request| request.result().ok())
+                .unwrap_or(JsValue::UNDEFINED);
+            let _ = resolve.call1(&JsValue::NULL, &result);
+        });
+        let error = Closure::once_into_js(move |event: web_sys::Event| {
+            let error = event
+                .target()
+                .and_then(|target| target.dyn_into::<IdbRequest>().ok())
+                .and_then(|request| request.error().ok().flatten())
+                .map_or(JsValue::UNDEFINED, JsValue::from);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(success.unchecked_ref()));
+        request.set_onerror(Some(error.unchecked_ref()));
+    });
+    JsFuture::from(promise)
+}
+
+/// Resolves once the transaction completes, or rejects with its error (e.g. when the quota is exceeded).
+fn transaction_future(transaction: &IdbTransaction) -> JsFuture {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let complete = Closure::once_into_js(move || {
+            let _ = resolve.call0(&JsValue::NULL);
+        });
+        // A failing transaction fires both error and abort, so each is handled by its own (single use) closure, with
+        // the second rejection being ignored
+        let failed = || {
+            let transaction = transaction.clone();
+            let reject = reject.clone();
+            Closure::once_into_js(move || {
+                let error = transaction
+                    .error()
+                    .map_or(JsValue::UNDEFINED, JsValue::from);
+                let _ = reject.call1(&JsValue::NULL, &error);
+            })
+        };
+        transaction.set_oncomplete(Some(complete.unchecked_ref()));
+        transaction.set_onerror(Some(failed().unchecked_ref()));
+        transaction.set_onabort(Some(failed().unchecked_ref()));
+    });
+    JsFuture::from(promise)
+}
@@ -0,0 +1,111 @@
+//! Local storage, falling back to memory where the browser restricts it (e.g. Safari private browsing, where writes
+//! throw, or where site data is blocked, where access itself throws), so that the app continues to work within the
+//! session.
+use gloo_storage::errors::StorageError;
+use gloo_storage::Storage;
+use serde::{de::DeserializeOwned, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The key written when probing whether local storage can be written to.
+const PROBE: &str = "nifty-gallery:probe";
+
+thread_local! {
+    /// The values stored in memory as json, where local storage is restricted.
+    static MEMORY: RefCell<Option<HashMap<String, String>>> = RefCell::new(probe());
+}
+
+/// A drop-in replacement for the local storage of `gloo_storage`, backed by memory where restricted.
+pub struct LocalStorage;
+
+impl LocalStorage {
+    pub fn get<T: DeserializeOwned>(key: impl AsRef<str>) -> gloo_storage::Result<T> {
+        let key = key.as_ref();
+        MEMORY.with(|memory| match memory.borrow().as_ref() {
+            Some(memory) => match memory.get(key) {
+                Some(value) => Ok(serde_json::from_str(value)?),
+                None => Err(StorageError::KeyNotFound(key.to_string())),
+            },
+            None => gloo_storage::LocalStorage::get(key),
+        })
+    }
+
+    pub fn set<T: Serialize>(key: impl AsRef<str>, value: T) -> gloo_storage::Result<()> {
+        let key = key.as_ref();
+        MEMORY.with(|memory| match memory.borrow_mut().as_mut() {
+            Some(memory) => {
+                memory.insert(key.to_string(), serde_json::to_string(&value)?);
+                Ok(())
+            }
+            None => gloo_storage::LocalStorage::set(key, value),
+        })
+    }
+
+    pub fn delete(key: impl AsRef<str>) {
+        let key = key.as_ref();
+        MEMORY.with(|memory| match memory.borrow_mut().as_mut() {
+            Some(memory) => {
+                memory.remove(key);
+            }
+            None => gloo_storage::LocalStorage::delete(key),
+        })
+    }
+
+    /// The number of keys stored.
+    pub fn len() -> usize {
+        MEMORY.with(|memory| match memory.borrow().as_ref() {
+            Some(memory) => memory.len(),
+            None => gloo_storage::LocalStorage::length() as usize,
+        })
+    }
+
+    /// Removes every key stored.
+    pub fn clear() {
+        MEMORY.with(|memory| match memory.borrow_mut().as_mut() {
+            Some(memory) => memory.clear(),
+            None => gloo_storage::LocalStorage::clear(),
+        })
+    }
+
+    /// Gets the raw (unparsed) value of the key.
+    pub fn get_raw(key: &str) -> Option<String> {
+        MEMORY.with(|memory| match memory.borrow().as_ref() {
+            Some(memory) => memory.get(key).cloned(),
+            None => gloo_storage::LocalStorage::raw()
+                .get_item(key)
+                .ok()
+                .flatten(),
+        })
+    }
+}
+
+/// Whether values are persisted across sessions, rather than held in memory.
+pub fn is_persistent() -> bool {
+    MEMORY.with(|memory| memory.borrow().is_none())
+}
+
+/// Probes whether local storage can be written to, returning the in-memory store to be used instead where not.
+fn probe() -> Option<HashMap<String, String>> {
+    let storage = match web_sys::window().map(|window| window.local_storage()) {
+        Some(Ok(Some(storage))) => storage,
+        _ => {
+            web_sys::console::warn_1(
+                &"local storage is unavailable, so data will not persist".into(),
+            );
+            return Some(HashMap::new());
+        }
+    };
+    match storage.set_item(PROBE, PROBE) {
+        Ok(_) => {
+            let _ = storage.remove_item(PROBE);
+            None
+        }
+        Err(e) => {
+            web_sys::console::warn_1(
+                &format!("local storage cannot be written to, so data will not persist: {e:?}")
+                    .into(),
+            );
+            Some(HashMap::new())
+        }
+    }
+}
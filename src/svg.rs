@@ -0,0 +1,296 @@
+/// The elements removed from SVG images along with their content, as either may run scripts or embed arbitrary HTML.
+const REMOVED: [&str; 2] = ["script", "foreignobject"];
+
+/// Whether the uri is of an SVG image, either inline or by the extension of its path.
+pub fn is_svg(uri: &str) -> bool {
+    let uri = uri.to_ascii_lowercase();
+    uri.starts_with("data:image/svg+xml")
+        || uri
+            .split(['?', '#'])
+            .next()
+            .map_or(false, |path| path.ends_with(".svg"))
+}
+
+/// Encodes the markup of an SVG image as a base64 data uri.
+pub fn data_uri(markup: &str) -> String {
+    format!("data:image/svg+xml;base64,{}", base64::encode(markup))
+}
+
+/// Sanitises the markup of an SVG image, removing any `script` and `foreignObject` elements, event handler attributes
+/// (e.g. `onload`), `javascript:` urls and declarations (such as the entities of a document type, which may expand into
+/// markup). Images are otherwise only safe when rendered via `<img>`, which never runs scripts but also never loads any
+/// external resources (such as fonts) the image relies upon.
+pub fn sanitize(markup: &str) -> String {
+    // Lowercasing ascii characters preserves the offsets of the markup
+    let lower = markup.to_ascii_lowercase();
+    let mut sanitized = String::with_capacity(markup.len());
+    let mut i = 0;
+    while let Some(start) = lower[i..].find('<').map(|start| i + start) {
+        sanitized.push_str(&markup[i..start]);
+        // Comments, character data and processing instructions are never parsed as markup (yet may contain unbalanced
+        // quotes), so are passed through as is
+        if let Some(terminator) = [("<!--", "-->"), ("<![cdata[", "]]>"), ("<?", "?>")]
+            .iter()
+            .find_map(|(open, close)| lower[start..].starts_with(open).then_some(*close))
+        {
+            i = lower[start..]
+                .find(terminator)
+                .map_or(markup.len(), |end| start + end + terminator.len());
+            sanitized.push_str(&markup[start..i]);
+            continue;
+        }
+        // Declarations are dropped entirely, along with any entities declared within them
+        if lower[start..].starts_with("<!") {
+            match declaration_end(&lower, start) {
+                Some(end) => i = end,
+                None => return sanitized,
+            }
+            continue;
+        }
+        // Any unterminated tag is dropped, along with the remainder of the markup
+        let end = match tag_end(&lower, start) {
+            Some(end) => end,
+            None => return sanitized,
+        };
+        let tag = &markup[start..end];
+        i = end;
+        match element(&lower[start..end]) {
+            Some(name) if REMOVED.contains(&name) => {
+                if !tag.ends_with("/>") {
+                    let close = format!("</{name}");
+                    match lower[end..]
+                        .find(&close)
+                        .and_then(|close| tag_end(&lower, end + close))
+                    {
+                        Some(close) => i = close,
+                        None => return sanitized,
+                    }
+                }
+            }
+            Some(_) => sanitized.push_str(&attributes(tag)),
+            None => match closing(tag) {
+                // Closing tags are rebuilt from their name, as they should have no attributes
+                Some(name) => sanitized.push_str(&format!("</{name}>")),
+                // A stray `<` is escaped, so that it cannot open any markup which follows
+                None => {
+                    sanitized.push_str("&lt;");
+                    i = start + 1;
+                }
+            },
+        }
+    }
+    sanitized.push_str(&markup[i..]);
+    sanitized
+}
+
+/// The offset following the end of the tag starting at the offset, ignoring any `>` within quoted attribute values.
+fn tag_end(markup: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in markup[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(start + i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The offset following the end of the declaration starting at the offset, including any internal subset (e.g.
+/// `<!DOCTYPE svg [<!ENTITY x "...">]>`).
+fn declaration_end(markup: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0;
+    for (i, c) in markup[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, '>') if depth <= 0 => return Some(start + i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The name of the element closed by the tag, where a closing tag.
+fn closing(tag: &str) -> Option<&str> {
+    let name = tag.strip_prefix("</")?;
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    name.split(|c: char| !c.is_ascii() || is_separator(c as u8))
+        .next()
+}
+
+/// The (lowercase) local name of the element opened by the tag, ignoring any namespace prefix (e.g. `svg:script`).
+fn element(tag: &str) -> Option<&str> {
+    let name = tag.strip_prefix('<')?;
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name = name
+        .split(|c: char| !c.is_ascii() || is_separator(c as u8))
+        .next()?;
+    name.rsplit(':').next()
+}
+
+/// Removes any event handler attributes, or attributes whose value is a `javascript:` url, from the tag.
+fn attributes(tag: &str) -> String {
+    let bytes = tag.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() && !is_separator(bytes[i]) {
+        i += 1;
+    }
+    let mut sanitized = tag[..i].to_string();
+    while i < bytes.len() {
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        let name = i;
+        while i < bytes.len() && !is_separator(bytes[i]) && bytes[i] != b'=' {
+            i += 1;
+        }
+        if i == name {
+            // The end of the tag
+            sanitized.push_str(&tag[start..]);
+            break;
+        }
+        let name = tag[name..i].to_ascii_lowercase();
+        let mut value = "";
+        let mut j = i;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if bytes.get(j) == Some(&b'=') {
+            j += 1;
+            while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                j += 1;
+            }
+            let start = j;
+            match bytes.get(j) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    j = tag[j + 1..]
+                        .find(quote as char)
+                        .map_or(bytes.len(), |end| j + end + 2);
+                }
+                _ => {
+                    while j < bytes.len() && !is_separator(bytes[j]) {
+                        j += 1;
+                    }
+                }
+            }
+            value = &tag[start..j];
+            i = j;
+        }
+        if !is_unsafe(&name, value) {
+            sanitized.push_str(&tag[start..i]);
+        }
+    }
+    sanitized
+}
+
+fn is_separator(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || byte == b'/' || byte == b'>'
+}
+
+fn is_unsafe(name: &str, value: &str) -> bool {
+    // Character references are decoded before the value is used, and browsers ignore whitespace and control characters
+    // within the scheme of a url
+    let value: String = decode(value.trim_matches(['"', '\'']))
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    // Animation values are separated by semicolons (e.g. `values="#;javascript:..."`)
+    name.starts_with("on")
+        || value
+            .split(';')
+            .any(|value| value.starts_with("javascript:"))
+}
+
+/// Decodes the character references (e.g. `&#x6A;` or `&amp;`) within an attribute value.
+fn decode(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest[1..].find(';').and_then(|end| {
+            let c = match &rest[1..end + 1] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                reference => match reference.strip_prefix('#') {
+                    Some(code) => match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => code.parse().ok(),
+                    }
+                    .and_then(char::from_u32),
+                    None => None,
+                },
+            }?;
+            Some((c, end + 2))
+        });
+        match reference {
+            Some((c, length)) => {
+                decoded.push(c);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_svg, sanitize};
+
+    #[test]
+    fn identifies_svg_images() {
+        assert!(is_svg("data:image/svg+xml;base64,PHN2Zz4="));
+        assert!(is_svg("https://example.com/1.SVG?v=2"));
+        assert!(!is_svg("https://example.com/1.png"));
+        assert!(!is_svg("data:image/png;base64,iVBORw0KGgo="));
+    }
+
+    #[test]
+    fn sanitizes_svg_images() {
+        let svg = "<?xml version=\"1.0\"?>\
+            <!DOCTYPE svg [<!ENTITY x \"<script>alert(1)</script>\">]>\
+            <svg xmlns='http://www.w3.org/2000/svg' onload=\"alert('a > b')\">\
+            <!-- it's -->&x;\
+            <script type=\"text/javascript\">alert(1)</script>\
+            <foreignObject width=\"10\"><body xmlns=\"http://www.w3.org/1999/xhtml\"><p>Hi</p></body></foreignObject>\
+            <a href=\" java\tscript:alert(1)\"><text x=\"1\" ONCLICK=alert(1)>Ünïcode #1</text></a>\
+            <a href=\"&#x6A;avascript:alert(1)\" xlink:href=\"&#106;avascript:alert(1)\">1 < 2</a>\
+            <set attributeName=\"href\" values=\"#;javascript:alert(1)\"/>\
+            <svg:script/><rect fill='#fff' /></svg >";
+        assert_eq!(
+            "<?xml version=\"1.0\"?>\
+            <svg xmlns='http://www.w3.org/2000/svg'>\
+            <!-- it's -->&x;\
+            <a><text x=\"1\">Ünïcode #1</text></a>\
+            <a>1 &lt; 2</a>\
+            <set attributeName=\"href\"/>\
+            <rect fill='#fff' /></svg>",
+            sanitize(svg)
+        );
+    }
+
+    #[test]
+    fn drops_unterminated_scripts() {
+        assert_eq!("<svg>", sanitize("<svg><script>alert(1)"));
+        assert_eq!("<svg>", sanitize("<svg><rect onclick=\"alert(1)"));
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+/// A named sequence of tokens, recorded whilst browsing, which can be replayed as a slideshow or shared as a link.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Tour {
+    #[serde(rename = "n")]
+    pub name: String,
+    #[serde(rename = "s")]
+    pub stops: Vec<Stop>,
+}
+
+/// A token visited on a tour.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Stop {
+    #[serde(rename = "c")]
+    pub collection: String,
+    #[serde(rename = "t")]
+    pub token: u32,
+}
+
+impl Tour {
+    pub fn new(name: String) -> Tour {
+        Tour {
+            name,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Adds the token as the next stop, unless it is already the current stop (e.g. when the token is revisited).
+    pub fn record(&mut self, collection: &str, token: u32) -> bool {
+        if self.stops.last().map_or(false, |stop| {
+            stop.collection == collection && stop.token == token
+        }) {
+            return false;
+        }
+        self.stops.push(Stop {
+            collection: collection.to_string(),
+            token,
+        });
+        true
+    }
+
+    /// Encodes the tour for use within a link, so that it can be replayed without having been stored.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Decodes a tour previously encoded within a link.
+    pub fn decode(encoded: &str) -> Option<Tour> {
+        let json = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stop, Tour};
+
+    #[test]
+    fn records_stops() {
+        let mut tour = Tour::new("Highlights".to_string());
+        assert!(tour.record("azuki", 1));
+        assert!(!tour.record("azuki", 1));
+        assert!(tour.record("azuki", 2));
+        assert!(tour.record("doodles", 2));
+        // Revisiting an earlier token is a further stop
+        assert!(tour.record("azuki", 1));
+        assert_eq!(
+            vec![("azuki", 1), ("azuki", 2), ("doodles", 2), ("azuki", 1)],
+            tour.stops
+                .iter()
+                .map(|stop| (stop.collection.as_str(), stop.token))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn encodes_tours() {
+        let tour = Tour {
+            name: "Apes & Punks".to_string(),
+            stops: vec![
+                Stop {
+                    collection: "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d".to_string(),
+                    token: 3749,
+                },
+                Stop {
+                    collection: "polygon:0x2953399124f0cbb46d2cbacd8a89cf0599974963".to_string(),
+                    token: 0,
+                },
+            ],
+        };
+        let encoded = tour.encode();
+        // Safe for use within a path segment
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Some(tour), Tour::decode(&encoded));
+        assert_eq!(None, Tour::decode("not a tour"));
+    }
+}
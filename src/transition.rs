@@ -0,0 +1,71 @@
+use std::cell::Cell;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// The class of the image shared between the grid and the token page, which is given a `view-transition-name` so that
+/// the browser animates it between the two layouts.
+pub const SHARED: &str = "is-shared";
+
+thread_local! {
+    /// The token last opened from the grid, whose card is named when navigating back so that its image is animated
+    /// into place.
+    static TOKEN: Cell<Option<u32>> = Cell::new(None);
+}
+
+/// Whether the token was last opened from the grid.
+pub fn is_shared(token: u32) -> bool {
+    TOKEN.with(|shared| shared.get() == Some(token))
+}
+
+/// Opens the token from the grid, animating the image of the clicked card into the token page.
+pub fn open(figure: Option<web_sys::Element>, token: u32, navigate: impl FnOnce() + 'static) {
+    TOKEN.with(|shared| shared.set(Some(token)));
+    // Only a single element may have the name when the transition starts, so any previously shared is cleared
+    if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+        let shared = document.get_elements_by_class_name(SHARED);
+        let shared: Vec<_> = (0..shared.length())
+            .filter_map(|i| shared.item(i))
+            .collect();
+        for element in shared {
+            let _ = element.class_list().remove_1(SHARED);
+        }
+    }
+    if let Some(figure) = figure {
+        let _ = figure.class_list().add_1(SHARED);
+    }
+    start(navigate)
+}
+
+/// Returns to the grid from the token page, animating its image into the card of the token.
+pub fn close(token: u32, navigate: impl FnOnce() + 'static) {
+    TOKEN.with(|shared| shared.set(Some(token)));
+    start(navigate)
+}
+
+/// Navigates within a view transition where supported by the browser, which snapshots the current page before
+/// navigating and then animates any elements sharing a name into their new positions once rendered.
+fn start(navigate: impl FnOnce() + 'static) {
+    let document = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => return navigate(),
+    };
+    let start = js_sys::Reflect::get(&document, &"startViewTransition".into())
+        .ok()
+        .and_then(|start| start.dyn_into::<js_sys::Function>().ok());
+    let start = match start {
+        Some(start) => start,
+        None => return navigate(),
+    };
+    // The new page is snapshotted once the returned promise resolves, so allow the route to be rendered first
+    let update = Closure::once_into_js(move || {
+        wasm_bindgen_futures::future_to_promise(async move {
+            navigate();
+            gloo_timers::future::sleep(Duration::ZERO).await;
+            Ok(JsValue::UNDEFINED)
+        })
+    });
+    if let Err(e) = start.call1(&document, &update) {
+        log::error!("unable to start the view transition: {e:?}");
+    }
+}
@@ -1,4 +1,5 @@
 use base64::DecodeError;
+use std::cell::RefCell;
 use std::str;
 use std::str::FromStr;
 use workers::{ParseError, Url};
@@ -15,6 +16,18 @@ pub fn encode(input: &str) -> String {
     base64::encode_config(input, base64::URL_SAFE_NO_PAD)
 }
 
+thread_local! {
+    static IPFS_GATEWAY: RefCell<String> = RefCell::new(crate::config::IPFS_GATEWAY.to_string());
+}
+
+/// Sets the gateway to which IPFS protocol addresses are converted, or restores the default where none.
+pub fn set_gateway(gateway: Option<&str>) {
+    let gateway = gateway.unwrap_or(crate::config::IPFS_GATEWAY).to_string();
+    IPFS_GATEWAY.with(|current| *current.borrow_mut() = gateway)
+}
+
+/// Parses the uri, converting IPFS and Arweave protocol addresses to their gateways. Data and blob uris are parsed as
+/// is, but should be displayed via [resolve] so that their content is not re-encoded.
 pub fn parse(input: &str) -> Result<Url, ParseError> {
     let mut url = Url::parse(input)?;
     if url.scheme() == "ipfs" {
@@ -24,16 +37,67 @@ pub fn parse(input: &str) -> Result<Url, ParseError> {
             .host_str()
             .expect("could not get host name from url")
             .to_string();
-        url.set_host(Some("ipfs.io"))?;
+        IPFS_GATEWAY.with(|gateway| url.set_host(Some(gateway.borrow().as_str())))?;
         url.set_path(&format!("/ipfs/{}{}", cid, url.path()));
 
         // New instance required due to internal url rules about changing schemes
         url = Url::parse(&url.to_string().replace("ipfs://", "https://"))
             .expect("could not parse url converted from ipfs to https")
+    } else if url.scheme() == "ar" {
+        // Convert Arweave protocol address to Arweave gateway
+        if let Some(gateway) = workers::metadata::arweave(url.as_str()) {
+            url = Url::parse(&gateway)?;
+        }
     }
     Ok(url)
 }
 
+/// Whether the uri is used as is, being either inline content (`data:`) or an object held by the browser (`blob:`,
+/// such as a local copy of an image).
+pub fn is_opaque(uri: &str) -> bool {
+    workers::metadata::is_data_uri(uri)
+        || uri
+            .get(..5)
+            .map_or(false, |scheme| scheme.eq_ignore_ascii_case("blob:"))
+}
+
+/// Resolves the uri of an image or animation to the url displayed, leaving data and blob uris untouched.
+pub fn resolve(uri: &str) -> String {
+    if is_opaque(uri) {
+        return uri.to_string();
+    }
+    parse(uri).map_or_else(|_| uri.to_string(), |url| url.to_string())
+}
+
+/// Extracts the IPFS content identifier from a gateway url, either from an `/ipfs/<cid>` path or a `<cid>.ipfs.`
+/// subdomain.
+pub fn cid(url: &Url) -> Option<String> {
+    let mut segments = url.path_segments()?;
+    if segments.next() == Some("ipfs") {
+        return segments
+            .next()
+            .filter(|cid| !cid.is_empty())
+            .map(|cid| cid.to_string());
+    }
+    url.host_str()
+        .and_then(|host| host.split_once(".ipfs."))
+        .map(|(cid, _)| cid.to_string())
+}
+
+/// Sanitises an inline SVG image (see [crate::svg::sanitize]), re-encoding it as base64 as characters such as `#`
+/// within the markup are otherwise interpreted by the browser as part of the url.
+pub fn svg(image: &str) -> Option<String> {
+    if !workers::metadata::is_data_uri(image) {
+        return None;
+    }
+    let media_type = image[5..].split_once(',')?.0.to_ascii_lowercase();
+    if !media_type.starts_with("image/svg+xml") {
+        return None;
+    }
+    workers::metadata::decode_data_uri(image)
+        .map(|svg| crate::svg::data_uri(&crate::svg::sanitize(&String::from_utf8_lossy(&svg))))
+}
+
 #[derive(Debug)]
 pub struct TokenUri {
     pub uri: String,
@@ -76,7 +140,7 @@ impl TokenUri {
 
 #[cfg(test)]
 mod tests {
-    use crate::uri::parse;
+    use crate::uri::{cid, parse, resolve, svg};
 
     #[test]
     fn parses_base_uri() {
@@ -92,4 +156,58 @@ mod tests {
             .expect("could not parse uri");
         assert_eq!(uri, url.as_str());
     }
+
+    #[test]
+    fn parses_arweave_uri() {
+        let url = parse("ar://3U2WUoK3bq7pcFkLtmYTjGJfL3pV5PvzRr6kNcAgRFQ/1.json")
+            .expect("could not parse uri");
+        assert_eq!(
+            "https://arweave.net/3U2WUoK3bq7pcFkLtmYTjGJfL3pV5PvzRr6kNcAgRFQ/1.json",
+            url.as_str()
+        );
+    }
+
+    #[test]
+    fn resolves_opaque_uris_untouched() {
+        let svg = "data:image/svg+xml;utf8,<svg><text>Ünïcode #1 </text></svg>";
+        assert_eq!(svg, resolve(svg));
+        let blob = "blob:https://nifty.gallery/6c0b6bd4-3f1c-4b8a-9d56-0d5b6a3e2f1a";
+        assert_eq!(blob, resolve(blob));
+        assert_eq!(
+            "https://ipfs.io/ipfs/QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq/1.png",
+            resolve("ipfs://QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq/1.png")
+        );
+    }
+
+    #[test]
+    fn extracts_cid() {
+        let cid = |uri| cid(&parse(uri).expect("could not parse uri"));
+        assert_eq!(
+            Some("QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq".to_string()),
+            cid("ipfs://QmeSjSinHpPnmXmspMjwiXyN6zS4E9zccariGR3jxcaWtq/1.png")
+        );
+        assert_eq!(
+            Some("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string()),
+            cid("https://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi.ipfs.dweb.link/1.png")
+        );
+        assert_eq!(None, cid("https://api.site.com/token/1.png"));
+    }
+
+    #[test]
+    fn encodes_inline_svg() {
+        assert_eq!(
+            Some("data:image/svg+xml;base64,PHN2Zz48cmVjdCBmaWxsPScjZmZmJy8+PC9zdmc+".to_string()),
+            svg("data:image/svg+xml;utf8,<svg><rect fill='#fff'/></svg>")
+        );
+        assert_eq!(
+            Some("data:image/svg+xml;base64,PHN2Zz4=".to_string()),
+            svg("data:image/svg+xml;base64,PHN2Zz4=")
+        );
+        // Scripts are removed from inline images
+        assert_eq!(
+            Some("data:image/svg+xml;base64,PHN2Zz48L3N2Zz4=".to_string()),
+            svg("data:image/svg+xml;utf8,<svg><script>alert(1)</script></svg>")
+        );
+        assert_eq!(None, svg("https://example.com/1.svg"));
+    }
 }
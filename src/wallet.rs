@@ -0,0 +1,43 @@
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// Whether an injected provider (e.g. MetaMask) is available.
+pub fn available() -> bool {
+    ethereum().is_some()
+}
+
+/// Requests the accounts of the injected provider, prompting the user to connect their wallet when `connect` is set.
+pub async fn accounts(connect: bool) -> Result<Vec<String>, JsValue> {
+    let ethereum = ethereum().ok_or_else(|| JsValue::from_str("no injected provider found"))?;
+    let request = Reflect::get(&ethereum, &JsValue::from_str("request"))?.dyn_into::<Function>()?;
+
+    let arguments = Object::new();
+    let method = if connect {
+        "eth_requestAccounts"
+    } else {
+        "eth_accounts"
+    };
+    Reflect::set(
+        &arguments,
+        &JsValue::from_str("method"),
+        &JsValue::from_str(method),
+    )?;
+    let accounts = JsFuture::from(
+        request
+            .call1(&ethereum, &arguments)?
+            .dyn_into::<Promise>()?,
+    )
+    .await?;
+
+    Ok(Array::from(&accounts)
+        .iter()
+        .filter_map(|account| account.as_string())
+        .collect())
+}
+
+fn ethereum() -> Option<JsValue> {
+    web_sys::window()
+        .and_then(|window| Reflect::get(&window, &JsValue::from_str("ethereum")).ok())
+        .filter(|ethereum| !ethereum.is_undefined() && !ethereum.is_null())
+}
@@ -0,0 +1,156 @@
+//! The sources of the current time and of randomness, which can be replaced (e.g. by tests) so that behaviour such as
+//! timestamps, cooldowns and backoff is deterministic. Each thread (i.e. the app and each worker) has its own sources,
+//! which default to the system clock and `Math.random()`.
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+thread_local! {
+    static CLOCK: RefCell<Rc<dyn Clock>> = RefCell::new(Rc::new(System));
+    static RNG: RefCell<Box<dyn Rng>> = RefCell::new(Box::new(System));
+}
+
+pub trait Clock {
+    /// The current time, in milliseconds since the unix epoch.
+    fn now(&self) -> f64;
+}
+
+pub trait Rng {
+    /// The next random number, between zero (inclusive) and one (exclusive).
+    fn next(&mut self) -> f64;
+}
+
+/// The system clock and random number generator.
+pub struct System;
+
+impl Clock for System {
+    #[cfg(target_arch = "wasm32")]
+    fn now(&self) -> f64 {
+        js_sys::Date::now()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now(&self) -> f64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_millis() as f64)
+    }
+}
+
+impl Rng for System {
+    fn next(&mut self) -> f64 {
+        js_sys::Math::random()
+    }
+}
+
+/// A clock which only advances when told to.
+pub struct Fixed(Cell<f64>);
+
+impl Fixed {
+    pub fn new(now: f64) -> Self {
+        Self(Cell::new(now))
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration.as_millis() as f64)
+    }
+}
+
+impl Clock for Fixed {
+    fn now(&self) -> f64 {
+        self.0.get()
+    }
+}
+
+/// A random number generator (xorshift64*) which always produces the same sequence for a given seed.
+pub struct Seeded(u64);
+
+impl Seeded {
+    pub fn new(seed: u64) -> Self {
+        // Zero is the only state which xorshift cannot leave
+        Self(seed.max(1))
+    }
+}
+
+impl Rng for Seeded {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        // Use the upper 53 bits, being the precision of an f64
+        (self.0.wrapping_mul(0x2545F4914F6CDD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Replaces the clock of the current thread.
+pub fn set_clock(clock: Rc<dyn Clock>) {
+    CLOCK.with(|c| *c.borrow_mut() = clock)
+}
+
+/// Replaces the random number generator of the current thread.
+pub fn set_rng(rng: impl Rng + 'static) {
+    RNG.with(|r| *r.borrow_mut() = Box::new(rng))
+}
+
+/// The current time, in milliseconds since the unix epoch.
+pub fn now() -> f64 {
+    CLOCK.with(|clock| clock.borrow().now())
+}
+
+pub fn random() -> f64 {
+    RNG.with(|rng| rng.borrow_mut().next())
+}
+
+/// The delay before the next attempt of a failed request, increasing linearly with each attempt plus up to half as
+/// much again as jitter, so that requests failing together are not all retried together.
+pub fn backoff(attempt: u8, base: Duration) -> Duration {
+    let delay = base * attempt.into();
+    delay + delay.mul_f64(random() / 2.0)
+}
+
+/// The delay before the next attempt of a failed request, doubling with each attempt plus up to half as much again as
+/// jitter, for requests which are expected to fail for longer (e.g. an unavailable host).
+pub fn exponential_backoff(attempt: u8, base: Duration) -> Duration {
+    let delay = base * 2u32.saturating_pow(attempt.saturating_sub(1).into());
+    delay + delay.mul_f64(random() / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backoff, exponential_backoff, set_rng, Rng, Seeded};
+    use std::time::Duration;
+
+    #[test]
+    fn seeded_rng_is_deterministic() {
+        let (mut a, mut b) = (Seeded::new(42), Seeded::new(42));
+        for _ in 0..100 {
+            let value = a.next();
+            assert_eq!(value, b.next());
+            assert!((0.0..1.0).contains(&value));
+        }
+        assert_ne!(Seeded::new(1).next(), Seeded::new(2).next());
+    }
+
+    #[test]
+    fn backoff_includes_jitter() {
+        set_rng(Seeded::new(7));
+        let first = backoff(2, Duration::from_secs(1));
+        assert!(first >= Duration::from_secs(2) && first <= Duration::from_secs(3));
+
+        // The same seed yields the same delays
+        set_rng(Seeded::new(7));
+        assert_eq!(first, backoff(2, Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn exponential_backoff_doubles() {
+        for (attempt, base) in [(1, 1), (2, 2), (3, 4), (4, 8)] {
+            let delay = exponential_backoff(attempt, Duration::from_secs(1));
+            assert!(
+                delay >= Duration::from_secs(base)
+                    && delay <= Duration::from_secs(base).mul_f64(1.5),
+                "attempt {attempt}: {delay:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::clock;
+use crate::metadata::Overrides;
+use gloo_net::Error;
+use gloo_worker::{HandlerId, Public, WorkerLink};
+use serde::{Deserialize, Serialize};
+
+pub struct Worker {
+    link: WorkerLink<Self>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Request {
+    /// The metadata url of a sample token of the collection.
+    pub url: String,
+    /// The identifier of the sample token, substituted into any overridden request body.
+    pub token: Option<u32>,
+    /// An optional url to be used as a CORS proxy, should the direct request fail
+    pub cors_proxy: Option<String>,
+    /// Overrides specific to the collection, which take precedence over the defaults.
+    pub overrides: Overrides,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Response {
+    pub url: String,
+    pub checks: Vec<Check>,
+}
+
+/// The result of a diagnostic check, along with a hint for remediation where applicable.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    pub fn new(name: &str, status: Status, detail: String, hint: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail,
+            hint: hint.map(|hint| hint.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Status {
+    Pass,
+    Warning,
+    Fail,
+}
+
+/// The latency (in milliseconds) beyond which a gateway is considered slow.
+const SLOW_LATENCY: f64 = 1_000.0;
+/// The latency (in milliseconds) beyond which a gateway is considered unusable for indexing.
+const UNUSABLE_LATENCY: f64 = 5_000.0;
+
+pub enum Message {
+    Completed(Response, HandlerId),
+}
+
+impl gloo_worker::Worker for Worker {
+    type Reach = Public<Self>;
+    type Message = Message;
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: WorkerLink<Self>) -> Self {
+        log::trace!("creating worker...");
+        Self { link }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match msg {
+            Message::Completed(response, id) => self.link.respond(id, response),
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        log::trace!("diagnosing {}...", msg.url);
+        self.link.send_future(async move {
+            let checks = diagnose(
+                &msg.url,
+                msg.token,
+                msg.cors_proxy.as_deref(),
+                &msg.overrides,
+            )
+            .await;
+            Message::Completed(
+                Response {
+                    url: msg.url,
+                    checks,
+                },
+                id,
+            )
+        });
+    }
+
+    fn name_of_resource() -> &'static str {
+        "diagnostics.js"
+    }
+}
+
+async fn diagnose(
+    url: &str,
+    token: Option<u32>,
+    cors_proxy: Option<&str>,
+    overrides: &Overrides,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    // Apply any overrides, with an overridden proxy used for every request rather than as a fallback
+    let url = overrides.gateway(url);
+    let (request, cors_proxy) = match &overrides.proxy {
+        Some(proxy) => (format!("{proxy}{url}"), None),
+        None => (url.clone(), cors_proxy),
+    };
+    let url = url.as_str();
+    let (method, body, headers) = (
+        overrides.method(),
+        overrides.body(token),
+        overrides.headers.as_slice(),
+    );
+
+    // Request directly, falling back to the proxy to distinguish CORS failures from an unreachable host
+    let start = clock::now();
+    let response = match crate::fetch::request(&request, method, body.as_deref(), headers).await {
+        Ok(response) => {
+            checks.push(Check::new(
+                "CORS",
+                Status::Pass,
+                "The host allows cross-origin requests".to_string(),
+                None,
+            ));
+            Some(response)
+        }
+        Err(Error::JsError(e)) => match cors_proxy {
+            Some(proxy) => match crate::fetch::request(
+                &format!("{proxy}{url}"),
+                method,
+                body.as_deref(),
+                headers,
+            )
+            .await
+            {
+                Ok(response) => {
+                    checks.push(Check::new(
+                        "CORS",
+                        Status::Warning,
+                        "The host does not allow cross-origin requests, so requests are routed via the CORS proxy"
+                            .to_string(),
+                        Some("Indexing still works but is slower. An alternative gateway for the base URI may avoid the proxy."),
+                    ));
+                    Some(response)
+                }
+                Err(_) => {
+                    checks.push(Check::new(
+                        "Reachable",
+                        Status::Fail,
+                        format!("The request failed, both directly and via the CORS proxy: {e}"),
+                        Some("Check that the base URI is correct and that the host is online."),
+                    ));
+                    None
+                }
+            },
+            None => {
+                checks.push(Check::new(
+                    "Reachable",
+                    Status::Fail,
+                    format!("The request failed: {e}"),
+                    Some("The failure may be CORS related. Configure a CORS proxy and try again."),
+                ));
+                None
+            }
+        },
+        Err(e) => {
+            checks.push(Check::new(
+                "Reachable",
+                Status::Fail,
+                format!("The request failed: {e}"),
+                Some("Check that the base URI is correct and that the host is online."),
+            ));
+            None
+        }
+    };
+    let latency = clock::now() - start;
+
+    let response = match response {
+        Some(response) => response,
+        None => return checks,
+    };
+
+    // Status
+    match response.status() {
+        200 => checks.push(Check::new(
+            "Reachable",
+            Status::Pass,
+            "The sample token metadata was returned".to_string(),
+            None,
+        )),
+        404 => {
+            checks.push(Check::new(
+                "Reachable",
+                Status::Fail,
+                "The sample token was not found (404)".to_string(),
+                Some(
+                    "The collection may not start at this token, or the base URI may be incorrect.",
+                ),
+            ));
+            return checks;
+        }
+        status @ (401 | 403) => {
+            checks.push(Check::new(
+                "Reachable",
+                Status::Fail,
+                format!("The request was not authorised: {status} {}", response.status_text()),
+                Some("The host may require an API key or other credentials. Add the required request headers to the overrides and try again."),
+            ));
+            return checks;
+        }
+        status => {
+            checks.push(Check::new(
+                "Reachable",
+                Status::Fail,
+                format!("The request failed: {status} {}", response.status_text()),
+                Some("The host may be rate limiting requests. Wait a while and try again."),
+            ));
+            return checks;
+        }
+    }
+
+    // Latency
+    checks.push(Check::new(
+        "Latency",
+        if latency < SLOW_LATENCY {
+            Status::Pass
+        } else if latency < UNUSABLE_LATENCY {
+            Status::Warning
+        } else {
+            Status::Fail
+        },
+        format!("The sample token metadata was returned in {latency:.0}ms"),
+        (latency >= SLOW_LATENCY).then(|| {
+            "The gateway is slow. Public IPFS gateways are often congested, so an alternative gateway may be faster."
+        }),
+    ));
+
+    // Parsing
+    match response.text().await {
+        Ok(text) => match overrides.parse_coerced(&text) {
+            Ok((metadata, coerced)) if coerced.is_empty() => checks.push(Check::new(
+                "Metadata",
+                Status::Pass,
+                format!(
+                    "The sample token metadata parsed successfully, with {} attributes",
+                    metadata.attributes.len()
+                ),
+                None,
+            )),
+            Ok((metadata, coerced)) => checks.push(Check::new(
+                "Metadata",
+                Status::Warning,
+                format!(
+                    "The sample token metadata was malformed, so was parsed leniently with {} attributes, coercing {}",
+                    metadata.attributes.len(),
+                    coerced.join(", ")
+                ),
+                None,
+            )),
+            Err(e) => {
+                // Suggest lenient parsing where it would recover the metadata
+                let lenient = Overrides {
+                    lenient: true,
+                    ..overrides.clone()
+                };
+                checks.push(Check::new(
+                    "Metadata",
+                    Status::Fail,
+                    format!("The sample token metadata could not be parsed: {e}"),
+                    Some(match !overrides.lenient && lenient.parse_coerced(&text).is_ok() {
+                        true => "The metadata is malformed, but can be recovered by enabling lenient parsing within the overrides.",
+                        false => "The metadata does not follow the expected standard. Check that the base URI points to the metadata rather than the images.",
+                    }),
+                ))
+            }
+        },
+        Err(e) => checks.push(Check::new(
+            "Metadata",
+            Status::Fail,
+            format!("The response could not be read: {e}"),
+            None,
+        )),
+    }
+
+    checks
+}
@@ -0,0 +1,73 @@
+use crate::etherscan::Address;
+use sha3::{Digest, Keccak256};
+
+/// The address of the ENS registry.
+pub(crate) const REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+/// The selector of the `resolver(bytes32)` function of the registry.
+pub(crate) const RESOLVER: &str = "0178b8bf";
+/// The selector of the `addr(bytes32)` function of a resolver.
+pub(crate) const ADDR: &str = "3b3b57de";
+
+/// Whether the value is an ENS name (e.g. `vitalik.eth`).
+pub fn is_name(value: &str) -> bool {
+    let value = value.trim();
+    value.len() > 4
+        && value.to_lowercase().ends_with(".eth")
+        && !value.contains(|c: char| c.is_whitespace() || c == '/')
+}
+
+/// Hashes the name as per EIP-137, for use as the node within calls to the registry and resolvers.
+pub fn namehash(name: &str) -> [u8; 32] {
+    let mut node = [0u8; 32];
+    for label in name.trim().to_lowercase().rsplit('.') {
+        if label.is_empty() {
+            continue;
+        }
+        let mut hasher = Keccak256::new();
+        hasher.update(node);
+        hasher.update(Keccak256::digest(label.as_bytes()));
+        node = hasher.finalize().into();
+    }
+    node
+}
+
+/// Encodes the call of the function with the node of the name as its argument.
+pub(crate) fn call_data(selector: &str, name: &str) -> String {
+    format!("{selector}{}", hex::encode(namehash(name)))
+}
+
+/// Decodes the address returned by a call, with the zero address signifying that none is set.
+pub(crate) fn address(result: &str) -> Option<Address> {
+    let bytes = hex::decode(result.trim_start_matches("0x")).ok()?;
+    if bytes.len() < 32 || bytes.iter().all(|b| *b == 0) {
+        return None;
+    }
+    Some(Address::from_slice(&bytes[12..32]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_name, namehash};
+
+    #[test]
+    fn hashes_names() {
+        assert_eq!([0u8; 32], namehash(""));
+        assert_eq!(
+            "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae",
+            hex::encode(namehash("eth"))
+        );
+        assert_eq!(
+            "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f",
+            hex::encode(namehash("foo.eth"))
+        );
+    }
+
+    #[test]
+    fn detects_names() {
+        assert!(is_name("vitalik.eth"));
+        assert!(is_name("Nifty.Gallery.ETH"));
+        assert!(!is_name(".eth"));
+        assert!(!is_name("0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d"));
+        assert!(!is_name("https://example.eth/1"));
+    }
+}
@@ -1,36 +1,167 @@
+use crate::{clock, ens};
 use ethabi::ParamType;
-use etherscan::{
-    contracts::{Contracts, ABI},
-    proxy::Proxy,
-    APIError,
-};
 use gloo_timers::future::sleep;
 use gloo_worker::{HandlerId, Public, WorkerLink};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 use std::future::Future;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub type Address = etherscan::Address;
-pub type Function = etherscan::contracts::Function;
+pub type Function = ethabi::Function;
 pub type TypeExtensions = dyn etherscan::TypeExtensions;
-pub type Token = etherscan::contracts::Token;
+pub type Token = ethabi::Token;
 
-pub const THROTTLE_SECONDS: u64 = 1;
+/// The interval between requests to an api with an api key, within the limit of five requests per second of the free
+/// tier.
+const THROTTLE: Duration = Duration::from_millis(200);
+/// The interval between requests to an api without an api key, which are limited to a single request every five
+/// seconds.
+const THROTTLE_WITHOUT_KEY: Duration = Duration::from_secs(5);
 const RETRY_ATTEMPTS: u8 = 5;
+const API: &str = "https://api.etherscan.io/api";
 
 pub struct Worker {
     link: WorkerLink<Self>,
-    client: etherscan::Client,
-    contracts: HashMap<Address, ABI>,
+    api_key: String,
+    /// The chain selected by each handler, along with the api used to explore it.
+    chains: HashMap<HandlerId, (Chain, String)>,
+    contracts: HashMap<(Chain, Address), ethabi::Contract>,
+    /// The handlers awaiting each call in flight, so that duplicate calls are only made once.
+    in_flight: HashMap<Call, Vec<HandlerId>>,
+    /// The contracts along with the results of their calls, returned to the persister as they are resolved so that
+    /// they are not requested again on subsequent visits.
+    cache: HashMap<(Chain, Address), Cached>,
+    persister: Option<HandlerId>,
+}
+
+/// A contract along with the results of its calls, cached by the app between sessions.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Cached {
+    #[serde(rename = "n")]
+    pub name: String,
+    /// The (json) abi of the contract.
+    #[serde(rename = "a")]
+    pub abi: String,
+    /// The uri resolved for the requested token, along with the token where included within the uri.
+    #[serde(rename = "u", default)]
+    pub uri: Option<(u32, String, Option<u32>)>,
+    #[serde(rename = "s", default)]
+    pub total_supply: Option<u32>,
+}
+
+/// A call to the api which is coalesced whilst in flight, should it be requested again (e.g. by multiple components
+/// viewing the same collection).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Call {
+    Contract(Chain, Address),
+    Uri(Chain, Address, u32),
+    TotalSupply(Chain, Address),
+}
+
+thread_local! {
+    /// The time (in milliseconds) from which the next request to each api may be started, so that requests from all
+    /// handlers are serialised within the rate limit of the api.
+    static SCHEDULE: RefCell<HashMap<String, f64>> = RefCell::new(HashMap::new());
+}
+
+/// An EVM chain, explored via its etherscan-compatible block explorer.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Chain {
+    Ethereum,
+    Polygon,
+    Arbitrum,
+    Optimism,
+    Bsc,
+}
+
+impl Chain {
+    pub const ALL: [Chain; 5] = [
+        Chain::Ethereum,
+        Chain::Polygon,
+        Chain::Arbitrum,
+        Chain::Optimism,
+        Chain::Bsc,
+    ];
+
+    /// The identifier of the chain, as used within collection identifiers and routes.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Chain::Ethereum => "eth",
+            Chain::Polygon => "polygon",
+            Chain::Arbitrum => "arbitrum",
+            Chain::Optimism => "optimism",
+            Chain::Bsc => "bsc",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Chain::Ethereum => "Ethereum",
+            Chain::Polygon => "Polygon",
+            Chain::Arbitrum => "Arbitrum",
+            Chain::Optimism => "Optimism",
+            Chain::Bsc => "BNB Smart Chain",
+        }
+    }
+
+    /// The block explorer of the chain.
+    pub fn explorer(&self) -> &'static str {
+        match self {
+            Chain::Ethereum => "etherscan.io",
+            Chain::Polygon => "polygonscan.com",
+            Chain::Arbitrum => "arbiscan.io",
+            Chain::Optimism => "optimistic.etherscan.io",
+            Chain::Bsc => "bscscan.com",
+        }
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Self {
+        Chain::Ethereum
+    }
+}
+
+impl Display for Chain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+impl FromStr for Chain {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Chain::ALL
+            .into_iter()
+            .find(|chain| chain.id().eq_ignore_ascii_case(value))
+            .ok_or_else(|| format!("{value} is not a supported chain"))
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum Request {
     ApiKey(String),
+    /// Registers the handler as the persister of the contracts cached as they are resolved.
+    Persist,
+    /// Restores the contract at the address (on the chain selected by the handler) cached by a previous session.
+    Cached(Address, Cached),
+    /// Selects the chain (and its api) used for subsequent requests from the handler. The etherscan.io api key is only
+    /// used for Ethereum, with requests to other chains throttled accordingly.
+    Chain(Chain, String),
     Contract(Address),
     Uri(Address, u32),
     TotalSupply(Address),
+    /// The current owner of the token, via the `ownerOf` function of the (ERC-721) contract.
+    Owner(Address, u32),
+    Holdings(Address),
+    GasOracle,
+    /// Resolves an ENS name to an address.
+    Name(String),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -47,21 +178,57 @@ pub enum Response {
     TotalSupply(u32),
     NoTotalSupply(Address),
     TotalSupplyFailed(Address),
+    // Owner
+    /// The contract and token, along with the address of its owner.
+    Owner(Address, u32, Address),
+    NoOwner(Address, u32),
+    OwnerFailed(Address, u32),
+    // Holdings
+    Holdings(Address, Vec<Holding>),
+    HoldingsFailed(Address),
+    // Gas
+    GasOracle(GasOracle),
+    GasOracleFailed,
+    // ENS
+    Name(String, Address),
+    NoName(String),
+    NameFailed(String),
+    // Cache
+    /// The contract on the chain along with the results of its calls, returned to the persister.
+    Cached(Chain, Address, Cached),
 }
 
 pub enum Message {
     RequestContract(Address, HandlerId),
-    Contract(Address, String, ABI, HandlerId),
-    NoContract(Address, HandlerId),
-    ContractFailed(Address, u8, HandlerId),
+    // The results of calls carry the chain requested, as the handler may have since disconnected
+    Contract(Chain, Address, String, ethabi::Contract, String, HandlerId),
+    NoContract(Chain, Address, HandlerId),
+    ContractFailed(Chain, Address, u8, HandlerId),
     // URI
     RequestUri(Address, u32, HandlerId),
-    Uri(String, Option<u32>, HandlerId),
-    UriFailed(Address, HandlerId),
+    Uri(Chain, Address, u32, String, Option<u32>, HandlerId),
+    UriFailed(Chain, Address, u32, HandlerId),
     // Total Supply
     RequestTotalSupply(Address, HandlerId),
-    TotalSupply(u32, HandlerId),
-    TotalSupplyFailed(Address, HandlerId),
+    TotalSupply(Chain, Address, u32, HandlerId),
+    TotalSupplyFailed(Chain, Address, HandlerId),
+    // Owner
+    RequestOwner(Address, u32, HandlerId),
+    Owner(Address, u32, Address, HandlerId),
+    OwnerFailed(Address, u32, HandlerId),
+    // Holdings
+    RequestHoldings(Address, HandlerId),
+    Holdings(Address, Vec<Holding>, HandlerId),
+    HoldingsFailed(Address, HandlerId),
+    // Gas
+    RequestGasOracle(HandlerId),
+    GasOracle(GasOracle, HandlerId),
+    GasOracleFailed(HandlerId),
+    // ENS
+    RequestName(String, HandlerId),
+    Name(String, Address, HandlerId),
+    NoName(String, HandlerId),
+    NameFailed(String, HandlerId),
 }
 
 const URI_FUNCTIONS: [&str; 4] = ["baseURI", "baseTokenURI", "tokenURI", "uri"];
@@ -76,8 +243,12 @@ impl gloo_worker::Worker for Worker {
         log::trace!("creating worker...");
         Self {
             link,
-            client: etherscan::Client::new(""),
+            api_key: String::new(),
+            chains: HashMap::new(),
             contracts: HashMap::new(),
+            in_flight: HashMap::new(),
+            cache: HashMap::new(),
+            persister: None,
         }
     }
 
@@ -85,53 +256,83 @@ impl gloo_worker::Worker for Worker {
         match msg {
             // Contract
             Message::RequestContract(address, id) => {
+                let (chain, api, api_key) = self.api(id);
+                if let (true, Some(cached)) = (
+                    self.contracts.contains_key(&(chain, address)),
+                    self.cache.get(&(chain, address)),
+                ) {
+                    log::trace!("contract at {address} restored from the cache");
+                    let name = cached.name.clone();
+                    self.link.respond(
+                        id,
+                        Response::Contract(Contract {
+                            address,
+                            name,
+                            chain,
+                        }),
+                    );
+                    return;
+                }
+                if self.coalesce(Call::Contract(chain, address), id) {
+                    return;
+                }
                 log::trace!("requesting contract for {}...", address);
-                let client = self.client.clone();
                 self.link.send_future(async move {
                     // Call API with retry attempts
-                    match Worker::call_api(|| client.get_source_code(&address), RETRY_ATTEMPTS)
-                        .await
+                    match Worker::call_api(
+                        || Worker::source_code(&api, &api_key, address),
+                        RETRY_ATTEMPTS,
+                    )
+                    .await
                     {
                         // Successful
-                        Ok(mut contracts) => {
-                            if contracts.len() > 0 {
-                                let contract = contracts.remove(0);
-                                return Message::Contract(
-                                    address,
-                                    contract.contract_name,
-                                    contract.abi,
-                                    id,
-                                );
-                            }
-
-                            Message::NoContract(address, id)
+                        Ok(Some((name, abi, json))) => {
+                            Message::Contract(chain, address, name, abi, json, id)
                         }
+                        Ok(None) => Message::NoContract(chain, address, id),
                         // Failed (after x attempts)
-                        Err(_) => Message::ContractFailed(address, RETRY_ATTEMPTS, id),
+                        Err(_) => Message::ContractFailed(chain, address, RETRY_ATTEMPTS, id),
                     }
                 });
             }
-            Message::Contract(address, name, abi, id) => {
+            Message::Contract(chain, address, name, abi, json, id) => {
                 log::trace!("contract found at {address}");
-                self.contracts.insert(address, abi); // cache abi for subsequent calls
-                self.link
-                    .respond(id, Response::Contract(Contract { address, name }));
+                self.contracts.insert((chain, address), abi); // cache abi for subsequent calls
+                self.cache(chain, address, |cached| {
+                    cached.name = name.clone();
+                    cached.abi = json;
+                });
+                for id in self.completed(Call::Contract(chain, address), id) {
+                    self.link.respond(
+                        id,
+                        Response::Contract(Contract {
+                            address,
+                            name: name.clone(),
+                            chain,
+                        }),
+                    );
+                }
             }
-            Message::NoContract(address, id) => {
+            Message::NoContract(chain, address, id) => {
                 log::trace!("no contract for {}...", address);
-                self.link.respond(id, Response::NoContract(address));
+                for id in self.completed(Call::Contract(chain, address), id) {
+                    self.link.respond(id, Response::NoContract(address));
+                }
             }
-            Message::ContractFailed(address, attempts, id) => {
+            Message::ContractFailed(chain, address, attempts, id) => {
                 log::error!(
                     "contract at {address} could not be retrieved after {attempts} attempts"
                 );
-                self.link
-                    .respond(id, Response::ContractFailed(address, attempts));
+                for id in self.completed(Call::Contract(chain, address), id) {
+                    self.link
+                        .respond(id, Response::ContractFailed(address, attempts));
+                }
             }
             // URI
             Message::RequestUri(address, token, id) => {
                 // Check if contract already exists
-                let contract = match self.contracts.get(&address) {
+                let (chain, ..) = self.api(id);
+                let contract = match self.contracts.get(&(chain, address)) {
                     None => {
                         log::trace!("contract does not exist locally, requesting...");
                         self.update(Message::RequestContract(address, id));
@@ -140,61 +341,89 @@ impl gloo_worker::Worker for Worker {
                     Some(contract) => contract,
                 };
 
+                let cached = self
+                    .cache
+                    .get(&(chain, address))
+                    .and_then(|cached| cached.uri.clone())
+                    .filter(|(requested, ..)| *requested == token);
+                if let Some((_, uri, uri_token)) = cached {
+                    log::trace!("uri restored from the cache");
+                    self.link.respond(id, Response::Uri(uri, uri_token));
+                    return;
+                }
+
                 // Check contract for possible functions
-                for name in URI_FUNCTIONS {
-                    if let Ok(function) = contract.function(name) {
-                        log::trace!(
-                            "{name} function found on contract, preparing contract call..."
-                        );
-                        let mut inputs = Vec::new();
-                        match function.inputs.len() {
-                            0 => {}
-                            1 => {
-                                if let ParamType::Uint(_) = function.inputs[0].kind {
-                                    inputs.push(Token::Uint(token.into()));
-                                }
+                let function = URI_FUNCTIONS.iter().find_map(|name| {
+                    let function = contract.function(name).ok()?;
+                    log::trace!("{name} function found on contract, preparing contract call...");
+                    let mut inputs = Vec::new();
+                    match function.inputs.len() {
+                        0 => {}
+                        1 => {
+                            if let ParamType::Uint(_) = function.inputs[0].kind {
+                                inputs.push(Token::Uint(token.into()));
                             }
-                            _ => continue,
-                        }
-
-                        // Signal whether url result includes a token
-                        let uri_token = if inputs.len() == 1 { Some(token) } else { None };
-
-                        if let Err(_) = self.call_contract(
-                            address,
-                            function,
-                            &inputs,
-                            id,
-                            move |tokens, id| match tokens.first() {
-                                Some(token) => Message::Uri(token.to_string(), uri_token, id),
-                                None => {
-                                    log::trace!("contract call did not return a result");
-                                    Message::UriFailed(address, id)
-                                }
-                            },
-                            move |address, id| Message::UriFailed(address, id),
-                        ) {
-                            self.link.respond(id, Response::UriFailed(address))
                         }
-
+                        _ => return None,
+                    }
+                    Some((function.clone(), inputs))
+                });
+                let (function, inputs) = match function {
+                    Some(function) => function,
+                    None => {
+                        self.link.respond(id, Response::NoUri(address));
                         return;
                     }
+                };
+                if self.coalesce(Call::Uri(chain, address, token), id) {
+                    return;
                 }
 
-                self.link.respond(id, Response::NoUri(address));
+                // Signal whether url result includes a token
+                let uri_token = if inputs.len() == 1 { Some(token) } else { None };
+
+                if let Err(_) = self.call_contract(
+                    address,
+                    &function,
+                    &inputs,
+                    id,
+                    move |tokens, id| match tokens.first() {
+                        Some(uri) => {
+                            Message::Uri(chain, address, token, uri.to_string(), uri_token, id)
+                        }
+                        None => {
+                            log::trace!("contract call did not return a result");
+                            Message::UriFailed(chain, address, token, id)
+                        }
+                    },
+                    move |address, id| Message::UriFailed(chain, address, token, id),
+                ) {
+                    self.update(Message::UriFailed(chain, address, token, id))
+                }
             }
-            Message::Uri(uri, token, id) => {
+            Message::Uri(chain, address, token, uri, uri_token, id) => {
                 log::trace!("uri succeeded: {uri}");
-                self.link.respond(id, Response::Uri(uri, token));
+                // The data uris of on-chain metadata are specific to each token, so are not worth caching
+                if !uri.starts_with("data:") {
+                    self.cache(chain, address, |cached| {
+                        cached.uri = Some((token, uri.clone(), uri_token))
+                    });
+                }
+                for id in self.completed(Call::Uri(chain, address, token), id) {
+                    self.link.respond(id, Response::Uri(uri.clone(), uri_token));
+                }
             }
-            Message::UriFailed(contract, id) => {
+            Message::UriFailed(chain, contract, token, id) => {
                 log::trace!("uri failed");
-                self.link.respond(id, Response::UriFailed(contract));
+                for id in self.completed(Call::Uri(chain, contract, token), id) {
+                    self.link.respond(id, Response::UriFailed(contract));
+                }
             }
             // Total Supply
             Message::RequestTotalSupply(address, id) => {
                 // Check if contract already exists
-                let contract = match self.contracts.get(&address) {
+                let (chain, ..) = self.api(id);
+                let contract = match self.contracts.get(&(chain, address)) {
                     None => {
                         log::trace!("contract does not exist locally, requesting...");
                         self.update(Message::RequestContract(address, id));
@@ -203,35 +432,194 @@ impl gloo_worker::Worker for Worker {
                     Some(contract) => contract,
                 };
 
+                if let Some(total_supply) = self
+                    .cache
+                    .get(&(chain, address))
+                    .and_then(|cached| cached.total_supply)
+                {
+                    log::trace!("total supply restored from the cache");
+                    self.link.respond(id, Response::TotalSupply(total_supply));
+                    return;
+                }
+
                 // Check for total supply function
-                match contract.function("totalSupply") {
-                    Err(_) => self.link.respond(id, Response::NoTotalSupply(address)),
+                let function = match contract.function("totalSupply") {
+                    Err(_) => {
+                        self.link.respond(id, Response::NoTotalSupply(address));
+                        return;
+                    }
+                    Ok(function) => function.clone(),
+                };
+                if self.coalesce(Call::TotalSupply(chain, address), id) {
+                    return;
+                }
+                if let Err(_) = self.call_contract(
+                    address,
+                    &function,
+                    &vec![],
+                    id,
+                    move |mut tokens, id| match tokens.remove(0).into_uint() {
+                        Some(total_supply) => {
+                            Message::TotalSupply(chain, address, total_supply.as_u32(), id)
+                        }
+                        None => Message::TotalSupplyFailed(chain, address, id),
+                    },
+                    move |address, id| Message::TotalSupplyFailed(chain, address, id),
+                ) {
+                    self.update(Message::TotalSupplyFailed(chain, address, id))
+                }
+            }
+            Message::TotalSupply(chain, address, total_supply, id) => {
+                log::trace!("total supply succeeded: {total_supply}");
+                self.cache(chain, address, |cached| {
+                    cached.total_supply = Some(total_supply)
+                });
+                for id in self.completed(Call::TotalSupply(chain, address), id) {
+                    self.link.respond(id, Response::TotalSupply(total_supply));
+                }
+            }
+            Message::TotalSupplyFailed(chain, address, id) => {
+                log::trace!("total supply failed");
+                for id in self.completed(Call::TotalSupply(chain, address), id) {
+                    self.link.respond(id, Response::TotalSupplyFailed(address));
+                }
+            }
+            // Owner
+            Message::RequestOwner(address, token, id) => {
+                // Check if contract already exists
+                let (chain, ..) = self.api(id);
+                let contract = match self.contracts.get(&(chain, address)) {
+                    None => {
+                        log::trace!("contract does not exist locally, requesting...");
+                        self.update(Message::RequestContract(address, id));
+                        return;
+                    }
+                    Some(contract) => contract,
+                };
+
+                // Check for owner function, which only ERC-721 contracts provide
+                match contract.function("ownerOf") {
+                    Err(_) => self.link.respond(id, Response::NoOwner(address, token)),
                     Ok(function) => {
                         if let Err(_) = self.call_contract(
                             address,
                             function,
-                            &vec![],
+                            &vec![Token::Uint(token.into())],
                             id,
-                            move |mut tokens, id| match tokens.remove(0).into_uint() {
-                                Some(total_supply) => {
-                                    Message::TotalSupply(total_supply.as_u32(), id)
-                                }
-                                None => Message::TotalSupplyFailed(address, id),
+                            move |mut tokens, id| match tokens.remove(0).into_address() {
+                                Some(owner) => Message::Owner(
+                                    address,
+                                    token,
+                                    Address::from_slice(owner.as_bytes()),
+                                    id,
+                                ),
+                                None => Message::OwnerFailed(address, token, id),
                             },
-                            move |address, id| Message::TotalSupplyFailed(address, id),
+                            move |address, id| Message::OwnerFailed(address, token, id),
                         ) {
-                            self.link.respond(id, Response::TotalSupplyFailed(address))
+                            self.link.respond(id, Response::OwnerFailed(address, token))
                         }
                     }
                 }
             }
-            Message::TotalSupply(total_supply, id) => {
-                log::trace!("total supply succeeded: {total_supply}");
-                self.link.respond(id, Response::TotalSupply(total_supply));
+            Message::Owner(address, token, owner, id) => {
+                log::trace!("owner of token {token} succeeded: {owner}");
+                self.link
+                    .respond(id, Response::Owner(address, token, owner));
             }
-            Message::TotalSupplyFailed(address, id) => {
-                log::trace!("total supply failed");
-                self.link.respond(id, Response::TotalSupplyFailed(address));
+            Message::OwnerFailed(address, token, id) => {
+                log::trace!("owner of token {token} failed");
+                self.link.respond(id, Response::OwnerFailed(address, token));
+            }
+            // Holdings
+            Message::RequestHoldings(address, id) => {
+                log::trace!("requesting holdings for {}...", address);
+                let (_, api, api_key) = self.api(id);
+                self.link.send_future(async move {
+                    match Worker::holdings(&api, &address, &api_key).await {
+                        Ok(holdings) => Message::Holdings(address, holdings, id),
+                        Err(e) => {
+                            log::error!("holdings for {address} could not be retrieved: {e}");
+                            Message::HoldingsFailed(address, id)
+                        }
+                    }
+                });
+            }
+            Message::Holdings(address, holdings, id) => {
+                log::trace!("{} holdings found for {address}", holdings.len());
+                self.link.respond(id, Response::Holdings(address, holdings));
+            }
+            Message::HoldingsFailed(address, id) => {
+                self.link.respond(id, Response::HoldingsFailed(address));
+            }
+            // Gas
+            Message::RequestGasOracle(id) => {
+                log::trace!("requesting gas oracle...");
+                let (_, api, api_key) = self.api(id);
+                self.link.send_future(async move {
+                    match Worker::gas_oracle(&api, &api_key).await {
+                        Ok(gas_oracle) => Message::GasOracle(gas_oracle, id),
+                        Err(e) => {
+                            log::error!("gas oracle could not be retrieved: {e}");
+                            Message::GasOracleFailed(id)
+                        }
+                    }
+                });
+            }
+            Message::GasOracle(gas_oracle, id) => {
+                self.link.respond(id, Response::GasOracle(gas_oracle));
+            }
+            Message::GasOracleFailed(id) => {
+                self.link.respond(id, Response::GasOracleFailed);
+            }
+            // ENS
+            Message::RequestName(name, id) => {
+                log::trace!("resolving {name}...");
+                // Names are always resolved via Ethereum, where the registry is deployed
+                let api_key = self.api_key.clone();
+                self.link.send_future(async move {
+                    // Request the resolver of the name from the registry, followed by the address from the resolver
+                    let registry = Address::from_str(ens::REGISTRY)
+                        .expect("expected a valid registry address");
+                    let data = ens::call_data(ens::RESOLVER, &name);
+                    let resolver = match Worker::call_api(
+                        || Worker::eth_call(API, &api_key, registry, &data),
+                        RETRY_ATTEMPTS,
+                    )
+                    .await
+                    {
+                        Ok(result) => match ens::address(&result) {
+                            Some(resolver) => resolver,
+                            None => return Message::NoName(name, id),
+                        },
+                        Err(_) => return Message::NameFailed(name, id),
+                    };
+
+                    let data = ens::call_data(ens::ADDR, &name);
+                    match Worker::call_api(
+                        || Worker::eth_call(API, &api_key, resolver, &data),
+                        RETRY_ATTEMPTS,
+                    )
+                    .await
+                    {
+                        Ok(result) => match ens::address(&result) {
+                            Some(address) => Message::Name(name, address, id),
+                            None => Message::NoName(name, id),
+                        },
+                        Err(_) => Message::NameFailed(name, id),
+                    }
+                });
+            }
+            Message::Name(name, address, id) => {
+                log::trace!("{name} resolved to {address}");
+                self.link.respond(id, Response::Name(name, address));
+            }
+            Message::NoName(name, id) => {
+                self.link.respond(id, Response::NoName(name));
+            }
+            Message::NameFailed(name, id) => {
+                log::error!("{name} could not be resolved");
+                self.link.respond(id, Response::NameFailed(name));
             }
         }
     }
@@ -239,10 +627,41 @@ impl gloo_worker::Worker for Worker {
     fn handle_input(&mut self, request: Self::Input, id: HandlerId) {
         log::trace!("processing worker request...");
         match request {
-            Request::ApiKey(api_key) => self.client.api_key = api_key,
+            Request::ApiKey(api_key) => self.api_key = api_key,
+            Request::Persist => self.persister = Some(id),
+            Request::Cached(address, cached) => {
+                let (chain, ..) = self.api(id);
+                match ethabi::Contract::load(cached.abi.as_bytes()) {
+                    Ok(abi) => {
+                        self.contracts.insert((chain, address), abi);
+                        self.cache.insert((chain, address), cached);
+                    }
+                    Err(e) => log::error!("unable to restore the cached abi of {address}: {e:?}"),
+                }
+            }
+            Request::Chain(chain, api) => {
+                self.chains.insert(id, (chain, api));
+            }
             Request::Contract(address) => self.update(Message::RequestContract(address, id)),
             Request::Uri(address, token) => self.update(Message::RequestUri(address, token, id)),
             Request::TotalSupply(address) => self.update(Message::RequestTotalSupply(address, id)),
+            Request::Owner(address, token) => {
+                self.update(Message::RequestOwner(address, token, id))
+            }
+            Request::Holdings(address) => self.update(Message::RequestHoldings(address, id)),
+            Request::GasOracle => self.update(Message::RequestGasOracle(id)),
+            Request::Name(name) => self.update(Message::RequestName(name, id)),
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.chains.remove(&id);
+        // Calls in flight are left to complete (and be cached), but are no longer responded to the handler
+        for waiting in self.in_flight.values_mut() {
+            waiting.retain(|waiting| *waiting != id);
+        }
+        if self.persister == Some(id) {
+            self.persister = None;
         }
     }
 
@@ -252,10 +671,44 @@ impl gloo_worker::Worker for Worker {
 }
 
 impl Worker {
-    async fn call_api<C, R, F>(call: C, retry_attempts: u8) -> Result<R, APIError>
+    /// Registers the handler as awaiting the call, returning whether the same call is already in flight, in which case
+    /// the handler is responded to once it completes rather than the call being made again.
+    fn coalesce(&mut self, call: Call, id: HandlerId) -> bool {
+        let waiting = self.in_flight.entry(call).or_default();
+        waiting.push(id);
+        if waiting.len() > 1 {
+            log::trace!("{call:?} already in flight, awaiting its result...");
+        }
+        waiting.len() > 1
+    }
+
+    /// The handlers awaiting the completed call.
+    fn completed(&mut self, call: Call, id: HandlerId) -> Vec<HandlerId> {
+        self.in_flight.remove(&call).unwrap_or_else(|| vec![id])
+    }
+
+    /// Updates the cached contract, returning it to the persister.
+    fn cache(&mut self, chain: Chain, address: Address, update: impl FnOnce(&mut Cached)) {
+        let cached = self.cache.entry((chain, address)).or_default();
+        update(cached);
+        if let Some(persister) = self.persister {
+            self.link
+                .respond(persister, Response::Cached(chain, address, cached.clone()));
+        }
+    }
+
+    /// The chain selected by the handler, along with its api and the api key to be used.
+    fn api(&self, id: HandlerId) -> (Chain, String, String) {
+        match self.chains.get(&id) {
+            Some((chain, api)) if *chain != Chain::Ethereum => (*chain, api.clone(), String::new()),
+            _ => (Chain::Ethereum, API.to_string(), self.api_key.clone()),
+        }
+    }
+
+    async fn call_api<C, R, F>(call: C, retry_attempts: u8) -> Result<R, ApiError>
     where
         C: Fn() -> F,
-        F: Future<Output = Result<R, APIError>>,
+        F: Future<Output = Result<R, ApiError>>,
     {
         let mut last_error = None;
         for i in 1..retry_attempts {
@@ -263,39 +716,21 @@ impl Worker {
                 Ok(result) => return Ok(result),
                 Err(e) => {
                     match e {
-                        APIError::RateLimitReached { ref message } => {
-                            log::warn!("{message}");
+                        ApiError::RateLimited(ref message) => log::warn!("{message}"),
+                        ApiError::Rpc(code, ref message) => {
+                            log::error!("rpc error {code}: {message}")
                         }
-                        APIError::ContractNotVerified => {
-                            log::error!("contract not verified");
-                            return Err(e);
+                        ApiError::Transport(ref message) => {
+                            log::error!("transport error {message}")
                         }
-                        APIError::DeserializationError { ref message } => {
+                        ApiError::Invalid(ref message) => {
                             log::error!("{message}");
                             return Err(e);
                         }
-                        APIError::InvalidAddress => {
-                            log::error!("invalid address");
-                            return Err(e);
-                        }
-                        APIError::InvalidAPIKey { ref message } => {
-                            log::error!("{message}");
-                            return Err(e);
-                        }
-                        APIError::RPCError { code, ref message } => {
-                            log::error!("rpc error {code}: {message}");
-                        }
-                        APIError::TooManyAddresses => {
-                            log::error!("too many addresses");
-                            return Err(e);
-                        }
-                        APIError::TransportError { .. } => {
-                            log::error!("transport error {e:?}");
-                        }
                     }
 
                     last_error = Some(e);
-                    let duration = Duration::from_secs(i.into());
+                    let duration = clock::backoff(i, Duration::from_secs(1));
                     log::trace!("retrying in {duration:?}...");
                     sleep(duration).await;
                 }
@@ -304,6 +739,163 @@ impl Worker {
         Err(last_error.unwrap())
     }
 
+    /// Waits until a request to the api can be started within its rate limit. Requests from all handlers are started
+    /// in the order scheduled, with those without an api key throttled further.
+    async fn schedule(api: &str, api_key: &str) {
+        let interval = match api_key.is_empty() {
+            true => THROTTLE_WITHOUT_KEY,
+            false => THROTTLE,
+        };
+        let wait = SCHEDULE.with(|schedule| reserve(&mut schedule.borrow_mut(), api, interval));
+        if wait > Duration::ZERO {
+            log::trace!("throttling request to {api} for {wait:?}...");
+            sleep(wait).await;
+        }
+    }
+
+    /// Gets the result of a request to the api, with errors reported either by a status of zero or an rpc error.
+    async fn get(api: &str, api_key: &str, url: &str) -> Result<serde_json::Value, ApiError> {
+        Worker::schedule(api, api_key).await;
+        let response = crate::fetch::get(url)
+            .await
+            .map_err(|e| ApiError::Transport(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| ApiError::Transport(e.to_string()))?;
+        let mut response: serde_json::Value =
+            serde_json::from_str(&response).map_err(|e| ApiError::Invalid(e.to_string()))?;
+        if response["status"] == "0" {
+            let message = response["result"].as_str().unwrap_or_default().to_string();
+            return Err(match message.to_lowercase().contains("rate limit") {
+                true => ApiError::RateLimited(message),
+                false => ApiError::Invalid(message),
+            });
+        }
+        if let Some(error) = response.get("error") {
+            return Err(ApiError::Rpc(
+                error["code"].as_i64().unwrap_or_default(),
+                error["message"].as_str().unwrap_or_default().to_string(),
+            ));
+        }
+        Ok(response["result"].take())
+    }
+
+    /// Gets the name and abi (both parsed and as json) of the contract at the address, provided its source code has been
+    /// verified.
+    async fn source_code(
+        api: &str,
+        api_key: &str,
+        address: Address,
+    ) -> Result<Option<(String, ethabi::Contract, String)>, ApiError> {
+        let url = format!(
+            "{api}?module=contract&action=getsourcecode&address={address:?}&apikey={api_key}"
+        );
+        let result: Vec<SourceCode> =
+            serde_json::from_value(Worker::get(api, api_key, &url).await?)
+                .map_err(|e| ApiError::Invalid(e.to_string()))?;
+        match result.into_iter().next() {
+            // Addresses without verified source code (including wallets) have no contract name
+            Some(source) if !source.contract_name.is_empty() => {
+                let abi = ethabi::Contract::load(source.abi.as_bytes())
+                    .map_err(|e| ApiError::Invalid(e.to_string()))?;
+                Ok(Some((source.contract_name, abi, source.abi)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Calls the contract at the address with the (hex encoded) data, without creating a transaction.
+    async fn eth_call(
+        api: &str,
+        api_key: &str,
+        address: Address,
+        data: &str,
+    ) -> Result<String, ApiError> {
+        let url = format!(
+            "{api}?module=proxy&action=eth_call&to={address:?}&data=0x{data}&tag=latest&apikey={api_key}"
+        );
+        match Worker::get(api, api_key, &url).await? {
+            serde_json::Value::String(result) => Ok(result),
+            result => Err(ApiError::Invalid(format!(
+                "unexpected call result: {result}"
+            ))),
+        }
+    }
+
+    /// Determines the tokens currently held by the address, by replaying its (ERC-721) token transfers.
+    async fn holdings(api: &str, address: &Address, api_key: &str) -> Result<Vec<Holding>, String> {
+        let owner = format!("{address:?}");
+        let url = format!(
+            "{api}?module=account&action=tokennfttx&address={owner}&page=1&offset=10000&sort=asc&apikey={api_key}"
+        );
+        Worker::schedule(api, api_key).await;
+        let response = crate::fetch::get(&url)
+            .await
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+        let response: ApiResponse = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+        if response.status != "1" {
+            // No transfers is reported as an error
+            if response.message.starts_with("No transactions found") {
+                return Ok(Vec::new());
+            }
+            return Err(format!("{}: {}", response.message, response.result));
+        }
+        let transfers: Vec<Transfer> =
+            serde_json::from_value(response.result).map_err(|e| e.to_string())?;
+
+        let mut holdings = BTreeMap::new();
+        for transfer in transfers {
+            // Token identifiers exceeding the supported range are ignored
+            let token = match u32::from_str(&transfer.token_id) {
+                Ok(token) => token,
+                Err(_) => continue,
+            };
+            let key = (transfer.contract_address.to_lowercase(), token);
+            if transfer.from.eq_ignore_ascii_case(&owner) {
+                holdings.remove(&key);
+            }
+            if transfer.to.eq_ignore_ascii_case(&owner) {
+                holdings.insert(key, transfer.token_name);
+            }
+        }
+
+        Ok(holdings
+            .into_iter()
+            .map(|((contract, token), name)| Holding {
+                contract,
+                name,
+                token,
+            })
+            .collect())
+    }
+
+    /// Gets the current gas prices.
+    async fn gas_oracle(api: &str, api_key: &str) -> Result<GasOracle, String> {
+        let url = format!("{api}?module=gastracker&action=gasoracle&apikey={api_key}");
+        Worker::schedule(api, api_key).await;
+        let response = crate::fetch::get(&url)
+            .await
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+        let response: ApiResponse = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+        if response.status != "1" {
+            return Err(format!("{}: {}", response.message, response.result));
+        }
+        let result: GasOracleResult =
+            serde_json::from_value(response.result).map_err(|e| e.to_string())?;
+        let parse = |price: &str| f64::from_str(price).map_err(|e| e.to_string());
+        Ok(GasOracle {
+            safe: parse(&result.safe)?,
+            propose: parse(&result.propose)?,
+            fast: parse(&result.fast)?,
+        })
+    }
+
     fn call_contract<S, F>(
         &self,
         address: Address,
@@ -323,13 +915,13 @@ impl Worker {
                     "calling '{}' function on contract at {address}...",
                     function.name
                 );
-                let client = self.client.clone();
+                let (_, api, api_key) = self.api(id);
                 let function = function.clone();
                 let data = hex::encode(&encoded);
                 self.link.send_future(async move {
                     // Call API with retry attempts
                     match Worker::call_api(
-                        || client.call(&address, &data, Some(etherscan::Tag::Latest)),
+                        || Worker::eth_call(&api, &api_key, address, &data),
                         RETRY_ATTEMPTS,
                     )
                     .await
@@ -364,12 +956,114 @@ impl Worker {
     }
 }
 
+/// Reserves the next slot in which a request to the api can be started, returning the time to wait until then.
+fn reserve(schedule: &mut HashMap<String, f64>, api: &str, interval: Duration) -> Duration {
+    let now = clock::now();
+    let next = schedule.entry(api.to_string()).or_insert(now);
+    let slot = next.max(now);
+    *next = slot + interval.as_millis() as f64;
+    Duration::from_millis((slot - now) as u64)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Contract {
     pub address: Address,
     pub name: String,
+    pub chain: Chain,
 }
 
 enum ContractError {
     FunctionEncodingError(String),
 }
+
+/// The errors returned by an etherscan-compatible api.
+#[derive(Debug)]
+enum ApiError {
+    RateLimited(String),
+    Rpc(i64, String),
+    Transport(String),
+    /// An error which is not resolved by retrying, such as an invalid api key or response.
+    Invalid(String),
+}
+
+/// A token held by an address.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Holding {
+    /// The contract address of the collection.
+    pub contract: String,
+    /// The name of the collection.
+    pub name: String,
+    pub token: u32,
+}
+
+/// The current gas prices (in gwei).
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GasOracle {
+    pub safe: f64,
+    pub propose: f64,
+    pub fast: f64,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    status: String,
+    message: String,
+    result: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct SourceCode {
+    #[serde(rename = "ContractName")]
+    contract_name: String,
+    #[serde(rename = "ABI")]
+    abi: String,
+}
+
+#[derive(Deserialize)]
+struct GasOracleResult {
+    #[serde(rename = "SafeGasPrice")]
+    safe: String,
+    #[serde(rename = "ProposeGasPrice")]
+    propose: String,
+    #[serde(rename = "FastGasPrice")]
+    fast: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transfer {
+    contract_address: String,
+    #[serde(rename = "tokenID")]
+    token_id: String,
+    token_name: String,
+    from: String,
+    to: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reserve, API, THROTTLE};
+    use crate::clock::{self, Fixed};
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn serialises_requests() {
+        let now = Rc::new(Fixed::new(0.0));
+        clock::set_clock(now.clone());
+        let mut schedule = HashMap::new();
+        assert_eq!(Duration::ZERO, reserve(&mut schedule, API, THROTTLE));
+        assert_eq!(THROTTLE, reserve(&mut schedule, API, THROTTLE));
+        assert_eq!(THROTTLE * 2, reserve(&mut schedule, API, THROTTLE));
+        // Other apis are limited independently
+        assert_eq!(
+            Duration::ZERO,
+            reserve(&mut schedule, "https://api.polygonscan.com/api", THROTTLE)
+        );
+
+        // Requests are started immediately once the schedule has elapsed
+        now.advance(Duration::from_secs(1));
+        assert_eq!(Duration::ZERO, reserve(&mut schedule, API, THROTTLE));
+    }
+}
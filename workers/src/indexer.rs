@@ -0,0 +1,857 @@
+use crate::clock;
+use crate::metadata::{self, Fetched, Hosts, Metadata, Overrides};
+use gloo_timers::future::sleep;
+use gloo_worker::{HandlerId, Public, WorkerLink};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use url::Url;
+
+/// The maximum number of metadata requests in progress for each collection.
+const CONCURRENCY: usize = 4;
+/// The minimum interval between metadata requests for each collection, limiting the rate of requests to its host, unless
+/// overridden for the collection.
+const INTERVAL: Duration = Duration::from_millis(250);
+/// The number of indexed tokens returned for persisting at a time.
+const BATCH_SIZE: usize = 25;
+/// The number of consecutive tokens not found before indexing stops, when the total supply is unknown.
+const MAX_MISSES: u32 = 10;
+/// The number of times a failed token is requested again once retried, before remaining failed.
+const RETRY_ATTEMPTS: u8 = 3;
+/// The delay before the second attempt of a failed token being retried, doubling with each attempt thereafter.
+const RETRY_BACKOFF: Duration = Duration::from_secs(2);
+/// The period over which the metadata requests counted against the budget are limited.
+const BUDGET_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// The default number of metadata requests per hour, considerate of those indexing via public gateways or mobile data.
+pub const DEFAULT_BUDGET: u32 = 2_500;
+
+/// Indexes entire collections in the background, independently of any component, so that indexing continues whilst
+/// navigating elsewhere within the app.
+pub struct Worker {
+    link: WorkerLink<Self>,
+    /// The requester to which indexed tokens are returned to be persisted, along with any changes to the knowledge of
+    /// hosts learned whilst indexing.
+    persister: Option<HandlerId>,
+    /// The knowledge of hosts last returned to the persister.
+    hosts: Hosts,
+    /// The collections being indexed, by collection identifier.
+    jobs: HashMap<String, Job>,
+    /// The collection whose progress is returned to each requester.
+    subscribers: HashMap<HandlerId, String>,
+    /// The metadata requests started within the current session, limited by the budget across all collections.
+    allowance: Allowance,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Request {
+    /// Indexes the collection, returning its progress to the requester. Requesting a collection already being indexed
+    /// only subscribes to its progress.
+    Index {
+        collection: String,
+        /// The base uri (or ERC-1155 template) of the token metadata.
+        base_uri: String,
+        start_token: u32,
+        total_supply: Option<u32>,
+        /// The tokens already indexed, which are not requested again.
+        indexed: Vec<u32>,
+        /// The cursor from which indexing resumes, where previously interrupted.
+        cursor: Option<Cursor>,
+        /// Whether the tokens which previously failed are requested again, with an exponential backoff between attempts.
+        retry: bool,
+        /// An optional url to be used as a CORS proxy, should the primary request fail
+        cors_proxy: Option<String>,
+        overrides: Overrides,
+    },
+    /// Registers the requester as that persisting the indexed tokens, loading the knowledge of hosts learned in
+    /// previous sessions.
+    Persist(Hosts),
+    /// Sets the number of metadata requests which may be started, across all collections being indexed.
+    Budget(Budget),
+}
+
+/// The number of metadata requests which may be started whilst indexing.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Budget {
+    Unlimited,
+    PerHour(u32),
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget::PerHour(DEFAULT_BUDGET)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Response {
+    /// Tokens indexed since last returned, to be persisted. Always returned ahead of the resulting progress.
+    Indexed(String, Vec<(u32, Metadata)>),
+    /// The final url of each token since returned whose request was redirected, to be persisted.
+    Redirected(String, Vec<(u32, String)>),
+    /// The cursor from which indexing of the collection resumes, to be persisted. Always returned ahead of the
+    /// resulting progress.
+    Cursor(String, Cursor),
+    Progress(Progress),
+    /// The knowledge of hosts learned whilst indexing, to be persisted. Returned whenever it changes.
+    Hosts(Hosts),
+}
+
+/// The position from which indexing of a collection resumes, along with the tokens which failed, so that indexing
+/// continues where it left off should it be interrupted (e.g. by closing the app).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Cursor {
+    /// The base uri of the collection when indexed, as the cursor no longer applies should it change.
+    #[serde(rename = "u")]
+    pub base_uri: String,
+    /// The lowest token not yet requested (or whose request had not completed).
+    #[serde(rename = "n")]
+    pub next: u32,
+    /// The tokens whose metadata could not be requested, which are not requested again when resuming.
+    #[serde(rename = "f", default)]
+    pub failed: BTreeSet<u32>,
+    /// The number of consecutive tokens not found.
+    #[serde(rename = "m", default)]
+    pub misses: u32,
+}
+
+/// The progress of indexing a collection.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Progress {
+    pub collection: String,
+    /// The number of tokens indexed, including those indexed previously.
+    pub indexed: usize,
+    /// The total number of tokens to be indexed, where the total supply is known.
+    pub total: Option<u32>,
+    /// The first token found, should it differ from the start token of the collection.
+    pub start_token: Option<u32>,
+    /// The next token to be requested.
+    pub cursor: u32,
+    /// The number of tokens whose metadata could not be requested, including those being retried.
+    pub failed: usize,
+    /// The number of failed tokens currently being retried.
+    pub retrying: usize,
+    /// The estimated number of seconds until indexing completes.
+    pub eta: Option<u32>,
+    pub completed: bool,
+    /// The consumption of the budget, where limited.
+    pub budget: Option<Usage>,
+}
+
+/// The consumption of the budget over the last hour.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Usage {
+    /// The number of metadata requests started.
+    pub requests: usize,
+    pub limit: u32,
+    /// The number of seconds until indexing resumes, should it be paused having exhausted the budget.
+    pub resumes: Option<u32>,
+}
+
+pub enum Message {
+    /// Starts the next request for the collection, should the number of requests in progress allow.
+    Tick(String),
+    Fetched(String, u32, Fetched),
+}
+
+struct Job {
+    base_uri: String,
+    cors_proxy: Option<String>,
+    overrides: Overrides,
+    /// The requester on whose behalf metadata is requested.
+    requester: HandlerId,
+    start_token: u32,
+    /// The token following the last to be indexed, where the total supply is known or the end has been detected.
+    end: Option<u32>,
+    /// The detection of the end of a collection without a total supply, until detected.
+    boundary: Option<Boundary>,
+    next: u32,
+    indexed: HashSet<u32>,
+    /// The tokens whose metadata could not be requested.
+    failed: BTreeSet<u32>,
+    /// The tokens whose requests are in progress.
+    active: BTreeSet<u32>,
+    /// Failed tokens awaiting a further attempt, along with the attempt and the time (in milliseconds) from which it
+    /// is made.
+    retrying: VecDeque<(u32, u8, f64)>,
+    /// The attempt of each failed token whose request is in progress.
+    attempts: HashMap<u32, u8>,
+    /// Whether requests are still being started, which stops once all tokens have been requested.
+    ticking: bool,
+    /// The first token found and the number of consecutive tokens since not found.
+    first: Option<u32>,
+    misses: u32,
+    /// Tokens indexed but not yet returned for persisting, along with the final url of any which were redirected.
+    pending: Vec<(u32, Metadata)>,
+    redirects: Vec<(u32, String)>,
+    /// The time at which indexing started and the number of tokens since fetched, from which the rate is estimated.
+    started: f64,
+    fetched: usize,
+    /// Whether requests are paused until the budget allows.
+    paused: bool,
+}
+
+/// Detects the end of a collection without a total supply, by probing exponentially further tokens until one is not
+/// found and then binary searching between the last token found and the first not found. Assumes that the tokens of
+/// the collection are contiguous.
+#[derive(Debug, PartialEq)]
+struct Boundary {
+    start_token: u32,
+    /// The highest token found and the lowest token not found, between which the end lies.
+    found: u32,
+    missing: Option<u32>,
+    /// The token being probed, whose result is awaited.
+    probing: Option<u32>,
+}
+
+impl Boundary {
+    fn new(start_token: u32, found: u32) -> Self {
+        Self {
+            start_token,
+            found: found.max(start_token),
+            missing: None,
+            probing: None,
+        }
+    }
+
+    /// The next token to be probed, unless a probe is already awaited or the end has been found.
+    fn next(&mut self) -> Option<u32> {
+        if self.probing.is_some() || self.end().is_some() {
+            return None;
+        }
+        let token = match self.missing {
+            // Double the distance from the start token, until a token is not found
+            None => self
+                .found
+                .checked_add((self.found - self.start_token).max(1))?,
+            Some(missing) => self.found + (missing - self.found) / 2,
+        };
+        self.probing = Some(token);
+        Some(token)
+    }
+
+    /// Records whether the token was found (or `None` where its request failed), returning whether it was being
+    /// probed.
+    fn probed(&mut self, token: u32, found: Option<bool>) -> bool {
+        match found {
+            Some(true) if token > self.found => {
+                self.found = token;
+                // A token found beyond the token not found (e.g. due to sparse ids, or a transient failure of the
+                // request of the latter) means the end has yet to be found
+                if self.missing.map_or(false, |missing| token >= missing) {
+                    self.missing = None;
+                }
+            }
+            Some(false) if token > self.found => {
+                self.missing = Some(self.missing.map_or(token, |missing| missing.min(token)))
+            }
+            _ => {}
+        }
+        if self.probing != Some(token) {
+            return false;
+        }
+        self.probing = None;
+        true
+    }
+
+    /// The token following the last token of the collection, once found.
+    fn end(&self) -> Option<u32> {
+        self.missing.filter(|missing| *missing == self.found + 1)
+    }
+}
+
+impl Job {
+    /// The next token to be requested, skipping those already indexed or which previously failed.
+    fn next(&mut self) -> Option<u32> {
+        while self.indexed.contains(&self.next) || self.failed.contains(&self.next) {
+            self.next += 1;
+        }
+        // Stop once tokens are no longer found, or should the first tokens not be found
+        if self.misses >= MAX_MISSES && (self.end.is_none() || self.first.is_none()) {
+            return None;
+        }
+        let remaining = self.end.map_or(true, |end| self.next < end);
+        remaining.then(|| {
+            self.next += 1;
+            self.next - 1
+        })
+    }
+
+    /// Requests the failed tokens again, unless already being retried.
+    fn retry(&mut self) {
+        let retrying: HashSet<u32> = self
+            .retrying
+            .iter()
+            .map(|(token, ..)| *token)
+            .chain(self.attempts.keys().copied())
+            .collect();
+        let now = clock::now();
+        for token in self.failed.iter().filter(|token| !retrying.contains(token)) {
+            self.retrying.push_back((*token, 1, now));
+        }
+    }
+
+    /// The next failed token to be retried, along with the attempt, once its backoff has elapsed.
+    fn next_retry(&mut self) -> Option<(u32, u8)> {
+        let now = clock::now();
+        let index = self.retrying.iter().position(|(_, _, at)| *at <= now)?;
+        self.retrying
+            .remove(index)
+            .map(|(token, attempt, _)| (token, attempt))
+    }
+
+    /// Whether all tokens have been requested and fetched.
+    fn completed(&mut self) -> bool {
+        if !self.active.is_empty() || !self.retrying.is_empty() {
+            return false;
+        }
+        match self.next() {
+            Some(token) => {
+                self.next = token;
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// The minimum interval between requests, as overridden for gateways which rate limit aggressively (or tolerate
+    /// bursts).
+    fn interval(&self) -> Duration {
+        self.overrides
+            .interval
+            .map_or(INTERVAL, |interval| Duration::from_millis(interval.into()))
+    }
+
+    fn url(&self, token: u32) -> Option<String> {
+        // ERC-1155 templates contain a placeholder for the token, rather than the token being appended
+        metadata::substitute(&self.base_uri, token).or_else(|| {
+            Url::parse(&self.base_uri)
+                .and_then(|url| url.join(&token.to_string()))
+                .map(|url| url.to_string())
+                .ok()
+        })
+    }
+
+    /// The cursor from which indexing resumes. Once completed, any trailing tokens not found are requested again when
+    /// next indexed, as they may since have been minted.
+    fn cursor(&self, completed: bool) -> Cursor {
+        let next = self.active.iter().next().copied().unwrap_or(self.next);
+        let (next, misses) = match completed {
+            true => (next.saturating_sub(self.misses).max(self.start_token), 0),
+            false => (next, self.misses),
+        };
+        Cursor {
+            base_uri: self.base_uri.clone(),
+            next,
+            failed: self.failed.clone(),
+            misses,
+        }
+    }
+
+    fn progress(&self, collection: &str, completed: bool) -> Progress {
+        let total = self.end.map(|end| end - self.start_token);
+        let elapsed = (clock::now() - self.started) / 1000.0;
+        let eta = total
+            .filter(|_| self.fetched > 0 && !completed)
+            .map(|total| {
+                let remaining = (total as usize).saturating_sub(self.indexed.len());
+                (remaining as f64 * elapsed / self.fetched as f64).round() as u32
+            });
+        Progress {
+            collection: collection.to_string(),
+            indexed: self.indexed.len(),
+            total,
+            start_token: self.first.filter(|first| *first != self.start_token),
+            cursor: self.next,
+            failed: self.failed.len(),
+            retrying: self.retrying.len() + self.attempts.len(),
+            eta,
+            completed,
+            budget: None,
+        }
+    }
+}
+
+/// The metadata requests started over the last hour, limited by the budget.
+struct Allowance {
+    budget: Budget,
+    /// The times at which requests were started, oldest first.
+    requests: VecDeque<f64>,
+}
+
+impl Allowance {
+    fn new() -> Self {
+        Self {
+            budget: Budget::default(),
+            requests: VecDeque::new(),
+        }
+    }
+
+    /// The time to wait until a further request is allowed, where the budget is exhausted.
+    fn wait(&mut self) -> Option<Duration> {
+        self.expire();
+        let limit = match self.budget {
+            Budget::Unlimited => return None,
+            // A budget of zero would never allow indexing to resume
+            Budget::PerHour(limit) => limit.max(1) as usize,
+        };
+        if self.requests.len() < limit {
+            return None;
+        }
+        // Allowed once the oldest request counted (whose limit can be lowered below those counted) leaves the window
+        let oldest = self.requests[self.requests.len() - limit];
+        let remaining = oldest + BUDGET_WINDOW.as_millis() as f64 - clock::now();
+        Some(Duration::from_millis(remaining.max(0.0) as u64))
+    }
+
+    fn record(&mut self) {
+        self.requests.push_back(clock::now());
+    }
+
+    fn usage(&mut self, paused: bool) -> Option<Usage> {
+        let limit = match self.budget {
+            Budget::Unlimited => return None,
+            Budget::PerHour(limit) => limit,
+        };
+        let resumes = paused
+            .then(|| self.wait())
+            .flatten()
+            .map(|wait| wait.as_secs_f64().ceil() as u32);
+        Some(Usage {
+            requests: self.requests.len(),
+            limit,
+            resumes,
+        })
+    }
+
+    /// Removes any requests started before the current window.
+    fn expire(&mut self) {
+        let start = clock::now() - BUDGET_WINDOW.as_millis() as f64;
+        while self
+            .requests
+            .front()
+            .map_or(false, |started| *started <= start)
+        {
+            self.requests.pop_front();
+        }
+    }
+}
+
+impl gloo_worker::Worker for Worker {
+    type Reach = Public<Self>;
+    type Message = Message;
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: WorkerLink<Self>) -> Self {
+        log::trace!("creating worker...");
+        Self {
+            link,
+            persister: None,
+            hosts: Hosts::default(),
+            jobs: HashMap::new(),
+            subscribers: HashMap::new(),
+            allowance: Allowance::new(),
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match msg {
+            Message::Tick(collection) => {
+                let job = match self.jobs.get_mut(&collection) {
+                    Some(job) => job,
+                    None => return,
+                };
+                let interval = job.interval();
+                if job.active.len() < CONCURRENCY {
+                    // Pause requests whilst the budget is exhausted, resuming once it allows
+                    if let Some(wait) = self.allowance.wait() {
+                        let paused = std::mem::replace(&mut job.paused, true);
+                        if !paused {
+                            log::trace!("budget exhausted, pausing indexing of {collection}...");
+                            self.publish(&collection, false);
+                        }
+                        self.link.send_future(async move {
+                            sleep(wait.max(interval)).await;
+                            Message::Tick(collection)
+                        });
+                        return;
+                    }
+                    job.paused = false;
+                    let next = job
+                        .boundary
+                        .as_mut()
+                        .and_then(Boundary::next)
+                        .map(|token| (token, 0))
+                        .or_else(|| job.next_retry())
+                        .or_else(|| job.next().map(|token| (token, 0)));
+                    let (token, attempt) = match next {
+                        Some(next) => next,
+                        // Failed tokens still to be retried, once their backoff has elapsed
+                        None if !job.retrying.is_empty() => {
+                            self.link.send_future(async move {
+                                sleep(interval).await;
+                                Message::Tick(collection)
+                            });
+                            return;
+                        }
+                        // All tokens requested, with completion determined once the last is fetched
+                        None => {
+                            job.ticking = false;
+                            // Completes immediately when all tokens were already indexed
+                            if job.active.is_empty() {
+                                self.publish(&collection, true);
+                                self.jobs.remove(&collection);
+                            }
+                            return;
+                        }
+                    };
+                    if let Some(url) = job.url(token) {
+                        log::trace!("indexing token {token} of {collection}...");
+                        if attempt > 0 {
+                            job.attempts.insert(token, attempt);
+                        }
+                        job.active.insert(token);
+                        self.allowance.record();
+                        let (id, cors_proxy, overrides) =
+                            (job.requester, job.cors_proxy.clone(), job.overrides.clone());
+                        let collection = collection.clone();
+                        self.link.send_future(async move {
+                            let fetched =
+                                metadata::fetch(url, token, id, cors_proxy, overrides).await;
+                            Message::Fetched(collection, token, fetched)
+                        });
+                    }
+                }
+                self.link.send_future(async move {
+                    sleep(interval).await;
+                    Message::Tick(collection)
+                });
+            }
+            Message::Fetched(collection, token, fetched) => {
+                let job = match self.jobs.get_mut(&collection) {
+                    Some(job) => job,
+                    None => return,
+                };
+                job.active.remove(&token);
+                // Probed tokens are requested out of order, so those not found only narrow the boundary of the
+                // collection rather than counting towards the tokens not found
+                let mut probe_missed = false;
+                if let Some(boundary) = job.boundary.as_mut() {
+                    let found = match fetched {
+                        Fetched::Completed(..) => Some(true),
+                        Fetched::NotFound => Some(false),
+                        Fetched::Failed => None,
+                    };
+                    let probed = boundary.probed(token, found);
+                    probe_missed = probed && found != Some(true);
+                    if probed && found.is_none() {
+                        log::trace!("probing token {token} of {collection} failed, so its end is not detected");
+                        job.boundary = None;
+                    } else if let Some(end) = boundary.end() {
+                        log::trace!("detected the end of {collection} at token {}", end - 1);
+                        job.end = Some(end);
+                        job.boundary = None;
+                    }
+                }
+                // Retried tokens are requested out of order, so do not count towards the tokens not found
+                let retried = job.attempts.remove(&token);
+                match fetched {
+                    _ if probe_missed => {}
+                    Fetched::Completed(metadata, resolved) => {
+                        job.indexed.insert(token);
+                        job.failed.remove(&token);
+                        job.pending.push((token, metadata));
+                        if let Some(resolved) = resolved {
+                            job.redirects.push((token, resolved));
+                        }
+                        job.fetched += 1;
+                        if retried.is_none() {
+                            job.misses = 0;
+                        }
+                        job.first = Some(job.first.map_or(token, |first| first.min(token)));
+                    }
+                    Fetched::NotFound => match retried {
+                        Some(_) => {
+                            job.failed.remove(&token);
+                        }
+                        None => job.misses += 1,
+                    },
+                    Fetched::Failed => match retried {
+                        Some(attempt) if attempt < RETRY_ATTEMPTS => {
+                            let backoff = clock::exponential_backoff(attempt, RETRY_BACKOFF);
+                            log::trace!("token {token} failed again, retrying in {backoff:?}...");
+                            job.retrying.push_back((
+                                token,
+                                attempt + 1,
+                                clock::now() + backoff.as_millis() as f64,
+                            ));
+                        }
+                        // Remains failed, until retried again
+                        Some(_) => {}
+                        None => {
+                            job.failed.insert(token);
+                            job.misses += 1
+                        }
+                    },
+                }
+
+                let completed = job.completed();
+                if !completed && !job.ticking {
+                    // Tokens found since all were requested (e.g. resetting the misses) allow indexing to continue
+                    job.ticking = true;
+                    self.link.send_message(Message::Tick(collection.clone()));
+                }
+                if completed || job.pending.len() >= BATCH_SIZE {
+                    self.publish(&collection, completed);
+                }
+                if completed {
+                    log::trace!("indexing of {collection} completed");
+                    self.jobs.remove(&collection);
+                }
+            }
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match msg {
+            Request::Index {
+                collection,
+                base_uri,
+                start_token,
+                total_supply,
+                indexed,
+                cursor,
+                retry,
+                cors_proxy,
+                overrides,
+            } => {
+                self.subscribers.insert(id, collection.clone());
+                if let Some(job) = self.jobs.get_mut(&collection) {
+                    // Already indexing, so only retry any failed tokens and return the current progress
+                    if retry {
+                        log::trace!(
+                            "retrying {} failed tokens of {collection}...",
+                            job.failed.len()
+                        );
+                        job.retry();
+                        if !job.ticking {
+                            job.ticking = true;
+                            self.link.send_message(Message::Tick(collection.clone()));
+                        }
+                    }
+                    let mut progress = job.progress(&collection, false);
+                    progress.budget = self.allowance.usage(job.paused);
+                    self.link.respond(id, Response::Progress(progress));
+                    return;
+                }
+
+                // Resume from where indexing left off, unless the base uri has since changed
+                let cursor = cursor.filter(|cursor| cursor.base_uri == base_uri);
+                match cursor.as_ref() {
+                    Some(cursor) => log::trace!(
+                        "resuming indexing of {collection} from token {} with {} failed...",
+                        cursor.next,
+                        cursor.failed.len()
+                    ),
+                    None => log::trace!("indexing {collection}..."),
+                }
+                let cursor = cursor.unwrap_or_default();
+                self.jobs.insert(
+                    collection.clone(),
+                    Job {
+                        base_uri,
+                        cors_proxy,
+                        overrides,
+                        requester: id,
+                        start_token,
+                        end: total_supply.map(|total_supply| start_token + total_supply),
+                        boundary: total_supply.is_none().then(|| {
+                            Boundary::new(
+                                start_token,
+                                indexed.iter().max().copied().unwrap_or(start_token),
+                            )
+                        }),
+                        next: cursor.next.max(start_token),
+                        indexed: indexed.into_iter().collect(),
+                        failed: cursor.failed,
+                        active: BTreeSet::new(),
+                        retrying: VecDeque::new(),
+                        attempts: HashMap::new(),
+                        ticking: true,
+                        first: None,
+                        misses: cursor.misses,
+                        pending: Vec::new(),
+                        redirects: Vec::new(),
+                        started: clock::now(),
+                        fetched: 0,
+                        paused: false,
+                    },
+                );
+                if let (true, Some(job)) = (retry, self.jobs.get_mut(&collection)) {
+                    log::trace!(
+                        "retrying {} failed tokens of {collection}...",
+                        job.failed.len()
+                    );
+                    job.retry();
+                }
+                self.update(Message::Tick(collection));
+            }
+            Request::Persist(hosts) => {
+                metadata::restore(hosts);
+                self.persister = Some(id);
+                self.hosts = Hosts::current();
+            }
+            Request::Budget(budget) => {
+                if self.allowance.budget != budget {
+                    log::trace!("budget set to {budget:?}");
+                    self.allowance.budget = budget;
+                }
+            }
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        if self.persister == Some(id) {
+            self.persister = None;
+        }
+        self.subscribers.remove(&id);
+    }
+
+    fn name_of_resource() -> &'static str {
+        "indexer.js"
+    }
+}
+
+impl Worker {
+    /// Returns the tokens indexed since last returned for persisting, followed by the progress to each subscriber.
+    fn publish(&mut self, collection: &str, completed: bool) {
+        let job = match self.jobs.get_mut(collection) {
+            Some(job) => job,
+            None => return,
+        };
+        let tokens = std::mem::take(&mut job.pending);
+        if !tokens.is_empty() {
+            match self.persister {
+                Some(persister) => self
+                    .link
+                    .respond(persister, Response::Indexed(collection.to_string(), tokens)),
+                None => log::error!("no persister, so {} indexed tokens were lost", tokens.len()),
+            }
+        }
+        let redirects = std::mem::take(&mut job.redirects);
+        if let (false, Some(persister)) = (redirects.is_empty(), self.persister) {
+            self.link.respond(
+                persister,
+                Response::Redirected(collection.to_string(), redirects),
+            );
+        }
+        if let Some(persister) = self.persister {
+            self.link.respond(
+                persister,
+                Response::Cursor(collection.to_string(), job.cursor(completed)),
+            );
+        }
+
+        let mut progress = job.progress(collection, completed);
+        progress.budget = self.allowance.usage(job.paused);
+        for (subscriber, _) in self.subscribers.iter().filter(|(_, c)| *c == collection) {
+            self.link
+                .respond(*subscriber, Response::Progress(progress.clone()));
+        }
+        self.publish_hosts();
+    }
+
+    /// Returns any changes to the knowledge of hosts to the persister, so that they can be persisted.
+    fn publish_hosts(&mut self) {
+        if let Some(persister) = self.persister {
+            let hosts = Hosts::current();
+            if hosts != self.hosts {
+                self.link.respond(persister, Response::Hosts(hosts.clone()));
+                self.hosts = hosts;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Allowance, Boundary, Budget, BUDGET_WINDOW};
+    use crate::clock::{self, Fixed};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn pauses_once_budget_exhausted() {
+        let clock = Rc::new(Fixed::new(0.0));
+        clock::set_clock(clock.clone());
+        let mut allowance = Allowance::new();
+        allowance.budget = Budget::PerHour(2);
+
+        allowance.record();
+        clock.advance(Duration::from_secs(60));
+        allowance.record();
+        assert_eq!(
+            Some(BUDGET_WINDOW - Duration::from_secs(60)),
+            allowance.wait()
+        );
+        let usage = allowance.usage(true).unwrap();
+        assert_eq!(
+            (2, 2, Some(59 * 60)),
+            (usage.requests, usage.limit, usage.resumes)
+        );
+
+        // Allowed again once the first request leaves the window
+        clock.advance(BUDGET_WINDOW - Duration::from_secs(60));
+        assert_eq!(None, allowance.wait());
+        assert_eq!(1, allowance.usage(false).unwrap().requests);
+    }
+
+    #[test]
+    fn unlimited_budget_never_pauses() {
+        clock::set_clock(Rc::new(Fixed::new(0.0)));
+        let mut allowance = Allowance::new();
+        allowance.budget = Budget::Unlimited;
+        for _ in 0..10_000 {
+            allowance.record();
+        }
+        assert_eq!(None, allowance.wait());
+        assert_eq!(None, allowance.usage(false));
+    }
+
+    #[test]
+    fn detects_end_of_collection() {
+        // Tokens 1 to 37 exist
+        let exists = |token: u32| (1..=37).contains(&token);
+        let mut boundary = Boundary::new(1, 1);
+        let mut probes = Vec::new();
+        while let Some(token) = boundary.next() {
+            probes.push(token);
+            assert!(boundary.probed(token, Some(exists(token))));
+        }
+        assert_eq!(vec![2, 3, 5, 9, 17, 33, 65, 49, 41, 37, 39, 38], probes);
+        assert_eq!(Some(38), boundary.end());
+    }
+
+    #[test]
+    fn failed_probes_do_not_narrow_boundary() {
+        let mut boundary = Boundary::new(0, 10);
+        let token = boundary.next().unwrap();
+        assert_eq!(20, token);
+        assert!(boundary.probed(token, None));
+        assert_eq!(None, boundary.missing);
+        // Sequentially indexed tokens also narrow the boundary, without being probed
+        assert!(!boundary.probed(15, Some(false)));
+        assert_eq!(Some(15), boundary.missing);
+    }
+
+    #[test]
+    fn tokens_found_beyond_missing_reset_boundary() {
+        let mut boundary = Boundary::new(0, 10);
+        assert!(!boundary.probed(15, Some(false)));
+        assert!(!boundary.probed(16, Some(true)));
+        assert_eq!(None, boundary.missing);
+        assert_eq!(None, boundary.end());
+        // Probing resumes from the highest token found
+        assert_eq!(Some(32), boundary.next());
+    }
+}
@@ -1,9 +1,21 @@
 pub use gloo_worker::{Bridge, Bridged, PublicWorker};
 pub use url::{ParseError, Url};
 
+pub mod clock;
+pub mod diagnostics;
+pub mod ens;
 pub mod etherscan;
+pub mod indexer;
+pub mod market;
 pub mod metadata;
 pub mod qr;
+pub mod rarity;
+
+// Use a size-optimized allocator when selected, otherwise the default (dlmalloc) allocator is used
+#[cfg(all(feature = "small-alloc", target_arch = "wasm32"))]
+#[global_allocator]
+static ALLOCATOR: lol_alloc::AssumeSingleThreaded<lol_alloc::FreeListAllocator> =
+    unsafe { lol_alloc::AssumeSingleThreaded::new(lol_alloc::FreeListAllocator::new()) };
 
 // Workaround to enable fetch api for worker: https://github.com/rustwasm/gloo/issues/201#issuecomment-1078454938
 mod fetch {
@@ -13,9 +25,31 @@ mod fetch {
     use wasm_bindgen_futures::JsFuture;
 
     pub(crate) async fn get(url: &str) -> Result<Response, Error> {
+        get_with_headers(url, &[]).await
+    }
+
+    pub(crate) async fn get_with_headers(
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Result<Response, Error> {
+        request(url, "GET", None, headers).await
+    }
+
+    pub(crate) async fn request(
+        url: &str,
+        method: &str,
+        body: Option<&str>,
+        headers: &[(String, String)],
+    ) -> Result<Response, Error> {
         let mut opts = web_sys::RequestInit::new();
-        opts.method("GET");
+        opts.method(method);
+        if let Some(body) = body {
+            opts.body(Some(&wasm_bindgen::JsValue::from_str(body)));
+        }
         let request = web_sys::Request::new_with_str_and_init(url, &opts).map_err(js_to_error)?;
+        for (name, value) in headers {
+            request.headers().set(name, value).map_err(js_to_error)?;
+        }
 
         let global = js_sys::global();
         let worker = global
@@ -56,6 +90,16 @@ mod fetch {
             self.0.status_text()
         }
 
+        /// Whether the response is the result of following one or more redirects.
+        pub fn redirected(&self) -> bool {
+            self.0.redirected()
+        }
+
+        /// The final url of the response, once any redirects have been followed.
+        pub fn url(&self) -> String {
+            self.0.url()
+        }
+
         pub async fn text(&self) -> Result<String, Error> {
             let promise = self.0.text().unwrap();
             let val = JsFuture::from(promise).await.map_err(js_to_error)?;
@@ -0,0 +1,348 @@
+use gloo_worker::{HandlerId, Public, WorkerLink};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub struct Worker {
+    link: WorkerLink<Self>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Request {
+    /// Requests the floor price of each trait value of the collection (contract address) from the market data api.
+    TraitFloors { api: String, collection: String },
+    /// Requests the floor price of the collection (contract address) from the market data api.
+    Floor { api: String, collection: String },
+    /// Requests the details (description, banner etc.) of the collection (contract address) from the market data api.
+    Details { api: String, collection: String },
+    /// Requests the floor price and last sale of the collection (contract address), or of the token where specified,
+    /// from the market data api using the api key.
+    Prices {
+        api: String,
+        api_key: String,
+        collection: String,
+        token: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Response {
+    TraitFloors(String, TraitFloors),
+    Floor(String, f64),
+    Details(String, Details),
+    Prices(String, Option<u32>, Prices),
+    Failed(String),
+}
+
+/// The details of a collection, as listed by the market data api.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Details {
+    #[serde(rename = "n")]
+    pub name: Option<String>,
+    #[serde(rename = "d")]
+    pub description: Option<String>,
+    #[serde(rename = "i")]
+    pub image: Option<String>,
+    #[serde(rename = "b")]
+    pub banner: Option<String>,
+}
+
+/// The floor price of a collection and the last sale of the collection or token, in the native currency of the chain.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Prices {
+    #[serde(rename = "f")]
+    pub floor: Option<f64>,
+    #[serde(rename = "l")]
+    pub last_sale: Option<f64>,
+}
+
+/// The floor prices (in the native currency of the chain) of each value, by trait type.
+pub type TraitFloors = HashMap<String, HashMap<String, f64>>;
+
+pub enum Message {
+    Completed(Response, HandlerId),
+}
+
+impl gloo_worker::Worker for Worker {
+    type Reach = Public<Self>;
+    type Message = Message;
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: WorkerLink<Self>) -> Self {
+        log::trace!("creating worker...");
+        Self { link }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match msg {
+            Message::Completed(response, id) => self.link.respond(id, response),
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match msg {
+            Request::TraitFloors { api, collection } => {
+                log::trace!("requesting trait floors for {collection}...");
+                self.link.send_future(async move {
+                    Message::Completed(
+                        match trait_floors(&api, &collection).await {
+                            Some(floors) => Response::TraitFloors(collection, floors),
+                            None => Response::Failed(collection),
+                        },
+                        id,
+                    )
+                });
+            }
+            Request::Floor { api, collection } => {
+                log::trace!("requesting floor for {collection}...");
+                self.link.send_future(async move {
+                    Message::Completed(
+                        match details(&api, None, &collection)
+                            .await
+                            .and_then(|details| details.pointer("/floorAsk/price").and_then(price))
+                        {
+                            Some(floor) => Response::Floor(collection, floor),
+                            None => Response::Failed(collection),
+                        },
+                        id,
+                    )
+                });
+            }
+            Request::Details { api, collection } => {
+                log::trace!("requesting details for {collection}...");
+                self.link.send_future(async move {
+                    Message::Completed(
+                        match details(&api, None, &collection).await {
+                            Some(details) => {
+                                let text = |pointer: &str| {
+                                    details
+                                        .pointer(pointer)
+                                        .and_then(Value::as_str)
+                                        .filter(|value| !value.is_empty())
+                                        .map(|value| value.to_string())
+                                };
+                                Response::Details(
+                                    collection,
+                                    Details {
+                                        name: text("/name"),
+                                        description: text("/description"),
+                                        image: text("/image"),
+                                        banner: text("/banner"),
+                                    },
+                                )
+                            }
+                            None => Response::Failed(collection),
+                        },
+                        id,
+                    )
+                });
+            }
+            Request::Prices {
+                api,
+                api_key,
+                collection,
+                token,
+            } => {
+                log::trace!("requesting prices for {collection}...");
+                self.link.send_future(async move {
+                    let floor = details(&api, Some(&api_key), &collection).await;
+                    let last_sale = last_sale(&api, &api_key, &collection, token).await;
+                    Message::Completed(
+                        match (floor, last_sale) {
+                            // Both requests failing indicates the api (or key) is unavailable
+                            (None, None) => Response::Failed(collection),
+                            (floor, last_sale) => Response::Prices(
+                                collection,
+                                token,
+                                Prices {
+                                    floor: floor.and_then(|details| {
+                                        details.pointer("/floorAsk/price").and_then(price)
+                                    }),
+                                    last_sale: last_sale.flatten(),
+                                },
+                            ),
+                        },
+                        id,
+                    )
+                });
+            }
+        }
+    }
+
+    fn name_of_resource() -> &'static str {
+        "market.js"
+    }
+}
+
+async fn trait_floors(api: &str, collection: &str) -> Option<TraitFloors> {
+    let url = format!("{api}collections/{collection}/attributes/all/v4");
+    let response = match crate::fetch::get(&url).await {
+        Ok(response) if response.status() == 200 => response,
+        Ok(response) => {
+            log::error!(
+                "trait floors request failed: {} {}",
+                response.status(),
+                response.status_text()
+            );
+            return None;
+        }
+        Err(e) => {
+            log::error!("{:?}", e);
+            return None;
+        }
+    };
+
+    let attributes = match response
+        .text()
+        .await
+        .map(|text| serde_json::from_str::<Attributes>(&text))
+    {
+        Ok(Ok(attributes)) => attributes,
+        Ok(Err(e)) => {
+            log::error!("an error occurred parsing the trait floors: {:?}", e);
+            return None;
+        }
+        Err(e) => {
+            log::error!("{:?}", e);
+            return None;
+        }
+    };
+
+    Some(
+        attributes
+            .attributes
+            .into_iter()
+            .map(|attribute| {
+                (
+                    attribute.key,
+                    attribute
+                        .values
+                        .into_iter()
+                        .filter_map(|value| {
+                            value
+                                .floor_ask_price
+                                .as_ref()
+                                .and_then(price)
+                                .map(|price| (value.value, price))
+                        })
+                        .collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Requests the collection from the market data api, returning the raw value so that the fields required can be read.
+async fn details(api: &str, api_key: Option<&str>, collection: &str) -> Option<Value> {
+    let url = format!("{api}collections/v5?id={collection}");
+    let headers: Vec<_> = api_key
+        .map(|key| ("x-api-key".to_string(), key.to_string()))
+        .into_iter()
+        .collect();
+    let response = match crate::fetch::get_with_headers(&url, &headers).await {
+        Ok(response) if response.status() == 200 => response,
+        Ok(response) => {
+            log::error!(
+                "collection request failed: {} {}",
+                response.status(),
+                response.status_text()
+            );
+            return None;
+        }
+        Err(e) => {
+            log::error!("{:?}", e);
+            return None;
+        }
+    };
+
+    match response
+        .text()
+        .await
+        .map(|text| serde_json::from_str::<Value>(&text))
+    {
+        Ok(Ok(mut collections)) => collections
+            .pointer_mut("/collections/0")
+            .map(|collection| collection.take()),
+        Ok(Err(e)) => {
+            log::error!("an error occurred parsing the collection: {:?}", e);
+            None
+        }
+        Err(e) => {
+            log::error!("{:?}", e);
+            None
+        }
+    }
+}
+
+/// Requests the most recent sale of the token, or of any token of the collection, from the market data api. Returns
+/// `Some(None)` when there have been no sales.
+async fn last_sale(
+    api: &str,
+    api_key: &str,
+    collection: &str,
+    token: Option<u32>,
+) -> Option<Option<f64>> {
+    let url = match token {
+        Some(token) => format!("{api}sales/v4?tokens={collection}:{token}&limit=1"),
+        None => format!("{api}sales/v4?collection={collection}&limit=1"),
+    };
+    let headers = [("x-api-key".to_string(), api_key.to_string())];
+    let response = match crate::fetch::get_with_headers(&url, &headers).await {
+        Ok(response) if response.status() == 200 => response,
+        Ok(response) => {
+            log::error!(
+                "sales request failed: {} {}",
+                response.status(),
+                response.status_text()
+            );
+            return None;
+        }
+        Err(e) => {
+            log::error!("{:?}", e);
+            return None;
+        }
+    };
+
+    match response
+        .text()
+        .await
+        .map(|text| serde_json::from_str::<Value>(&text))
+    {
+        Ok(Ok(sales)) => Some(sales.pointer("/sales/0/price").and_then(price)),
+        Ok(Err(e)) => {
+            log::error!("an error occurred parsing the sales: {:?}", e);
+            None
+        }
+        Err(e) => {
+            log::error!("{:?}", e);
+            None
+        }
+    }
+}
+
+/// Reads a price, which is either a plain number or an amount object depending on the api version.
+fn price(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.pointer("/amount/native").and_then(Value::as_f64))
+        .or_else(|| value.pointer("/amount/decimal").and_then(Value::as_f64))
+}
+
+#[derive(Deserialize)]
+struct Attributes {
+    attributes: Vec<Attribute>,
+}
+
+#[derive(Deserialize)]
+struct Attribute {
+    key: String,
+    values: Vec<AttributeValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttributeValue {
+    value: String,
+    floor_ask_price: Option<Value>,
+}
@@ -0,0 +1,130 @@
+use crate::clock;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+use url::Url;
+
+/// The number of consecutive failures after which requests to a host are suspended.
+const THRESHOLD: u8 = 5;
+/// The period (in milliseconds) for which requests to a host are suspended before being attempted again.
+const COOLDOWN: f64 = 60_000.0;
+/// Public IPFS gateways, used as alternatives when the gateway of a token uri is failing.
+const IPFS_GATEWAYS: [&str; 3] = ["ipfs.io", "cloudflare-ipfs.com", "dweb.link"];
+
+static HOSTS: Lazy<Mutex<HashMap<String, Host>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+struct Host {
+    /// The number of consecutive failed requests.
+    failures: u8,
+    /// The time (in milliseconds) at which requests to the host were suspended.
+    opened: Option<f64>,
+}
+
+/// Whether requests to the host are currently suspended.
+pub(super) fn is_open(host: &str) -> bool {
+    let mut hosts = HOSTS.lock().unwrap();
+    let state = match hosts.get_mut(host) {
+        Some(state) => state,
+        None => return false,
+    };
+    match state.opened {
+        Some(opened) if clock::now() - opened < COOLDOWN => true,
+        Some(_) => {
+            // Cooldown elapsed, so allow a trial request: a single further failure suspends the host again
+            log::trace!("cooldown elapsed, resuming requests to {host}...");
+            state.opened = None;
+            state.failures = THRESHOLD - 1;
+            false
+        }
+        None => false,
+    }
+}
+
+pub(super) fn failed(host: &str) {
+    let mut hosts = HOSTS.lock().unwrap();
+    let state = hosts.entry(host.to_string()).or_default();
+    state.failures = state.failures.saturating_add(1);
+    if state.failures >= THRESHOLD && state.opened.is_none() {
+        log::warn!(
+            "{host} failed {} times in a row, suspending requests...",
+            state.failures
+        );
+        state.opened = Some(clock::now());
+    }
+}
+
+pub(super) fn succeeded(host: &str) {
+    HOSTS.lock().unwrap().remove(host);
+}
+
+/// The hosts currently suspended, along with the time (in milliseconds) at which they were suspended.
+pub(super) fn suspended() -> HashMap<String, f64> {
+    HOSTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(host, state)| state.opened.map(|opened| (host.clone(), opened)))
+        .collect()
+}
+
+/// Restores previously suspended hosts, ignoring those whose cooldown has since elapsed.
+pub(super) fn restore(suspended: HashMap<String, f64>) {
+    let now = clock::now();
+    let mut hosts = HOSTS.lock().unwrap();
+    for (host, opened) in suspended
+        .into_iter()
+        .filter(|(_, opened)| now - opened < COOLDOWN)
+    {
+        hosts.insert(
+            host,
+            Host {
+                failures: THRESHOLD,
+                opened: Some(opened),
+            },
+        );
+    }
+}
+
+/// Rewrites an IPFS gateway uri to an alternative gateway whose requests are not currently suspended.
+pub(super) fn alternative(uri: &str) -> Option<String> {
+    let mut url = Url::parse(uri).ok()?;
+    if !url.path().starts_with("/ipfs/") {
+        return None;
+    }
+    let host = url.host_str()?.to_string();
+    let gateway = IPFS_GATEWAYS
+        .iter()
+        .find(|gateway| **gateway != host && !is_open(gateway))?;
+    url.set_host(Some(gateway)).ok()?;
+    Some(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{failed, is_open, COOLDOWN, THRESHOLD};
+    use crate::clock::{self, Fixed};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn resumes_after_cooldown() {
+        let now = Rc::new(Fixed::new(1_000_000.0));
+        clock::set_clock(now.clone());
+        let host = "breaker.example.com";
+
+        for _ in 0..THRESHOLD {
+            assert!(!is_open(host));
+            failed(host);
+        }
+        assert!(is_open(host));
+
+        now.advance(Duration::from_millis(COOLDOWN as u64 - 1));
+        assert!(is_open(host));
+        now.advance(Duration::from_millis(1));
+        assert!(!is_open(host));
+
+        // A single further failure suspends the host again
+        failed(host);
+        assert!(is_open(host));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::clock;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+
+/// The period (in milliseconds) after which a host requiring the CORS proxy is requested directly again, as the
+/// failure which led to it being proxied may have been transient.
+const REPROBE_INTERVAL: f64 = 3_600_000.0;
+/// Hosts known to send permissive CORS headers (along with their subdomains), so whose failures are never attributed to
+/// CORS.
+const PERMISSIVE: [&str; 7] = [
+    "arweave.net",
+    "cloudflare-ipfs.com",
+    "dweb.link",
+    "gateway.pinata.cloud",
+    "ipfs.io",
+    "nftstorage.link",
+    "w3s.link",
+];
+
+/// The hosts requiring the proxy, along with the time (in milliseconds) at which they last required it.
+static PROXIED: Lazy<Mutex<HashMap<String, f64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Whether requests to the host are routed via the proxy. Once the reprobe interval has elapsed, a single request is
+/// made directly, with any others continuing via the proxy until the host is either found to no longer require it or
+/// is proxied again.
+pub(super) fn requires_proxy(host: &str) -> bool {
+    let mut proxied = PROXIED.lock().unwrap();
+    match proxied.get_mut(host) {
+        Some(since) if clock::now() - *since < REPROBE_INTERVAL => true,
+        Some(since) => {
+            log::trace!("reprobing {host} without the cors proxy...");
+            *since = clock::now();
+            false
+        }
+        None => false,
+    }
+}
+
+/// Records that a request to the host only succeeded via the proxy, unless the host is known to be permissive.
+pub(super) fn proxied(host: &str) {
+    if is_permissive(host) {
+        log::trace!("{host} is permissive, so is not proxied for future requests");
+        return;
+    }
+    log::trace!("cors proxy successful, adding host to cors list for future requests");
+    PROXIED
+        .lock()
+        .unwrap()
+        .insert(host.to_string(), clock::now());
+}
+
+/// Records that a direct request to the host succeeded, so that it is no longer proxied.
+pub(super) fn succeeded(host: &str) {
+    if PROXIED.lock().unwrap().remove(host).is_some() {
+        log::info!("{host} no longer requires the cors proxy");
+    }
+}
+
+/// Whether the host is the origin of the app, or is known to send permissive CORS headers.
+pub(super) fn is_permissive(host: &str) -> bool {
+    is_known(host) || origin().map_or(false, |origin| origin == host)
+}
+
+fn is_known(host: &str) -> bool {
+    PERMISSIVE.iter().any(|permissive| {
+        host == *permissive
+            || host
+                .strip_suffix(permissive)
+                .map_or(false, |subdomain| subdomain.ends_with('.'))
+    })
+}
+
+/// The host of the app, from the location of the worker.
+#[cfg(target_arch = "wasm32")]
+fn origin() -> Option<String> {
+    let location = js_sys::Reflect::get(&js_sys::global(), &"location".into()).ok()?;
+    js_sys::Reflect::get(&location, &"host".into())
+        .ok()?
+        .as_string()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn origin() -> Option<String> {
+    None
+}
+
+/// The hosts requiring the proxy, along with the time (in milliseconds) at which they last required it.
+pub(super) fn hosts() -> HashMap<String, f64> {
+    PROXIED.lock().unwrap().clone()
+}
+
+/// Restores the hosts previously requiring the proxy, excluding any since found to be permissive.
+pub(super) fn restore(hosts: HashMap<String, f64>) {
+    PROXIED
+        .lock()
+        .unwrap()
+        .extend(hosts.into_iter().filter(|(host, _)| !is_known(host)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_known, requires_proxy, succeeded, PROXIED, REPROBE_INTERVAL};
+    use crate::clock::{self, Fixed};
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    #[test]
+    fn reprobes_after_interval() {
+        let now = Rc::new(Fixed::new(1_000_000.0));
+        clock::set_clock(now.clone());
+        let host = "cors.example.com";
+        assert!(!requires_proxy(host));
+        PROXIED
+            .lock()
+            .unwrap()
+            .insert(host.to_string(), clock::now());
+        assert!(requires_proxy(host));
+
+        now.advance(Duration::from_millis(REPROBE_INTERVAL as u64));
+        // A single request is made directly, with any others in the meantime continuing via the proxy
+        assert!(!requires_proxy(host));
+        assert!(requires_proxy(host));
+
+        succeeded(host);
+        assert!(!requires_proxy(host));
+    }
+
+    #[test]
+    fn knows_permissive_hosts() {
+        assert!(is_known("ipfs.io"));
+        assert!(is_known(
+            "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi.ipfs.dweb.link"
+        ));
+        assert!(!is_known("notipfs.io"));
+        assert!(!is_known("api.example.com"));
+    }
+}
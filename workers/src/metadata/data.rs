@@ -0,0 +1,76 @@
+/// Whether the uri is a data uri, such as the `data:application/json;base64,...` returned by the tokenURI of
+/// collections storing their metadata on-chain.
+pub fn is_data_uri(uri: &str) -> bool {
+    uri.get(..5)
+        .map_or(false, |scheme| scheme.eq_ignore_ascii_case("data:"))
+}
+
+/// Decodes the content of the data uri, which is either base64 or percent-encoded.
+pub fn decode_data_uri(uri: &str) -> Option<Vec<u8>> {
+    if !is_data_uri(uri) {
+        return None;
+    }
+    let (media_type, content) = uri[5..].split_once(',')?;
+    if media_type.to_ascii_lowercase().ends_with(";base64") {
+        // Whitespace is permitted within base64 content, but not by the decoder
+        let content: String = content
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect();
+        return base64::decode(percent_decode(&content)).ok();
+    }
+    Some(percent_decode(content))
+}
+
+/// Decodes any percent-encoded octets, leaving any invalid sequences (e.g. an unencoded `%`) as is.
+fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(octet) = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(octet);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_data_uri, is_data_uri};
+
+    #[test]
+    fn decodes_base64() {
+        let uri = "data:application/json;base64,eyJuYW1lIjogIk9uLWNoYWluICMxIn0=";
+        assert!(is_data_uri(uri));
+        assert_eq!(
+            Some(r#"{"name": "On-chain #1"}"#.as_bytes().to_vec()),
+            decode_data_uri(uri)
+        );
+    }
+
+    #[test]
+    fn decodes_percent_encoded() {
+        let uri =
+            r#"data:application/json;utf8,{"name":%20"On-chain%20%231",%20"rarity":%20"100%"}"#;
+        assert_eq!(
+            Some(r#"{"name": "On-chain #1", "rarity": "100%"}"#.as_bytes().to_vec()),
+            decode_data_uri(uri)
+        );
+    }
+
+    #[test]
+    fn ignores_other_uris() {
+        assert!(!is_data_uri("https://example.com/data:1"));
+        assert_eq!(None, decode_data_uri("ipfs://cid/1"));
+    }
+}
@@ -4,7 +4,7 @@ use serde::{
     ser::SerializeStruct,
     Deserialize, Deserializer, Serialize, Serializer,
 };
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::{fmt, marker::PhantomData};
 
 const BOOST_NUMBER: &str = "boost_number";
@@ -15,6 +15,17 @@ const MAX_VALUE: &str = "max_value";
 const NUMBER: &str = "number";
 const TRAIT_TYPE: &str = "trait_type";
 const VALUE: &str = "value";
+/// The fields of the metadata whose values are strings.
+const STRING_FIELDS: [&str; 8] = [
+    "name",
+    "description",
+    "image",
+    "external_url",
+    "background_color",
+    "created_by",
+    "animation_url",
+    "youtube_url",
+];
 
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Metadata {
@@ -247,9 +258,195 @@ fn sequence_or_map<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Att
     deserializer.deserialize_any(SequenceOrMap(PhantomData))
 }
 
+/// Parses malformed metadata leniently, recovering what it can: trailing commas are removed, scalar values are
+/// converted to strings where strings are expected (and numeric strings to numbers where numbers are expected), and
+/// attributes are accepted as a bare object or JSON string, with any which cannot be salvaged dropped. Returns the
+/// metadata along with the fields coerced.
+pub(crate) fn lenient(json: &str) -> serde_json::Result<(Metadata, Vec<String>)> {
+    let mut coerced = Vec::new();
+    let value = match serde_json::from_str(json) {
+        Ok(value) => value,
+        Err(e) => match serde_json::from_str(&strip_trailing_commas(json)) {
+            Ok(value) => {
+                coerced.push("trailing commas".to_string());
+                value
+            }
+            Err(_) => return Err(e),
+        },
+    };
+    coerce(value, coerced)
+}
+
+/// Parses the metadata from a JSON value leniently, as per [lenient].
+pub(crate) fn coerce(
+    mut value: Value,
+    mut coerced: Vec<String>,
+) -> serde_json::Result<(Metadata, Vec<String>)> {
+    if let Some(metadata) = value.as_object_mut() {
+        for field in STRING_FIELDS {
+            match metadata.get(field) {
+                Some(Value::Number(_) | Value::Bool(_)) => {
+                    let value = metadata[field].to_string();
+                    metadata.insert(field.to_string(), Value::String(value));
+                    coerced.push(field.to_string());
+                }
+                // The image is required, so any missing image is left empty
+                Some(Value::Null) | None if field == "image" => {
+                    metadata.insert(field.to_string(), Value::String(String::new()));
+                    coerced.push(field.to_string());
+                }
+                _ => {}
+            }
+        }
+        let attributes = metadata.remove("attributes").unwrap_or(Value::Null);
+        metadata.insert(
+            "attributes".to_string(),
+            coerce_attributes(attributes, &mut coerced),
+        );
+    }
+    serde_json::from_value(value).map(|metadata| (metadata, coerced))
+}
+
+fn coerce_attributes(attributes: Value, coerced: &mut Vec<String>) -> Value {
+    let attributes = match attributes {
+        Value::Array(attributes) => attributes,
+        // Attributes encoded as a JSON string, or as a bare object of trait types and values
+        Value::String(json) => match serde_json::from_str::<Value>(&json) {
+            Ok(attributes @ (Value::Array(_) | Value::Object(_))) => {
+                coerced.push("attributes".to_string());
+                return coerce_attributes(attributes, coerced);
+            }
+            _ => {
+                coerced.push("attributes".to_string());
+                Vec::new()
+            }
+        },
+        Value::Object(map) => {
+            coerced.push("attributes".to_string());
+            map.into_iter()
+                .map(|(trait_type, value)| {
+                    Value::Object(Map::from_iter([
+                        (TRAIT_TYPE.to_string(), Value::String(trait_type)),
+                        (VALUE.to_string(), value),
+                    ]))
+                })
+                .collect()
+        }
+        _ => {
+            coerced.push("attributes".to_string());
+            Vec::new()
+        }
+    };
+    Value::Array(
+        attributes
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, attribute)| coerce_attribute(i, attribute, coerced))
+            .collect(),
+    )
+}
+
+fn coerce_attribute(i: usize, attribute: Value, coerced: &mut Vec<String>) -> Option<Value> {
+    let mut attribute = match attribute {
+        Value::Object(attribute) => attribute,
+        _ => {
+            coerced.push(format!("attributes[{i}]"));
+            return None;
+        }
+    };
+    if !attribute.contains_key(VALUE) {
+        coerced.push(format!("attributes[{i}]"));
+        return None;
+    }
+    if let Some(trait_type) = attribute.get(TRAIT_TYPE).filter(|t| !t.is_string()) {
+        let trait_type = match trait_type {
+            Value::Null => "Attribute".to_string(),
+            trait_type => trait_type.to_string(),
+        };
+        attribute.insert(TRAIT_TYPE.to_string(), Value::String(trait_type));
+        coerced.push(format!("attributes[{i}].{TRAIT_TYPE}"));
+    }
+    if let Some(max_value) = attribute.get(MAX_VALUE).filter(|v| !v.is_u64()) {
+        match max_value
+            .as_str()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+        {
+            Some(max_value) => attribute.insert(MAX_VALUE.to_string(), max_value.into()),
+            None => attribute.remove(MAX_VALUE),
+        };
+        coerced.push(format!("attributes[{i}].{MAX_VALUE}"));
+    }
+
+    let display_type = match attribute.get(DISPLAY_TYPE) {
+        Some(Value::String(display_type)) => display_type.clone(),
+        Some(_) => {
+            attribute.remove(DISPLAY_TYPE);
+            coerced.push(format!("attributes[{i}].{DISPLAY_TYPE}"));
+            return Some(Value::Object(attribute));
+        }
+        None => return Some(Value::Object(attribute)),
+    };
+    let value = &attribute[VALUE];
+    let number = value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|v| v.trim().parse::<f64>().ok()));
+    let valid = match display_type.as_str() {
+        NUMBER => value.is_i64(),
+        DATE => value.is_u64(),
+        BOOST_NUMBER | BOOST_PERCENTAGE => value.is_number(),
+        _ => return Some(Value::Object(attribute)),
+    };
+    if valid {
+        return Some(Value::Object(attribute));
+    }
+    // Numeric values which are not numbers of the expected type are converted, or otherwise treated as strings
+    let number = match (display_type.as_str(), number) {
+        (NUMBER, Some(number)) => Some(Value::from(number.round() as i64)),
+        (DATE, Some(number)) if number >= 0.0 => Some(Value::from(number.round() as u64)),
+        (BOOST_NUMBER | BOOST_PERCENTAGE, Some(number)) => Some(Value::from(number)),
+        _ => None,
+    };
+    match number {
+        Some(number) => {
+            attribute.insert(VALUE.to_string(), number);
+            coerced.push(format!("attributes[{i}].{VALUE}"));
+        }
+        None => {
+            attribute.remove(DISPLAY_TYPE);
+            coerced.push(format!("attributes[{i}].{DISPLAY_TYPE}"));
+        }
+    }
+    Some(Value::Object(attribute))
+}
+
+/// Removes any commas trailing the last element of an array or object, outside of strings.
+fn strip_trailing_commas(json: &str) -> String {
+    let mut stripped = String::with_capacity(json.len());
+    let (mut in_string, mut escaped) = (false, false);
+    for (i, c) in json.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = json[i + 1..].trim_start().chars().next();
+            if matches!(next, Some('}' | ']')) {
+                continue;
+            }
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::metadata::json::Attribute;
+    use crate::metadata::json::{lenient, Attribute};
 
     #[test]
     fn attribute_handles_missing_trait_type() {
@@ -263,4 +460,59 @@ mod tests {
             panic!("Attribute was not deserialised as expected")
         }
     }
+
+    #[test]
+    fn parses_malformed_metadata_leniently() {
+        let json = r#"{
+            "name": 42,
+            "image": "ipfs://image",
+            "attributes": [
+                { "trait_type": "Level", "display_type": "number", "value": "7", "max_value": "10" },
+                { "trait_type": "Speed", "display_type": "number", "value": "fast" },
+                { "trait_type": "Hat, \"Top\"", "value": "Yes", },
+                "orphan",
+            ],
+        }"#;
+        let (metadata, coerced) = lenient(json).expect("unable to parse metadata leniently");
+        assert_eq!(Some("42".to_string()), metadata.name);
+        assert_eq!(3, metadata.attributes.len());
+        assert!(matches!(
+            metadata.attributes[0],
+            Attribute::Number {
+                value: 7,
+                max_value: Some(10),
+                ..
+            }
+        ));
+        assert!(
+            matches!(&metadata.attributes[1], Attribute::String { value, .. } if value == "fast")
+        );
+        assert!(
+            matches!(&metadata.attributes[2], Attribute::String { trait_type, .. } if trait_type == "Hat, \"Top\"")
+        );
+        assert_eq!(
+            vec![
+                "trailing commas",
+                "name",
+                "attributes[0].max_value",
+                "attributes[0].value",
+                "attributes[1].display_type",
+                "attributes[3]"
+            ],
+            coerced
+        );
+    }
+
+    #[test]
+    fn parses_bare_attributes_leniently() {
+        let json = r#"{ "image": "ipfs://image", "attributes": { "Eyes": "Blue", "Age": 3 } }"#;
+        let (metadata, coerced) = lenient(json).expect("unable to parse metadata leniently");
+        assert_eq!(2, metadata.attributes.len());
+        assert!(metadata
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute,
+            Attribute::String { trait_type, value } if trait_type == "Age" && value == "3")));
+        assert_eq!(vec!["attributes"], coerced);
+    }
 }
@@ -1,24 +1,52 @@
 use async_recursion::async_recursion;
 use gloo_net::Error;
 use gloo_worker::{HandlerId, Public, WorkerLink};
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, sync::Mutex};
+use std::collections::{HashMap, VecDeque};
 use url::{ParseError, Url};
 
+/// Suspension of requests to hosts which are repeatedly failing
+mod breaker;
+/// Hosts whose requests are routed via the CORS proxy, which are periodically requested directly again
+mod cors;
+/// Metadata encoded within data uris, as stored on-chain
+mod data;
 /// JSON-specific serialisation/deserialisation, as workers use bincode
 mod json;
+/// ERC-1155 metadata uri templates
+mod template;
+
+pub use data::{decode_data_uri, is_data_uri};
+
+/// The gateway to which Arweave (`ar://`) uris are converted, as browsers cannot request them directly.
+pub const ARWEAVE_GATEWAY: &str = "arweave.net";
+pub use template::{is_template, substitute};
 
 pub struct Worker {
     link: WorkerLink<Self>,
+    /// The requester to which changes to the knowledge of hosts are returned, along with the last returned.
+    subscriber: Option<HandlerId>,
+    hosts: Hosts,
+    /// The number of requests of normal priority currently in progress.
+    active: usize,
+    /// Requests of low priority, deferred until no requests of normal priority are in progress.
+    deferred: VecDeque<Message>,
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct Request {
-    pub url: String,
-    pub token: Option<u32>,
-    /// An optional url to be used as a CORS proxy, should the primary request fail
-    pub cors_proxy: Option<String>,
+pub enum Request {
+    Metadata {
+        url: String,
+        token: Option<u32>,
+        /// An optional url to be used as a CORS proxy, should the primary request fail
+        cors_proxy: Option<String>,
+        /// Overrides specific to the collection, which take precedence over the defaults.
+        overrides: Overrides,
+        priority: Priority,
+    },
+    /// Loads the knowledge of hosts learned in previous sessions, with any subsequent changes returned to the
+    /// requester so that they can be persisted.
+    Hosts(Hosts),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -26,16 +54,169 @@ pub enum Response {
     Completed(String, Option<u32>, Metadata),
     NotFound(String, Option<u32>),
     Failed(String, Option<u32>),
+    Hosts(Hosts),
+}
+
+/// The priority of a metadata request.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Priority {
+    Normal,
+    /// Prefetching of metadata which may be required shortly, only requested once all requests of normal priority
+    /// have completed.
+    Low,
+}
+
+/// Overrides of how the metadata of a collection is requested, for collections which only work via a specific gateway
+/// or proxy, or which serve metadata from POST/GraphQL endpoints.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Overrides {
+    /// The IPFS gateway host (e.g. `cloudflare-ipfs.com`) to be used in place of that of the base uri.
+    #[serde(rename = "g", default)]
+    pub gateway: Option<String>,
+    /// A proxy through which all requests are routed, rather than only those failing due to CORS.
+    #[serde(rename = "p", default)]
+    pub proxy: Option<String>,
+    /// Additional request headers.
+    #[serde(rename = "h", default)]
+    pub headers: Vec<(String, String)>,
+    /// The request method (e.g. `POST`), in place of GET.
+    #[serde(rename = "m", default)]
+    pub method: Option<String>,
+    /// The request body (e.g. a GraphQL query), with any `{id}` replaced by the token identifier.
+    #[serde(rename = "b", default)]
+    pub body: Option<String>,
+    /// A JSON pointer (e.g. `/data/token/metadata`) to the metadata within the response, for endpoints which wrap it.
+    #[serde(rename = "r", default)]
+    pub pointer: Option<String>,
+    /// Whether malformed metadata is parsed leniently, recovering what it can rather than failing.
+    #[serde(rename = "l", default)]
+    pub lenient: bool,
+    /// The minimum interval (in milliseconds) between the requests made whilst indexing, in place of the default.
+    #[serde(rename = "i", default)]
+    pub interval: Option<u32>,
+}
+
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        self == &Overrides::default()
+    }
+
+    /// Rewrites the uri to the overridden gateway, where served via IPFS.
+    pub(crate) fn gateway(&self, uri: &str) -> String {
+        if let (Some(gateway), Ok(mut url)) = (&self.gateway, Url::parse(uri)) {
+            if url.path().starts_with("/ipfs/") && url.set_host(Some(gateway)).is_ok() {
+                return url.to_string();
+            }
+        }
+        uri.to_string()
+    }
+
+    pub(crate) fn method(&self) -> &str {
+        self.method.as_deref().unwrap_or("GET")
+    }
+
+    /// The request body for the token, if any.
+    pub(crate) fn body(&self, token: Option<u32>) -> Option<String> {
+        self.body.as_ref().map(|body| match token {
+            Some(token) => body.replace("{id}", &token.to_string()),
+            None => body.clone(),
+        })
+    }
+
+    /// Parses the metadata from the response, logging any fields coerced whilst parsing leniently.
+    pub(crate) fn parse(&self, response: &str) -> serde_json::Result<Metadata> {
+        let (metadata, coerced) = self.parse_coerced(response)?;
+        if !coerced.is_empty() {
+            log::debug!("metadata parsed leniently, coercing {}", coerced.join(", "));
+        }
+        Ok(metadata)
+    }
+
+    /// Parses the metadata from the response, located via the pointer where overridden, along with any fields coerced
+    /// whilst parsing leniently. Metadata located within the response may itself be a JSON string.
+    pub(crate) fn parse_coerced(
+        &self,
+        response: &str,
+    ) -> serde_json::Result<(Metadata, Vec<String>)> {
+        let parse = |json: &str| match self.lenient {
+            true => json::lenient(json),
+            false => serde_json::from_str::<json::Metadata>(json).map(|m| (m, Vec::new())),
+        };
+        let pointer = match &self.pointer {
+            Some(pointer) => pointer,
+            None => return parse(response).map(|(m, coerced)| (m.into(), coerced)),
+        };
+        let response: serde_json::Value = serde_json::from_str(response)?;
+        let (metadata, coerced) = match response.pointer(pointer) {
+            Some(serde_json::Value::String(json)) => parse(json),
+            Some(value) if self.lenient => json::coerce(value.clone(), Vec::new()),
+            Some(value) => {
+                serde_json::from_value::<json::Metadata>(value.clone()).map(|m| (m, Vec::new()))
+            }
+            None => Err(serde::de::Error::custom(format!(
+                "no value found at {pointer}"
+            ))),
+        }?;
+        Ok((metadata.into(), coerced))
+    }
+
+    /// Builds the request for the uri, rewriting it to the gateway and routing it via the proxy where overridden.
+    fn request(&self, uri: String) -> Uri {
+        let effective = self.gateway(&uri);
+        match &self.proxy {
+            Some(proxy) => Uri::Proxied {
+                uri: format!("{proxy}{effective}"),
+                original: uri,
+            },
+            None if effective != uri => Uri::Alternative {
+                uri: effective,
+                original: uri,
+            },
+            None => Uri::Standard { uri },
+        }
+    }
+}
+
+/// The knowledge of hosts learned whilst requesting metadata.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Hosts {
+    /// Hosts which require the CORS proxy, along with the time (in milliseconds) at which they last required it.
+    #[serde(rename = "cp", default)]
+    pub cors: HashMap<String, f64>,
+    /// Hosts suspended following repeated failures, along with the time (in milliseconds) at which they were
+    /// suspended.
+    #[serde(rename = "s")]
+    pub suspended: HashMap<String, f64>,
+}
+
+impl Hosts {
+    pub(crate) fn current() -> Self {
+        Self {
+            cors: cors::hosts(),
+            suspended: breaker::suspended(),
+        }
+    }
 }
 
 pub enum Message {
     /// Requests metadata at the specified uri.
-    Request(String, Option<u32>, HandlerId, Option<String>),
+    Request(
+        String,
+        Option<u32>,
+        HandlerId,
+        Option<String>,
+        Overrides,
+        Priority,
+    ),
+    /// The result of a request, along with its priority.
+    Resolved(Box<Message>, Priority),
     /// Processes the resulting metadata before completing.
     Process {
         metadata: Metadata,
         /// The (requested) metadata uri
         uri: String,
+        /// The final url of the metadata, where the request was redirected.
+        resolved: Option<String>,
         token: Option<u32>,
         id: HandlerId,
     },
@@ -53,49 +234,110 @@ impl gloo_worker::Worker for Worker {
 
     fn create(link: WorkerLink<Self>) -> Self {
         log::trace!("creating worker...");
-        Self { link }
+        Self {
+            link,
+            subscriber: None,
+            hosts: Hosts::default(),
+            active: 0,
+            deferred: VecDeque::new(),
+        }
     }
 
     fn update(&mut self, msg: Self::Message) {
         log::trace!("updating...");
         match msg {
-            Message::Request(uri, token, id, cors_proxy) => {
+            Message::Request(uri, token, id, cors_proxy, overrides, priority) => {
                 log::trace!("requesting {uri}...");
+                if priority == Priority::Normal {
+                    self.active += 1;
+                }
                 self.link.send_future(async move {
-                    request_metadata(Uri::Standard { uri }, token, id, cors_proxy).await
+                    // Overridden proxy replaces the cors proxy, so is not also used as a fallback
+                    let cors_proxy = cors_proxy.filter(|_| overrides.proxy.is_none());
+                    let request = overrides.request(uri);
+                    let result = request_metadata(request, token, id, cors_proxy, overrides).await;
+                    Message::Resolved(Box::new(result), priority)
                 });
             }
+            Message::Resolved(result, priority) => {
+                if priority == Priority::Normal {
+                    self.active -= 1;
+                }
+                self.update(*result);
+                self.dequeue();
+            }
             Message::Process {
                 metadata,
                 uri,
+                resolved,
                 token,
                 id,
             } => {
                 log::trace!("processing");
-                // Process the metadata before returning as completed
-                let metadata = process(metadata, Url::parse(&uri).expect("could not parse url"));
+                // Process the metadata before returning as completed, with data uris having no base to resolve against
+                let metadata = match Url::parse(resolved.as_deref().unwrap_or(&uri)) {
+                    Ok(url) => process(metadata, url),
+                    Err(_) if is_data_uri(&uri) => metadata,
+                    Err(e) => panic!("could not parse url: {e:?}"),
+                };
                 self.update(Message::Completed(uri, token, metadata, id));
             }
             Message::Completed(url, token, metadata, id) => {
                 log::trace!("metadata completed");
                 self.link
                     .respond(id, Response::Completed(url, token, metadata));
+                self.publish();
             }
             Message::Redirect(_) => {}
             Message::Failed(url, token, id) => {
                 log::trace!("metadata failed at {url}");
                 self.link.respond(id, Response::Failed(url, token));
+                self.publish();
             }
             Message::NotFound(url, token, id) => {
                 log::trace!("metadata not found at {url}");
                 self.link.respond(id, Response::NotFound(url, token));
+                self.publish();
             }
         }
     }
 
     fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
-        log::trace!("request received for {}", msg.url);
-        self.update(Message::Request(msg.url, msg.token, id, msg.cors_proxy));
+        match msg {
+            Request::Metadata {
+                url,
+                token,
+                cors_proxy,
+                overrides,
+                priority,
+            } => {
+                log::trace!("request received for {url}");
+                // Substitute the token into any ERC-1155 template
+                let url = token
+                    .and_then(|token| substitute(&url, token))
+                    .unwrap_or(url);
+                let request = Message::Request(url, token, id, cors_proxy, overrides, priority);
+                match priority {
+                    Priority::Low if self.active > 0 => self.deferred.push_back(request),
+                    _ => self.update(request),
+                }
+            }
+            Request::Hosts(hosts) => {
+                restore(hosts);
+                self.subscriber = Some(id);
+                self.hosts = Hosts::current();
+            }
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        if self.subscriber == Some(id) {
+            self.subscriber = None;
+        }
+        // Deferred requests are no longer required once the requester has disconnected
+        self.deferred.retain(
+            |request| !matches!(request, Message::Request(_, _, handler, ..) if *handler == id),
+        );
     }
 
     fn name_of_resource() -> &'static str {
@@ -103,6 +345,77 @@ impl gloo_worker::Worker for Worker {
     }
 }
 
+impl Worker {
+    /// Starts any deferred requests of low priority, once no requests of normal priority are in progress.
+    fn dequeue(&mut self) {
+        if self.active == 0 {
+            while let Some(request) = self.deferred.pop_front() {
+                self.update(request);
+            }
+        }
+    }
+
+    /// Returns any changes to the knowledge of hosts to the subscriber, so that they can be persisted.
+    fn publish(&mut self) {
+        if let Some(subscriber) = self.subscriber {
+            let hosts = Hosts::current();
+            if hosts != self.hosts {
+                self.link
+                    .respond(subscriber, Response::Hosts(hosts.clone()));
+                self.hosts = hosts;
+            }
+        }
+    }
+}
+
+/// Loads the knowledge of hosts learned in previous sessions.
+pub(crate) fn restore(hosts: Hosts) {
+    log::trace!("loading {} cors hosts...", hosts.cors.len());
+    cors::restore(hosts.cors);
+    breaker::restore(hosts.suspended);
+}
+
+/// The outcome of requesting the metadata of a token from outside of the worker (e.g. by the indexer).
+pub(crate) enum Fetched {
+    /// The metadata, along with its final url where the request was redirected.
+    Completed(Metadata, Option<String>),
+    NotFound,
+    Failed,
+}
+
+/// Requests the metadata of the token at the url, as the worker would, on behalf of the requester.
+pub(crate) async fn fetch(
+    url: String,
+    token: u32,
+    id: HandlerId,
+    cors_proxy: Option<String>,
+    overrides: Overrides,
+) -> Fetched {
+    // Overridden proxy replaces the cors proxy, so is not also used as a fallback
+    let cors_proxy = cors_proxy.filter(|_| overrides.proxy.is_none());
+    let request = overrides.request(url);
+    match request_metadata(request, Some(token), id, cors_proxy, overrides).await {
+        Message::Process {
+            metadata,
+            uri,
+            resolved,
+            ..
+        } => match Url::parse(resolved.as_deref().unwrap_or(&uri)) {
+            // Relative uris within the metadata are resolved against the final url, where redirected
+            Ok(url) => Fetched::Completed(process(metadata, url), resolved),
+            Err(_) if is_data_uri(&uri) => Fetched::Completed(metadata, None),
+            Err(_) => Fetched::Failed,
+        },
+        Message::NotFound(..) => Fetched::NotFound,
+        _ => Fetched::Failed,
+    }
+}
+
+/// Parses the metadata from its JSON representation.
+pub fn parse(json: &str) -> serde_json::Result<Metadata> {
+    serde_json::from_str::<json::Metadata>(json).map(|metadata| metadata.into())
+}
+
 fn process(mut metadata: Metadata, url: Url) -> Metadata {
     // Adjust uris
     metadata.image = parse_uri(metadata.image, &url);
@@ -113,6 +426,17 @@ fn process(mut metadata: Metadata, url: Url) -> Metadata {
 }
 
 fn parse_uri(uri: String, base_uri: &Url) -> String {
+    // Inline content and objects held by the browser are used as is
+    if is_data_uri(&uri)
+        || uri
+            .get(..5)
+            .map_or(false, |s| s.eq_ignore_ascii_case("blob:"))
+    {
+        return uri;
+    }
+    if let Some(uri) = arweave(&uri) {
+        return uri;
+    }
     if let Err(e) = Url::parse(&uri) {
         // If uri is relative, a
         if matches!(e, ParseError::RelativeUrlWithoutBase) {
@@ -122,7 +446,14 @@ fn parse_uri(uri: String, base_uri: &Url) -> String {
     uri
 }
 
-static CORS_DOMAINS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+/// Converts an Arweave protocol address (e.g. `ar://<transaction>/1.png`) to the Arweave gateway.
+pub fn arweave(uri: &str) -> Option<String> {
+    let path = uri
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("ar://"))
+        .map(|_| &uri[5..])?;
+    Some(format!("https://{ARWEAVE_GATEWAY}/{path}"))
+}
 
 #[async_recursion(?Send)]
 async fn request_metadata(
@@ -130,25 +461,110 @@ async fn request_metadata(
     token: Option<u32>,
     id: HandlerId,
     cors_proxy: Option<String>,
+    overrides: Overrides,
 ) -> Message {
     log::trace!("requesting...");
 
+    // On-chain metadata is encoded within the uri itself, so is decoded rather than requested
+    if is_data_uri(request.original_uri()) {
+        let uri = request.original_uri().to_string();
+        let json = decode_data_uri(&uri).and_then(|content| String::from_utf8(content).ok());
+        return match json.map(|json| overrides.parse(&json)) {
+            Some(Ok(metadata)) => Message::Process {
+                metadata,
+                uri,
+                resolved: None,
+                token,
+                id,
+            },
+            Some(Err(e)) => {
+                log::error!("{:?}", e);
+                Message::Failed(
+                    "An error occurred parsing the metadata".to_string(),
+                    token,
+                    id,
+                )
+            }
+            None => Message::Failed(
+                "The metadata could not be decoded from the data uri".to_string(),
+                token,
+                id,
+            ),
+        };
+    }
+
     // Check if standard uri should use cors proxy (based on previous requests for same host)
     if let Uri::Standard { uri } = &request {
         if let Some(ref host) = request.host() {
-            if CORS_DOMAINS.lock().unwrap().contains(host) {
+            if cors::requires_proxy(host) {
                 if let Some(proxy) = &cors_proxy {
                     // Update request to use proxy, appending original uri to proxy address as parameter
                     log::trace!("using cors proxy...");
                     request = Uri::proxy(uri, proxy)
                 }
+            } else if breaker::is_open(host) {
+                // Host is failing, so serve from an alternative gateway where possible, otherwise via the proxy
+                match breaker::alternative(uri) {
+                    Some(alternative) => {
+                        log::trace!("{host} suspended, using alternative gateway...");
+                        request = Uri::Alternative {
+                            uri: alternative,
+                            original: uri.to_string(),
+                        }
+                    }
+                    None => match &cors_proxy {
+                        Some(proxy) => {
+                            log::trace!("{host} suspended, using cors proxy...");
+                            request = Uri::proxy(uri, proxy)
+                        }
+                        None => {
+                            return Message::Failed(
+                                format!("Requests to {host} are temporarily suspended following repeated failures"),
+                                token,
+                                id,
+                            )
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    let result = crate::fetch::request(
+        &request.effective_uri(),
+        overrides.method(),
+        overrides.body(token).as_deref(),
+        &overrides.headers,
+    )
+    .await;
+    if let Some(host) = request.effective_host() {
+        match &result {
+            Ok(response) if response.status() == 429 || response.status() >= 500 => {
+                breaker::failed(&host)
+            }
+            Ok(_) => {
+                breaker::succeeded(&host);
+                if matches!(request, Uri::Standard { .. }) {
+                    cors::succeeded(&host)
+                }
             }
+            // Assumed to be CORS related when a proxy is available, so only recorded should the proxy also fail.
+            // Permissive hosts do not fail due to CORS, so are always recorded.
+            Err(Error::JsError(_))
+                if cors_proxy.is_some()
+                    && matches!(request, Uri::Standard { .. })
+                    && !cors::is_permissive(&host) => {}
+            Err(_) => breaker::failed(&host),
         }
     }
 
-    match crate::fetch::get(&request.effective_uri()).await {
+    match result {
         Ok(response) => match response.status() {
             200 => {
+                // Record the final url of requests redirected elsewhere (e.g. by an api to IPFS), excluding those via
+                // a proxy or alternative gateway
+                let resolved = (matches!(request, Uri::Standard { .. }) && response.redirected())
+                    .then(|| response.url());
                 // Read response as text to handle empty result
                 match response.text().await {
                     Ok(response) => {
@@ -159,10 +575,11 @@ async fn request_metadata(
                                 id,
                             );
                         }
-                        match serde_json::from_str::<json::Metadata>(&response) {
+                        match overrides.parse(&response) {
                             Ok(metadata) => Message::Process {
-                                metadata: metadata.into(),
+                                metadata,
                                 uri: request.original_uri().to_string(),
+                                resolved,
                                 token,
                                 id,
                             },
@@ -213,12 +630,21 @@ async fn request_metadata(
                     if let Uri::Standard { uri } = &request {
                         if let Some(proxy) = &cors_proxy {
                             log::info!("request failed, re-attempting via cors proxy...");
-                            let proxied_result =
-                                request_metadata(Uri::proxy(uri, proxy), token, id, None).await;
-                            if !matches!(proxied_result, Message::Failed(_, _, _)) {
-                                if let Some(host) = request.host() {
-                                    log::trace!("cors proxy successful, adding host to cors list for future requests");
-                                    CORS_DOMAINS.lock().unwrap().insert(host);
+                            let proxied_result = request_metadata(
+                                Uri::proxy(uri, proxy),
+                                token,
+                                id,
+                                None,
+                                overrides,
+                            )
+                            .await;
+                            if let Some(host) = request.host() {
+                                if matches!(proxied_result, Message::Failed(_, _, _)) {
+                                    if !cors::is_permissive(&host) {
+                                        breaker::failed(&host);
+                                    }
+                                } else {
+                                    cors::proxied(&host);
                                 }
                             }
 
@@ -251,8 +677,18 @@ async fn request_metadata(
 }
 
 enum Uri {
-    Standard { uri: String },
-    Proxied { uri: String, original: String },
+    Standard {
+        uri: String,
+    },
+    Proxied {
+        uri: String,
+        original: String,
+    },
+    /// The original uri, rewritten to an alternative gateway.
+    Alternative {
+        uri: String,
+        original: String,
+    },
 }
 
 impl Uri {
@@ -262,17 +698,27 @@ impl Uri {
             .and_then(|url| url.host_str().map(|host| host.to_string()))
     }
 
+    /// The host actually requested, excluding the proxy as its failures are not those of the host.
+    fn effective_host(&self) -> Option<String> {
+        match self {
+            Uri::Proxied { .. } => None,
+            _ => Url::parse(self.effective_uri())
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_string())),
+        }
+    }
+
     fn original_uri(&self) -> &str {
         match self {
             Uri::Standard { uri } => uri,
-            Uri::Proxied { original, .. } => original,
+            Uri::Proxied { original, .. } | Uri::Alternative { original, .. } => original,
         }
     }
 
     fn effective_uri(&self) -> &str {
         match self {
             Uri::Standard { uri } => uri,
-            Uri::Proxied { uri, .. } => uri,
+            Uri::Proxied { uri, .. } | Uri::Alternative { uri, .. } => uri,
         }
     }
 
@@ -430,3 +876,62 @@ impl From<json::Attribute> for Attribute {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_uri, Overrides};
+    use url::Url;
+
+    #[test]
+    fn converts_arweave_uris() {
+        let base_uri = Url::parse("https://arweave.net/metadata/").unwrap();
+        assert_eq!(
+            "https://arweave.net/abc123/1.png",
+            parse_uri("ar://abc123/1.png".to_string(), &base_uri)
+        );
+        assert_eq!(
+            "https://arweave.net/metadata/1.png",
+            parse_uri("1.png".to_string(), &base_uri)
+        );
+    }
+
+    #[test]
+    fn passes_through_data_and_blob_uris() {
+        let base_uri = Url::parse("https://example.com/metadata/").unwrap();
+        let svg = "data:image/svg+xml;utf8,<svg><text>#1</text></svg>".to_string();
+        assert_eq!(svg, parse_uri(svg.clone(), &base_uri));
+        let blob = "blob:https://nifty.gallery/6c0b6bd4-3f1c-4b8a-9d56-0d5b6a3e2f1a".to_string();
+        assert_eq!(blob, parse_uri(blob.clone(), &base_uri));
+    }
+
+    #[test]
+    fn substitutes_token_into_body() {
+        let overrides = Overrides {
+            body: Some(r#"{"query": "{ token(id: \"{id}\") { metadata } }"}"#.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(r#"{"query": "{ token(id: \"42\") { metadata } }"}"#.to_string()),
+            overrides.body(Some(42))
+        );
+        assert_eq!("GET", overrides.method());
+    }
+
+    #[test]
+    fn parses_metadata_at_pointer() {
+        let overrides = Overrides {
+            pointer: Some("/data/token/metadata".to_string()),
+            ..Default::default()
+        };
+        let wrapped = r#"{ "data": { "token": { "metadata": { "name": "Wrapped", "image": "ipfs://image", "attributes": [] } } } }"#;
+        let metadata = overrides.parse(wrapped).expect("unable to parse metadata");
+        assert_eq!(Some("Wrapped".to_string()), metadata.name);
+
+        // Metadata returned as a JSON string
+        let encoded = r#"{ "data": { "token": { "metadata": "{ \"name\": \"Encoded\", \"image\": \"ipfs://image\", \"attributes\": [] }" } } }"#;
+        let metadata = overrides.parse(encoded).expect("unable to parse metadata");
+        assert_eq!(Some("Encoded".to_string()), metadata.name);
+
+        assert!(overrides.parse(r#"{ "data": null }"#).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+/// The placeholder for the token identifier within an ERC-1155 metadata uri, either as returned by the contract or
+/// once percent-encoded as part of a url.
+const PLACEHOLDERS: [&str; 3] = ["{id}", "%7Bid%7D", "%7bid%7d"];
+
+/// Whether the uri is an ERC-1155 template, containing an `{id}` placeholder for the token identifier.
+pub fn is_template(uri: &str) -> bool {
+    PLACEHOLDERS
+        .iter()
+        .any(|placeholder| uri.contains(placeholder))
+}
+
+/// Substitutes the token identifier into an ERC-1155 template uri, as lowercase hex padded to 64 characters per the
+/// specification. Returns `None` where the uri is not a template.
+pub fn substitute(uri: &str, token: u32) -> Option<String> {
+    if !is_template(uri) {
+        return None;
+    }
+    let id = format!("{:064x}", token);
+    Some(
+        PLACEHOLDERS
+            .iter()
+            .fold(uri.to_string(), |uri, placeholder| {
+                uri.replace(placeholder, &id)
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_template, substitute};
+
+    #[test]
+    fn substitutes_id() {
+        assert_eq!(
+            Some(
+                "https://api.example.com/0000000000000000000000000000000000000000000000000000000000000314.json"
+                    .to_string()
+            ),
+            substitute("https://api.example.com/{id}.json", 788)
+        );
+        assert_eq!(
+            Some(
+                "https://api.example.com/000000000000000000000000000000000000000000000000000000000000000a"
+                    .to_string()
+            ),
+            substitute("https://api.example.com/%7Bid%7D", 10)
+        );
+    }
+
+    #[test]
+    fn ignores_non_templates() {
+        assert!(!is_template("https://api.example.com/1.json"));
+        assert_eq!(None, substitute("https://api.example.com/", 1));
+    }
+}
@@ -9,6 +9,8 @@ pub struct Worker {
 #[derive(Serialize, Deserialize)]
 pub struct Request {
     pub url: String,
+    /// The width (and height) of the code, in pixels.
+    pub size: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -30,7 +32,7 @@ impl gloo_worker::Worker for Worker {
     fn update(&mut self, _msg: Self::Message) {}
 
     fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
-        if let Ok(qr_code) = qrcode_generator::to_png_to_vec(&msg.url, QrCodeEcc::Low, 80) {
+        if let Ok(qr_code) = qrcode_generator::to_png_to_vec(&msg.url, QrCodeEcc::Low, msg.size) {
             log::trace!("qr code generated");
             self.link.respond(
                 id,
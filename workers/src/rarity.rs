@@ -0,0 +1,179 @@
+use gloo_worker::{HandlerId, Public, WorkerLink};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+pub struct Worker {
+    link: WorkerLink<Self>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Request {
+    pub collection: String,
+    /// The indexed tokens of the collection, along with their attributes as (trait type, value) pairs.
+    pub tokens: Vec<(u32, Vec<(String, String)>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Response {
+    pub collection: String,
+    pub rarity: Rarity,
+}
+
+/// The rarity of a collection, calculated from its indexed tokens.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Rarity {
+    /// The number of tokens used to calculate the rarity.
+    #[serde(rename = "t")]
+    pub total: usize,
+    /// The number of tokens having each value, by trait type.
+    #[serde(rename = "f")]
+    pub frequencies: HashMap<String, HashMap<String, usize>>,
+    /// The tokens, ordered by rank.
+    #[serde(rename = "r")]
+    pub ranks: Vec<Rank>,
+}
+
+impl Rarity {
+    /// The score of an individual trait value, with rarer values scoring higher.
+    pub fn score(&self, trait_type: &str, value: &str) -> f64 {
+        self.frequencies
+            .get(trait_type)
+            .and_then(|values| values.get(value))
+            .map_or(0.0, |frequency| score(self.total, *frequency))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Rank {
+    #[serde(rename = "i")]
+    pub token: u32,
+    #[serde(rename = "r")]
+    pub rank: u32,
+    #[serde(rename = "s")]
+    pub score: f64,
+}
+
+impl gloo_worker::Worker for Worker {
+    type Reach = Public<Self>;
+    type Message = ();
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: WorkerLink<Self>) -> Self {
+        log::trace!("creating worker...");
+        Self { link }
+    }
+
+    fn update(&mut self, _msg: Self::Message) {}
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        log::trace!("calculating rarity of {} tokens...", msg.tokens.len());
+        let rarity = calculate(&msg.tokens);
+        log::trace!("rarity calculated");
+        self.link.respond(
+            id,
+            Response {
+                collection: msg.collection,
+                rarity,
+            },
+        )
+    }
+
+    fn name_of_resource() -> &'static str {
+        "rarity.js"
+    }
+}
+
+/// Calculates the rarity of the tokens, scoring each token as the sum of the rarity scores of its trait values.
+pub fn calculate(tokens: &[(u32, Vec<(String, String)>)]) -> Rarity {
+    let total = tokens.len();
+    let mut frequencies: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    for (_, attributes) in tokens {
+        for (trait_type, value) in attributes {
+            *frequencies
+                .entry(trait_type.clone())
+                .or_default()
+                .entry(value.clone())
+                .or_default() += 1;
+        }
+    }
+
+    let mut ranks: Vec<Rank> = tokens
+        .iter()
+        .map(|(token, attributes)| Rank {
+            token: *token,
+            rank: 0,
+            score: attributes
+                .iter()
+                .map(|(trait_type, value)| score(total, frequencies[trait_type][value]))
+                .sum(),
+        })
+        .collect();
+    ranks.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then(a.token.cmp(&b.token))
+    });
+
+    // Assign ranks, with tokens of equal score sharing the same rank
+    for i in 0..ranks.len() {
+        ranks[i].rank = if i > 0 && ranks[i].score == ranks[i - 1].score {
+            ranks[i - 1].rank
+        } else {
+            i as u32 + 1
+        };
+    }
+
+    Rarity {
+        total,
+        frequencies,
+        ranks,
+    }
+}
+
+fn score(total: usize, frequency: usize) -> f64 {
+    total as f64 / frequency as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rarity::calculate;
+
+    fn token(id: u32, attributes: &[(&str, &str)]) -> (u32, Vec<(String, String)>) {
+        (
+            id,
+            attributes
+                .iter()
+                .map(|(t, v)| (t.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn ranks_rarest_token_first() {
+        let rarity = calculate(&[
+            token(0, &[("Fur", "Brown")]),
+            token(1, &[("Fur", "Gold")]),
+            token(2, &[("Fur", "Brown")]),
+            token(3, &[("Fur", "Brown")]),
+        ]);
+        assert_eq!(4, rarity.total);
+        assert_eq!(1, rarity.ranks[0].token);
+        assert_eq!(1, rarity.ranks[0].rank);
+        assert_eq!(4.0, rarity.ranks[0].score);
+        assert_eq!(4.0, rarity.score("Fur", "Gold"));
+    }
+
+    #[test]
+    fn equal_scores_share_rank() {
+        let rarity = calculate(&[
+            token(0, &[("Fur", "Brown")]),
+            token(1, &[("Fur", "Gold")]),
+            token(2, &[("Fur", "Brown")]),
+        ]);
+        let ranks: Vec<(u32, u32)> = rarity.ranks.iter().map(|r| (r.token, r.rank)).collect();
+        assert_eq!(vec![(1, 1), (0, 2), (2, 2)], ranks);
+    }
+}